@@ -16,7 +16,7 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use std::sync::{Arc, Mutex};
+use std::{fmt::{self, Debug}, sync::{Arc, Mutex}};
 
 use glib::{clone, Continue, MainContext, Sender};
 use once_cell::sync::OnceCell;
@@ -41,6 +41,15 @@ where
     }
 }
 
+impl<T> Debug for Future<T>
+where
+    T: Send,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Future").finish_non_exhaustive()
+    }
+}
+
 impl<T> Future<T>
 where
     T: Send + Sync + 'static,
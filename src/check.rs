@@ -0,0 +1,96 @@
+/* check.rs
+ *
+ * Copyright 2021-2022 Bohong Huang
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::time::{Duration, Instant};
+
+use async_std::task;
+use gst::{prelude::*, MessageView};
+
+use crate::i18n::tr;
+use crate::preferences::PreferencesModel;
+use crate::slave::{
+    request_informations,
+    slave_config::{SlaveConfigModel, SlaveSession},
+    slave_video::create_pipeline_from_config,
+    RpcClientBuilder,
+};
+
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn check_communication(config: &SlaveConfigModel) -> Result<Duration, String> {
+    let rpc_client = RpcClientBuilder::default()
+        .request_timeout(CHECK_TIMEOUT)
+        .build(config.get_slave_url().as_str())
+        .map_err(|err| err.to_string())?;
+    let start_time = Instant::now();
+    task::block_on(request_informations(&rpc_client)).map_err(|err| err.to_string())?;
+    Ok(start_time.elapsed())
+}
+
+// 启动管道并等待第一帧画面，随后立即停止管道
+fn check_video(config: &SlaveConfigModel) -> Result<Duration, String> {
+    let pipeline = create_pipeline_from_config(config)
+        .ok_or_else(|| String::from(tr("拉流 URL 有误，请检查并修改后重试。")))??;
+    let appsink = pipeline
+        .by_name("display")
+        .and_then(|element| element.dynamic_cast::<gst_app::AppSink>().ok())
+        .ok_or_else(|| String::from("Missing element: appsink"))?;
+    let start_time = Instant::now();
+    let result = match pipeline.set_state(gst::State::Playing) {
+        Ok(_) => match appsink.try_pull_sample(gst::ClockTime::from_seconds(CHECK_TIMEOUT.as_secs())) {
+            Some(_) => Ok(start_time.elapsed()),
+            None => Err(pipeline
+                .bus()
+                .and_then(|bus| bus.pop_filtered(&[gst::MessageType::Error]))
+                .and_then(|message| match message.view() {
+                    MessageView::Error(err) => Some(err.error().to_string()),
+                    _ => None,
+                })
+                .unwrap_or_else(|| String::from(tr("在限定时间内未收到画面")))),
+        },
+        Err(_) => Err(String::from(tr("无法启动管道"))),
+    };
+    pipeline.set_state(gst::State::Null).ok();
+    result
+}
+
+fn report(name: &str, url: &str, result: &Result<Duration, String>) {
+    match result {
+        Ok(latency) => println!("[PASS] {} {} ({} ms)", name, url, latency.as_millis()),
+        Err(err) => println!("[FAIL] {} {}: {}", name, url, err),
+    }
+}
+
+// 依次检查各机位的通信与视频，全部通过时返回 true
+pub fn run(sessions: &[SlaveSession], preferences: &PreferencesModel) -> bool {
+    if sessions.is_empty() {
+        println!("{}", tr("没有已配置的机位"));
+        return false;
+    }
+    let mut passed = true;
+    for session in sessions {
+        let mut config = SlaveConfigModel::from_preferences(preferences);
+        config.apply_session(session.clone());
+        let communication_result = check_communication(&config);
+        report(tr("通信"), config.get_slave_url().as_str(), &communication_result);
+        let video_result = check_video(&config);
+        report(tr("视频"), config.get_video_url().as_str(), &video_result);
+        passed &= communication_result.is_ok() && video_result.is_ok();
+    }
+    passed
+}
@@ -0,0 +1,128 @@
+/* cli.rs
+ *
+ * Copyright 2021-2022 Bohong Huang
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::path::PathBuf;
+
+use crate::i18n::tr;
+use crate::preferences::PreferencesModel;
+use crate::slave::slave_config::{parse_slave_url, parse_video_url, SlaveSession};
+
+#[derive(Debug, Clone)]
+pub enum CommandLineError {
+    UnknownArgument(String),
+    MissingValue(String),
+    VideoWithoutSlave(String),
+    InvalidUrl(String, &'static str),
+}
+
+impl ToString for CommandLineError {
+    fn to_string(&self) -> String {
+        match self {
+            CommandLineError::UnknownArgument(arg) => format!("{}{}", tr("未知的参数："), arg),
+            CommandLineError::MissingValue(arg) => format!("{}{}", tr("参数缺少取值："), arg),
+            CommandLineError::VideoWithoutSlave(url) => {
+                format!("{}{}", tr("--video 之前缺少对应的 --slave："), url)
+            }
+            CommandLineError::InvalidUrl(url, err) => format!("{}：{}", url, err),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct CommandLineArgs {
+    pub program_name: String,
+    pub slaves: Vec<(String, Option<String>)>, // 连接 URL 与视频 URL，在语言设置完成后才解析以便翻译错误信息
+    pub auto_connect: bool,
+    pub preferences_path: Option<PathBuf>,
+    pub check: bool,
+    pub help: bool,
+}
+
+impl CommandLineArgs {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<CommandLineArgs, CommandLineError> {
+        let mut result = CommandLineArgs {
+            program_name: args.next().unwrap_or_else(|| String::from(env!("CARGO_PKG_NAME"))),
+            ..Default::default()
+        };
+        while let Some(arg) = args.next() {
+            // 同时支持 --slave URL 与 --slave=URL 两种写法
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => (name.to_string(), Some(value.to_string())),
+                _ => (arg.clone(), None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| CommandLineError::MissingValue(name.clone()))
+            };
+            match name.as_str() {
+                "--slave" => result.slaves.push((value()?, None)),
+                "--video" => {
+                    let video_url = value()?;
+                    match result.slaves.last_mut() {
+                        Some((_, video @ None)) => *video = Some(video_url),
+                        _ => return Err(CommandLineError::VideoWithoutSlave(video_url)),
+                    }
+                }
+                "--autoconnect" => result.auto_connect = true,
+                "--check" => result.check = true,
+                "--preferences" => result.preferences_path = Some(PathBuf::from(value()?)),
+                "-h" | "--help" => result.help = true,
+                _ => return Err(CommandLineError::UnknownArgument(arg)),
+            }
+        }
+        Ok(result)
+    }
+
+    // 命令行指定的机位，未指定视频 URL 时使用首选项中的默认视频 URL
+    pub fn slave_sessions(&self, preferences: &PreferencesModel) -> Result<Vec<SlaveSession>, CommandLineError> {
+        self.slaves
+            .iter()
+            .map(|(slave_url, video_url)| {
+                Ok(SlaveSession {
+                    slave_url: parse_slave_url(slave_url)
+                        .map_err(|err| CommandLineError::InvalidUrl(slave_url.clone(), err))?,
+                    video_url: match video_url {
+                        Some(video_url) => parse_video_url(video_url)
+                            .map_err(|err| CommandLineError::InvalidUrl(video_url.clone(), err))?,
+                        None => preferences.get_default_video_url().clone(),
+                    },
+                    auto_connect: self.auto_connect,
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+
+    pub fn usage(program_name: &str) -> String {
+        let options = [
+            ("--slave <URL>", tr("添加机位并指定其连接 URL，可多次指定以添加多个机位，命令行指定的机位不会被保存")),
+            ("--video <URL>", tr("指定前一个 --slave 所添加机位的视频 URL，未指定时使用首选项中的默认视频 URL")),
+            ("--autoconnect", tr("启动后自动连接各机位")),
+            ("--check", tr("不启动界面，检查各机位的通信与视频能否连通并输出结果，存在失败项时以非零状态码退出")),
+            ("--preferences <PATH>", tr("使用指定的首选项文件代替默认的首选项文件")),
+            ("-h, --help", tr("显示此帮助信息")),
+        ];
+        let mut usage = format!("{}{} [{}]\n\n{}\n", tr("用法："), program_name, tr("选项"), tr("选项："));
+        for (option, description) in options {
+            usage.push_str(&format!("  {:<22}{}\n", option, description));
+        }
+        usage
+    }
+}
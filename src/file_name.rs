@@ -0,0 +1,191 @@
+/* file_name.rs
+ *
+ * Copyright 2021-2022 Bohong Huang
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::{fs, path::{Path, PathBuf}};
+
+use glib::DateTime;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
+use url::Url;
+
+use crate::i18n::tr;
+use crate::preferences::PreferencesModel;
+
+pub const DEFAULT_FILE_NAME_TEMPLATE: &str = "{datetime}";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileNameTemplateError {
+    UnknownToken(String),
+    UnmatchedBrace,
+    Empty,
+}
+
+impl ToString for FileNameTemplateError {
+    fn to_string(&self) -> String {
+        match self {
+            FileNameTemplateError::UnknownToken(token) => format!("{}{{{}}}", tr("未知的占位符："), token),
+            FileNameTemplateError::UnmatchedBrace => String::from(tr("花括号不匹配")),
+            FileNameTemplateError::Empty => String::from(tr("生成的文件名为空")),
+        }
+    }
+}
+
+// 截图与录制文件名中可用的占位符及其取值
+pub struct FileNameContext<'a> {
+    pub slave_url: &'a Url,
+    pub counter: u32, // 本次启动以来该机位保存文件的序号
+}
+
+fn format_now(format: &str) -> String {
+    DateTime::now_local()
+        .and_then(|time| time.format(format))
+        .map(|time| time.to_string())
+        .unwrap_or_default()
+}
+
+// 原有的 ISO 8601 时间戳文件名，也是模板无效时的后备文件名
+pub fn timestamp_file_name() -> String {
+    DateTime::now_local()
+        .unwrap()
+        .format_iso8601()
+        .unwrap()
+        .replace(":", "-")
+}
+
+fn expand_token(token: &str, context: &FileNameContext) -> Result<String, FileNameTemplateError> {
+    Ok(match token {
+        "datetime" => timestamp_file_name(),
+        "date" => format_now("%Y-%m-%d"),
+        "time" => format_now("%H-%M-%S"),
+        "slave" => match context.slave_url.port_or_known_default() {
+            Some(port) => format!("{}:{}", context.slave_url.host_str().unwrap_or_default(), port),
+            None => context.slave_url.host_str().unwrap_or_default().to_string(),
+        },
+        "counter" => context.counter.to_string(),
+        _ => return Err(FileNameTemplateError::UnknownToken(token.to_string())),
+    })
+}
+
+// 替换文件系统不允许的字符，并去除首尾的空白与点号
+pub fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_control() => '-',
+            c => c,
+        })
+        .collect::<String>()
+        .trim_matches(|c: char| c.is_whitespace() || c == '.')
+        .to_string()
+}
+
+// 展开文件名模板，返回不含扩展名的文件名
+pub fn expand_file_name_template(template: &str, context: &FileNameContext) -> Result<String, FileNameTemplateError> {
+    let mut result = String::new();
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                let mut token = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some('{') | None => return Err(FileNameTemplateError::UnmatchedBrace),
+                        Some(c) => token.push(c),
+                    }
+                }
+                result.push_str(&expand_token(token.trim(), context)?);
+            }
+            '}' => return Err(FileNameTemplateError::UnmatchedBrace),
+            c => result.push(c),
+        }
+    }
+    let result = sanitize_file_name(&result);
+    if result.is_empty() {
+        return Err(FileNameTemplateError::Empty);
+    }
+    Ok(result)
+}
+
+// 仅检查模板能否展开，用于在首选项中提示模板是否有效
+pub fn validate_file_name_template(template: &str) -> Result<(), FileNameTemplateError> {
+    let slave_url = PreferencesModel::default().default_slave_url;
+    let context = FileNameContext { slave_url: &slave_url, counter: 1 };
+    expand_file_name_template(template, &context).map(|_| ())
+}
+
+// 截图与录像在保存目录下的子文件夹划分方式，本次启动的子文件夹以启动时间命名
+#[derive(EnumIter, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum SaveDirectoryScheme {
+    Flat, Date, DateSession,
+}
+
+impl ToString for SaveDirectoryScheme {
+    fn to_string(&self) -> String {
+        match self {
+            SaveDirectoryScheme::Flat => tr("不划分"),
+            SaveDirectoryScheme::Date => tr("按日期"),
+            SaveDirectoryScheme::DateSession => tr("按日期与本次启动"),
+        }.to_string()
+    }
+}
+
+impl Default for SaveDirectoryScheme {
+    fn default() -> Self { Self::Flat }
+}
+
+static SESSION_DIRECTORY_NAME: Lazy<String> = Lazy::new(|| format_now("%H-%M-%S"));
+
+// 在启动时调用，使本次启动的子文件夹以启动时间而不是首次保存文件的时间命名
+pub fn init_session() {
+    Lazy::force(&SESSION_DIRECTORY_NAME);
+}
+
+impl SaveDirectoryScheme {
+    pub fn directory(&self, base: &Path) -> PathBuf {
+        match self {
+            SaveDirectoryScheme::Flat => base.to_path_buf(),
+            SaveDirectoryScheme::Date => base.join(format_now("%Y-%m-%d")),
+            SaveDirectoryScheme::DateSession => base.join(format_now("%Y-%m-%d")).join(SESSION_DIRECTORY_NAME.as_str()),
+        }
+    }
+
+    // 在写入第一个文件前创建所需的目录，失败时返回可直接显示给用户的错误信息
+    pub fn prepare_directory(&self, base: &Path) -> Result<PathBuf, String> {
+        let directory = self.directory(base);
+        create_directory(&directory)?;
+        Ok(directory)
+    }
+}
+
+// 递归创建目录，失败时返回可直接显示给用户的错误信息，而不是使程序崩溃
+pub fn create_directory(path: &Path) -> Result<(), String> {
+    fs::create_dir_all(path).map_err(|err| format!("{}{}", tr("无法创建目录："), err))
+}
+
+// 模板不含时间或序号时文件名可能重复，此时在文件名后追加序号以免覆盖已有文件
+pub fn unique_file_path(directory: &Path, name: &str, extension: &str) -> PathBuf {
+    let mut path = directory.join(format!("{}.{}", name, extension));
+    let mut index = 1;
+    while path.exists() {
+        path = directory.join(format!("{}_{}.{}", name, index, extension));
+        index += 1;
+    }
+    path
+}
@@ -0,0 +1,722 @@
+/* i18n.rs
+ *
+ * Copyright 2021-2022 Bohong Huang
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
+
+#[derive(EnumIter, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Language {
+    SimplifiedChinese,
+    English,
+}
+
+impl ToString for Language {
+    fn to_string(&self) -> String {
+        match self {
+            Language::SimplifiedChinese => "简体中文",
+            Language::English => "English",
+        }
+        .to_string()
+    }
+}
+
+impl Default for Language {
+    fn default() -> Self {
+        Self::SimplifiedChinese
+    }
+}
+
+static LANGUAGE: OnceCell<Language> = OnceCell::new();
+
+lazy_static! {
+    // 以中文原文作为键，未收录的字符串将原样显示
+    static ref ENGLISH_CATALOG: HashMap<&'static str, &'static str> = [
+        ("未知设备", "Unknown device"),
+        ("取消", "Cancel"),
+        ("打开", "Open"),
+        ("确定", "OK"),
+        ("无法创建目录：", "Failed to create directory: "),
+        ("目录不可写：", "Directory is not writable: "),
+        ("无法打开目录：", "Failed to open directory: "),
+        ("首选项", "Preferences"),
+        ("通用", "General"),
+        ("外观", "Appearance"),
+        ("更改上位机的外观设置", "Change the appearance of the host"),
+        ("配色方案", "Color Scheme"),
+        ("上位机界面使用的配色方案", "Color scheme used by the host interface"),
+        ("语言", "Language"),
+        ("上位机界面使用的语言（需要重启以应用设置）", "Language used by the host interface (restart required)"),
+        ("语言设置将在重启上位机后生效", "The language setting will take effect after restarting the host"),
+        ("机位", "Slaves"),
+        ("配置上位机的多机位功能", "Configure multiple slaves"),
+        ("初始机位", "Initial Slaves"),
+        ("上位机启动时的初始机位数量，依次恢复上次保存的机位配置，不足时新建机位", "Number of slaves when the host starts; saved slave configurations are restored in order, and new slaves are added if there are not enough"),
+        ("重置", "Reset"),
+        ("恢复上位机的默认设置", "Restore the default settings of the host"),
+        ("恢复默认设置", "Restore Defaults"),
+        ("将所有首选项恢复为默认值，已打开的机位将使用新的默认设置", "Reset all preferences to their default values; open slaves will use the new defaults"),
+        ("恢复", "Restore"),
+        ("确定要将所有首选项恢复为默认值吗？", "Are you sure you want to reset all preferences to their default values?"),
+        ("通信", "Communication"),
+        ("与机器人的连接通信设置", "Connection settings for the robot"),
+        ("连接", "Connect"),
+        ("默认连接 URL", "Default Connection URL"),
+        ("连接第一机位的机器人使用的默认 URL，其他机位会自动累加 IPV4 地址，", "Default URL used to connect the robot of the first slave; other slaves increment the IPv4 address, "),
+        ("格式为 http://主机:端口 或 https://主机[:端口]", "in the form http://host:port or https://host[:port]"),
+        ("格式为 rtp://主机:端口、udp://主机:端口 或 rtsp://主机[:端口]/路径", "in the form rtp://host:port, udp://host:port or rtsp://host[:port]/path"),
+        ("机器人状态信息接收设置", "Settings for receiving robot status"),
+        ("状态信息", "Status"),
+        ("状态信息更新时间间隔", "Status Update Interval"),
+        ("用于确定每秒钟向机器人请求接收状态信息并测试连接状态的频率（需要重新连接以应用设置）", "How often status is requested from the robot and the connection is tested (reconnect to apply)"),
+        ("毫秒", "ms"),
+        ("控制", "Control"),
+        ("发送", "Sending"),
+        ("向机器人发送控制信号的设置（需要重新连接以应用设置）", "Settings for sending control signals to the robot (reconnect to apply)"),
+        ("输入发送率", "Input Sending Rate"),
+        ("摇杆与扳机", "Sticks and Triggers"),
+        ("对所有输入设备生效", "Applies to all input devices"),
+        ("反转左摇杆水平方向", "Invert Left Stick Horizontally"),
+        ("反转左摇杆垂直方向", "Invert Left Stick Vertically"),
+        ("反转右摇杆水平方向", "Invert Right Stick Horizontally"),
+        ("反转右摇杆垂直方向", "Invert Right Stick Vertically"),
+        ("扳机触发阈值", "Trigger Threshold"),
+        ("扳机按下的行程超过该比例时才视为按下，用于避免轻触扳机时误开灯或误操作机械臂", "A trigger only counts as pressed beyond this fraction of its travel, so light touches do not switch the light or move the arm"),
+        ("线性", "Linear"),
+        ("平方", "Squared"),
+        ("立方", "Cubic"),
+        ("自定义指数", "Custom exponent"),
+        ("平移响应曲线", "Translation Response Curve"),
+        ("左摇杆与右摇杆垂直方向的输出随摇杆偏移量变化的方式，指数越大摇杆中心附近越便于微调", "How the output of the left stick and the right stick's vertical axis follows stick deflection; larger exponents allow finer control near the center"),
+        ("平移自定义指数", "Translation Custom Exponent"),
+        ("转向响应曲线", "Rotation Response Curve"),
+        ("右摇杆水平方向的输出随摇杆偏移量变化的方式", "How the output of the right stick's horizontal axis follows stick deflection"),
+        ("转向自定义指数", "Rotation Custom Exponent"),
+        ("屏幕虚拟摇杆", "On-screen Joystick"),
+        ("适用于没有手柄的触屏设备", "For touchscreen devices without a gamepad"),
+        ("启用屏幕虚拟摇杆", "Enable on-screen joystick"),
+        ("启用后可在机位的输入设备列表中选择虚拟摇杆，同一时间仅在一个机位的画面上显示", "Makes the on-screen joystick selectable in each slave's input device list; it is shown on one slave at a time"),
+        ("张开机械臂", "Open arm"),
+        ("闭合机械臂", "Close arm"),
+        ("打开灯光", "Light on"),
+        ("关闭灯光", "Light off"),
+        ("未知的参数：", "Unknown argument: "),
+        ("参数缺少取值：", "Missing value for argument: "),
+        ("--video 之前缺少对应的 --slave：", "--video must follow a --slave: "),
+        ("添加机位并指定其连接 URL，可多次指定以添加多个机位，命令行指定的机位不会被保存", "Add a slave with the given connection URL; repeat to add more slaves. Slaves given on the command line are not saved"),
+        ("指定前一个 --slave 所添加机位的视频 URL，未指定时使用首选项中的默认视频 URL", "Video URL of the slave added by the preceding --slave; defaults to the video URL in the preferences"),
+        ("启动后自动连接各机位", "Connect to every slave after launch"),
+        ("使用指定的首选项文件代替默认的首选项文件", "Use the given preferences file instead of the default one"),
+        ("显示此帮助信息", "Show this help"),
+        ("用法：", "Usage: "),
+        ("选项", "OPTIONS"),
+        ("选项：", "Options:"),
+        ("不启动界面，检查各机位的通信与视频能否连通并输出结果，存在失败项时以非零状态码退出", "Check communication and video reachability of each slave without the GUI, print the results and exit with a nonzero code on any failure"),
+        ("在限定时间内未收到画面", "No frame received within the time limit"),
+        ("无法启动管道", "Failed to start the pipeline"),
+        ("没有已配置的机位", "No slaves configured"),
+        ("方向指示阈值", "Direction Indicator Threshold"),
+        ("摇杆偏移量超过该值时点亮状态面板中对应的方向指示，仅影响显示而不影响实际输出", "Light up the direction indicators in the status panel when the stick deflection exceeds this value; only affects the display, not the actual output"),
+        ("锁定的设定值", "Locked setpoint"),
+        ("SDL 不可用", "SDL is unavailable"),
+        ("未设置手柄映射文件", "No controller mapping file is set"),
+        ("手柄映射", "Controller Mappings"),
+        ("为无法被识别的手柄加载 SDL 格式的映射文件（如 gamecontrollerdb.txt），上位机启动时将自动加载", "Load an SDL mapping file (such as gamecontrollerdb.txt) for unrecognized controllers; it is loaded automatically on startup"),
+        ("手柄映射文件", "Mapping File"),
+        ("重新加载映射", "Reload mappings"),
+        ("已加载的手柄映射数量：", "Controller mappings loaded: "),
+        ("无法加载手柄映射：", "Failed to load controller mappings: "),
+        ("未设置", "Not set"),
+        ("清除", "Clear"),
+        ("选择", "Choose"),
+        ("保活间隔", "Keep-Alive Interval"),
+        ("控制数据包仅在输入变化时发送且只包含变化的部分，输入长时间不变时每隔该时间重新发送一次完整的控制数据包", "Control packets are sent only when the input changes and contain only the changed parts; when the input stays unchanged, the full control packet is resent at this interval"),
+        ("<b>控制延迟</b>", "<b>Control Latency</b>"),
+        ("最近一次发送控制指令的往返耗时", "Round-trip time of the most recently sent control commands"),
+        ("逐条请求", "Separate Requests"),
+        ("批量请求", "Batch Request"),
+        ("合并请求", "Combined Request"),
+        ("控制指令发送方式", "Control Command Sending Mode"),
+        ("下位机固件支持 control 方法时可选择合并请求，将全部控制指令合并为一次请求发送以降低延迟", "If the slave firmware supports the control method, choose combined request to send all control commands in a single request and reduce latency"),
+        ("文件名", "File Name"),
+        ("截图与录制文件的命名方式", "How screenshot and recording files are named"),
+        ("文件名模板", "File Name Template"),
+        ("可用的占位符有 {date}（日期）、{time}（时间）、{datetime}（完整时间戳）、{slave}（机位连接地址）与 {counter}（本次启动以来该机位保存文件的序号），模板无效时使用时间戳作为文件名", "Available placeholders are {date}, {time}, {datetime} (full timestamp), {slave} (slave connection address) and {counter} (number of files saved by the slave since launch); the timestamp is used as the file name when the template is invalid"),
+        ("未知的占位符：", "Unknown placeholder: "),
+        ("花括号不匹配", "Unmatched braces"),
+        ("生成的文件名为空", "The resulting file name is empty"),
+        ("文件名模板无效，已使用时间戳作为文件名：", "Invalid file name template, the timestamp is used as the file name: "),
+        ("最低可用空间", "Minimum Free Space"),
+        ("视频保存目录所在磁盘的可用空间低于该值时无法开始录制，录制过程中低于该值的一半时自动停止录制", "Recording cannot be started when the free space on the disk of the video save directory is below this value, and is stopped automatically when it drops below half of this value during recording"),
+        ("视频保存目录所在磁盘的可用空间不足，无法开始录制。剩余空间：", "Not enough free space on the disk of the video save directory to start recording. Free space: "),
+        ("磁盘可用空间过低，已自动停止录制。剩余空间：", "Disk space is critically low, recording has been stopped automatically. Free space: "),
+        ("固定分配", "Static Assignment"),
+        ("跟随焦点", "Follow Focus"),
+        ("输入分配", "Input Dispatch"),
+        ("多个机位共用输入设备时的分配方式", "How input devices are shared between multiple slaves"),
+        ("分配方式", "Dispatch Mode"),
+        ("固定分配时输入设备只控制选择了它的机位；跟随焦点时所有输入设备都控制最后点击画面的机位", "With static assignment an input device only controls the slaves that selected it; with follow focus all input devices control the slave whose video was clicked last"),
+        ("控制中", "In Control"),
+        ("深度锁定状态信息项", "Depth Lock State Info Key"),
+        ("下位机报告实际深度锁定状态的信息项名称，用于校正深度锁定开关，留空则不校正", "Name of the info item the slave uses to report the actual depth lock state, used to correct the depth lock switch; leave empty to disable correction"),
+        ("方向锁定状态信息项", "Heading Lock State Info Key"),
+        ("下位机报告实际方向锁定状态的信息项名称，用于校正方向锁定开关，留空则不校正", "Name of the info item the slave uses to report the actual heading lock state, used to correct the heading lock switch; leave empty to disable correction"),
+        ("下位机未能开启深度锁定，已关闭深度锁定开关。", "The slave failed to engage depth lock, the depth lock switch has been turned off."),
+        ("下位机未能开启方向锁定，已关闭方向锁定开关。", "The slave failed to engage heading lock, the heading lock switch has been turned off."),
+        ("置顶的状态信息项", "Pinned Info Keys"),
+        ("以逗号分隔的信息项名称，按列出的顺序显示在状态信息顶部，其余信息项按名称排序", "Comma-separated info keys shown at the top of the status info in the listed order, the remaining items are sorted by name"),
+        ("隐藏的状态信息项", "Hidden Info Keys"),
+        ("以逗号分隔的信息项名称，这些信息项不会显示在状态信息中", "Comma-separated info keys that are not shown in the status info"),
+        ("下位机拒绝了请求：", "The slave rejected the request: "),
+        ("网络存储写入重试次数", "Network Storage Write Retries"),
+        ("图片保存目录位于网络存储上且截图写入失败时，将画面保留在内存中并重试，每次重试的间隔翻倍", "When the image directory is on network storage and writing a screenshot fails, keep the frame in memory and retry, doubling the interval each time"),
+        ("次", "times"),
+        ("录像写入失败，录制已停止：", "Failed to write the recording, recording stopped: "),
+        ("记录控制指令", "Log Control Commands"),
+        ("将每个实际发送的控制数据包及其发送时间记录到文件中，可在机位中回放以复现操作过程", "Record every control packet actually sent along with its time to a file, which can be replayed on a slave to reproduce the maneuver"),
+        ("控制指令记录", "Control Log"),
+        ("控制指令记录格式有误，行号：", "Malformed control log at line "),
+        ("无法读取控制指令记录：", "Cannot read the control log: "),
+        ("控制指令回放结束。", "Control replay finished."),
+        ("停止回放控制指令", "Stop Control Replay"),
+        ("回放控制指令记录", "Replay Control Log"),
+        ("正在连接或断开连接…", "Connecting or disconnecting…"),
+        ("正在启动或停止拉流…", "Starting or stopping the stream…"),
+        ("正在连接或断开连接，暂时无法修改通讯选项", "Connecting or disconnecting, communication options cannot be changed now"),
+        ("正在启动或停止拉流，暂时无法修改管道", "Starting or stopping the stream, the pipeline cannot be changed now"),
+        ("丢包 ", "Loss "),
+        ("<b>视频码率</b>", "<b>Video Bitrate</b>"),
+        ("最近一秒接收视频流的码率，RTP 视频流同时显示根据包序号估计的丢包率", "Bitrate of the video stream received in the last second, with the packet loss estimated from sequence numbers for RTP streams"),
+        ("通讯中断时继续录制", "Keep Recording When Communication Is Lost"),
+        ("与下位机的通讯意外中断时继续录制视频流，视频流中断时录制总会停止", "Keep recording the video stream when communication with the slave is lost unexpectedly, recording always stops when the video stream is lost"),
+        ("下位机通讯中断，录制已停止。", "Communication with the slave was lost, recording stopped."),
+        ("画面已在单独窗口中显示", "The video is shown in a separate window"),
+        ("收回画面", "Dock Video"),
+        ("机位画面", "Slave Video"),
+        ("在单独窗口中显示画面", "Show Video in a Separate Window"),
+        ("标注截图", "Annotate Screenshot"),
+        ("撤销", "Undo"),
+        ("矩形", "Rectangle"),
+        ("箭头", "Arrow"),
+        ("说明文字", "Caption"),
+        ("同时保存原始画面", "Also save the original frame"),
+        ("截图时添加标注", "Annotate Screenshots"),
+        ("通过工具栏按钮截图时打开标注窗口，可在画面上绘制矩形与箭头并添加说明文字后再保存", "Open an annotation window when taking a screenshot from the toolbar to draw rectangles and arrows and add a caption before saving"),
+        ("自动白平衡", "Auto White Balance"),
+        ("白平衡强度", "White Balance Strength"),
+        ("自动白平衡校正色偏的程度，越低画面越接近原始色彩", "How strongly auto white balance corrects color casts, lower values keep the picture closer to its original colors"),
+        ("去雾（高 CPU 占用）", "Dehaze (High CPU Usage)"),
+        ("去雾强度", "Dehaze Strength"),
+        ("去除水中悬浮物造成的雾气的比例，过高时画面可能偏暗或出现噪点", "How much of the haze caused by suspended particles is removed, high values may darken the picture or amplify noise"),
+        ("降噪", "Denoise"),
+        ("快速（双边滤波）", "Fast (Bilateral Filter)"),
+        ("高质量（非局部均值，高 CPU 占用）", "Quality (Non-Local Means, High CPU Usage)"),
+        ("降噪方式", "Denoise Mode"),
+        ("快速方式适合实时操控，高质量方式细节保留更好，但在高分辨率画面上可能无法维持帧率", "The fast mode suits real-time piloting, the quality mode preserves more detail but may not keep up with the frame rate on high resolution video"),
+        ("处理分辨率", "Processing Resolution"),
+        ("原始分辨率", "Original Resolution"),
+        ("增强算法处理画面时使用的分辨率，降低分辨率可减少 CPU 占用，但画面会变得模糊，录像不受影响", "Resolution used by the enhancement algorithms, lower resolutions reduce CPU usage but blur the picture, recordings are not affected"),
+        ("<b>视频格式</b>", "<b>Video Format</b>"),
+        ("显示的视频画面的分辨率、宽高比与帧率，分辨率过低时以警告颜色显示", "Resolution, aspect ratio and frame rate of the displayed video, shown in warning color when the resolution is too low"),
+        ("新建多个机位…", "New Multiple Slaves…"),
+        ("新建多个机位", "New Multiple Slaves"),
+        ("请输入要新建的机位数量", "Enter the number of slaves to create"),
+        ("正在进行的录制此后将缺少遥测数据", "The ongoing recording will miss telemetry from now on"),
+        ("已启用的深度或方向锁定将不再收到上位机的控制指令", "Enabled depth or direction locks will no longer receive control commands from the host"),
+        ("断开连接将中断以下操作，确定要断开连接吗？", "Disconnecting will interrupt the following operations, are you sure to disconnect?"),
+        ("退出将断开所有机位的连接并中断以下操作，确定要退出吗？", "Quitting will disconnect all slaves and interrupt the following operations, are you sure to quit?"),
+        ("退出", "Quit"),
+        ("正在进行的录制将停止", "The ongoing recording will be stopped"),
+        ("显示帧数", "Frames displayed"),
+        ("丢弃帧数", "Frames dropped"),
+        ("已发送控制指令", "Control packets sent"),
+        ("通讯错误", "Communication errors"),
+        ("视频流重连次数", "Video reconnects"),
+        ("连接时长", "Connected for"),
+        ("诊断信息", "Diagnostics"),
+        ("<b>诊断信息</b>", "<b>Diagnostics</b>"),
+        ("推进器混控预览", "Thruster Mixing Preview"),
+        ("恢复默认的六推进器矢量布局", "Restore the default vectored six-thruster layout"),
+        ("自定义命令", "Custom Commands"),
+        ("在状态信息面板中添加调用下位机自定义 RPC 方法的按钮", "Add buttons to the status panel that call custom RPC methods of the slave"),
+        ("、", ", "),
+        ("编辑", "Edit"),
+        ("名称", "Label"),
+        ("RPC 方法", "RPC Method"),
+        ("参数（可选，JSON 数组或对象）", "Parameters (optional, JSON array or object)"),
+        ("参数须为 JSON 数组或对象", "Parameters must be a JSON array or object"),
+        ("无法解析 JSON", "Unable to parse JSON"),
+        ("RPC 方法不能为空", "The RPC method cannot be empty"),
+        ("移除", "Remove"),
+        ("添加命令", "Add Command"),
+        ("命令按钮显示在状态信息面板中，点击时在暂停状态信息请求与控制指令发送的情况下调用一次对应的 RPC 方法，结果以通知的形式显示。", "Command buttons are shown in the status panel. Clicking one calls the RPC method once while status requests and control commands are paused, and shows the result as a notification."),
+        (" 执行成功", " succeeded"),
+        (" 执行成功：", " succeeded: "),
+        (" 执行失败：", " failed: "),
+        ("<b>配平</b>", "<b>Trim</b>"),
+        ("叠加到运动输出上的偏移量，用于抵消浮力不平衡等造成的漂移", "Offsets added to the motion outputs to compensate for drift such as that caused by buoyancy imbalance"),
+        ("将当前运动输出设为配平", "Set Current Motion as Trim"),
+        ("清除配平", "Reset Trim"),
+        ("已设置配平：", "Trim set: "),
+        ("已清除配平。", "Trim reset."),
+        ("连接或断开连接", "Connect or Disconnect"),
+        ("启动或停止拉流", "Start or Stop Streaming"),
+        ("开始或停止录制", "Start or Stop Recording"),
+        ("键盘快捷键", "Keyboard Shortcuts"),
+        ("<b>键盘快捷键</b>", "<b>Keyboard Shortcuts</b>"),
+        ("除全屏外均作用于最后点击画面的机位，在输入框中输入时不会触发", "All shortcuts except fullscreen act on the slave whose video was clicked last, and they do not fire while typing in an entry"),
+        ("请先点击要操作的机位画面", "Click the video of the slave to operate first"),
+        ("使用 GTK 视频输出", "Use GTK Video Output"),
+        ("未启用图像增强、直方图、测量、裁剪与旋转时由 GTK 直接渲染视频流，省去画面的复制与转换，需要 gtk4paintablesink 元素，下次启动拉流时生效", "Render the video stream directly with GTK when enhancement, histogram, measurement, cropping and rotation are disabled, avoiding frame copies and conversion. Requires the gtk4paintablesink element and takes effect the next time the stream starts"),
+        ("录制期间无法切换画面的显示方式，画面处理将在重新拉流后生效。", "Cannot switch the video output while recording. Frame processing will take effect after the stream restarts."),
+        ("无法读取当前画面，截图失败。", "Cannot read the current frame, screenshot failed."),
+        ("同步录制进行中，无法停止拉流", "Sync recording in progress, cannot stop the stream"),
+        ("同步录制进行中，请使用标题栏中的按钮停止", "Sync recording in progress, stop it with the button in the header bar"),
+        ("正在开始或停止录制…", "Starting or stopping recording…"),
+        ("需要先启动拉流", "Start the stream first"),
+        ("等待视频画面…", "Waiting for video frames…"),
+        ("不划分", "None"),
+        ("按日期", "By Date"),
+        ("按日期与本次启动", "By Date and Session"),
+        ("子文件夹", "Subfolders"),
+        ("将截图与录像按日期或按日期与本次启动时间保存到保存目录下的子文件夹中，子文件夹在保存第一个文件时创建", "Save screenshots and recordings into subfolders of the save directory by date, or by date and the time this session started. Subfolders are created when the first file is saved"),
+        ("刷新画面（请求关键帧）", "Refresh Frame (Request Keyframe)"),
+        ("单独设置输入发送率", "Per-Slave Input Sending Rate"),
+        ("为该机位单独指定每秒钟发送的控制数据包的个数，未启用时使用首选项中的设置，连接期间修改立即生效", "Set the number of control packets sent per second for this slave. Uses the preference when disabled. Changes apply immediately while connected"),
+        ("单独设置状态信息更新时间间隔", "Per-Slave Status Update Interval"),
+        ("为该机位单独指定请求状态信息的时间间隔，未启用时使用首选项中的设置，连接期间修改立即生效", "Set the status request interval for this slave. Uses the preference when disabled. Changes apply immediately while connected"),
+        ("多个机位使用了相同的连接 URL：", "Multiple slaves use the same connection URL: "),
+        ("多个机位使用了相同的视频 URL：", "Multiple slaves use the same video URL: "),
+        ("图片与 JSON 文件", "Image and JSON File"),
+        ("HTML 文件", "HTML File"),
+        ("采集时间", "Capture Time"),
+        ("视频 URL", "Video URL"),
+        ("视频格式", "Video Format"),
+        ("采集报告", "Capture Report"),
+        ("机位信息", "Slave Information"),
+        ("将当前画面与采集时刻的完整状态信息、机位信息一并保存，便于整理巡检结果", "Save the current frame together with the full status information and slave information at the time of capture, for compiling inspection results"),
+        ("报告保存目录", "Report Save Directory"),
+        ("报告格式", "Report Format"),
+        ("保存为截图与同名的 JSON 文件（截图使用图片保存格式），或内嵌截图的单个 HTML 文件", "Save as a screenshot with a JSON file of the same name (the screenshot uses the image save format), or as a single HTML file with the screenshot embedded"),
+        ("当前没有画面，无法生成采集报告。", "No video frame available, cannot create a capture report."),
+        ("采集报告保存成功：", "Capture report saved: "),
+        ("采集报告保存失败：", "Failed to save capture report: "),
+        ("保存采集报告（画面与状态信息）", "Save Capture Report (Frame and Status Information)"),
+        ("仅提示", "Warn Only"),
+        ("空闲超时", "Idle Timeout"),
+        ("连接期间超过指定时间没有控制输入变化时给出提示，并可选择紧急停止或断开连接", "Warn when the control input has not changed for the given time while connected, optionally with an emergency stop or disconnect"),
+        ("超时时间", "Timeout"),
+        ("超时后的操作", "Action on Timeout"),
+        ("长时间没有控制输入，已超过 ", "No control input for over "),
+        (" 秒", " seconds"),
+        (" 秒，已紧急停止", " seconds, emergency stop performed"),
+        (" 秒，已断开连接", " seconds, disconnected"),
+        ("<b>潜水时间</b>", "<b>Dive Time</b>"),
+        ("连接下位机以来经过的时间", "Time elapsed since connecting to the slave"),
+        ("副摄像头", "Secondary Camera"),
+        ("以画中画方式在画面上显示另一路视频流，修改在下次启动副摄像头画面时生效", "Show another video stream as picture-in-picture over the video; changes take effect the next time the secondary camera is started"),
+        ("画中画副摄像头", "Picture-in-Picture Secondary Camera"),
+        ("启用后可通过工具栏按钮单独启动或停止副摄像头画面，点击画中画可交换主画面与画中画", "When enabled, the secondary camera can be started and stopped independently from the toolbar; click the inset to swap it with the main video"),
+        ("同时录制副摄像头", "Record Secondary Camera"),
+        ("开始录制时副摄像头画面正在显示则一并录制，录像文件名带有 _secondary 后缀", "Also record the secondary camera if it is shown when recording starts; its file name has the _secondary suffix"),
+        ("停止副摄像头画面", "Stop Secondary Camera"),
+        ("显示副摄像头画面（画中画）", "Show Secondary Camera (Picture-in-Picture)"),
+        ("点击交换主画面与画中画", "Click to swap the main video and the inset"),
+        ("无法录制副摄像头：", "Cannot record the secondary camera: "),
+        ("副摄像头视频 URL 有误，请检查并修改后重试。", "The secondary camera video URL is invalid, please check and try again."),
+        ("无法启动副摄像头的管道，请检查副摄像头视频 URL 及相关资源是否可用。", "Cannot start the secondary camera pipeline, please check its video URL and whether the related resources are available."),
+        ("副摄像头视频流中断，其录制已停止，录像文件可能不完整。", "The secondary camera stream was interrupted and its recording stopped; the file may be incomplete."),
+        ("副摄像头画面已停止：", "Secondary camera stopped: "),
+        ("解码后按指定的编码器与码率重新编码再录制，会占用大量 CPU，关闭时直接录制接收到的视频流", "Decode and re-encode recordings with the selected encoder and bitrate; this is CPU-intensive. When off, the received stream is recorded as is"),
+        ("码率", "Bitrate"),
+        ("重新编码录制时的目标码率", "Target bitrate when re-encoding recordings"),
+        ("视频管道", "Video Pipeline"),
+        ("配置视频管道调试选项", "Video pipeline debugging options"),
+        ("导出管道结构图", "Export Pipeline Graph"),
+        ("在机位的诊断信息中提供导出当前视频管道结构图（DOT 格式）的按钮，用于排查元素与连接问题", "Offer a button in the slave diagnostics to export the running video pipeline graph (DOT format) for diagnosing element and linking issues"),
+        ("当前没有运行中的视频管道。", "No video pipeline is running."),
+        ("已导出视频管道结构图：", "Exported video pipeline graph: "),
+        ("无法导出视频管道结构图：", "Failed to export video pipeline graph: "),
+        ("下位机已断开连接，请在重新连接后继续。", "The slave is disconnected. Please reconnect to continue."),
+        ("下位机已断开连接，参数调校已关闭，请在重新连接后重新打开。", "The slave is disconnected and parameter tuning has been closed. Please reopen it after reconnecting."),
+        ("按位置传参", "Positional"),
+        ("将对象参数作为唯一的位置参数传递，而不是按名称传递其各字段", "Pass an object parameter as the single positional argument instead of passing its fields by name"),
+        ("浮点数 [-1, 1]", "Float [-1, 1]"),
+        ("百分比 [0, 100]", "Percent [0, 100]"),
+        ("PWM 脉宽 [1000, 2000]", "PWM Pulse Width [1000, 2000]"),
+        ("16 位整数 [-32768, 32767]", "16-bit Integer [-32768, 32767]"),
+        ("控制量编码", "Control Value Encoding"),
+        ("发送运动、机械臂与灯光控制量时使用的数值格式，需与下位机固件一致，重新连接后生效", "Numeric format of motion, robotic arm and light values sent to the slave. Must match the firmware; takes effect after reconnecting"),
+        ("<b>状态信息更新</b>", "<b>Status Updates</b>"),
+        ("带宽紧张时可暂停请求状态信息以优先发送控制指令", "Pause status requests when bandwidth is tight so that control commands take priority"),
+        ("已暂停，数据已过时 ", "Paused, data age: "),
+        ("已暂停", "Paused"),
+        ("连接档案", "Connection Profiles"),
+        ("使用保存的地址、解码与通讯设置填充该机位的配置", "Fill this slave's configuration with saved addresses, decoding and communication settings"),
+        ("应用", "Apply"),
+        ("管理", "Manage"),
+        ("未保存连接档案", "No connection profiles saved"),
+        ("档案名称不能为空", "Profile name cannot be empty"),
+        ("将档案应用到当前机位", "Apply the profile to the current slave"),
+        ("以此档案新建机位", "New slave from this profile"),
+        ("以当前机位的配置添加档案", "Add a profile from the current slave's configuration"),
+        ("无法保存连接档案：", "Failed to save connection profiles: "),
+        ("连接档案保存下位机与视频地址以及添加时机位的解码与通讯设置，可应用到当前机位或以此新建机位。", "A connection profile stores the slave and video addresses along with the decoding and communication settings of the slave it was added from. It can be applied to the current slave or used to create a new slave."),
+        ("请先断开连接并停止拉流再应用连接档案。", "Disconnect and stop the video stream before applying a connection profile."),
+        ("记录帧时间戳", "Record Frame Timestamps"),
+        ("录制时将每帧的 PTS 与到达上位机的系统时间写入与录像同名的 .timestamps.csv 文件，用于对齐多个机位的录像；到达时间为软件时间戳，包含网络与解码延迟，机位之间的误差通常为数十毫秒，无法代替硬件同步", "Write each frame's PTS and its arrival time on the host to a .timestamps.csv file named after the recording, for aligning recordings from multiple slaves. Arrival times are software timestamps that include network and decoding delays; the error between slaves is typically tens of milliseconds and cannot replace hardware synchronization"),
+        ("画面 PTS（微秒）", "Frame PTS (μs)"),
+        ("画面到达时间（Unix 微秒）", "Frame Arrival Time (Unix μs)"),
+        ("请先开始调试再载入参数档案", "Start debugging before loading a parameter profile"),
+        ("添加", "Add"),
+        ("下位机拒绝保存参数，重启后将恢复原有参数", "The slave refused to save the parameters, they will be reverted after it restarts"),
+        ("组合键", "Button Chords"),
+        ("在短时间内同时按下一组按键以触发指定的动作，按住组合键期间这些按键原本的功能不会生效", "Press a group of buttons together to trigger an action; the buttons' own functions are suppressed while the chord is held"),
+        ("添加组合键", "Add Chord"),
+        ("未定义组合键", "No chords defined"),
+        ("组合键中的按键不能重复。", "A chord cannot contain the same button twice."),
+        ("紧急停止", "Emergency stop"),
+        ("紧急上浮（按住时）", "Emergency surface (while held)"),
+        ("开始/停止录制", "Start/stop recording"),
+        ("切换深度锁定", "Toggle depth lock"),
+        ("切换方向锁定", "Toggle direction lock"),
+        ("已紧急停止，所有运动输出已清零。", "Emergency stop, all motion outputs were reset."),
+        ("每秒钟向机器人发送的控制数据包的个数，该值越高意味着控制越灵敏，但在较差的网络条件下可能产生更大的延迟", "Control packets sent to the robot per second; higher values mean more responsive control but may increase latency on poor networks"),
+        ("视频", "Video"),
+        ("显示", "Display"),
+        ("上位机的显示的画面设置", "Video display settings of the host"),
+        ("默认保持长宽比", "Keep Aspect Ratio by Default"),
+        ("画面边框颜色", "Letterbox Color"),
+        ("保持长宽比时画面两侧空白区域的填充颜色", "Color filling the empty area around the video when keeping the aspect ratio"),
+        ("在改变窗口大小的时是否保持画面比例，这可能导致画面无法全屏", "Whether to keep the video aspect ratio when resizing, which may prevent the video from filling the window"),
+        ("管道", "Pipeline"),
+        ("配置拉流以及录制所使用的管道", "Configure the pipelines used for streaming and recording"),
+        ("默认视频 URL", "Default Video URL"),
+        ("第一机位使用的视频 URL，其他机位会自动累加端口，", "Video URL used by the first slave; other slaves increment the port, "),
+        ("默认画面缓存策略", "Default Display Buffering"),
+        ("默认手动配置管道", "Configure Pipeline Manually by Default"),
+        ("默认接收缓冲区延迟", "Default Receive Buffer Latency"),
+        ("若接收的视频流出现卡顿、花屏等现象，可以增加接收缓冲区延迟，牺牲视频的实时性来换取流畅度的提升", "If the stream stutters or shows artifacts, increase the receive buffer latency to trade real-time response for smoothness"),
+        ("默认解码器", "Default Decoder"),
+        ("指定解码视频流默认使用的解码器", "Decoder used by default to decode the video stream"),
+        ("默认解码器接口", "Default Decoder Interface"),
+        ("指定解码视频流默认使用的解码器接口", "Decoder interface used by default to decode the video stream"),
+        ("默认色彩空间转换", "Default Colorspace Conversion"),
+        ("设置视频编解码、视频流显示要求的色彩空间转换所使用的默认硬件", "Hardware used by default for colorspace conversion during coding and display"),
+        ("管道等待超时", "Pipeline Timeout"),
+        ("由于网络等原因，管道可能失去响应，超过设定时间后上位机将强制终止管道，设置为 0 以禁用等待超时（需要重启管道以应用设置）", "The pipeline may stop responding due to network issues; it is forcibly terminated after this time. Set to 0 to disable (restart the pipeline to apply)"),
+        ("秒", "s"),
+        ("截图", "Screenshots"),
+        ("画面的截图选项", "Screenshot options"),
+        ("图片保存目录", "Image Directory"),
+        ("恢复默认目录", "Restore default directory"),
+        ("更改", "Change"),
+        ("图片保存格式", "Image Format"),
+        ("截图保存的图片格式", "Image format of saved screenshots"),
+        ("录制", "Recording"),
+        ("视频流的录制选项", "Video recording options"),
+        ("视频保存目录", "Video Directory"),
+        ("同步录制时使用单独文件夹", "Separate Folder for Synchronized Recording"),
+        ("每次进行同步录制时，都在视频保存目录下创建新的文件夹，并在其中保存录制的视频文件", "Create a new folder in the video directory for each synchronized recording and save the videos in it"),
+        ("默认录制时重新编码", "Re-encode Recordings by Default"),
+        ("默认编码器", "Default Encoder"),
+        ("视频录制时默认使用的编码器", "Encoder used by default when recording"),
+        ("默认编码器接口", "Default Encoder Interface"),
+        ("视频录制时默认调用的编码器接口", "Encoder interface used by default when recording"),
+        ("调试", "Debugging"),
+        ("控制环", "Control Loops"),
+        ("配置控制环调试选项", "Control loop debugging options"),
+        ("反馈曲线最大点数", "Feedback Curve Point Limit"),
+        ("绘制控制环反馈曲线时使用最多使用点数，这将影响最多能观测的历史数据", "Maximum points drawn on control loop feedback curves, which limits the visible history"),
+        ("反馈曲线更新时间间隔", "Feedback Curve Update Interval"),
+        ("控制环反馈曲线的更新速率，这将影响最多能观测的历史数据", "Update rate of control loop feedback curves, which affects the visible history"),
+        ("原生 (软件)", "Native (Software)"),
+        ("FFMPEG (软件)", "FFMPEG (Software)"),
+        ("NVIDIA (硬件)", "NVIDIA (Hardware)"),
+        ("VAAPI (硬件)", "VAAPI (Hardware)"),
+        ("Direct3D 11 (硬件)", "Direct3D 11 (Hardware)"),
+        ("无法解析 URL", "Unable to parse URL"),
+        ("连接 URL 仅支持 http 与 https 协议", "Connection URL must use http or https"),
+        ("连接 URL 不支持 ", "Connection URL does not support the "),
+        (" 协议，仅支持 http 与 https 协议", " scheme, only http and https are supported"),
+        ("连接 URL 缺少主机地址", "Connection URL is missing a host"),
+        ("连接 URL 缺少端口", "Connection URL is missing a port"),
+        ("视频 URL 缺少主机地址", "Video URL is missing a host"),
+        ("视频 URL 缺少端口", "Video URL is missing a port"),
+        ("视频 URL 仅支持 rtp、udp 与 rtsp 协议", "Video URL must use rtp, udp or rtsp"),
+        ("自动连接", "Auto Connect"),
+        ("启动上位机后自动连接该机位的下位机，连接失败时不会重试", "Connect to this slave automatically on startup, without retrying if the connection fails"),
+        ("自动拉流", "Auto Stream"),
+        ("启动上位机后自动启动该机位的视频流拉流", "Start receiving this slave's video stream automatically on startup"),
+        ("通讯", "Communication"),
+        ("设置下位机的通讯选项", "Communication options of the slave"),
+        ("连接 URL", "Connection URL"),
+        ("连接下位机使用的 URL，", "URL used to connect to the slave, "),
+        ("调整机位控制选项", "Control options of the slave"),
+        ("交换 X/Y 轴", "Swap X/Y Axes"),
+        ("若下位机规定的 X/Y 轴与上位机不一致，可以使用此选项进行交换", "Swap the X/Y axes if the slave defines them differently from the host"),
+        ("画面", "Video"),
+        ("上位机端对画面进行的处理选项", "Processing applied to the video by the host"),
+        ("保持长宽比", "Keep Aspect Ratio"),
+        ("增强算法", "Enhancement Algorithm"),
+        ("对画面使用的增强算法", "Enhancement algorithm applied to the video"),
+        ("无", "None"),
+        ("顺时针 90°", "90° Clockwise"),
+        ("180°", "180°"),
+        ("逆时针 90°", "90° Counterclockwise"),
+        ("水平翻转", "Horizontal"),
+        ("垂直翻转", "Vertical"),
+        ("旋转", "Rotation"),
+        ("裁剪画面", "Crop"),
+        ("取样与测量", "Sampling and Measurement"),
+        ("直方图", "Histogram"),
+        ("在画面右上角显示 RGB 直方图，用于检查曝光是否过度或不足", "Show an RGB histogram in the top right corner to check for over- or underexposure"),
+        ("点击画面以读取像素坐标与颜色，再次点击以测量两点间的像素距离", "Click the video to read pixel coordinates and color; click again to measure the distance between two points in pixels"),
+        ("距离：", "Distance: "),
+        (" 像素", " px"),
+        ("仅显示画面中的指定区域，各项数值为相对画面宽高的比例，同样作用于截图", "Only show a region of the video, given as fractions of the frame size; also applies to screenshots"),
+        ("左边距", "Left"),
+        ("上边距", "Top"),
+        ("宽度", "Width"),
+        ("高度", "Height"),
+        ("对画面进行的旋转，同样作用于截图", "Rotation applied to the video, including screenshots"),
+        ("翻转", "Flip"),
+        ("在旋转之后对画面进行的翻转，同样作用于截图", "Flip applied to the video after rotation, including screenshots"),
+        ("录制时旋转与翻转", "Rotate and Flip Recordings"),
+        ("对录制的视频应用与画面相同的旋转与翻转，未重新编码时录制的视频始终保持原样", "Apply the same rotation and flip to recordings; recordings that are not re-encoded are always kept as received"),
+        ("配置视频流接收以及录制所使用的管道", "Configure the pipelines used for receiving and recording the stream"),
+        ("视频流 URL", "Video Stream URL"),
+        ("配置机位视频流的 URL，", "URL of the video stream of the slave, "),
+        ("画面缓存策略", "Display Buffering"),
+        ("低延迟优先时画面落后于视频流会自动跳帧，完整性优先时不跳帧并缓存最多 120 帧，会占用更多内存并可能增大延迟", "Prefer latency drops frames when the display falls behind the stream; prefer completeness keeps every frame and buffers up to 120 frames, which uses more memory and may add latency"),
+        ("低延迟优先", "Prefer Latency"),
+        ("完整性优先", "Prefer Completeness"),
+        ("手动配置管道", "Configure Pipeline Manually"),
+        ("接收缓冲区延迟", "Receive Buffer Latency"),
+        ("可以增加接收缓冲区延迟，牺牲视频的实时性来换取流畅度的提升", "Increase the receive buffer latency to trade real-time response for smoothness"),
+        ("色彩空间转换", "Colorspace Conversion"),
+        ("设置视频编解码、视频流显示要求的色彩空间转换所使用的硬件", "Hardware used for colorspace conversion during coding and display"),
+        ("解码器", "Decoder"),
+        ("解码视频流使用的解码器", "Decoder used to decode the video stream"),
+        ("解码器接口", "Decoder Interface"),
+        ("解码视频流使用的解码器接口", "Decoder interface used to decode the video stream"),
+        ("录制时重新编码", "Re-encode Recordings"),
+        ("编码器", "Encoder"),
+        ("视频录制时使用的编码器", "Encoder used when recording"),
+        ("编码器接口", "Encoder Interface"),
+        ("视频录制时调用的编码器接口", "Encoder interface used when recording"),
+        ("左前", "Front Left"),
+        ("右前", "Front Right"),
+        ("左后", "Back Left"),
+        ("右后", "Back Right"),
+        ("左中", "Center Left"),
+        ("右中", "Center Right"),
+        ("深度锁定", "Depth Lock"),
+        ("方向锁定", "Direction Lock"),
+        ("启用", "Enabled"),
+        ("反转", "Reversed"),
+        ("正向动力", "Forward Power"),
+        ("反向动力", "Backward Power"),
+        ("死区上限", "Dead Zone Upper Bound"),
+        ("死区下限", "Dead Zone Lower Bound"),
+        ("推进器", "Propellers"),
+        ("PWM 控制器", "PWM Controller"),
+        ("频率校准", "Frequency Calibration"),
+        ("推进器参数", "Propeller Parameters"),
+        ("PID 参数", "PID Parameters"),
+        ("参数调校", "Parameter Tuning"),
+        ("保存", "Save"),
+        ("读取", "Load"),
+        ("档案", "Profiles"),
+        ("保存档案", "Save Profile"),
+        ("将当前的参数保存为档案，以便之后重新载入", "Save the current parameters as a profile to reload later"),
+        ("档案名称", "Profile Name"),
+        ("已保存的档案", "Saved Profiles"),
+        ("载入档案会将其中的参数逐项发送至下位机，被拒绝的参数将恢复原值", "Loading a profile sends its parameters to the slave one by one; rejected parameters are reverted"),
+        ("操作", "Actions"),
+        ("载入", "Load"),
+        ("导出", "Export"),
+        ("删除", "Delete"),
+        ("删除档案", "Delete Profile"),
+        ("确定要删除所选的参数档案吗？", "Delete the selected parameter profile?"),
+        ("导入档案", "Import Profile"),
+        ("从 JSON 文件导入他人分享的参数档案", "Import a shared parameter profile from a JSON file"),
+        ("参数档案", "Parameter Profile"),
+        ("无法读取参数档案：", "Unable to read parameter profile: "),
+        ("参数档案格式错误：", "Invalid parameter profile: "),
+        ("无法写入参数档案：", "Unable to write parameter profile: "),
+        ("参数档案已保存：", "Parameter profile saved: "),
+        ("下位机已接受档案中的全部参数", "The slave accepted all parameters in the profile"),
+        ("下位机拒绝的参数数量：", "Parameters rejected by the slave: "),
+        ("无法删除参数档案：", "Unable to delete parameter profile: "),
+        ("参数档案已导出至：", "Parameter profile exported to: "),
+        ("无效的档案名称", "Invalid profile name"),
+        ("参数超出允许范围，未发送：", "Parameter out of the allowed range, not sent: "),
+        ("档案中的参数超出允许范围，已取消载入", "The profile contains parameters out of the allowed range and was not loaded"),
+        ("响应曲线", "Response Plot"),
+        ("实时绘制所选反馈信号的变化，下位机提供对应的设定值（信号名加 _setpoint）时将以虚线一并绘制", "Plots the selected feedback signal live; its setpoint (the signal name with _setpoint) is drawn dashed when the slave provides it"),
+        ("信号", "Signal"),
+        ("曲线控制", "Plot Controls"),
+        ("暂停", "Pause"),
+        ("警告", "Warning"),
+        ("信息", "Info"),
+        ("跟踪", "Trace"),
+        ("日志", "Logging"),
+        ("上位机的诊断日志，可用于报告问题", "Diagnostic logs of the host, useful for bug reports"),
+        ("日志级别", "Log Level"),
+        ("记录到日志文件的最低级别", "Minimum level written to the log file"),
+        ("日志目录", "Log Directory"),
+        ("日志控制台", "Log Console"),
+        ("复制全部", "Copy All"),
+        ("暂无通知", "No notifications"),
+        ("通知历史", "Notification History"),
+        ("<b>通知历史</b>", "<b>Notification History</b>"),
+        ("清空", "Clear"),
+        ("参数档案已导入：", "Parameter profile imported: "),
+        ("固件更新向导", "Firmware Update Wizard"),
+        ("欢迎使用固件更新向导", "Welcome to the Firmware Update Wizard"),
+        ("请确保固件更新期间机器人有充足的电量供应。", "Make sure the robot has sufficient power during the update."),
+        ("下一步", "Next"),
+        ("请选择固件文件", "Select a Firmware File"),
+        ("选择的固件文件必须为下位机的可执行文件。", "The firmware file must be an executable for the slave."),
+        ("固件文件", "Firmware File"),
+        ("请选择文件", "No file selected"),
+        ("浏览", "Browse"),
+        (" 字节", " bytes"),
+        ("无法读取文件：", "Unable to read file: "),
+        ("正在计算... ", "Calculating... "),
+        ("预期校验和", "Expected Checksum"),
+        ("可选，填写后仅在文件的 SHA-256 与之相符时允许更新", "Optional; if given, the update is only allowed when the SHA-256 of the file matches"),
+        ("确定要使用该固件文件进行更新吗？", "Update the firmware with this file?"),
+        ("开始更新", "Start Update"),
+        ("正在更新固件...", "Updating Firmware..."),
+        ("请不要切断连接或电源。", "Do not disconnect the network or power."),
+        ("固件更新成功", "Firmware Updated"),
+        ("固件更新失败", "Firmware Update Failed"),
+        ("机器人将自动重启，请稍后手动进行连接。", "The robot will restart automatically; reconnect manually later."),
+        ("请检查文件与网络连接是否正常。", "Check the file and the network connection."),
+        ("完成", "Done"),
+        ("无法启动管道，这可能是由于管道使用的资源不存在或被占用导致的，请检查相关资源是否可用。", "Failed to start the pipeline, possibly because a resource it uses is missing or busy. Check that the resources are available."),
+        ("拉流 URL 有误，请检查并修改后重试。", "The stream URL is invalid. Check it and try again."),
+        ("等待管道响应超时，已将其强制终止。", "The pipeline timed out and was terminated."),
+        ("截图保存成功：", "Screenshot saved: "),
+        ("截图保存失败：", "Failed to save screenshot: "),
+        ("无信号", "No Signal"),
+        ("管道元素 ", "Pipeline element "),
+        ("视频流中断，录制已停止，录像文件可能不完整。", "The video stream was interrupted and recording stopped; the recording may be incomplete."),
+        ("视频流已结束", "The video stream has ended"),
+        ("视频流中断，正在尝试重新连接：", "Video stream interrupted, reconnecting: "),
+        ("正在重新连接…", "Reconnecting…"),
+        ("视频流已中断，重试次数：", "Video stream interrupted, attempt "),
+        ("自动重连", "Auto Reconnect"),
+        ("同时录制代理视频", "Record Proxy Video"),
+        ("录制时额外保存一份降低分辨率的视频以便快速回看，使用上方配置的编码器", "Save an additional lower-resolution copy for quick review while recording, using the encoder configured above"),
+        ("代理视频高度", "Proxy Video Height"),
+        ("像素", "px"),
+        ("录制遥测字幕", "Record Telemetry Subtitles"),
+        ("录制时将下位机状态信息写入与录像同名的字幕文件", "Write the slave status information to a subtitle file named after the recording"),
+        ("字幕格式", "Subtitle Format"),
+        ("遥测字幕文件的格式", "Format of the telemetry subtitle file"),
+        ("代理视频的垂直分辨率，宽度按画面比例缩放", "Vertical resolution of the proxy video; the width follows the aspect ratio"),
+        ("RTSP 传输协议", "RTSP Transport"),
+        ("接收 RTSP 视频流所使用的传输协议，自动时依次尝试 UDP 与 TCP，位于 NAT 之后的摄像头通常需要使用 TCP", "Transport protocol used to receive RTSP video streams; Automatic tries UDP and then TCP, and cameras behind NAT usually require TCP"),
+        ("自动", "Automatic"),
+        ("视频流中断时自动重新启动管道，重试间隔逐渐增加，直至手动停止拉流", "Restart the pipeline automatically when the video stream drops, backing off between attempts until streaming is stopped"),
+        (" 出错：", " failed: "),
+        (" 警告：", " warned: "),
+        ("视频流已结束，管道已停止。", "The video stream has ended and the pipeline was stopped."),
+        ("管道已在运行。", "The pipeline is already running."),
+        ("当前没有画面，无法截图。", "There is no picture to take a screenshot of."),
+        ("请点击上方按钮启动视频拉流", "Click the button above to start streaming"),
+        ("无可用设备", "No devices available"),
+        ("电量耗尽", "Battery empty"),
+        ("电量低", "Battery low"),
+        ("电量中等", "Battery medium"),
+        ("电量充足", "Battery full"),
+        ("有线供电", "Wired"),
+        ("手柄电量不足，请及时充电或更换电池：", "Controller battery is low, please charge it or replace the batteries: "),
+        ("手柄已连接：", "Controller connected: "),
+        ("手柄已断开：", "Controller disconnected: "),
+        ("该机位唯一的输入设备已断开，当前无法控制机器人。", "The only input device of this slave was disconnected, the robot cannot be controlled now."),
+        ("断开连接", "Disconnect"),
+        ("停止拉流", "Stop streaming"),
+        ("启动拉流", "Start streaming"),
+        ("画面截图", "Take screenshot"),
+        ("停止录制", "Stop recording"),
+        ("开始录制", "Start recording"),
+        ("供外部脚本查询机位状态并控制连接、录制与截图，仅接受来自本机的请求", "Lets external scripts query the slaves and control connection, recording and screenshots; only local requests are accepted"),
+        ("远程控制接口", "Remote Control API"),
+        ("启用远程控制接口", "Enable Remote Control API"),
+        ("在本机回环地址上提供 HTTP 接口，接口说明见 README（需要重启以应用设置）", "Serve an HTTP API on the loopback address, see the README for details (restart required)"),
+        ("端口", "Port"),
+        ("远程控制接口监听的 TCP 端口", "TCP port the remote control API listens on"),
+        ("开始定时截图", "Start timelapse capture"),
+        ("停止定时截图", "Stop timelapse capture"),
+        ("已截图：", "Captured: "),
+        ("（无画面，已暂停）", " (no video, paused)"),
+        ("定时截图间隔", "Timelapse Interval"),
+        ("定时截图时每两张截图之间的时间间隔，截图保存在截图目录下以开始时间命名的文件夹中", "Time between two timelapse screenshots; they are saved in a folder named after the start time under the screenshot directory"),
+        ("切换当前机位使用的输入设备", "Switch the input devices of this slave"),
+        ("<b>输入设备</b>", "<b>Input Devices</b>"),
+        ("刷新输入设备", "Refresh input devices"),
+        ("固件更新", "Firmware update"),
+        ("机位设置", "Slave settings"),
+        ("移除机位", "Remove slave"),
+        ("<b>深度锁定</b>", "<b>Depth Lock</b>"),
+        ("<b>方向锁定</b>", "<b>Direction Lock</b>"),
+        ("错误", "Error"),
+        ("无法创建 RPC 客户端。", "Failed to create the RPC client."),
+        ("请确保下位机处于连接状态。", "Make sure the slave is connected."),
+        ("下位机通讯错误：", "Slave communication error: "),
+        ("水下机器人上位机", "ROV Host"),
+        ("跨平台的水下机器人上位机程序", "Cross-platform host application for underwater robots"),
+        ("跟随系统", "Follow System"),
+        ("浅色", "Light"),
+        ("暗色", "Dark"),
+        ("停止", "Stop"),
+        ("同步录制", "Sync Recording"),
+        ("切换全屏模式", "Toggle fullscreen"),
+        ("新建机位", "New slave"),
+        ("无机位", "No Slaves"),
+        ("请点击标题栏右侧按钮添加机位", "Click the button on the right of the header bar to add a slave"),
+        ("正在退出…", "Finishing Up…"),
+        ("缺少 GStreamer 插件", "Missing GStreamer Plugins"),
+        ("以下 GStreamer 元素缺失，视频拉流或录制可能无法正常工作，请安装括号中的软件包。下位机的控制功能不受影响。", "The following GStreamer elements are missing, so video streaming or recording may not work. Please install the packages shown in parentheses. Slave control is not affected."),
+        ("正在停止录制并断开与下位机的连接", "Stopping recordings and disconnecting from slaves"),
+        ("关于", "About"),
+        ("无法进行同步录制，请确保所有机位均已启动拉流并未处于录制状态。", "Unable to start synchronized recording. Make sure every slave is streaming and not already recording."),
+        ("© 2021-2023 集美大学水下智能创新实验室", "© 2021-2023 JMU Underwater Robot Laboratory"),
+    ]
+    .into_iter()
+    .collect();
+}
+
+// 界面语言仅在启动时设置一次，修改后需要重启以应用
+pub fn set_language(language: Language) {
+    LANGUAGE.set(language).ok();
+}
+
+pub fn tr(msgid: &'static str) -> &'static str {
+    match LANGUAGE.get().copied().unwrap_or_default() {
+        Language::SimplifiedChinese => msgid,
+        Language::English => ENGLISH_CATALOG.get(msgid).copied().unwrap_or(msgid),
+    }
+}
@@ -17,10 +17,11 @@
  */
 
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     collections::HashMap,
     fmt::Debug,
     ops::Deref,
+    path::Path,
     rc::Rc,
     sync::{Arc, Mutex},
     time::Duration,
@@ -28,18 +29,27 @@ use std::{
 
 use glib::{Continue, Sender};
 
+use derivative::*;
 use fragile::Fragile;
-use sdl2::{event::Event, GameControllerSubsystem, Sdl};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use strum_macros::EnumIter;
+use sdl2::{event::Event, joystick::Joystick, GameControllerSubsystem, JoystickSubsystem, Sdl};
 
 use lazy_static::lazy_static;
 
+use crate::i18n::tr;
+
 pub type Button = sdl2::controller::Button;
 pub type Axis = sdl2::controller::Axis;
 pub type GameController = sdl2::controller::GameController;
+pub type PowerLevel = sdl2::joystick::PowerLevel;
+
+const POWER_LEVEL_POLL_INTERVAL: Duration = Duration::from_secs(10);
 
 #[derive(Hash, Debug, PartialEq, Clone, Eq)]
 pub enum InputSource {
     GameController(u32),
+    Virtual, // 屏幕虚拟摇杆
 }
 
 pub enum InputSystemMessage {
@@ -55,38 +65,383 @@ pub enum InputSourceEvent {
 
 pub struct InputEvent(pub InputSource, pub InputSourceEvent);
 
+#[derive(Debug, Clone)]
+pub enum InputDeviceEvent {
+    Added(InputSource, String),
+    Removed(InputSource, String),
+}
+
+// 输入事件的分发方式：按机位选择的输入设备分发，或全部发送给最后点击画面的机位
+#[derive(EnumIter, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum InputDispatchMode {
+    StaticAssignment,
+    FollowFocus,
+}
+
+impl ToString for InputDispatchMode {
+    fn to_string(&self) -> String {
+        match self {
+            InputDispatchMode::StaticAssignment => tr("固定分配"),
+            InputDispatchMode::FollowFocus => tr("跟随焦点"),
+        }
+        .to_string()
+    }
+}
+
+impl Default for InputDispatchMode {
+    fn default() -> Self {
+        Self::StaticAssignment
+    }
+}
+
+#[derive(EnumIter, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum ResponseCurve {
+    Linear,
+    Squared,
+    Cubic,
+    Custom,
+}
+
+impl ToString for ResponseCurve {
+    fn to_string(&self) -> String {
+        match self {
+            ResponseCurve::Linear => tr("线性"),
+            ResponseCurve::Squared => tr("平方"),
+            ResponseCurve::Cubic => tr("立方"),
+            ResponseCurve::Custom => tr("自定义指数"),
+        }
+        .to_string()
+    }
+}
+
+impl Default for ResponseCurve {
+    fn default() -> Self {
+        Self::Linear
+    }
+}
+
+impl ResponseCurve {
+    pub fn exponent(&self, custom_exponent: f64) -> f64 {
+        match self {
+            ResponseCurve::Linear => 1.0,
+            ResponseCurve::Squared => 2.0,
+            ResponseCurve::Cubic => 3.0,
+            ResponseCurve::Custom => custom_exponent,
+        }
+    }
+
+    // 保持方向不变，将摇杆偏移量的比例按指数缩放，满偏时仍为最大值
+    pub fn apply(&self, custom_exponent: f64, value: i16) -> i16 {
+        let exponent = self.exponent(custom_exponent);
+        if exponent == 1.0 || value == 0 {
+            return value;
+        }
+        let ratio = (value as f64 / i16::MAX as f64).clamp(-1.0, 1.0);
+        (ratio.signum() * ratio.abs().powf(exponent) * i16::MAX as f64).round() as i16
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum AxisGroup {
+    Translation,
+    Rotation,
+}
+
+impl AxisGroup {
+    pub fn from_axis(axis: Axis) -> Option<AxisGroup> {
+        match axis {
+            Axis::LeftX | Axis::LeftY | Axis::RightY => Some(AxisGroup::Translation),
+            Axis::RightX => Some(AxisGroup::Rotation),
+            Axis::TriggerLeft | Axis::TriggerRight => None,
+        }
+    }
+}
+
+// 摇杆反转、响应曲线与扳机触发阈值，在发出输入事件前应用，默认值保持输入原样
+#[derive(Derivative, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[derivative(Default)]
+#[serde(default)]
+pub struct InputAxisSettings {
+    pub invert_left_x: bool,
+    pub invert_left_y: bool,
+    pub invert_right_x: bool,
+    pub invert_right_y: bool,
+    pub trigger_threshold: u8, // 百分比，扳机行程未超过该值时视为未按下
+    pub translation_curve: ResponseCurve, // 左摇杆与右摇杆垂直方向
+    #[derivative(Default(value = "2.0"))]
+    pub translation_exponent: f64,
+    pub rotation_curve: ResponseCurve, // 右摇杆水平方向
+    #[derivative(Default(value = "2.0"))]
+    pub rotation_exponent: f64,
+}
+
+impl InputAxisSettings {
+    pub fn is_inverted(&self, axis: Axis) -> bool {
+        match axis {
+            Axis::LeftX => self.invert_left_x,
+            Axis::LeftY => self.invert_left_y,
+            Axis::RightX => self.invert_right_x,
+            Axis::RightY => self.invert_right_y,
+            Axis::TriggerLeft | Axis::TriggerRight => false,
+        }
+    }
+
+    pub fn set_inverted(&mut self, axis: Axis, inverted: bool) {
+        match axis {
+            Axis::LeftX => self.invert_left_x = inverted,
+            Axis::LeftY => self.invert_left_y = inverted,
+            Axis::RightX => self.invert_right_x = inverted,
+            Axis::RightY => self.invert_right_y = inverted,
+            Axis::TriggerLeft | Axis::TriggerRight => (),
+        }
+    }
+
+    pub fn response_curve(&self, group: AxisGroup) -> (ResponseCurve, f64) {
+        match group {
+            AxisGroup::Translation => (self.translation_curve, self.translation_exponent),
+            AxisGroup::Rotation => (self.rotation_curve, self.rotation_exponent),
+        }
+    }
+
+    pub fn set_response_curve(&mut self, group: AxisGroup, curve: ResponseCurve) {
+        match group {
+            AxisGroup::Translation => self.translation_curve = curve,
+            AxisGroup::Rotation => self.rotation_curve = curve,
+        }
+    }
+
+    pub fn set_response_exponent(&mut self, group: AxisGroup, exponent: f64) {
+        match group {
+            AxisGroup::Translation => self.translation_exponent = exponent,
+            AxisGroup::Rotation => self.rotation_exponent = exponent,
+        }
+    }
+
+    pub fn apply(&self, axis: Axis, value: i16) -> i16 {
+        match axis {
+            Axis::TriggerLeft | Axis::TriggerRight => {
+                let threshold = i16::MAX as i32 * self.trigger_threshold.min(100) as i32 / 100;
+                if (value as i32) <= threshold {
+                    0
+                } else {
+                    value
+                }
+            }
+            axis => {
+                let value = if self.is_inverted(axis) {
+                    value.saturating_neg()
+                } else {
+                    value
+                };
+                match AxisGroup::from_axis(axis) {
+                    Some(group) => {
+                        let (curve, exponent) = self.response_curve(group);
+                        curve.apply(exponent, value)
+                    }
+                    None => value,
+                }
+            }
+        }
+    }
+}
+
+// 可用于组合键的按键，不包括部分手柄才有的背键与触摸板
+pub const CHORD_BUTTONS: [Button; 15] = [
+    Button::A,
+    Button::B,
+    Button::X,
+    Button::Y,
+    Button::LeftShoulder,
+    Button::RightShoulder,
+    Button::LeftStick,
+    Button::RightStick,
+    Button::Back,
+    Button::Start,
+    Button::Guide,
+    Button::DPadUp,
+    Button::DPadDown,
+    Button::DPadLeft,
+    Button::DPadRight,
+];
+
+pub fn button_name(button: Button) -> &'static str {
+    match button {
+        Button::A => "A",
+        Button::B => "B",
+        Button::X => "X",
+        Button::Y => "Y",
+        Button::LeftShoulder => "LB",
+        Button::RightShoulder => "RB",
+        Button::LeftStick => "LS",
+        Button::RightStick => "RS",
+        Button::Back => "Back",
+        Button::Start => "Start",
+        Button::Guide => "Guide",
+        Button::DPadUp => "↑",
+        Button::DPadDown => "↓",
+        Button::DPadLeft => "←",
+        Button::DPadRight => "→",
+        _ => "?",
+    }
+}
+
+#[derive(EnumIter, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum ChordAction {
+    EmergencyStop,
+    EmergencySurface,
+    ToggleRecord,
+    TakeScreenshot,
+    ToggleDepthLocked,
+    ToggleDirectionLocked,
+    SetTrim,
+    ResetTrim,
+}
+
+impl ToString for ChordAction {
+    fn to_string(&self) -> String {
+        match self {
+            ChordAction::EmergencyStop => tr("紧急停止"),
+            ChordAction::EmergencySurface => tr("紧急上浮（按住时）"),
+            ChordAction::ToggleRecord => tr("开始/停止录制"),
+            ChordAction::TakeScreenshot => tr("画面截图"),
+            ChordAction::ToggleDepthLocked => tr("切换深度锁定"),
+            ChordAction::ToggleDirectionLocked => tr("切换方向锁定"),
+            ChordAction::SetTrim => tr("将当前运动输出设为配平"),
+            ChordAction::ResetTrim => tr("清除配平"),
+        }
+        .to_string()
+    }
+}
+
+// 按键以 SDL 的映射名称保存，无法识别的名称在读取时忽略
+mod button_list_serde {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(buttons: &[Button], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(buttons.iter().map(|button| button.string()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Button>, D::Error> {
+        Ok(Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .filter_map(|name| Button::from_string(name))
+            .collect())
+    }
+}
+
+// 同时按下的一组按键，触发时对应按键原本的功能不生效
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ButtonChord {
+    #[serde(with = "button_list_serde")]
+    pub buttons: Vec<Button>,
+    pub action: ChordAction,
+}
+
+impl ToString for ButtonChord {
+    fn to_string(&self) -> String {
+        self.buttons
+            .iter()
+            .map(|button| button_name(*button))
+            .collect::<Vec<_>>()
+            .join(" + ")
+    }
+}
+
 lazy_static! {
-    pub static ref SDL: Result<Fragile<Sdl>, String> = sdl2::init().map(Fragile::new);
+    pub static ref SDL: Result<Fragile<SdlContext>, String> = SdlContext::init()
+        .map(Fragile::new)
+        .map_err(|err| {
+            log::error!("无法初始化 SDL，手柄输入将不可用：{}", err);
+            err
+        });
 }
 
-pub struct InputSystem {
+#[derive(Clone)]
+pub struct SdlContext {
     pub sdl: Sdl,
     pub game_controller_subsystem: GameControllerSubsystem,
+    pub joystick_subsystem: JoystickSubsystem,
+}
+
+impl SdlContext {
+    fn init() -> Result<SdlContext, String> {
+        let sdl = sdl2::init()?;
+        let game_controller_subsystem = sdl.game_controller()?;
+        let joystick_subsystem = sdl.joystick()?;
+        Ok(SdlContext {
+            sdl,
+            game_controller_subsystem,
+            joystick_subsystem,
+        })
+    }
+}
+
+pub struct InputSystem {
+    pub sdl_context: Option<SdlContext>, // SDL 不可用时为 None，此时仅能使用虚拟摇杆
     pub game_controllers: Arc<Mutex<HashMap<u32, GameController>>>, // GameController 在 drop 时会自动断开连接，因此容器来保存
+    pub joysticks: Arc<Mutex<HashMap<u32, Joystick>>>, // 仅用于读取手柄电量
     pub event_sender: Rc<RefCell<Option<Sender<InputEvent>>>>,
+    pub power_level_sender: Rc<RefCell<Option<Sender<(InputSource, PowerLevel)>>>>,
+    pub device_event_sender: Rc<RefCell<Option<Sender<InputDeviceEvent>>>>,
+    pub axis_settings: Rc<RefCell<InputAxisSettings>>,
+    pub virtual_joystick_enabled: Cell<bool>,
     running: Arc<Mutex<bool>>,
 }
 
 impl InputSystem {
     pub fn get_sources(&self) -> Result<Vec<(InputSource, String)>, String> {
-        let num = self.game_controller_subsystem.num_joysticks()?;
-        Ok((0..num)
-            .map(|index| {
-                (
-                    InputSource::GameController(index),
-                    self.game_controller_subsystem
-                        .name_for_index(index)
-                        .unwrap_or("未知设备".to_string()),
-                )
-            })
-            .collect())
+        // 手柄以 SDL 的实例 ID 标识，与输入事件中的 which 一致，设备序号在插拔后会发生变化
+        let mut sources: Vec<(InputSource, String)> = self
+            .game_controllers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, game_controller)| (InputSource::GameController(*id), game_controller.name()))
+            .collect();
+        sources.sort_by_key(|(source, _)| match source {
+            InputSource::GameController(id) => *id,
+            InputSource::Virtual => u32::MAX,
+        });
+        if self.virtual_joystick_enabled.get() {
+            sources.push((InputSource::Virtual, tr("屏幕虚拟摇杆").to_string()));
+        }
+        Ok(sources)
+    }
+
+    // 虚拟摇杆的输入与手柄一样应用摇杆设置，再经由同一通道分发给各机位
+    pub fn send_virtual_event(&self, event: InputSourceEvent) {
+        let event = match event {
+            InputSourceEvent::AxisChanged(axis, value) => {
+                InputSourceEvent::AxisChanged(axis, self.axis_settings.borrow().apply(axis, value))
+            }
+            event => event,
+        };
+        if let Some(sender) = self.event_sender.borrow().as_ref() {
+            sender
+                .send(InputEvent(InputSource::Virtual, event))
+                .unwrap_or_default();
+        }
+    }
+
+    // 无法得知电量（如设备不支持）时返回 None
+    pub fn power_level(&self, source: &InputSource) -> Option<PowerLevel> {
+        match source {
+            InputSource::GameController(id) => self
+                .joysticks
+                .lock()
+                .unwrap()
+                .get(id)
+                .and_then(|joystick| joystick.power_level().ok())
+                .filter(|level| *level != PowerLevel::Unknown),
+            InputSource::Virtual => None,
+        }
     }
 }
 
 impl Debug for InputSystem {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("InputSystem")
-            .field("game_controller_subsystem", &self.game_controller_subsystem)
+            .field("sdl_available", &self.sdl_context.is_some())
             .field("event_sender", &self.event_sender)
             .field("running", &self.running)
             .finish()
@@ -95,54 +450,80 @@ impl Debug for InputSystem {
 
 impl Default for InputSystem {
     fn default() -> Self {
-        let sdl_fragile = Deref::deref(&SDL).clone().unwrap();
-        let sdl = sdl_fragile.get();
-        let game_controller_subsystem = sdl.game_controller().unwrap();
-        InputSystem::new(&sdl, &game_controller_subsystem)
+        let sdl_context = Deref::deref(&SDL)
+            .as_ref()
+            .ok()
+            .map(|sdl_context| sdl_context.get().clone());
+        InputSystem::new(sdl_context)
     }
 }
 
 impl InputSystem {
-    pub fn new(sdl: &Sdl, game_controller_subsystem: &GameControllerSubsystem) -> Self {
+    pub fn new(sdl_context: Option<SdlContext>) -> Self {
         let event_sender: Rc<RefCell<Option<Sender<InputEvent>>>> = Rc::new(RefCell::new(None));
 
         Self {
-            sdl: sdl.clone(),
-            game_controller_subsystem: game_controller_subsystem.clone(),
+            sdl_context,
             game_controllers: Arc::new(Mutex::new(HashMap::new())),
+            joysticks: Arc::new(Mutex::new(HashMap::new())),
             event_sender,
+            power_level_sender: Rc::new(RefCell::new(None)),
+            device_event_sender: Rc::new(RefCell::new(None)),
+            axis_settings: Rc::new(RefCell::new(Default::default())),
+            virtual_joystick_enabled: Cell::new(false),
             running: Arc::new(Mutex::new(false)),
         }
     }
 
+    // 加载 SDL 格式的手柄映射文件（如 gamecontrollerdb.txt），返回加载的映射数量
+    // 已连接的手柄因此得以识别时，SDL 会发出设备连接事件
+    pub fn load_mappings(&self, path: &Path) -> Result<i32, String> {
+        let sdl_context = self
+            .sdl_context
+            .as_ref()
+            .ok_or_else(|| String::from(tr("SDL 不可用")))?;
+        sdl_context
+            .game_controller_subsystem
+            .load_mappings(path)
+            .map_err(|err| err.to_string())
+    }
+
     pub fn run(&self) {
         if *self.running.lock().unwrap() {
             return;
         }
+        let SdlContext {
+            sdl,
+            game_controller_subsystem,
+            joystick_subsystem,
+        } = match &self.sdl_context {
+            Some(sdl_context) => sdl_context.clone(),
+            None => return, // 错误已在初始化 SDL 时记录
+        };
 
-        let available = self
-            .game_controller_subsystem
+        let available = game_controller_subsystem
             .num_joysticks()
             .map_err(|e| format!("Can't enumerate joysticks: {}", e))
             .unwrap();
-        for (id, game_controller) in (0..available).filter_map(|id| {
-            self.game_controller_subsystem
-                .open(id)
-                .ok()
-                .map(|c| (id, c))
-        }) {
-            self.game_controllers
-                .lock()
-                .unwrap()
-                .insert(id, game_controller);
+        for index in 0..available {
+            if let Ok(game_controller) = game_controller_subsystem.open(index) {
+                self.game_controllers
+                    .lock()
+                    .unwrap()
+                    .insert(game_controller.instance_id(), game_controller);
+            }
+            if let Ok(joystick) = joystick_subsystem.open(index) {
+                self.joysticks.lock().unwrap().insert(joystick.instance_id(), joystick);
+            }
         }
 
-        let sdl = self.sdl.clone();
         let sender = self.event_sender.clone();
         let running = self.running.clone();
         *self.running.lock().unwrap() = true;
-        let game_controller_subsystem = self.game_controller_subsystem.clone();
         let game_controllers = self.game_controllers.clone();
+        let joysticks = self.joysticks.clone();
+        let device_event_sender = self.device_event_sender.clone();
+        let axis_settings = self.axis_settings.clone();
         glib::timeout_add_local(Duration::from_millis(16), move || {
             let mut event_pump = sdl.event_pump().expect("Cannot get event pump from SDL");
             if let Some(sender) = sender.as_ref().borrow().as_ref() {
@@ -153,7 +534,10 @@ impl InputSystem {
                         } => sender
                             .send(InputEvent(
                                 InputSource::GameController(which),
-                                InputSourceEvent::AxisChanged(axis, value),
+                                InputSourceEvent::AxisChanged(
+                                    axis,
+                                    axis_settings.borrow().apply(axis, value),
+                                ),
                             ))
                             .unwrap(),
                         Event::ControllerButtonDown { button, which, .. } => sender
@@ -168,16 +552,39 @@ impl InputSystem {
                                 InputSourceEvent::ButtonChanged(button, false),
                             ))
                             .unwrap(),
+                        // 添加事件中的 which 为设备序号，打开后改用实例 ID 标识设备
                         Event::ControllerDeviceAdded { which, .. } => {
                             if let Ok(game_controller) = game_controller_subsystem.open(which) {
+                                let id = game_controller.instance_id();
+                                if let Some(device_event_sender) = device_event_sender.borrow().as_ref() {
+                                    device_event_sender
+                                        .send(InputDeviceEvent::Added(
+                                            InputSource::GameController(id),
+                                            game_controller.name(),
+                                        ))
+                                        .unwrap_or_default();
+                                }
                                 game_controllers
                                     .lock()
                                     .unwrap()
-                                    .insert(which, game_controller);
+                                    .insert(id, game_controller);
+                            }
+                            if let Ok(joystick) = joystick_subsystem.open(which) {
+                                joysticks.lock().unwrap().insert(joystick.instance_id(), joystick);
                             }
                         }
+                        // 移除事件中的 which 为实例 ID
                         Event::ControllerDeviceRemoved { which, .. } => {
-                            game_controllers.lock().unwrap().remove(&which);
+                            let removed = game_controllers.lock().unwrap().remove(&which);
+                            joysticks.lock().unwrap().remove(&which);
+                            if let Some(device_event_sender) = device_event_sender.borrow().as_ref() {
+                                let name = removed
+                                    .map(|game_controller| game_controller.name())
+                                    .unwrap_or_else(|| tr("未知设备").to_string());
+                                device_event_sender
+                                    .send(InputDeviceEvent::Removed(InputSource::GameController(which), name))
+                                    .unwrap_or_default();
+                            }
                         }
                         Event::Quit { .. } => break,
                         _ => (),
@@ -188,6 +595,28 @@ impl InputSystem {
             }
             Continue(*running.clone().lock().unwrap())
         });
+
+        // 定时读取各手柄的电量，仅在电量变化时通知
+        let joysticks = self.joysticks.clone();
+        let power_level_sender = self.power_level_sender.clone();
+        let running = self.running.clone();
+        let mut power_levels: HashMap<u32, PowerLevel> = HashMap::new();
+        glib::timeout_add_local(POWER_LEVEL_POLL_INTERVAL, move || {
+            let joysticks = joysticks.lock().unwrap();
+            power_levels.retain(|id, _| joysticks.contains_key(id));
+            if let Some(sender) = power_level_sender.as_ref().borrow().as_ref() {
+                for (id, joystick) in joysticks.iter() {
+                    if let Ok(level) = joystick.power_level() {
+                        if power_levels.insert(*id, level) != Some(level) {
+                            sender
+                                .send((InputSource::GameController(*id), level))
+                                .unwrap_or_default();
+                        }
+                    }
+                }
+            }
+            Continue(*running.lock().unwrap())
+        });
     }
 
     pub fn stop(&self) {
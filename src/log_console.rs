@@ -0,0 +1,159 @@
+/* log_console.rs
+ *
+ * Copyright 2021-2022 Bohong Huang
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use adw::{prelude::*, HeaderBar, Window};
+use glib::{clone, Sender};
+use gtk::{
+    Box as GtkBox, Button, Inhibit, Orientation, PolicyType, ScrolledWindow, TextBuffer, TextView,
+    WrapMode,
+};
+use relm4::{send, ComponentUpdate, Model, Widgets};
+use relm4_macros::widget;
+
+use crate::i18n::tr;
+use crate::logger;
+use crate::{AppModel, AppMsg};
+
+const LOG_CONSOLE_LINE_LIMIT: i32 = 1000;
+
+pub struct LogConsoleModel {
+    buffer: TextBuffer,
+    scroll_to_end: bool,
+}
+
+pub enum LogConsoleMsg {
+    AppendLine(String),
+    CopyAll,
+    Clear,
+}
+
+impl Model for LogConsoleModel {
+    type Msg = LogConsoleMsg;
+    type Widgets = LogConsoleWidgets;
+    type Components = ();
+}
+
+#[widget(pub)]
+impl Widgets<LogConsoleModel, AppModel> for LogConsoleWidgets {
+    view! {
+        window = Window {
+            set_title: Some(tr("日志控制台")),
+            set_transient_for: parent!(Some(&parent_widgets.app_window)),
+            set_destroy_with_parent: true,
+            set_default_width: 720,
+            set_default_height: 480,
+            connect_close_request => move |window| {
+                window.hide();
+                Inhibit(true)
+            },
+            set_content = Some(&GtkBox) {
+                set_orientation: Orientation::Vertical,
+                append = &HeaderBar {
+                    pack_start = &Button {
+                        set_label: tr("复制全部"),
+                        connect_clicked(sender) => move |_button| {
+                            send!(sender, LogConsoleMsg::CopyAll);
+                        },
+                    },
+                    pack_end = &Button {
+                        set_icon_name: "edit-clear-all-symbolic",
+                        set_tooltip_text: Some(tr("清空")),
+                        connect_clicked(sender) => move |_button| {
+                            send!(sender, LogConsoleMsg::Clear);
+                        },
+                    },
+                },
+                append: scrolled_window = &ScrolledWindow {
+                    set_vexpand: true,
+                    set_hscrollbar_policy: PolicyType::Never,
+                    set_child: text_view = Some(&TextView) {
+                        set_buffer: Some(&model.buffer),
+                        set_editable: false,
+                        set_cursor_visible: false,
+                        set_monospace: true,
+                        set_wrap_mode: WrapMode::WordChar,
+                        set_left_margin: 6,
+                        set_right_margin: 6,
+                    },
+                },
+            },
+        }
+    }
+
+    fn post_init() {
+        logger::subscribe().attach(
+            None,
+            clone!(@strong sender => move |line| {
+                send!(sender, LogConsoleMsg::AppendLine(line));
+                Continue(true)
+            }),
+        );
+    }
+
+    fn post_view() {
+        if model.scroll_to_end {
+            let mut end = model.buffer.end_iter();
+            self.text_view.scroll_to_iter(&mut end, 0.0, false, 0.0, 1.0);
+        }
+    }
+}
+
+impl ComponentUpdate<AppModel> for LogConsoleModel {
+    fn init_model(_parent_model: &AppModel) -> Self {
+        LogConsoleModel {
+            buffer: TextBuffer::new(None),
+            scroll_to_end: false,
+        }
+    }
+
+    fn update(
+        &mut self,
+        msg: LogConsoleMsg,
+        _components: &(),
+        _sender: Sender<LogConsoleMsg>,
+        _parent_sender: Sender<AppMsg>,
+    ) {
+        self.scroll_to_end = false;
+        match msg {
+            LogConsoleMsg::AppendLine(line) => {
+                let mut end = self.buffer.end_iter();
+                if self.buffer.char_count() > 0 {
+                    self.buffer.insert(&mut end, "\n");
+                }
+                self.buffer.insert(&mut end, &line);
+                let excess = self.buffer.line_count() - LOG_CONSOLE_LINE_LIMIT;
+                if excess > 0 {
+                    // 仅保留最近的日志，防止占用过多内存
+                    let mut start = self.buffer.start_iter();
+                    let mut cut = self.buffer.iter_at_line(excess).unwrap_or_else(|| self.buffer.end_iter());
+                    self.buffer.delete(&mut start, &mut cut);
+                }
+                self.scroll_to_end = true;
+            }
+            LogConsoleMsg::CopyAll => {
+                let (start, end) = self.buffer.bounds();
+                if let Some(display) = gdk::Display::default() {
+                    display
+                        .clipboard()
+                        .set_text(&self.buffer.text(&start, &end, false));
+                }
+            }
+            LogConsoleMsg::Clear => self.buffer.set_text(""),
+        }
+    }
+}
@@ -0,0 +1,219 @@
+/* logger.rs
+ *
+ * Copyright 2021-2022 Bohong Huang
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::{
+    collections::VecDeque,
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::Mutex,
+};
+
+use glib::{DateTime, MainContext, Receiver, Sender, PRIORITY_DEFAULT};
+use log::{LevelFilter, Log, Metadata, Record};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
+
+use crate::i18n::tr;
+use crate::preferences::get_data_path;
+
+const LOG_FILE_NAME: &str = "rov-host.log";
+const LOG_FILE_MAX_SIZE: u64 = 4 * 1024 * 1024;
+const LOG_FILE_MAX_BACKUPS: usize = 3;
+const LOG_HISTORY_LIMIT: usize = 1000;
+
+// 供日志控制台使用的最近日志及其订阅者
+#[derive(Default)]
+struct LogHistory {
+    lines: VecDeque<String>,
+    subscribers: Vec<Sender<String>>,
+}
+
+static LOG_HISTORY: Lazy<Mutex<LogHistory>> = Lazy::new(Default::default);
+static FILE_LEVEL: Lazy<Mutex<LevelFilter>> = Lazy::new(|| Mutex::new(LevelFilter::Info));
+
+#[derive(EnumIter, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl ToString for LogLevel {
+    fn to_string(&self) -> String {
+        match self {
+            LogLevel::Error => tr("错误"),
+            LogLevel::Warn => tr("警告"),
+            LogLevel::Info => tr("信息"),
+            LogLevel::Debug => tr("调试"),
+            LogLevel::Trace => tr("跟踪"),
+        }
+        .to_string()
+    }
+}
+
+impl Default for LogLevel {
+    fn default() -> Self {
+        Self::Info
+    }
+}
+
+impl LogLevel {
+    pub fn level_filter(&self) -> LevelFilter {
+        match self {
+            LogLevel::Error => LevelFilter::Error,
+            LogLevel::Warn => LevelFilter::Warn,
+            LogLevel::Info => LevelFilter::Info,
+            LogLevel::Debug => LevelFilter::Debug,
+            LogLevel::Trace => LevelFilter::Trace,
+        }
+    }
+}
+
+pub fn get_log_path() -> PathBuf {
+    let mut log_path = get_data_path();
+    log_path.push("Logs");
+    if !log_path.exists() {
+        fs::create_dir(log_path.clone()).expect("无法创建日志文件夹");
+    }
+    log_path
+}
+
+struct FileLogger {
+    directory: PathBuf,
+    file: Mutex<Option<File>>,
+}
+
+impl FileLogger {
+    fn log_file_path(&self, index: usize) -> PathBuf {
+        let mut path = self.directory.clone();
+        match index {
+            0 => path.push(LOG_FILE_NAME),
+            index => path.push(format!("{}.{}", LOG_FILE_NAME, index)),
+        }
+        path
+    }
+
+    fn open(&self) -> Option<File> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.log_file_path(0))
+            .ok()
+    }
+
+    // 日志文件超出大小限制时依次重命名为 rov-host.log.1、rov-host.log.2 等
+    fn rotate(&self) {
+        for index in (0..LOG_FILE_MAX_BACKUPS).rev() {
+            let from = self.log_file_path(index);
+            if from.exists() {
+                fs::rename(from, self.log_file_path(index + 1)).unwrap_or_default();
+            }
+        }
+    }
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let timestamp = DateTime::now_local()
+            .and_then(|time| time.format("%F %T"))
+            .map(|time| time.to_string())
+            .unwrap_or_default();
+        let line = format!(
+            "{} {:<5} [{}] {}",
+            timestamp,
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        if let Ok(mut history) = LOG_HISTORY.lock() {
+            history.lines.push_back(line.clone());
+            while history.lines.len() > LOG_HISTORY_LIMIT {
+                history.lines.pop_front();
+            }
+            history
+                .subscribers
+                .retain(|subscriber| subscriber.send(line.clone()).is_ok());
+        }
+        if record.level() > *FILE_LEVEL.lock().unwrap() {
+            return;
+        }
+        eprintln!("{}", line);
+        if let Ok(mut file) = self.file.lock() {
+            if file
+                .as_ref()
+                .and_then(|file| file.metadata().ok())
+                .map_or(false, |metadata| metadata.len() >= LOG_FILE_MAX_SIZE)
+            {
+                *file = None;
+                self.rotate();
+            }
+            if file.is_none() {
+                *file = self.open();
+            }
+            if let Some(file) = file.as_mut() {
+                writeln!(file, "{}", line).unwrap_or_default();
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(file) = file.as_mut() {
+                file.flush().unwrap_or_default();
+            }
+        }
+    }
+}
+
+pub fn init(level: LogLevel) {
+    let logger = FileLogger {
+        directory: get_log_path(),
+        file: Mutex::new(None),
+    };
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        set_level(level);
+    }
+}
+
+// 日志控制台始终显示调试级别的事件，日志文件则按照设置的级别记录
+pub fn set_level(level: LogLevel) {
+    *FILE_LEVEL.lock().unwrap() = level.level_filter();
+    log::set_max_level(level.level_filter().max(LevelFilter::Debug));
+}
+
+// 订阅日志，接收端将先收到已保留的最近日志
+pub fn subscribe() -> Receiver<String> {
+    let (sender, receiver) = MainContext::channel(PRIORITY_DEFAULT);
+    let mut history = LOG_HISTORY.lock().unwrap();
+    for line in history.lines.iter() {
+        sender.send(line.clone()).unwrap_or_default();
+    }
+    history.subscribers.push(sender);
+    receiver
+}
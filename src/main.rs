@@ -17,23 +17,41 @@
  */
 
 pub mod async_glib;
+pub mod check;
+pub mod cli;
+pub mod file_name;
 pub mod function;
+pub mod i18n;
 pub mod input;
+pub mod log_console;
+pub mod logger;
 pub mod preferences;
 pub mod prelude;
+pub mod remote_api;
 pub mod slave;
 pub mod ui;
 
-use std::{cell::RefCell, fs, net::Ipv4Addr, ops::Deref, rc::Rc, str::FromStr};
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashSet, VecDeque},
+    net::Ipv4Addr,
+    ops::Deref,
+    path::{Path, PathBuf},
+    rc::Rc,
+    str::FromStr,
+    time::Duration,
+};
 
 use adw::{
     prelude::*, ApplicationWindow, CenteringPolicy, ColorScheme, HeaderBar, StatusPage,
-    StyleManager,
+    StyleManager, Toast, ToastOverlay,
 };
 use glib::{clone, DateTime, MainContext, Sender, WeakRef, PRIORITY_DEFAULT};
 use gtk::{
-    AboutDialog, Align, Box as GtkBox, Button, Grid, Image, Inhibit, Label, License, MenuButton,
-    Orientation, Separator, Stack, ToggleButton,
+    AboutDialog, Align, Box as GtkBox, Button, CenterBox, CssProvider, Grid, Image, Inhibit, Label,
+    License, ListBox, MenuButton, NamedAction, Orientation, Popover, PropagationPhase, SelectionMode,
+    Separator, Shortcut, ShortcutController, ShortcutLabel, ShortcutTrigger, Spinner, Stack,
+    ToggleButton, Widget,
 };
 use relm4::{
     actions::{RelmAction, RelmActionGroup},
@@ -47,12 +65,26 @@ use derivative::*;
 use serde::{Deserialize, Serialize};
 use strum_macros::EnumIter;
 
-use crate::input::{InputEvent, InputSystem};
+use crate::async_glib::{Future, Promise};
+use crate::cli::CommandLineArgs;
+use crate::file_name::SaveDirectoryScheme;
+use crate::i18n::tr;
+use crate::input::{
+    InputDeviceEvent, InputDispatchMode, InputEvent, InputSource, InputSystem, PowerLevel,
+};
+use crate::log_console::LogConsoleModel;
 use crate::preferences::{PreferencesModel, PreferencesMsg};
+use crate::remote_api::RemoteApiRequest;
 use crate::slave::{
-    slave_config::SlaveConfigModel, slave_video::SlaveVideoMsg, MyComponent, SlaveModel, SlaveMsg,
+    connection_profile::ConnectionProfile,
+    slave_config::{parse_slave_url, parse_video_url, SlaveConfigModel, SlaveSession},
+    slave_video::SlaveVideoMsg,
+    MyComponent, SlaveModel, SlaveMsg, SlaveShortcut,
 };
-use crate::ui::generic::error_message;
+use crate::ui::generic::{confirm_message, error_message, number_message};
+
+const AUTO_CONNECT_INTERVAL_MILLIS: u64 = 500;
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
 
 struct AboutModel {}
 enum AboutMsg {}
@@ -75,9 +107,9 @@ impl Widgets<AboutModel, AppModel> for AboutWidgets {
             },
             set_website: Some("https://github.com/BohongHuang/rov-host"),
             set_authors: &["黄博宏 https://bohonghuang.github.io", "彭剑锋 https://qff233.com"],
-            set_program_name: Some("水下机器人上位机"),
-            set_copyright: Some("© 2021-2023 集美大学水下智能创新实验室"),
-            set_comments: Some("跨平台的水下机器人上位机程序"),
+            set_program_name: Some(tr("水下机器人上位机")),
+            set_copyright: Some(tr("© 2021-2023 集美大学水下智能创新实验室")),
+            set_comments: Some(tr("跨平台的水下机器人上位机程序")),
             set_logo_icon_name: Some("input-gaming"),
             set_version: Some(env!("CARGO_PKG_VERSION")),
             set_license_type: License::Gpl30,
@@ -109,9 +141,9 @@ pub enum AppColorScheme {
 impl ToString for AppColorScheme {
     fn to_string(&self) -> String {
         match self {
-            AppColorScheme::FollowSystem => "跟随系统",
-            AppColorScheme::Light => "浅色",
-            AppColorScheme::Dark => "暗色",
+            AppColorScheme::FollowSystem => tr("跟随系统"),
+            AppColorScheme::Light => tr("浅色"),
+            AppColorScheme::Dark => tr("暗色"),
         }
         .to_string()
     }
@@ -137,6 +169,130 @@ pub struct AppModel {
     preferences: Rc<RefCell<PreferencesModel>>,
     #[no_eq]
     input_system: Rc<InputSystem>,
+    #[no_eq]
+    #[derivative(Default(value = "CssProvider::new()"))]
+    css_provider: CssProvider,
+    shutting_down: Option<bool>,
+    toast_messages: Rc<RefCell<VecDeque<String>>>,
+    #[no_eq]
+    close_allowed: Rc<Cell<bool>>,
+    #[no_eq]
+    command_line_sessions: Option<Vec<SlaveSession>>, // 由命令行指定时代替已保存的机位配置
+    #[no_eq]
+    focused_slave: Option<*const SlaveModel>, // 最后点击画面的机位
+    #[no_eq]
+    duplicate_url_messages: HashSet<String>, // 已提示过的重复 URL，避免每次修改配置时重复提示
+}
+
+impl AppModel {
+    fn add_slave(
+        &mut self,
+        slave_config: SlaveConfigModel,
+        app_window: WeakRef<ApplicationWindow>,
+        sender: &Sender<AppMsg>,
+    ) -> Sender<SlaveMsg> {
+        let (input_event_sender, input_event_receiver) = MainContext::channel(PRIORITY_DEFAULT);
+        let (slave_event_sender, slave_event_receiver) = MainContext::channel(PRIORITY_DEFAULT);
+        let slave = SlaveModel::new(
+            slave_config,
+            self.get_preferences().clone(),
+            &slave_event_sender,
+            input_event_sender,
+        );
+        let component = MyComponent::new(slave, (sender.clone(), app_window));
+        let component_sender = component.sender().clone();
+        input_event_receiver.attach(
+            None,
+            clone!(@strong component_sender => move |event| {
+                component_sender.send(SlaveMsg::InputReceived(event)).unwrap();
+                Continue(true)
+            }),
+        );
+        slave_event_receiver.attach(
+            None,
+            clone!(@strong component_sender => move |event| {
+                component_sender.send(event).unwrap();
+                Continue(true)
+            }),
+        );
+        self.get_mut_slaves().push(component);
+        self.set_sync_recording(Some(false));
+        component_sender
+    }
+
+    fn save_slave_sessions(&self) {
+        if self.command_line_sessions.is_some() {
+            return; // 命令行指定的机位不覆盖已保存的机位配置
+        }
+        let sessions: Vec<SlaveSession> = self
+            .slaves
+            .iter()
+            .map(|slave| slave.model().unwrap().get_config().model().unwrap().to_session())
+            .collect();
+        if let Err(err) = SlaveSession::save_all(&sessions) {
+            log::warn!("无法保存机位配置：{}", err);
+        }
+    }
+
+    // 多个机位连接同一下位机会争夺控制权并成倍占用视频带宽，仅对新出现的重复 URL 给出提示
+    fn check_duplicate_urls(&mut self) {
+        let mut slave_urls = HashSet::new();
+        let mut video_urls = HashSet::new();
+        let mut messages = HashSet::new();
+        for slave in self.slaves.iter() {
+            let slave_model = slave.model().unwrap();
+            let config = slave_model.get_config().model().unwrap();
+            if !slave_urls.insert(config.get_slave_url().to_string()) {
+                messages.insert(format!("{}{}", tr("多个机位使用了相同的连接 URL："), config.get_slave_url()));
+            }
+            if !video_urls.insert(config.get_video_url().to_string()) {
+                messages.insert(format!("{}{}", tr("多个机位使用了相同的视频 URL："), config.get_video_url()));
+            }
+        }
+        for message in messages.difference(&self.duplicate_url_messages) {
+            log::warn!("{}", message);
+            self.toast_messages.borrow_mut().push_back(message.clone());
+        }
+        self.duplicate_url_messages = messages;
+    }
+
+    // 以跟随焦点方式分配输入时接收输入的机位序号
+    fn input_focused_slave_index(&self) -> Option<usize> {
+        if *self.preferences.borrow().get_input_dispatch_mode() != InputDispatchMode::FollowFocus {
+            return None;
+        }
+        let focused_slave = self.focused_slave?;
+        self.slaves.iter().position(|component| {
+            Deref::deref(&component.model().unwrap()) as *const SlaveModel == focused_slave
+        })
+    }
+
+    // 快捷键操作的机位：最后点击画面的机位，仅有一个机位时为该机位
+    fn shortcut_slave_index(&self) -> Option<usize> {
+        if self.slaves.len() == 1 {
+            return Some(0);
+        }
+        let focused_slave = self.focused_slave?;
+        self.slaves.iter().position(|component| {
+            Deref::deref(&component.model().unwrap()) as *const SlaveModel == focused_slave
+        })
+    }
+
+    fn update_input_focus(&self) {
+        let focused_index = self.input_focused_slave_index();
+        for (index, slave) in self.slaves.iter().enumerate() {
+            send!(slave.sender(), SlaveMsg::SetInputFocused(focused_index == Some(index)));
+        }
+    }
+
+    fn update_css(&self) {
+        let preferences = self.preferences.borrow();
+        let color = gdk::RGBA::parse(preferences.get_video_letterbox_color())
+            .unwrap_or_else(|_| gdk::RGBA::new(0.0, 0.0, 0.0, 1.0));
+        self.css_provider.load_from_data(
+            format!(".video-frame {{ background-color: {}; }}", color.to_string()).as_bytes(),
+        );
+    }
 }
 
 impl Model for AppModel {
@@ -148,15 +304,60 @@ impl Model for AppModel {
 new_action_group!(AppActionGroup, "main");
 new_stateless_action!(PreferencesAction, AppActionGroup, "preferences");
 new_stateless_action!(AboutDialogAction, AppActionGroup, "about");
+new_stateless_action!(LogConsoleAction, AppActionGroup, "log-console");
+new_stateless_action!(NewSlaveAction, AppActionGroup, "new-slave");
+new_stateless_action!(NewSlavesAction, AppActionGroup, "new-slaves");
+new_stateless_action!(ToggleFullscreenAction, AppActionGroup, "toggle-fullscreen");
+new_stateless_action!(ToggleConnectAction, AppActionGroup, "toggle-connect");
+new_stateless_action!(TogglePollingAction, AppActionGroup, "toggle-polling");
+new_stateless_action!(ToggleRecordAction, AppActionGroup, "toggle-record");
+new_stateless_action!(TakeScreenshotAction, AppActionGroup, "take-screenshot");
+
+// 快捷键、对应的动作与说明，除全屏外均作用于最后点击画面的机位
+const KEYBOARD_SHORTCUTS: [(&str, &str, &str); 5] = [
+    ("f", "main.toggle-fullscreen", "切换全屏模式"),
+    ("c", "main.toggle-connect", "连接或断开连接"),
+    ("p", "main.toggle-polling", "启动或停止拉流"),
+    ("r", "main.toggle-record", "开始或停止录制"),
+    ("s", "main.take-screenshot", "画面截图"),
+];
+
+fn keyboard_shortcuts_list_box() -> Widget {
+    let list_box = ListBox::builder().selection_mode(SelectionMode::None).build();
+    for (accelerator, _, description) in KEYBOARD_SHORTCUTS {
+        let row = CenterBox::builder()
+            .margin_top(4)
+            .margin_bottom(4)
+            .margin_start(8)
+            .margin_end(8)
+            .build();
+        row.set_start_widget(Some(&Label::new(Some(tr(description)))));
+        row.set_end_widget(Some(&ShortcutLabel::builder().accelerator(accelerator).margin_start(20).build()));
+        list_box.append(&row);
+    }
+    list_box.upcast()
+}
+
+// 单键快捷键在冒泡阶段处理，输入框获得焦点时按键先由输入框处理，输入文字时不会触发
+fn keyboard_shortcut_controller() -> ShortcutController {
+    let controller = ShortcutController::new();
+    controller.set_propagation_phase(PropagationPhase::Bubble);
+    for (accelerator, action, _) in KEYBOARD_SHORTCUTS {
+        controller.add_shortcut(&Shortcut::new(
+            ShortcutTrigger::parse_string(accelerator).as_ref(),
+            Some(&NamedAction::new(action)),
+        ));
+    }
+    controller
+}
 
 #[widget(pub)]
 impl Widgets<AppModel, ()> for AppWidgets {
     view! {
         app_window = ApplicationWindow::default() {
-            set_title: Some("水下机器人上位机"),
-            set_default_width: 1280,
-            set_default_height: 720,
+            set_title: Some(tr("水下机器人上位机")),
             set_icon_name: Some("input-gaming"),
+            set_maximized: *model.get_preferences().borrow().get_window_maximized(),
             set_fullscreened: track!(model.changed(AppModel::fullscreened()), *model.get_fullscreened()),
             set_content = Some(&GtkBox) {
                 set_orientation: Orientation::Vertical,
@@ -171,7 +372,7 @@ impl Widgets<AppModel, ()> for AppWidgets {
                                 set_icon_name?: watch!(model.sync_recording.map(|x| Some(if x { "media-playback-stop-symbolic" } else { "media-record-symbolic" })))
                             },
                             append = &Label {
-                                set_label?: watch!(model.sync_recording.map(|x| if x { "停止" } else { "同步录制" })),
+                                set_label?: watch!(model.sync_recording.map(|x| if x { tr("停止") } else { tr("同步录制") })),
                             },
                         },
                         set_visible: track!(model.changed(AppModel::slaves()), model.slaves.len() > 1),
@@ -185,9 +386,35 @@ impl Widgets<AppModel, ()> for AppWidgets {
                         set_focus_on_click: false,
                         set_valign: Align::Center,
                     },
+                    pack_end = &MenuButton {
+                        set_icon_name: "input-keyboard-symbolic",
+                        set_tooltip_text: Some(tr("键盘快捷键")),
+                        set_focus_on_click: false,
+                        set_valign: Align::Center,
+                        set_popover = Some(&Popover) {
+                            set_child = Some(&GtkBox) {
+                                set_spacing: 5,
+                                set_orientation: Orientation::Vertical,
+                                append = &Label {
+                                    set_margin_start: 10,
+                                    set_margin_end: 10,
+                                    set_markup: tr("<b>键盘快捷键</b>"),
+                                },
+                                append: &keyboard_shortcuts_list_box(),
+                                append = &Label {
+                                    add_css_class: "dim-label",
+                                    set_margin_start: 10,
+                                    set_margin_end: 10,
+                                    set_wrap: true,
+                                    set_max_width_chars: 32,
+                                    set_label: tr("除全屏外均作用于最后点击画面的机位，在输入框中输入时不会触发"),
+                                },
+                            },
+                        },
+                    },
                     pack_end = &ToggleButton {
                         set_icon_name: "view-fullscreen-symbolic",
-                        set_tooltip_text: Some("切换全屏模式"),
+                        set_tooltip_text: Some(tr("切换全屏模式")),
                         set_active: track!(model.changed(AppModel::fullscreened()), *model.get_fullscreened()),
                         connect_clicked(sender) => move |button| {
                             send!(sender, AppMsg::SetFullscreened(button.is_active()));
@@ -196,7 +423,7 @@ impl Widgets<AppModel, ()> for AppWidgets {
                     pack_end = &Separator {},
                     pack_end = &Button {
                         set_icon_name: "list-remove-symbolic",
-                        set_tooltip_text: Some("移除机位"),
+                        set_tooltip_text: Some(tr("移除机位")),
                         set_sensitive: track!(model.changed(AppModel::sync_recording()) || model.changed(AppModel::slaves()), model.get_slaves().len() > 0 && *model.get_sync_recording() ==  Some(false)),
                         connect_clicked(sender) => move |_button| {
                             send!(sender, AppMsg::RemoveLastSlave);
@@ -204,30 +431,55 @@ impl Widgets<AppModel, ()> for AppWidgets {
                     },
                     pack_end = &Button {
                         set_icon_name: "list-add-symbolic",
-                        set_tooltip_text: Some("新建机位"),
+                        set_tooltip_text: Some(tr("新建机位")),
                         set_sensitive: track!(model.changed(AppModel::sync_recording()), model.sync_recording == Some(false)),
                         connect_clicked[sender = sender.clone(), window = app_window.clone().downgrade()] => move |_button| {
                             send!(sender, AppMsg::NewSlave(window.clone()));
                         },
                     },
                 },
-                append: body_stack = &Stack {
-                    set_hexpand: true,
-                    set_vexpand: true,
-                    add_child: welcome_page = &StatusPage {
-                        set_icon_name: Some("window-new-symbolic"),
-                        set_title: "无机位",
-                        set_description: Some("请点击标题栏右侧按钮添加机位"),
-                    },
-                    add_child: slaves_page = &Grid {
-                        set_column_homogeneous: true,
-                        set_row_homogeneous: true,
-                        factory!(model.slaves),
+                append = &ToastOverlay {
+                    add_toast?: watch!(model.toast_messages.borrow_mut().pop_front().map(|x| Toast::new(&x)).as_ref()),
+                    set_child: body_stack = Some(&Stack) {
+                        set_hexpand: true,
+                        set_vexpand: true,
+                        add_child: welcome_page = &StatusPage {
+                            set_icon_name: Some("window-new-symbolic"),
+                            set_title: tr("无机位"),
+                            set_description: Some(tr("请点击标题栏右侧按钮添加机位")),
+                        },
+                        add_child: slaves_page = &Grid {
+                            set_column_homogeneous: true,
+                            set_row_homogeneous: true,
+                            factory!(model.slaves),
+                        },
+                        add_child: shutdown_page = &StatusPage {
+                            set_title: tr("正在退出…"),
+                            set_description: Some(tr("正在停止录制并断开与下位机的连接")),
+                            set_child = Some(&Spinner) {
+                                set_spinning: true,
+                                set_width_request: 32,
+                                set_height_request: 32,
+                            },
+                        },
                     },
                 },
             },
-            connect_close_request(sender) => move |_window| {
+            connect_close_request[sender = sender.clone(), preferences = model.get_preferences().clone(), close_allowed = model.close_allowed.clone()] => move |window| {
+                if !close_allowed.get() {
+                    send!(sender, AppMsg::RequestShutdown(window.downgrade()));
+                    return Inhibit(true); // 等待录制结束与断开连接后再关闭窗口
+                }
                 send!(sender, AppMsg::StopInputSystem);
+                let mut preferences = preferences.borrow_mut();
+                if !window.is_maximized() && !window.is_fullscreen() {
+                    let (width, height) = window.default_size();
+                    preferences.set_window_width(width);
+                    preferences.set_window_height(height);
+                }
+                preferences.set_window_maximized(window.is_maximized());
+                preferences.set_window_fullscreened(window.is_fullscreen());
+                preferences.save_to_file().unwrap_or_default(); // 窗口关闭后主循环随即退出，因此需要同步保存
                 Inhibit(false)
             },
         }
@@ -235,7 +487,10 @@ impl Widgets<AppModel, ()> for AppWidgets {
 
     menu! {
         main_menu: {
+            "新建机位"     => NewSlaveAction,
+            "新建多个机位…" => NewSlavesAction,
             "首选项"     => PreferencesAction,
+            "日志控制台" => LogConsoleAction,
             "关于"       => AboutDialogAction,
         }
     }
@@ -248,9 +503,30 @@ impl Widgets<AppModel, ()> for AppWidgets {
                 self.body_stack.set_visible_child(&self.slaves_page);
             }
         }
+        if model.changed(AppModel::shutting_down()) {
+            match model.shutting_down {
+                Some(false) => self.app_window.close(),
+                Some(true) => self.body_stack.set_visible_child(&self.shutdown_page),
+                None => (),
+            }
+        }
     }
 
     fn post_init() {
+        let (width, height) = {
+            let preferences = model.get_preferences().borrow();
+            clamp_window_size(
+                *preferences.get_window_width(),
+                *preferences.get_window_height(),
+            )
+        };
+        app_window.set_default_size(width, height);
+        gtk::StyleContext::add_provider_for_display(
+            &app_window.display(),
+            &model.css_provider,
+            gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+        );
+        model.update_css();
         send!(
             components.preferences.sender(),
             PreferencesMsg::SetApplicationColorScheme(None)
@@ -265,12 +541,91 @@ impl Widgets<AppModel, ()> for AppWidgets {
             RelmAction::new_stateless(clone!(@strong sender => move |_| {
                 send!(sender, AppMsg::OpenAboutDialog);
             }));
+        let action_log_console: RelmAction<LogConsoleAction> =
+            RelmAction::new_stateless(clone!(@strong sender => move |_| {
+                send!(sender, AppMsg::OpenLogConsole);
+            }));
+        let action_new_slave: RelmAction<NewSlaveAction> =
+            RelmAction::new_stateless(clone!(@strong sender, @strong app_window => move |_| {
+                send!(sender, AppMsg::NewSlaves(app_window.downgrade(), 1));
+            }));
+        let action_new_slaves: RelmAction<NewSlavesAction> =
+            RelmAction::new_stateless(clone!(@strong sender, @strong app_window => move |_| {
+                send!(sender, AppMsg::RequestNewSlaves(app_window.downgrade()));
+            }));
+
+        let action_toggle_fullscreen: RelmAction<ToggleFullscreenAction> =
+            RelmAction::new_stateless(clone!(@strong sender => move |_| {
+                send!(sender, AppMsg::ToggleFullscreened);
+            }));
+        let action_toggle_connect: RelmAction<ToggleConnectAction> =
+            RelmAction::new_stateless(clone!(@strong sender => move |_| {
+                send!(sender, AppMsg::SlaveShortcut(SlaveShortcut::ToggleConnect));
+            }));
+        let action_toggle_polling: RelmAction<TogglePollingAction> =
+            RelmAction::new_stateless(clone!(@strong sender => move |_| {
+                send!(sender, AppMsg::SlaveShortcut(SlaveShortcut::TogglePolling));
+            }));
+        let action_toggle_record: RelmAction<ToggleRecordAction> =
+            RelmAction::new_stateless(clone!(@strong sender => move |_| {
+                send!(sender, AppMsg::SlaveShortcut(SlaveShortcut::ToggleRecord));
+            }));
+        let action_take_screenshot: RelmAction<TakeScreenshotAction> =
+            RelmAction::new_stateless(clone!(@strong sender => move |_| {
+                send!(sender, AppMsg::SlaveShortcut(SlaveShortcut::TakeScreenshot));
+            }));
+
+        // menu! 宏只接受字面量，因此在此处替换为翻译后的菜单项
+        main_menu.remove_all();
+        main_menu.append(Some(tr("新建机位")), Some("main.new-slave"));
+        main_menu.append(Some(tr("新建多个机位…")), Some("main.new-slaves"));
+        main_menu.append(Some(tr("首选项")), Some("main.preferences"));
+        main_menu.append(Some(tr("日志控制台")), Some("main.log-console"));
+        main_menu.append(Some(tr("关于")), Some("main.about"));
 
         app_group.add_action(action_preferences);
         app_group.add_action(action_about);
+        app_group.add_action(action_log_console);
+        app_group.add_action(action_new_slave);
+        app_group.add_action(action_new_slaves);
+        app_group.add_action(action_toggle_fullscreen);
+        app_group.add_action(action_toggle_connect);
+        app_group.add_action(action_toggle_polling);
+        app_group.add_action(action_toggle_record);
+        app_group.add_action(action_take_screenshot);
         app_window.insert_action_group("main", Some(&app_group.into_action_group()));
-        for _ in 0..*model.get_preferences().borrow().get_initial_slave_num() {
-            send!(sender, AppMsg::NewSlave(app_window.clone().downgrade()));
+        app_window.add_controller(&keyboard_shortcut_controller());
+        match model.command_line_sessions.clone() {
+            Some(sessions) => {
+                for session in sessions {
+                    send!(sender, AppMsg::RestoreSlave(app_window.clone().downgrade(), session));
+                }
+            }
+            // 按照初始机位数量新建机位，已保存配置的机位恢复其配置，多出的配置不再恢复
+            None => {
+                let initial_slave_num = *model.get_preferences().borrow().get_initial_slave_num() as usize;
+                let mut sessions = SlaveSession::load_all().into_iter();
+                for _ in 0..initial_slave_num {
+                    match sessions.next() {
+                        Some(session) => send!(sender, AppMsg::RestoreSlave(app_window.clone().downgrade(), session)),
+                        None => send!(sender, AppMsg::NewSlave(app_window.clone().downgrade())),
+                    }
+                }
+            }
+        }
+
+        send!(sender, AppMsg::CheckGstElements(app_window.clone().downgrade()));
+
+        if *model.get_preferences().borrow().get_remote_api_enabled() {
+            let (request_sender, request_receiver) = MainContext::channel(PRIORITY_DEFAULT);
+            request_receiver.attach(
+                None,
+                clone!(@strong sender => move |request| {
+                    send!(sender, AppMsg::RemoteApiRequest(request));
+                    Continue(true)
+                }),
+            );
+            remote_api::start(*model.get_preferences().borrow().get_remote_api_port(), request_sender);
         }
 
         let (input_event_sender, input_event_receiver) = MainContext::channel(PRIORITY_DEFAULT);
@@ -283,27 +638,106 @@ impl Widgets<AppModel, ()> for AppWidgets {
                 Continue(true)
             }),
         );
+
+        let (power_level_sender, power_level_receiver) = MainContext::channel(PRIORITY_DEFAULT);
+        *model.input_system.power_level_sender.borrow_mut() = Some(power_level_sender);
+
+        power_level_receiver.attach(
+            None,
+            clone!(@strong sender => move |(source, level)| {
+                send!(sender, AppMsg::InputPowerLevelChanged(source, level));
+                Continue(true)
+            }),
+        );
+
+        let (device_event_sender, device_event_receiver) = MainContext::channel(PRIORITY_DEFAULT);
+        *model.input_system.device_event_sender.borrow_mut() = Some(device_event_sender);
+
+        device_event_receiver.attach(
+            None,
+            clone!(@strong sender => move |event| {
+                send!(sender, AppMsg::InputDeviceChanged(event));
+                Continue(true)
+            }),
+        );
+    }
+}
+
+// 按整个地址进行累加以便向前一段进位，并跳过以 .0 和 .255 结尾的地址
+fn offset_ipv4_address(ip: Ipv4Addr, offset: u32) -> Ipv4Addr {
+    let mut address = u32::from(ip);
+    for _ in 0..offset {
+        loop {
+            address = address.wrapping_add(1);
+            if !matches!(address & 0xFF, 0 | 0xFF) {
+                break;
+            }
+        }
+    }
+    Ipv4Addr::from(address)
+}
+
+fn clamp_window_size(width: i32, height: i32) -> (i32, i32) {
+    const MIN_WIDTH: i32 = 640;
+    const MIN_HEIGHT: i32 = 360;
+    let max_size = gdk::Display::default().and_then(|display| {
+        let monitors = display.monitors();
+        (0..monitors.n_items())
+            .filter_map(|index| monitors.item(index))
+            .filter_map(|monitor| monitor.downcast::<gdk::Monitor>().ok())
+            .map(|monitor| {
+                let geometry = monitor.geometry();
+                (geometry.width(), geometry.height())
+            })
+            .max_by_key(|(width, height)| width * height)
+    }); // 无法得知窗口将位于哪一显示器，因此以最大的显示器为准
+    match max_size {
+        Some((max_width, max_height)) => (
+            width.clamp(MIN_WIDTH.min(max_width), max_width),
+            height.clamp(MIN_HEIGHT.min(max_height), max_height),
+        ),
+        None => (width.max(MIN_WIDTH), height.max(MIN_HEIGHT)),
     }
 }
 
 pub enum AppMsg {
     NewSlave(WeakRef<ApplicationWindow>),
+    NewSlaves(WeakRef<ApplicationWindow>, u8),
+    RequestNewSlaves(WeakRef<ApplicationWindow>),
+    RestoreSlave(WeakRef<ApplicationWindow>, SlaveSession),
+    NewSlaveFromConnectionProfile(WeakRef<ApplicationWindow>, ConnectionProfile),
+    ConnectionProfilesChanged,
+    SaveSlaveSessions,
     RemoveLastSlave,
     DestroySlave(*const SlaveModel),
+    VirtualJoystickAssigned(*const SlaveModel),
+    LoadControllerMappings(PathBuf),
     DispatchInputEvent(InputEvent),
+    FocusSlave(*const SlaveModel),
+    InputPowerLevelChanged(InputSource, PowerLevel),
+    InputDeviceChanged(InputDeviceEvent),
     PreferencesUpdated(PreferencesModel),
     SetColorScheme(AppColorScheme),
     ToggleSyncRecording(WeakRef<ApplicationWindow>),
     SetFullscreened(bool),
+    ToggleFullscreened,
+    SlaveShortcut(SlaveShortcut),
     OpenAboutDialog,
     OpenPreferencesWindow,
+    OpenLogConsole,
     StopInputSystem,
+    CheckGstElements(WeakRef<ApplicationWindow>),
+    RemoteApiRequest(RemoteApiRequest),
+    RequestShutdown(WeakRef<ApplicationWindow>), // 有机位正在录制或已锁定深度、方向时先确认再退出
+    Shutdown,
+    ShutdownCompleted,
 }
 
 #[derive(relm4_macros::Components)]
 pub struct AppComponents {
     about: RelmComponent<AboutModel, AppModel>,
     preferences: RelmComponent<PreferencesModel, AppModel>,
+    log_console: RelmComponent<LogConsoleModel, AppModel>,
 }
 
 impl AppUpdate for AppModel {
@@ -316,6 +750,9 @@ impl AppUpdate for AppModel {
             AppMsg::OpenPreferencesWindow => {
                 components.preferences.root_widget().present();
             }
+            AppMsg::OpenLogConsole => {
+                components.log_console.root_widget().present();
+            }
             AppMsg::NewSlave(app_window) => {
                 let index = self.get_slaves().len() as u8;
                 let mut slave_url: url::Url = self
@@ -327,10 +764,8 @@ impl AppUpdate for AppModel {
                     .host_str()
                     .and_then(|str| Ipv4Addr::from_str(str).ok())
                 {
-                    let mut ip_octets = ip.octets();
-                    ip_octets[3] = ip_octets[3].wrapping_add(index);
                     slave_url
-                        .set_host(Some(Ipv4Addr::from(ip_octets).to_string().as_str()))
+                        .set_host(Some(offset_ipv4_address(ip, index as u32).to_string().as_str()))
                         .unwrap_or_default();
                 }
                 let mut video_url = self
@@ -343,56 +778,178 @@ impl AppUpdate for AppModel {
                         .set_port(Some(port.wrapping_add(index as u16)))
                         .unwrap();
                 }
-                let (input_event_sender, input_event_receiver) =
-                    MainContext::channel(PRIORITY_DEFAULT);
-                let (slave_event_sender, slave_event_receiver) =
-                    MainContext::channel(PRIORITY_DEFAULT);
                 let mut slave_config =
                     SlaveConfigModel::from_preferences(&self.preferences.borrow());
                 slave_config.set_slave_url(slave_url);
                 slave_config.set_video_url(video_url);
-                slave_config.set_keep_video_display_ratio(
-                    *self
-                        .get_preferences()
-                        .borrow()
-                        .get_default_keep_video_display_ratio(),
-                );
-                let slave = SlaveModel::new(
-                    slave_config,
-                    self.get_preferences().clone(),
-                    &slave_event_sender,
-                    input_event_sender,
-                );
-                let component = MyComponent::new(slave, (sender.clone(), app_window));
-                let component_sender = component.sender().clone();
-                input_event_receiver.attach(
-                    None,
-                    clone!(@strong component_sender => move |event| {
-                        component_sender.send(SlaveMsg::InputReceived(event)).unwrap();
-                        Continue(true)
-                    }),
-                );
-                slave_event_receiver.attach(
-                    None,
-                    clone!(@strong component_sender => move |event| {
-                        component_sender.send(event).unwrap();
-                        Continue(true)
+                self.add_slave(slave_config, app_window, &sender);
+                self.save_slave_sessions();
+                self.check_duplicate_urls();
+            }
+            // 依次新建机位，每个机位的地址与端口按照新建时的机位数量递增
+            AppMsg::NewSlaves(app_window, num) => {
+                if *self.get_sync_recording() == Some(true) {
+                    return true; // 同步录制期间不能新建机位
+                }
+                for _ in 0..num {
+                    send!(sender, AppMsg::NewSlave(app_window.clone()));
+                }
+            }
+            AppMsg::RequestNewSlaves(app_window) => {
+                let initial_slave_num = *self.get_preferences().borrow().get_initial_slave_num();
+                number_message(
+                    tr("新建多个机位"),
+                    tr("请输入要新建的机位数量"),
+                    (1.0, 12.0),
+                    initial_slave_num.max(1) as f64,
+                    app_window.upgrade().as_ref(),
+                    clone!(@strong sender, @strong app_window => move |num| {
+                        send!(sender, AppMsg::NewSlaves(app_window.clone(), num as u8));
                     }),
-                );
-                self.get_mut_slaves().push(component);
-                self.set_sync_recording(Some(false));
+                )
+                .present();
+            }
+            AppMsg::RestoreSlave(app_window, session) => {
+                let index = self.get_slaves().len() as u32;
+                let mut slave_config =
+                    SlaveConfigModel::from_preferences(&self.preferences.borrow());
+                slave_config.apply_session(session.clone());
+                let slave_sender = self.add_slave(slave_config, app_window, &sender);
+                self.check_duplicate_urls();
+                let auto_connect = session.auto_connect
+                    && match parse_slave_url(session.slave_url.as_str()) {
+                        Ok(_) => true,
+                        Err(err) => {
+                            log::warn!("[{}] 无法自动连接：{}", session.slave_url, err);
+                            false
+                        }
+                    };
+                let auto_polling = session.auto_polling
+                    && match parse_video_url(session.video_url.as_str()) {
+                        Ok(_) => true,
+                        Err(err) => {
+                            log::warn!("[{}] 无法自动拉流：{}", session.video_url, err);
+                            false
+                        }
+                    };
+                if auto_connect || auto_polling {
+                    // 错开各机位的连接时机，避免同时发起大量连接
+                    glib::timeout_add_local_once(
+                        Duration::from_millis(AUTO_CONNECT_INTERVAL_MILLIS * index as u64),
+                        move || {
+                            if auto_connect {
+                                slave_sender.send(SlaveMsg::Connect).unwrap_or_default();
+                            }
+                            if auto_polling {
+                                slave_sender.send(SlaveMsg::TogglePolling).unwrap_or_default();
+                            }
+                        },
+                    );
+                }
+            }
+            AppMsg::NewSlaveFromConnectionProfile(app_window, profile) => {
+                if *self.get_sync_recording() == Some(true) {
+                    return true; // 同步录制期间不能新建机位
+                }
+                let mut slave_config =
+                    SlaveConfigModel::from_preferences(&self.preferences.borrow());
+                slave_config.apply_connection_profile(profile);
+                self.add_slave(slave_config, app_window, &sender);
+                self.save_slave_sessions();
+                self.check_duplicate_urls();
+            }
+            // 连接档案由某一机位保存后，其余机位也需重新读取
+            AppMsg::ConnectionProfilesChanged => {
+                for slave in self.slaves.iter() {
+                    send!(slave.sender(), SlaveMsg::ReloadConnectionProfiles);
+                }
+            }
+            AppMsg::SaveSlaveSessions => {
+                self.save_slave_sessions();
+                self.check_duplicate_urls(); // 机位配置修改后也会保存机位配置
             }
             AppMsg::PreferencesUpdated(preferences) => {
+                *self.input_system.axis_settings.borrow_mut() =
+                    preferences.get_input_axis_settings().clone();
+                let virtual_joystick_enabled = *preferences.get_virtual_joystick_enabled();
+                if self.input_system.virtual_joystick_enabled.replace(virtual_joystick_enabled)
+                    != virtual_joystick_enabled
+                {
+                    for slave in self.slaves.iter() {
+                        if !virtual_joystick_enabled {
+                            send!(slave.sender(), SlaveMsg::RemoveInputSource(InputSource::Virtual));
+                        }
+                        send!(slave.sender(), SlaveMsg::UpdateInputSources);
+                    }
+                }
                 *self.get_mut_preferences().borrow_mut() = preferences;
+                self.update_css();
+                self.update_input_focus();
             }
             AppMsg::DispatchInputEvent(InputEvent(source, event)) => {
-                for slave in self.slaves.iter() {
+                // 跟随焦点时输入只发送给获得焦点的机位，尚无机位获得焦点时仍按固定分配发送
+                let focused_index = self.input_focused_slave_index();
+                for (index, slave) in self.slaves.iter().enumerate() {
                     let slave_model = slave.model().unwrap();
-                    if slave_model.get_input_sources().contains(&source) {
+                    let receives = match focused_index {
+                        Some(focused_index) => index == focused_index,
+                        None => slave_model.get_input_sources().contains(&source),
+                    };
+                    if receives {
                         slave_model.input_event_sender.send(event.clone()).unwrap();
                     }
                 }
             }
+            AppMsg::FocusSlave(slave_ptr) => {
+                self.focused_slave = Some(slave_ptr);
+                self.update_input_focus();
+            }
+            AppMsg::VirtualJoystickAssigned(slave_ptr) => {
+                // 虚拟摇杆只显示在最后选择它的机位上
+                for slave in self.slaves.iter() {
+                    let slave_model = slave.model().unwrap();
+                    if Deref::deref(&slave_model) as *const SlaveModel != slave_ptr
+                        && slave_model.get_input_sources().contains(&InputSource::Virtual)
+                    {
+                        send!(slave.sender(), SlaveMsg::RemoveInputSource(InputSource::Virtual));
+                        send!(slave.sender(), SlaveMsg::UpdateInputSources);
+                    }
+                }
+            }
+            AppMsg::LoadControllerMappings(path) => {
+                let message = load_controller_mappings(&self.input_system, &path);
+                send!(components.preferences.sender(), PreferencesMsg::ShowToastMessage(message));
+            }
+            AppMsg::InputPowerLevelChanged(source, level) => {
+                log::debug!("输入设备 {:?} 的电量变为 {:?}", source, level);
+                for slave in self.slaves.iter() {
+                    send!(slave.sender(), SlaveMsg::InputPowerLevelChanged(source.clone(), level));
+                }
+            }
+            AppMsg::InputDeviceChanged(event) => {
+                let message = match &event {
+                    InputDeviceEvent::Added(_, name) => format!("{}{}", tr("手柄已连接："), name),
+                    InputDeviceEvent::Removed(_, name) => format!("{}{}", tr("手柄已断开："), name),
+                };
+                log::info!("{}", message);
+                self.toast_messages.borrow_mut().push_back(message);
+                if let InputDeviceEvent::Removed(source, _) = event {
+                    for slave in self.slaves.iter() {
+                        let slave_model = slave.model().unwrap();
+                        let input_sources = slave_model.get_input_sources();
+                        if input_sources.len() == 1 && input_sources.contains(&source) {
+                            log::warn!("[{}] 唯一的输入设备已断开", slave_model.get_config().model().unwrap().get_slave_url());
+                            send!(
+                                slave.sender(),
+                                SlaveMsg::ShowToastMessage(String::from(tr("该机位唯一的输入设备已断开，当前无法控制机器人。")))
+                            );
+                        }
+                    }
+                }
+                for slave in self.slaves.iter() {
+                    send!(slave.sender(), SlaveMsg::UpdateInputSources);
+                }
+            }
             AppMsg::ToggleSyncRecording(window) => {
                 match *self.get_sync_recording() {
                     Some(recording) => {
@@ -401,18 +958,45 @@ impl AppUpdate for AppModel {
                                 *x.model().unwrap().get_polling() == Some(true)
                                     && *x.model().unwrap().get_recording() == Some(false)
                             }) {
+                                let shortage = self.preferences.borrow().video_save_space_shortage(false);
+                                if let Some(space) = shortage {
+                                    error_message(
+                                        tr("错误"),
+                                        &format!("{}{} MB", tr("视频保存目录所在磁盘的可用空间不足，无法开始录制。剩余空间："), space),
+                                        window.upgrade().as_ref(),
+                                    )
+                                    .present();
+                                    return true;
+                                }
                                 let timestamp = DateTime::now_local()
                                     .unwrap()
                                     .format_iso8601()
                                     .unwrap()
                                     .replace(":", "-");
+                                let directory = {
+                                    let preferences = self.preferences.borrow();
+                                    let separate = *preferences.get_video_sync_record_use_separate_directory();
+                                    preferences
+                                        .get_save_directory_scheme()
+                                        .prepare_directory(&preferences.video_save_directory())
+                                        .and_then(|directory| if separate {
+                                            SaveDirectoryScheme::Flat.prepare_directory(&directory.join(&timestamp))
+                                        } else {
+                                            Ok(directory)
+                                        })
+                                };
+                                let directory = match directory {
+                                    Ok(directory) => directory,
+                                    Err(msg) => {
+                                        self.toast_messages.borrow_mut().push_back(msg);
+                                        return true;
+                                    }
+                                };
                                 for (index, component) in self.slaves.iter().enumerate() {
                                     let model = component.model().unwrap();
                                     let preferences = self.preferences.borrow();
-                                    let mut pathbuf = preferences.get_video_save_path().clone();
+                                    let mut pathbuf = directory.clone();
                                     if *preferences.get_video_sync_record_use_separate_directory() {
-                                        pathbuf.push(&timestamp);
-                                        fs::create_dir_all(&pathbuf).unwrap();
                                         pathbuf.push(format!("{}.mkv", index + 1));
                                     } else {
                                         pathbuf.push(format!("{}_{}.mkv", &timestamp, index + 1));
@@ -424,14 +1008,14 @@ impl AppUpdate for AppModel {
                                 }
                                 self.set_sync_recording(Some(true));
                             } else {
-                                error_message("错误", "无法进行同步录制，请确保所有机位均已启动拉流并未处于录制状态。", window.upgrade().as_ref()).present();
+                                error_message(tr("错误"), tr("无法进行同步录制，请确保所有机位均已启动拉流并未处于录制状态。"), window.upgrade().as_ref()).present();
                             }
                         } else {
                             for (_index, component) in self.get_slaves().iter().enumerate() {
                                 let model = component.model().unwrap();
                                 model
                                     .get_video()
-                                    .send(SlaveVideoMsg::StopRecord(None))
+                                    .send(SlaveVideoMsg::StopRecord(None, None))
                                     .unwrap();
                             }
                             self.set_sync_recording(Some(false));
@@ -443,6 +1027,112 @@ impl AppUpdate for AppModel {
             AppMsg::StopInputSystem => {
                 self.input_system.stop();
             }
+            AppMsg::CheckGstElements(window) => {
+                let missing_elements = {
+                    let preferences = self.preferences.borrow();
+                    slave::video::missing_gst_elements(
+                        *preferences.get_default_video_decoder(),
+                        preferences.get_default_video_encoder(),
+                        *preferences.get_default_colorspace_conversion(),
+                    )
+                };
+                if !missing_elements.is_empty() {
+                    let list = missing_elements
+                        .iter()
+                        .map(|(element, package)| format!("{} ({})", element, package))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    log::warn!("缺少以下 GStreamer 元素：{}", list.replace("\n", ", "));
+                    error_message(
+                        tr("缺少 GStreamer 插件"),
+                        &format!("{}\n\n{}", tr("以下 GStreamer 元素缺失，视频拉流或录制可能无法正常工作，请安装括号中的软件包。下位机的控制功能不受影响。"), list),
+                        window.upgrade().as_ref(),
+                    );
+                }
+            }
+            AppMsg::RemoteApiRequest(RemoteApiRequest { command, responder }) => {
+                let result = match self.shutting_down {
+                    Some(_) => Err((503, String::from("上位机正在退出"))),
+                    None => remote_api::handle_command(&self.slaves, command),
+                };
+                responder.try_send(result).unwrap_or_default();
+            }
+            AppMsg::Shutdown if self.shutting_down.is_some() => (),
+            AppMsg::RequestShutdown(app_window) => {
+                if *self.get_shutting_down() == Some(true) {
+                    return true; // 正在退出
+                }
+                let interruptions: Vec<String> = self
+                    .slaves
+                    .iter()
+                    .flat_map(|slave| {
+                        let model = slave.model().unwrap();
+                        let slave_url = model.get_config().model().unwrap().get_slave_url().to_string();
+                        model
+                            .disconnect_interruptions(true)
+                            .into_iter()
+                            .map(move |interruption| format!("• [{}] {}", slave_url, interruption))
+                    })
+                    .collect();
+                if interruptions.is_empty() {
+                    send!(sender, AppMsg::Shutdown);
+                } else {
+                    confirm_message(
+                        tr("退出"),
+                        &format!("{}\n\n{}", tr("退出将断开所有机位的连接并中断以下操作，确定要退出吗？"), interruptions.join("\n")),
+                        app_window.upgrade().as_ref(),
+                        clone!(@strong sender => move || {
+                            send!(sender, AppMsg::Shutdown);
+                        }),
+                    )
+                    .present();
+                }
+            }
+            AppMsg::Shutdown => {
+                log::info!("正在退出");
+                self.set_shutting_down(Some(true));
+                self.set_sync_recording(None);
+                let futures: Vec<Future<()>> = self
+                    .slaves
+                    .iter()
+                    .map(|slave| {
+                        let promise = Promise::new();
+                        let future = promise.future();
+                        send!(slave.sender(), SlaveMsg::Shutdown(promise));
+                        future
+                    })
+                    .collect();
+                let (completed_sender, completed_receiver) = MainContext::channel(PRIORITY_DEFAULT);
+                completed_receiver.attach(
+                    None,
+                    clone!(@strong sender => move |_| {
+                        send!(sender, AppMsg::ShutdownCompleted);
+                        Continue(false)
+                    }),
+                );
+                Future::sequence(futures.into_iter()).for_each(move |_| {
+                    completed_sender.send(()).unwrap_or_default();
+                });
+                glib::timeout_add_local_once(
+                    SHUTDOWN_TIMEOUT,
+                    clone!(@strong sender => move || {
+                        send!(sender, AppMsg::ShutdownCompleted);
+                    }),
+                ); // 防止管道或通讯无响应时无法退出
+            }
+            AppMsg::ShutdownCompleted => {
+                if !self.close_allowed.get() {
+                    if self.slaves.iter().any(|slave| {
+                        let model = slave.model().unwrap();
+                        model.get_video().model().unwrap().is_recording()
+                            || *model.get_connected() != Some(false)
+                    }) {
+                        log::warn!("部分机位未能在超时前停止录制或断开连接");
+                    }
+                    self.close_allowed.set(true);
+                    self.set_shutting_down(Some(false));
+                }
+            }
             AppMsg::DestroySlave(slave_ptr) => {
                 if slave_ptr == std::ptr::null() {
                     self.get_mut_slaves().pop();
@@ -465,8 +1155,30 @@ impl AppUpdate for AppModel {
                         self.get_mut_slaves().pop();
                     }
                 }
+                if !self.slaves.iter().any(|component| {
+                    self.focused_slave
+                        == Some(Deref::deref(&component.model().unwrap()) as *const SlaveModel)
+                }) {
+                    self.focused_slave = None; // 获得焦点的机位已被移除
+                }
+                self.save_slave_sessions();
+                self.check_duplicate_urls();
             }
             AppMsg::SetFullscreened(fullscreened) => self.set_fullscreened(fullscreened),
+            AppMsg::ToggleFullscreened => self.set_fullscreened(!self.fullscreened),
+            AppMsg::SlaveShortcut(shortcut) => match self.shortcut_slave_index() {
+                Some(index) => {
+                    let slave = self.slaves.get(index).unwrap();
+                    let message = slave.model().unwrap().shortcut_message(shortcut);
+                    if let Some(message) = message {
+                        send!(slave.sender(), message);
+                    }
+                }
+                None => self
+                    .toast_messages
+                    .borrow_mut()
+                    .push_back(String::from(tr("请先点击要操作的机位画面"))),
+            },
             AppMsg::RemoveLastSlave => {
                 if let Some(slave) = self.get_slaves().iter().last() {
                     send!(slave.sender(), SlaveMsg::DestroySlave);
@@ -484,14 +1196,120 @@ impl AppUpdate for AppModel {
     }
 }
 
+fn load_controller_mappings(input_system: &InputSystem, path: &Path) -> String {
+    match input_system.load_mappings(path) {
+        Ok(count) => {
+            log::info!("已从 {} 加载 {} 条手柄映射", path.display(), count);
+            format!("{}{}", tr("已加载的手柄映射数量："), count)
+        }
+        Err(err) => {
+            log::warn!("无法从 {} 加载手柄映射：{}", path.display(), err);
+            format!("{}{}", tr("无法加载手柄映射："), err)
+        }
+    }
+}
+
 fn main() {
+    let args = CommandLineArgs::parse(std::env::args());
+    if let Ok(CommandLineArgs { preferences_path: Some(path), .. }) = &args {
+        preferences::set_preference_path(path.clone());
+    }
+    let preferences = PreferencesModel::load_or_default();
+    i18n::set_language(*preferences.get_language());
+    let program_name = std::env::args().next().unwrap_or_default();
+    let exit_with_error = |err: cli::CommandLineError| -> ! {
+        eprintln!("{}\n\n{}", err.to_string(), CommandLineArgs::usage(&program_name));
+        std::process::exit(2)
+    };
+    let args = match args {
+        Ok(args) if args.help => {
+            print!("{}", CommandLineArgs::usage(&program_name));
+            return;
+        }
+        Ok(args) => args,
+        Err(err) => exit_with_error(err),
+    };
+    let command_line_sessions = if !args.slaves.is_empty() {
+        Some(args.slave_sessions(&preferences).unwrap_or_else(|err| exit_with_error(err)))
+    } else if args.auto_connect {
+        Some(
+            SlaveSession::load_all()
+                .into_iter()
+                .map(|session| SlaveSession { auto_connect: true, ..session })
+                .collect(),
+        )
+    } else {
+        None
+    };
     gst::init().expect("无法初始化 GStreamer");
+    if args.check {
+        let sessions = command_line_sessions.unwrap_or_else(SlaveSession::load_all);
+        std::process::exit(if check::run(&sessions, &preferences) { 0 } else { 1 });
+    }
     gtk::init().map(|_| adw::init()).expect("无法初始化 GTK4");
+    logger::init(*preferences.get_log_level());
+    log::info!("上位机已启动，版本 {}", env!("CARGO_PKG_VERSION"));
+    file_name::init_session();
     let model = AppModel {
-        preferences: Rc::new(RefCell::new(PreferencesModel::load_or_default())),
+        fullscreened: *preferences.get_window_fullscreened(),
+        preferences: Rc::new(RefCell::new(preferences)),
+        command_line_sessions,
         ..Default::default()
     };
+    *model.input_system.axis_settings.borrow_mut() =
+        model.preferences.borrow().get_input_axis_settings().clone();
+    model
+        .input_system
+        .virtual_joystick_enabled
+        .set(*model.preferences.borrow().get_virtual_joystick_enabled());
+    if let Some(path) = model.preferences.borrow().get_controller_mapping_path() {
+        let message = load_controller_mappings(&model.input_system, path);
+        model.toast_messages.borrow_mut().push_back(message);
+    }
     model.input_system.run();
     let relm = RelmApp::new(model);
-    relm.run()
+    relm.run_with_args(&[program_name]) // 命令行参数已自行解析，不再交由 GTK 处理
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset_ipv4_address_carries_into_higher_octets() {
+        let cases = [
+            ("192.168.1.10", 0, "192.168.1.10"),
+            ("192.168.1.10", 1, "192.168.1.11"),
+            ("192.168.1.254", 1, "192.168.2.1"),
+            ("192.168.1.253", 3, "192.168.2.2"),
+            ("192.168.255.254", 1, "192.169.0.1"),
+            ("10.255.255.254", 1, "11.0.0.1"),
+        ];
+        for (ip, offset, expected) in cases {
+            assert_eq!(
+                offset_ipv4_address(ip.parse().unwrap(), offset),
+                expected.parse::<Ipv4Addr>().unwrap(),
+                "{} + {}",
+                ip,
+                offset
+            );
+        }
+    }
+
+    #[test]
+    fn offset_ipv4_address_wraps_past_broadcast() {
+        let cases = [
+            ("255.255.255.254", 1, "0.0.0.1"),
+            ("255.255.255.253", 2, "0.0.0.2"),
+        ];
+        for (ip, offset, expected) in cases {
+            assert_eq!(
+                offset_ipv4_address(ip.parse().unwrap(), offset),
+                expected.parse::<Ipv4Addr>().unwrap(),
+                "{} + {}",
+                ip,
+                offset
+            );
+        }
+    }
 }
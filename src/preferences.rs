@@ -16,27 +16,55 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use std::{fs, path::PathBuf, str::FromStr, time::Duration};
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+    str::FromStr,
+    time::Duration,
+};
 
 use adw::{
     prelude::*, ActionRow, ComboRow, ExpanderRow, PreferencesGroup, PreferencesPage,
-    PreferencesWindow,
+    PreferencesWindow, Toast,
+};
+use glib::{clone, Sender};
+use gtk::{
+    Align, Button, ColorButton, DropDown, Entry, FileChooserAction, Frame, Inhibit, Label, ListBox,
+    SelectionMode, SpinButton, StringList, Switch, Widget,
 };
-use glib::Sender;
-use gtk::{Align, Entry, Inhibit, Label, SpinButton, StringList, Switch};
 use relm4::{send, ComponentUpdate, Model, Widgets};
 use relm4_macros::widget;
 
 use derivative::*;
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 use url::Url;
 
 use crate::{
-    slave::video::{
-        ColorspaceConversion, ImageFormat, VideoCodec, VideoCodecProvider, VideoDecoder,
-        VideoEncoder,
+    file_name::{create_directory, validate_file_name_template, SaveDirectoryScheme, DEFAULT_FILE_NAME_TEMPLATE},
+    i18n::{tr, Language},
+    input::{
+        button_name, Axis, AxisGroup, ButtonChord, ChordAction, InputAxisSettings,
+        InputDispatchMode, ResponseCurve,
+        CHORD_BUTTONS,
     },
+    logger::{self, get_log_path, LogLevel},
+    slave::{
+        capture_report::CaptureReportFormat,
+        protocol::{INFO_KEY_DEPTH_LOCKED, INFO_KEY_DIRECTION_LOCKED},
+        slave_config::{
+            parse_slave_url, parse_video_url, SLAVE_URL_FORMAT_HINT, VIDEO_URL_FORMAT_HINT,
+        },
+        video::{
+            AppSinkQueueMode, ColorspaceConversion, ImageFormat, VideoCodec, VideoCodecProvider, VideoDecoder,
+            VideoEncoder, VIDEO_LATENCY_MAX,
+        },
+    },
+    ui::generic::{confirm_message, select_path},
     AppColorScheme, AppModel, AppMsg,
 };
 
@@ -50,43 +78,155 @@ pub fn get_data_path() -> PathBuf {
     data_path
 }
 
+static PREFERENCE_PATH: OnceCell<PathBuf> = OnceCell::new();
+
+// 由命令行指定首选项文件时，须在首次读取首选项前调用
+pub fn set_preference_path(path: PathBuf) {
+    PREFERENCE_PATH.set(path).unwrap_or_default();
+}
+
 pub fn get_preference_path() -> PathBuf {
+    if let Some(path) = PREFERENCE_PATH.get() {
+        return path.clone();
+    }
     let mut path = get_data_path();
     path.push("preferences.json");
     path
 }
 
+// 截图与录像的默认目录在写入文件前由 SaveDirectoryScheme::prepare_directory 创建
 pub fn get_video_path() -> PathBuf {
     let mut video_path = get_data_path();
     video_path.push("Videos");
-    if !video_path.exists() {
-        fs::create_dir(video_path.clone()).expect("无法创建视频文件夹");
-    }
     video_path
 }
 
 pub fn get_image_path() -> PathBuf {
     let mut video_path = get_data_path();
     video_path.push("Images");
-    if !video_path.exists() {
-        fs::create_dir(video_path.clone()).expect("无法创建图片文件夹");
-    }
     video_path
 }
 
+pub fn get_capture_report_path() -> PathBuf {
+    let mut capture_report_path = get_data_path();
+    capture_report_path.push("Reports");
+    capture_report_path
+}
+
+pub fn get_pipeline_graph_path() -> PathBuf {
+    let mut pipeline_graph_path = get_data_path();
+    pipeline_graph_path.push("PipelineGraphs");
+    pipeline_graph_path
+}
+
+// 设置该环境变量时无需在首选项中开启即可导出视频管道结构图
+pub const PIPELINE_GRAPH_ENV: &str = "ROVHOST_PIPELINE_GRAPH";
+
+pub fn get_control_log_path() -> PathBuf {
+    let mut control_log_path = get_data_path();
+    control_log_path.push("ControlLogs");
+    if !control_log_path.exists() {
+        fs::create_dir(control_log_path.clone()).expect("无法创建控制指令记录文件夹");
+    }
+    control_log_path
+}
+
+// 查询路径所在文件系统的可用空间（字节），路径尚不存在时查询其最近的已存在的上级目录
+pub fn available_space(path: &Path) -> Option<u64> {
+    let path = path.ancestors().find(|path| path.exists())?;
+    gio::File::for_path(path)
+        .query_filesystem_info("filesystem::free", None::<&gio::Cancellable>)
+        .ok()
+        .map(|info| info.attribute_uint64("filesystem::free"))
+}
+
+// 路径是否位于网络文件系统（如 SMB、NFS）上，无法查询时视为本地路径
+pub fn is_remote_path(path: &Path) -> bool {
+    path.ancestors()
+        .find(|path| path.exists())
+        .and_then(|path| {
+            gio::File::for_path(path)
+                .query_filesystem_info("filesystem::remote", None::<&gio::Cancellable>)
+                .ok()
+        })
+        .map_or(false, |info| info.attribute_boolean("filesystem::remote"))
+}
+
+// 以中英文逗号分隔的信息项名称列表
+pub fn parse_info_keys(text: &str) -> Vec<String> {
+    text.split(|c| c == ',' || c == '，')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+// 创建目录（如不存在）并检查其是否可写
+fn prepare_directory(path: &Path) -> Result<(), String> {
+    create_directory(path)?;
+    let mut test_file_path = path.to_path_buf();
+    test_file_path.push(".rovhost_write_test");
+    fs::write(&test_file_path, &[])
+        .and_then(|_| fs::remove_file(&test_file_path))
+        .map_err(|err| format!("{}{}", tr("目录不可写："), err))
+}
+
+fn button_chords_list_box(button_chords: &[ButtonChord], sender: &Sender<PreferencesMsg>) -> Widget {
+    if button_chords.is_empty() {
+        return Label::builder()
+            .label(tr("未定义组合键"))
+            .margin_top(12)
+            .margin_bottom(12)
+            .build()
+            .upcast();
+    }
+    let list_box = ListBox::builder().selection_mode(SelectionMode::None).build();
+    for (index, chord) in button_chords.iter().enumerate() {
+        let row = ActionRow::builder()
+            .title(&chord.to_string())
+            .subtitle(&chord.action.to_string())
+            .build();
+        let remove_button = Button::builder()
+            .icon_name("user-trash-symbolic")
+            .tooltip_text(tr("删除"))
+            .valign(Align::Center)
+            .css_classes(vec![String::from("flat")])
+            .build();
+        remove_button.connect_clicked(clone!(@strong sender => move |_button| {
+            send!(sender, PreferencesMsg::RemoveButtonChord(index));
+        }));
+        row.add_suffix(&remove_button);
+        list_box.append(&row);
+    }
+    list_box.upcast()
+}
+
+fn open_directory(path: &Path) -> Result<(), String> {
+    create_directory(path)?;
+    let uri = glib::filename_to_uri(path, None).map_err(|err| format!("{}{}", tr("无法打开目录："), err))?;
+    gtk::show_uri(None as Option<&PreferencesWindow>, uri.as_str(), gdk::CURRENT_TIME);
+    Ok(())
+}
+
 #[tracker::track]
 #[derive(Derivative, Clone, PartialEq, Debug, Serialize, Deserialize)]
 #[derivative(Default)]
+#[serde(default)]
 pub struct PreferencesModel {
     #[derivative(Default(value = "1"))]
     pub initial_slave_num: u8,
     pub application_color_scheme: AppColorScheme,
-    #[derivative(Default(value = "get_video_path()"))]
-    pub video_save_path: PathBuf,
-    #[derivative(Default(value = "get_image_path()"))]
-    pub image_save_path: PathBuf,
+    pub language: Language,
+    pub log_level: LogLevel,
+    pub video_save_path: Option<PathBuf>, // 为 None 时使用默认目录
+    pub image_save_path: Option<PathBuf>,
     #[derivative(Default(value = "ImageFormat::JPEG"))]
     pub image_save_format: ImageFormat,
+    #[derivative(Default(value = "String::from(DEFAULT_FILE_NAME_TEMPLATE)"))]
+    pub file_name_template: String,
+    pub save_directory_scheme: SaveDirectoryScheme,
+    pub capture_report_save_path: Option<PathBuf>,
+    pub capture_report_format: CaptureReportFormat,
     pub default_reencode_recording_video: bool,
     pub default_video_encoder: VideoEncoder,
     #[derivative(Default(value = "Url::from_str(\"http://192.168.137.219:8888\").unwrap()"))]
@@ -97,8 +237,22 @@ pub struct PreferencesModel {
     pub default_video_url: Url,
     #[derivative(Default(value = "60"))]
     pub default_input_sending_rate: u16,
+    #[derivative(Default(value = "1000"))]
+    pub control_keep_alive_interval: u16,
+    pub control_log_enabled: bool,
+    pub input_axis_settings: InputAxisSettings,
+    pub button_chords: Vec<ButtonChord>,
+    pub controller_mapping_path: Option<PathBuf>,
+    #[derivative(Default(value = "500"))]
+    pub joystick_display_threshold: i16,
+    pub virtual_joystick_enabled: bool,
+    pub input_dispatch_mode: InputDispatchMode,
     #[derivative(Default(value = "true"))]
     pub default_keep_video_display_ratio: bool,
+    #[derivative(Default(value = "String::from(\"rgb(0,0,0)\")"))]
+    pub video_letterbox_color: String,
+    #[derivative(Default(value = "false"))]
+    pub native_video_sink_enabled: bool, // 无需处理画面时由 GTK 直接渲染视频流
     pub default_video_decoder: VideoDecoder,
     pub default_colorspace_conversion: ColorspaceConversion,
     #[derivative(Default(value = "64"))]
@@ -108,15 +262,42 @@ pub struct PreferencesModel {
     #[derivative(Default(value = "Duration::from_secs(10)"))]
     pub pipeline_timeout: Duration,
     #[derivative(Default(value = "false"))]
-    pub default_appsink_queue_leaky_enabled: bool,
+    pub pipeline_graph_export_enabled: bool,
+    pub default_appsink_queue_mode: AppSinkQueueMode,
     #[derivative(Default(value = "false"))]
     pub default_use_decodebin: bool,
     #[derivative(Default(value = "false"))]
     pub video_sync_record_use_separate_directory: bool,
+    #[derivative(Default(value = "true"))]
+    pub keep_recording_on_connection_lost: bool, // 录像由视频流产生，与下位机的通讯中断时无需停止
+    #[derivative(Default(value = "1024"))]
+    pub recording_min_free_space: u32, // 单位为 MB，录制过程中低于该值的一半时自动停止录制
+    #[derivative(Default(value = "3"))]
+    pub network_write_retries: u8, // 截图写入网络存储失败时的重试次数
+    #[derivative(Default(value = "false"))]
+    pub annotate_screenshots: bool,
     #[derivative(Default(value = "200"))]
     pub default_video_latency: u32,
     #[derivative(Default(value = "500"))]
     pub default_status_info_update_interval: u16,
+    #[derivative(Default(value = "String::from(INFO_KEY_DEPTH_LOCKED)"))]
+    pub depth_locked_info_key: String, // 为空时不根据下位机的报告校正锁定开关
+    #[derivative(Default(value = "String::from(INFO_KEY_DIRECTION_LOCKED)"))]
+    pub direction_locked_info_key: String,
+    pub pinned_info_keys: Vec<String>, // 按列出的顺序显示在状态信息顶部，其余信息项按名称排序
+    pub hidden_info_keys: Vec<String>,
+    pub remote_api_enabled: bool,
+    #[derivative(Default(value = "8787"))]
+    pub remote_api_port: u16,
+    #[derivative(Default(value = "1280"))]
+    pub window_width: i32,
+    #[derivative(Default(value = "720"))]
+    pub window_height: i32,
+    pub window_maximized: bool,
+    pub window_fullscreened: bool,
+    #[no_eq]
+    #[serde(skip)]
+    pub toast_messages: Rc<RefCell<VecDeque<String>>>,
 }
 
 impl PreferencesModel {
@@ -129,17 +310,134 @@ impl PreferencesModel {
             None => Default::default(),
         }
     }
+
+    pub fn save_to_file(&self) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(&self)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+        fs::write(get_preference_path(), json)
+    }
+
+    // 通过 setter 逐项恢复，使界面上的各个设置项得到刷新，窗口状态不在此列
+    pub fn restore_defaults(&mut self) {
+        let defaults = PreferencesModel::default();
+        self.set_initial_slave_num(defaults.initial_slave_num);
+        self.set_application_color_scheme(defaults.application_color_scheme);
+        self.set_language(defaults.language);
+        self.set_log_level(defaults.log_level);
+        logger::set_level(defaults.log_level);
+        self.set_video_save_path(defaults.video_save_path);
+        self.set_image_save_path(defaults.image_save_path);
+        self.set_image_save_format(defaults.image_save_format);
+        self.set_file_name_template(defaults.file_name_template);
+        self.set_save_directory_scheme(defaults.save_directory_scheme);
+        self.set_capture_report_save_path(defaults.capture_report_save_path);
+        self.set_capture_report_format(defaults.capture_report_format);
+        self.set_default_reencode_recording_video(defaults.default_reencode_recording_video);
+        self.set_default_video_encoder(defaults.default_video_encoder);
+        self.set_default_slave_url(defaults.default_slave_url);
+        self.set_default_video_url(defaults.default_video_url);
+        self.set_default_input_sending_rate(defaults.default_input_sending_rate);
+        self.set_control_keep_alive_interval(defaults.control_keep_alive_interval);
+        self.set_control_log_enabled(defaults.control_log_enabled);
+        self.set_input_axis_settings(defaults.input_axis_settings);
+        self.set_button_chords(defaults.button_chords);
+        self.set_controller_mapping_path(defaults.controller_mapping_path);
+        self.set_joystick_display_threshold(defaults.joystick_display_threshold);
+        self.set_virtual_joystick_enabled(defaults.virtual_joystick_enabled);
+        self.set_input_dispatch_mode(defaults.input_dispatch_mode);
+        self.set_default_keep_video_display_ratio(defaults.default_keep_video_display_ratio);
+        self.set_video_letterbox_color(defaults.video_letterbox_color);
+        self.set_native_video_sink_enabled(defaults.native_video_sink_enabled);
+        self.set_default_video_decoder(defaults.default_video_decoder);
+        self.set_default_colorspace_conversion(defaults.default_colorspace_conversion);
+        self.set_param_tuner_graph_view_point_num_limit(
+            defaults.param_tuner_graph_view_point_num_limit,
+        );
+        self.set_param_tuner_graph_view_update_interval(
+            defaults.param_tuner_graph_view_update_interval,
+        );
+        self.set_pipeline_timeout(defaults.pipeline_timeout);
+        self.set_pipeline_graph_export_enabled(defaults.pipeline_graph_export_enabled);
+        self.set_default_appsink_queue_mode(defaults.default_appsink_queue_mode);
+        self.set_default_use_decodebin(defaults.default_use_decodebin);
+        self.set_video_sync_record_use_separate_directory(
+            defaults.video_sync_record_use_separate_directory,
+        );
+        self.set_keep_recording_on_connection_lost(defaults.keep_recording_on_connection_lost);
+        self.set_recording_min_free_space(defaults.recording_min_free_space);
+        self.set_network_write_retries(defaults.network_write_retries);
+        self.set_annotate_screenshots(defaults.annotate_screenshots);
+        self.set_default_video_latency(defaults.default_video_latency);
+        self.set_default_status_info_update_interval(defaults.default_status_info_update_interval);
+        self.set_depth_locked_info_key(defaults.depth_locked_info_key);
+        self.set_direction_locked_info_key(defaults.direction_locked_info_key);
+        self.set_pinned_info_keys(defaults.pinned_info_keys);
+        self.set_hidden_info_keys(defaults.hidden_info_keys);
+        self.set_remote_api_enabled(defaults.remote_api_enabled);
+        self.set_remote_api_port(defaults.remote_api_port);
+    }
+
+    pub fn video_save_directory(&self) -> PathBuf {
+        self.video_save_path.clone().unwrap_or_else(get_video_path)
+    }
+
+    pub fn image_save_directory(&self) -> PathBuf {
+        self.image_save_path.clone().unwrap_or_else(get_image_path)
+    }
+
+    pub fn capture_report_save_directory(&self) -> PathBuf {
+        self.capture_report_save_path.clone().unwrap_or_else(get_capture_report_path)
+    }
+
+    pub fn pipeline_graph_export_available(&self) -> bool {
+        self.pipeline_graph_export_enabled || std::env::var_os(PIPELINE_GRAPH_ENV).is_some()
+    }
+
+    // 视频保存目录的可用空间（MB）低于阈值时返回可用空间，critical 为 true 时使用减半的阈值
+    pub fn video_save_space_shortage(&self, critical: bool) -> Option<u64> {
+        let threshold = if critical {
+            self.recording_min_free_space / 2
+        } else {
+            self.recording_min_free_space
+        } as u64;
+        available_space(&self.video_save_directory())
+            .map(|space| space / 1024 / 1024)
+            .filter(|space| *space < threshold)
+    }
 }
 
 #[derive(Debug)]
 pub enum PreferencesMsg {
-    SetVideoSavePath(PathBuf),
-    SetImageSavePath(PathBuf),
+    SetVideoSavePath(Option<PathBuf>),
+    SetImageSavePath(Option<PathBuf>),
     SetImageSaveFormat(ImageFormat),
+    SetFileNameTemplate(String),
+    SetSaveDirectoryScheme(SaveDirectoryScheme),
+    SetCaptureReportSavePath(Option<PathBuf>),
+    SetCaptureReportFormat(CaptureReportFormat),
+    OpenCaptureReportDirectory,
     SetInitialSlaveNum(u8),
     SetInputSendingRate(u16),
+    SetControlKeepAliveInterval(u16),
+    SetControlLogEnabled(bool),
+    OpenControlLogDirectory,
+    SetAxisInverted(Axis, bool),
+    SetTriggerThreshold(u8),
+    SetResponseCurve(AxisGroup, ResponseCurve),
+    SetResponseExponent(AxisGroup, f64),
+    SetJoystickDisplayThreshold(i16),
+    SetControllerMappingPath(Option<PathBuf>),
+    ReloadControllerMappings,
+    ShowToastMessage(String),
+    AddButtonChord(ButtonChord),
+    RemoveButtonChord(usize),
+    SetVirtualJoystickEnabled(bool),
+    SetInputDispatchMode(InputDispatchMode),
     SetParamTunerGraphViewUpdateInterval(u16),
+    SetPipelineGraphExportEnabled(bool),
     SetDefaultKeepVideoDisplayRatio(bool),
+    SetVideoLetterboxColor(String),
+    SetNativeVideoSinkEnabled(bool),
     SetDefaultVideoDecoderCodec(VideoCodec),
     SetDefaultVideoDecoderCodecProvider(VideoCodecProvider),
     SetDefaultVideoEncoderCodec(VideoCodec),
@@ -148,14 +446,28 @@ pub enum PreferencesMsg {
     SetDefaultColorspaceConversion(ColorspaceConversion),
     SetDefaultReencodeRecordingVideo(bool),
     SetDefaultUseDecodebin(bool),
-    SetDefaultAppSinkQueueLeakyEnabled(bool),
+    SetDefaultAppSinkQueueMode(AppSinkQueueMode),
     SetVideoSyncRecordUseSeparateDirectory(bool),
+    SetKeepRecordingOnConnectionLost(bool),
+    SetRecordingMinFreeSpace(u32),
+    SetNetworkWriteRetries(u8),
+    SetAnnotateScreenshots(bool),
     SetDefaultVideoLatency(u32),
     SetDefaultVideoUrl(Url),
     SetDefaultSlaveUrl(Url),
     SetPipelineTimeout(Duration),
     SetApplicationColorScheme(Option<AppColorScheme>),
+    SetLanguage(Language),
+    SetLogLevel(LogLevel),
+    OpenLogDirectory,
     SetDefaultStatusInfoUpdateInterval(u16),
+    SetDepthLockedInfoKey(String),
+    SetDirectionLockedInfoKey(String),
+    SetPinnedInfoKeys(Vec<String>),
+    SetHiddenInfoKeys(Vec<String>),
+    SetRemoteApiEnabled(bool),
+    SetRemoteApiPort(u16),
+    RestoreDefaults,
     SaveToFile,
     OpenVideoDirectory,
     OpenImageDirectory,
@@ -171,25 +483,26 @@ impl Model for PreferencesModel {
 impl Widgets<PreferencesModel, AppModel> for PreferencesWidgets {
     view! {
         window = PreferencesWindow {
-            set_title: Some("首选项"),
+            set_title: Some(tr("首选项")),
             set_transient_for: parent!(Some(&parent_widgets.app_window)),
             set_destroy_with_parent: true,
             set_modal: true,
             set_search_enabled: false,
+            add_toast?: watch!(model.get_toast_messages().borrow_mut().pop_front().map(|x| Toast::new(&x)).as_ref()),
             connect_close_request(sender) => move |window| {
                 send!(sender, PreferencesMsg::SaveToFile);
                 window.hide();
                 Inhibit(true)
             },
             add = &PreferencesPage {
-                set_title: "通用",
+                set_title: tr("通用"),
                 set_icon_name: Some("view-grid-symbolic"),
                 add = &PreferencesGroup {
-                    set_title: "外观",
-                    set_description: Some("更改上位机的外观设置"),
+                    set_title: tr("外观"),
+                    set_description: Some(tr("更改上位机的外观设置")),
                     add = &ComboRow {
-                        set_title: "配色方案",
-                        set_subtitle: "上位机界面使用的配色方案",
+                        set_title: tr("配色方案"),
+                        set_subtitle: tr("上位机界面使用的配色方案"),
                         set_model: Some(&{
                             let model = StringList::new(&[]);
                             for value in AppColorScheme::iter() {
@@ -202,13 +515,28 @@ impl Widgets<PreferencesModel, AppModel> for PreferencesWidgets {
                             send!(sender, PreferencesMsg::SetApplicationColorScheme(Some(AppColorScheme::iter().nth(row.selected() as usize).unwrap())))
                         },
                     },
+                    add = &ComboRow {
+                        set_title: tr("语言"),
+                        set_subtitle: tr("上位机界面使用的语言（需要重启以应用设置）"),
+                        set_model: Some(&{
+                            let model = StringList::new(&[]);
+                            for value in Language::iter() {
+                                model.append(&value.to_string());
+                            }
+                            model
+                        }),
+                        set_selected: track!(model.changed(PreferencesModel::language()), Language::iter().position(|x| x == model.language).unwrap() as u32),
+                        connect_selected_notify(sender) => move |row| {
+                            send!(sender, PreferencesMsg::SetLanguage(Language::iter().nth(row.selected() as usize).unwrap()))
+                        },
+                    },
                 },
                 add = &PreferencesGroup {
-                    set_title: "机位",
-                    set_description: Some("配置上位机的多机位功能"),
+                    set_title: tr("机位"),
+                    set_description: Some(tr("配置上位机的多机位功能")),
                     add = &ActionRow {
-                        set_title: "初始机位",
-                        set_subtitle: "上位机启动时的初始机位数量",
+                        set_title: tr("初始机位"),
+                        set_subtitle: tr("上位机启动时的初始机位数量，依次恢复上次保存的机位配置，不足时新建机位"),
                         add_suffix = &SpinButton::with_range(0.0, 12.0, 1.0) {
                             set_value: track!(model.changed(PreferencesModel::initial_slave_num()), model.initial_slave_num as f64),
                             set_digits: 0,
@@ -220,37 +548,90 @@ impl Widgets<PreferencesModel, AppModel> for PreferencesWidgets {
                         }
                     }
                 },
+                add = &PreferencesGroup {
+                    set_title: tr("日志"),
+                    set_description: Some(tr("上位机的诊断日志，可用于报告问题")),
+                    add = &ComboRow {
+                        set_title: tr("日志级别"),
+                        set_subtitle: tr("记录到日志文件的最低级别"),
+                        set_model: Some(&{
+                            let model = StringList::new(&[]);
+                            for value in LogLevel::iter() {
+                                model.append(&value.to_string());
+                            }
+                            model
+                        }),
+                        set_selected: track!(model.changed(PreferencesModel::log_level()), LogLevel::iter().position(|x| x == model.log_level).unwrap() as u32),
+                        connect_selected_notify(sender) => move |row| {
+                            send!(sender, PreferencesMsg::SetLogLevel(LogLevel::iter().nth(row.selected() as usize).unwrap()))
+                        },
+                    },
+                    add = &ActionRow {
+                        set_title: tr("日志目录"),
+                        set_subtitle: &get_log_path().to_string_lossy(),
+                        add_suffix = &Button {
+                            set_label: tr("打开"),
+                            set_valign: Align::Center,
+                            connect_clicked(sender) => move |_button| {
+                                send!(sender, PreferencesMsg::OpenLogDirectory);
+                            },
+                        },
+                    },
+                },
+                add = &PreferencesGroup {
+                    set_title: tr("重置"),
+                    set_description: Some(tr("恢复上位机的默认设置")),
+                    add = &ActionRow {
+                        set_title: tr("恢复默认设置"),
+                        set_subtitle: tr("将所有首选项恢复为默认值，已打开的机位将使用新的默认设置"),
+                        add_suffix = &Button {
+                            set_label: tr("恢复"),
+                            set_css_classes: &["destructive-action"],
+                            set_valign: Align::Center,
+                            connect_clicked(sender, window) => move |_button| {
+                                confirm_message(tr("恢复默认设置"), tr("确定要将所有首选项恢复为默认值吗？"), Some(&window), clone!(@strong sender => move || {
+                                    send!(sender, PreferencesMsg::RestoreDefaults);
+                                }));
+                            },
+                        },
+                    },
+                },
             },
             add = &PreferencesPage {
-                set_title: "通信",
+                set_title: tr("通信"),
                 set_icon_name: Some("network-transmit-receive-symbolic"),
                 add = &PreferencesGroup {
-                    set_description: Some("与机器人的连接通信设置"),
-                    set_title: "连接",
+                    set_description: Some(tr("与机器人的连接通信设置")),
+                    set_title: tr("连接"),
                     add = &ActionRow {
-                        set_title: "默认连接 URL",
-                        set_subtitle: "连接第一机位的机器人使用的默认 URL，其他机位会自动累加 IPV4 地址",
+                        set_title: tr("默认连接 URL"),
+                        set_subtitle: &format!("{}{}", tr("连接第一机位的机器人使用的默认 URL，其他机位会自动累加 IPV4 地址，"), tr(SLAVE_URL_FORMAT_HINT)),
                         add_suffix = &Entry {
                             set_text: track!(model.changed(PreferencesModel::default_slave_url()), model.get_default_slave_url().to_string().as_str()),
                             set_valign: Align::Center,
                             set_width_request: 200,
                             connect_changed(sender) => move |entry| {
-                                if let Ok(url) = Url::from_str(&entry.text()) {
-                                    send!(sender, PreferencesMsg::SetDefaultSlaveUrl(url));
-                                    entry.remove_css_class("error");
-                                } else {
-                                    entry.add_css_class("error");
+                                match parse_slave_url(&entry.text()) {
+                                    Ok(url) => {
+                                        send!(sender, PreferencesMsg::SetDefaultSlaveUrl(url));
+                                        entry.remove_css_class("error");
+                                        entry.set_tooltip_text(None);
+                                    },
+                                    Err(err) => {
+                                        entry.add_css_class("error");
+                                        entry.set_tooltip_text(Some(err));
+                                    },
                                 }
                             }
                          },
                     },
                 },
                 add = &PreferencesGroup {
-                    set_description: Some("机器人状态信息接收设置"),
-                    set_title: "状态信息",
+                    set_description: Some(tr("机器人状态信息接收设置")),
+                    set_title: tr("状态信息"),
                     add = &ActionRow {
-                        set_title: "状态信息更新时间间隔",
-                        set_subtitle: "用于确定每秒钟向机器人请求接收状态信息并测试连接状态的频率（需要重新连接以应用设置）",
+                        set_title: tr("状态信息更新时间间隔"),
+                        set_subtitle: tr("用于确定每秒钟向机器人请求接收状态信息并测试连接状态的频率（需要重新连接以应用设置）"),
                         add_suffix = &SpinButton::with_range(50.0, 10000.0, 50.0) {
                             set_value: track!(model.changed(PreferencesModel::default_status_info_update_interval()), model.default_status_info_update_interval as f64),
                             set_digits: 0,
@@ -261,30 +642,109 @@ impl Widgets<PreferencesModel, AppModel> for PreferencesWidgets {
                             }
                         },
                         add_suffix = &Label {
-                            set_label: "毫秒",
+                            set_label: tr("毫秒"),
+                        },
+                    },
+                    add = &ActionRow {
+                        set_title: tr("深度锁定状态信息项"),
+                        set_subtitle: tr("下位机报告实际深度锁定状态的信息项名称，用于校正深度锁定开关，留空则不校正"),
+                        add_suffix = &Entry {
+                            set_text: track!(model.changed(PreferencesModel::depth_locked_info_key()), model.get_depth_locked_info_key().as_str()),
+                            set_valign: Align::Center,
+                            connect_changed(sender) => move |entry| {
+                                send!(sender, PreferencesMsg::SetDepthLockedInfoKey(entry.text().to_string()));
+                            }
+                        },
+                    },
+                    add = &ActionRow {
+                        set_title: tr("方向锁定状态信息项"),
+                        set_subtitle: tr("下位机报告实际方向锁定状态的信息项名称，用于校正方向锁定开关，留空则不校正"),
+                        add_suffix = &Entry {
+                            set_text: track!(model.changed(PreferencesModel::direction_locked_info_key()), model.get_direction_locked_info_key().as_str()),
+                            set_valign: Align::Center,
+                            connect_changed(sender) => move |entry| {
+                                send!(sender, PreferencesMsg::SetDirectionLockedInfoKey(entry.text().to_string()));
+                            }
+                        },
+                    },
+                    add = &ActionRow {
+                        set_title: tr("置顶的状态信息项"),
+                        set_subtitle: tr("以逗号分隔的信息项名称，按列出的顺序显示在状态信息顶部，其余信息项按名称排序"),
+                        add_suffix = &Entry {
+                            set_text: track!(model.changed(PreferencesModel::pinned_info_keys()), model.get_pinned_info_keys().join(", ").as_str()),
+                            set_valign: Align::Center,
+                            set_width_request: 200,
+                            connect_changed(sender) => move |entry| {
+                                send!(sender, PreferencesMsg::SetPinnedInfoKeys(parse_info_keys(&entry.text())));
+                            }
+                        },
+                    },
+                    add = &ActionRow {
+                        set_title: tr("隐藏的状态信息项"),
+                        set_subtitle: tr("以逗号分隔的信息项名称，这些信息项不会显示在状态信息中"),
+                        add_suffix = &Entry {
+                            set_text: track!(model.changed(PreferencesModel::hidden_info_keys()), model.get_hidden_info_keys().join(", ").as_str()),
+                            set_valign: Align::Center,
+                            set_width_request: 200,
+                            connect_changed(sender) => move |entry| {
+                                send!(sender, PreferencesMsg::SetHiddenInfoKeys(parse_info_keys(&entry.text())));
+                            }
+                        },
+                    },
+                },
+                add = &PreferencesGroup {
+                    set_description: Some(tr("供外部脚本查询机位状态并控制连接、录制与截图，仅接受来自本机的请求")),
+                    set_title: tr("远程控制接口"),
+                    add = &ExpanderRow {
+                        set_title: tr("启用远程控制接口"),
+                        set_subtitle: tr("在本机回环地址上提供 HTTP 接口，接口说明见 README（需要重启以应用设置）"),
+                        set_show_enable_switch: true,
+                        set_expanded: *model.get_remote_api_enabled(),
+                        set_enable_expansion: track!(model.changed(PreferencesModel::remote_api_enabled()), *model.get_remote_api_enabled()),
+                        connect_enable_expansion_notify(sender) => move |expander| {
+                            send!(sender, PreferencesMsg::SetRemoteApiEnabled(expander.enables_expansion()));
+                        },
+                        add_row = &ActionRow {
+                            set_title: tr("端口"),
+                            set_subtitle: tr("远程控制接口监听的 TCP 端口"),
+                            add_suffix = &SpinButton::with_range(1024.0, 65535.0, 1.0) {
+                                set_value: track!(model.changed(PreferencesModel::remote_api_port()), model.remote_api_port as f64),
+                                set_digits: 0,
+                                set_valign: Align::Center,
+                                set_can_focus: false,
+                                connect_value_changed(sender) => move |button| {
+                                    send!(sender, PreferencesMsg::SetRemoteApiPort(button.value() as u16));
+                                }
+                            },
                         },
                     },
                 },
             },
             add = &PreferencesPage {
-                set_title: "控制",
+                set_title: tr("控制"),
                 set_icon_name: Some("input-gaming-symbolic"),
                 add = &PreferencesGroup {
-                    set_title: "发送",
-                    set_description: Some("向机器人发送控制信号的设置（需要重新连接以应用设置）"),
+                    set_title: tr("发送"),
+                    set_description: Some(tr("向机器人发送控制信号的设置（需要重新连接以应用设置）")),
                     add = &ActionRow {
-                        set_title: "增量发送",
-                        set_subtitle: "每次发送只发送相对上一次发送的变化值以节省数据发送量",
-                        set_sensitive: false,
-                        add_suffix: increamental_sending_switch = &Switch {
-                            set_active: false,
+                        set_title: tr("保活间隔"),
+                        set_subtitle: tr("控制数据包仅在输入变化时发送且只包含变化的部分，输入长时间不变时每隔该时间重新发送一次完整的控制数据包"),
+                        add_suffix = &SpinButton::with_range(100.0, 10000.0, 100.0) {
+                            set_value: track!(model.changed(PreferencesModel::control_keep_alive_interval()), model.control_keep_alive_interval as f64),
+                            set_digits: 0,
                             set_valign: Align::Center,
+                            set_can_focus: false,
+                            connect_value_changed(sender) => move |button| {
+                                send!(sender, PreferencesMsg::SetControlKeepAliveInterval(button.value() as u16));
+                            }
+                        },
+                        add_suffix = &Label {
+                            set_label: tr("毫秒"),
                         },
-                        set_activatable_widget: Some(&increamental_sending_switch),
                     },
                     add = &ActionRow {
-                        set_title: "输入发送率",
-                        set_subtitle: "每秒钟向机器人发送的控制数据包的个数，该值越高意味着控制越灵敏，但在较差的网络条件下可能产生更大的延迟",
+                        set_title: tr("输入发送率"),
+                        set_subtitle: tr("每秒钟向机器人发送的控制数据包的个数，该值越高意味着控制越灵敏，但在较差的网络条件下可能产生更大的延迟"),
                         add_suffix = &SpinButton::with_range(1.0, 1000.0, 1.0) {
                             set_value: track!(model.changed(PreferencesModel::default_input_sending_rate()), model.default_input_sending_rate as f64),
                             set_digits: 0,
@@ -298,17 +758,303 @@ impl Widgets<PreferencesModel, AppModel> for PreferencesWidgets {
                             set_label: "Hz",
                         },
                     },
+                    add = &ActionRow {
+                        set_title: tr("记录控制指令"),
+                        set_subtitle: tr("将每个实际发送的控制数据包及其发送时间记录到文件中，可在机位中回放以复现操作过程"),
+                        add_suffix = &Button {
+                            set_label: tr("打开"),
+                            set_valign: Align::Center,
+                            connect_clicked(sender) => move |_button| {
+                                send!(sender, PreferencesMsg::OpenControlLogDirectory);
+                            },
+                        },
+                        add_suffix: control_log_enabled_switch = &Switch {
+                            set_active: track!(model.changed(PreferencesModel::control_log_enabled()), *model.get_control_log_enabled()),
+                            set_valign: Align::Center,
+                            connect_state_set(sender) => move |_switch, state| {
+                                send!(sender, PreferencesMsg::SetControlLogEnabled(state));
+                                Inhibit(false)
+                            }
+                        },
+                        set_activatable_widget: Some(&control_log_enabled_switch),
+                    },
+                },
+                add = &PreferencesGroup {
+                    set_title: tr("手柄映射"),
+                    set_description: Some(tr("为无法被识别的手柄加载 SDL 格式的映射文件（如 gamecontrollerdb.txt），上位机启动时将自动加载")),
+                    add = &ActionRow {
+                        set_title: tr("手柄映射文件"),
+                        set_subtitle: track!(model.changed(PreferencesModel::controller_mapping_path()), &model.controller_mapping_path.as_ref().map_or_else(|| String::from(tr("未设置")), |path| path.to_string_lossy().to_string())),
+                        add_suffix = &Button {
+                            set_icon_name: "edit-clear-symbolic",
+                            set_tooltip_text: Some(tr("清除")),
+                            set_valign: Align::Center,
+                            set_visible: track!(model.changed(PreferencesModel::controller_mapping_path()), model.controller_mapping_path.is_some()),
+                            connect_clicked(sender) => move |_button| {
+                                send!(sender, PreferencesMsg::SetControllerMappingPath(None));
+                            },
+                        },
+                        add_suffix = &Button {
+                            set_icon_name: "view-refresh-symbolic",
+                            set_tooltip_text: Some(tr("重新加载映射")),
+                            set_valign: Align::Center,
+                            set_sensitive: track!(model.changed(PreferencesModel::controller_mapping_path()), model.controller_mapping_path.is_some()),
+                            connect_clicked(sender) => move |_button| {
+                                send!(sender, PreferencesMsg::ReloadControllerMappings);
+                            },
+                        },
+                        add_suffix = &Button {
+                            set_label: tr("选择"),
+                            set_valign: Align::Center,
+                            connect_clicked(sender, window) => move |_button| {
+                                std::mem::forget(select_path(FileChooserAction::Open, &[], &window, clone!(@strong sender => move |path| {
+                                    if let Some(path) = path {
+                                        send!(sender, PreferencesMsg::SetControllerMappingPath(Some(path)));
+                                    }
+                                })));
+                            },
+                        },
+                    },
+                },
+                add = &PreferencesGroup {
+                    set_title: tr("摇杆与扳机"),
+                    set_description: Some(tr("对所有输入设备生效")),
+                    add = &ActionRow {
+                        set_title: tr("反转左摇杆水平方向"),
+                        add_suffix: invert_left_x_switch = &Switch {
+                            set_active: track!(model.changed(PreferencesModel::input_axis_settings()), model.input_axis_settings.invert_left_x),
+                            set_valign: Align::Center,
+                            connect_state_set(sender) => move |_switch, state| {
+                                send!(sender, PreferencesMsg::SetAxisInverted(Axis::LeftX, state));
+                                Inhibit(false)
+                            }
+                        },
+                        set_activatable_widget: Some(&invert_left_x_switch),
+                    },
+                    add = &ActionRow {
+                        set_title: tr("反转左摇杆垂直方向"),
+                        add_suffix: invert_left_y_switch = &Switch {
+                            set_active: track!(model.changed(PreferencesModel::input_axis_settings()), model.input_axis_settings.invert_left_y),
+                            set_valign: Align::Center,
+                            connect_state_set(sender) => move |_switch, state| {
+                                send!(sender, PreferencesMsg::SetAxisInverted(Axis::LeftY, state));
+                                Inhibit(false)
+                            }
+                        },
+                        set_activatable_widget: Some(&invert_left_y_switch),
+                    },
+                    add = &ActionRow {
+                        set_title: tr("反转右摇杆水平方向"),
+                        add_suffix: invert_right_x_switch = &Switch {
+                            set_active: track!(model.changed(PreferencesModel::input_axis_settings()), model.input_axis_settings.invert_right_x),
+                            set_valign: Align::Center,
+                            connect_state_set(sender) => move |_switch, state| {
+                                send!(sender, PreferencesMsg::SetAxisInverted(Axis::RightX, state));
+                                Inhibit(false)
+                            }
+                        },
+                        set_activatable_widget: Some(&invert_right_x_switch),
+                    },
+                    add = &ActionRow {
+                        set_title: tr("反转右摇杆垂直方向"),
+                        add_suffix: invert_right_y_switch = &Switch {
+                            set_active: track!(model.changed(PreferencesModel::input_axis_settings()), model.input_axis_settings.invert_right_y),
+                            set_valign: Align::Center,
+                            connect_state_set(sender) => move |_switch, state| {
+                                send!(sender, PreferencesMsg::SetAxisInverted(Axis::RightY, state));
+                                Inhibit(false)
+                            }
+                        },
+                        set_activatable_widget: Some(&invert_right_y_switch),
+                    },
+                    add = &ActionRow {
+                        set_title: tr("扳机触发阈值"),
+                        set_subtitle: tr("扳机按下的行程超过该比例时才视为按下，用于避免轻触扳机时误开灯或误操作机械臂"),
+                        add_suffix = &SpinButton::with_range(0.0, 100.0, 1.0) {
+                            set_value: track!(model.changed(PreferencesModel::input_axis_settings()), model.input_axis_settings.trigger_threshold as f64),
+                            set_digits: 0,
+                            set_valign: Align::Center,
+                            set_can_focus: false,
+                            connect_value_changed(sender) => move |button| {
+                                send!(sender, PreferencesMsg::SetTriggerThreshold(button.value() as u8));
+                            }
+                        },
+                        add_suffix = &Label {
+                            set_label: "%",
+                        },
+                    },
+                    add = &ComboRow {
+                        set_title: tr("平移响应曲线"),
+                        set_subtitle: tr("左摇杆与右摇杆垂直方向的输出随摇杆偏移量变化的方式，指数越大摇杆中心附近越便于微调"),
+                        set_model: Some(&{
+                            let model = StringList::new(&[]);
+                            for value in ResponseCurve::iter() {
+                                model.append(&value.to_string());
+                            }
+                            model
+                        }),
+                        set_selected: track!(model.changed(PreferencesModel::input_axis_settings()), ResponseCurve::iter().position(|x| x == model.input_axis_settings.translation_curve).unwrap() as u32),
+                        connect_selected_notify(sender) => move |row| {
+                            send!(sender, PreferencesMsg::SetResponseCurve(AxisGroup::Translation, ResponseCurve::iter().nth(row.selected() as usize).unwrap()))
+                        },
+                    },
+                    add = &ActionRow {
+                        set_title: tr("平移自定义指数"),
+                        set_sensitive: track!(model.changed(PreferencesModel::input_axis_settings()), model.input_axis_settings.translation_curve == ResponseCurve::Custom),
+                        add_suffix = &SpinButton::with_range(1.0, 5.0, 0.1) {
+                            set_value: track!(model.changed(PreferencesModel::input_axis_settings()), model.input_axis_settings.translation_exponent),
+                            set_digits: 1,
+                            set_valign: Align::Center,
+                            set_can_focus: false,
+                            connect_value_changed(sender) => move |button| {
+                                send!(sender, PreferencesMsg::SetResponseExponent(AxisGroup::Translation, button.value()));
+                            }
+                        },
+                    },
+                    add = &ComboRow {
+                        set_title: tr("转向响应曲线"),
+                        set_subtitle: tr("右摇杆水平方向的输出随摇杆偏移量变化的方式"),
+                        set_model: Some(&{
+                            let model = StringList::new(&[]);
+                            for value in ResponseCurve::iter() {
+                                model.append(&value.to_string());
+                            }
+                            model
+                        }),
+                        set_selected: track!(model.changed(PreferencesModel::input_axis_settings()), ResponseCurve::iter().position(|x| x == model.input_axis_settings.rotation_curve).unwrap() as u32),
+                        connect_selected_notify(sender) => move |row| {
+                            send!(sender, PreferencesMsg::SetResponseCurve(AxisGroup::Rotation, ResponseCurve::iter().nth(row.selected() as usize).unwrap()))
+                        },
+                    },
+                    add = &ActionRow {
+                        set_title: tr("转向自定义指数"),
+                        set_sensitive: track!(model.changed(PreferencesModel::input_axis_settings()), model.input_axis_settings.rotation_curve == ResponseCurve::Custom),
+                        add_suffix = &SpinButton::with_range(1.0, 5.0, 0.1) {
+                            set_value: track!(model.changed(PreferencesModel::input_axis_settings()), model.input_axis_settings.rotation_exponent),
+                            set_digits: 1,
+                            set_valign: Align::Center,
+                            set_can_focus: false,
+                            connect_value_changed(sender) => move |button| {
+                                send!(sender, PreferencesMsg::SetResponseExponent(AxisGroup::Rotation, button.value()));
+                            }
+                        },
+                    },
+                    add = &ActionRow {
+                        set_title: tr("方向指示阈值"),
+                        set_subtitle: tr("摇杆偏移量超过该值时点亮状态面板中对应的方向指示，仅影响显示而不影响实际输出"),
+                        add_suffix = &SpinButton::with_range(0.0, i16::MAX as f64, 100.0) {
+                            set_value: track!(model.changed(PreferencesModel::joystick_display_threshold()), model.joystick_display_threshold as f64),
+                            set_digits: 0,
+                            set_valign: Align::Center,
+                            set_can_focus: false,
+                            connect_value_changed(sender) => move |button| {
+                                send!(sender, PreferencesMsg::SetJoystickDisplayThreshold(button.value() as i16));
+                            }
+                        },
+                    },
+                },
+                add = &PreferencesGroup {
+                    set_title: tr("输入分配"),
+                    set_description: Some(tr("多个机位共用输入设备时的分配方式")),
+                    add = &ComboRow {
+                        set_title: tr("分配方式"),
+                        set_subtitle: tr("固定分配时输入设备只控制选择了它的机位；跟随焦点时所有输入设备都控制最后点击画面的机位"),
+                        set_model: Some(&{
+                            let model = StringList::new(&[]);
+                            for value in InputDispatchMode::iter() {
+                                model.append(&value.to_string());
+                            }
+                            model
+                        }),
+                        set_selected: track!(model.changed(PreferencesModel::input_dispatch_mode()), InputDispatchMode::iter().position(|x| x == model.input_dispatch_mode).unwrap() as u32),
+                        connect_selected_notify(sender) => move |row| {
+                            send!(sender, PreferencesMsg::SetInputDispatchMode(InputDispatchMode::iter().nth(row.selected() as usize).unwrap()))
+                        }
+                    },
+                },
+                add = &PreferencesGroup {
+                    set_title: tr("屏幕虚拟摇杆"),
+                    set_description: Some(tr("适用于没有手柄的触屏设备")),
+                    add = &ActionRow {
+                        set_title: tr("启用屏幕虚拟摇杆"),
+                        set_subtitle: tr("启用后可在机位的输入设备列表中选择虚拟摇杆，同一时间仅在一个机位的画面上显示"),
+                        add_suffix: virtual_joystick_switch = &Switch {
+                            set_active: track!(model.changed(PreferencesModel::virtual_joystick_enabled()), model.virtual_joystick_enabled),
+                            set_valign: Align::Center,
+                            connect_state_set(sender) => move |_switch, state| {
+                                send!(sender, PreferencesMsg::SetVirtualJoystickEnabled(state));
+                                Inhibit(false)
+                            }
+                        },
+                        set_activatable_widget: Some(&virtual_joystick_switch),
+                    },
+                },
+                add = &PreferencesGroup {
+                    set_title: tr("组合键"),
+                    set_description: Some(tr("在短时间内同时按下一组按键以触发指定的动作，按住组合键期间这些按键原本的功能不会生效")),
+                    add = &Frame {
+                        set_child: track!(model.changed(PreferencesModel::button_chords()), Some(&button_chords_list_box(&model.button_chords, &sender))),
+                    },
+                    add = &ActionRow {
+                        set_title: tr("添加组合键"),
+                        add_suffix: chord_first_button = &DropDown {
+                            set_valign: Align::Center,
+                            set_model: Some(&{
+                                let model = StringList::new(&[]);
+                                for button in CHORD_BUTTONS {
+                                    model.append(button_name(button));
+                                }
+                                model
+                            }),
+                            set_selected: 4,
+                        },
+                        add_suffix = &Label {
+                            set_label: "+",
+                        },
+                        add_suffix: chord_second_button = &DropDown {
+                            set_valign: Align::Center,
+                            set_model: Some(&{
+                                let model = StringList::new(&[]);
+                                for button in CHORD_BUTTONS {
+                                    model.append(button_name(button));
+                                }
+                                model
+                            }),
+                            set_selected: 5,
+                        },
+                        add_suffix: chord_action = &DropDown {
+                            set_valign: Align::Center,
+                            set_model: Some(&{
+                                let model = StringList::new(&[]);
+                                for action in ChordAction::iter() {
+                                    model.append(&action.to_string());
+                                }
+                                model
+                            }),
+                        },
+                        add_suffix = &Button {
+                            set_icon_name: "list-add-symbolic",
+                            set_tooltip_text: Some(tr("添加")),
+                            set_valign: Align::Center,
+                            connect_clicked[sender = sender.clone(), first = chord_first_button.clone(), second = chord_second_button.clone(), action = chord_action.clone()] => move |_button| {
+                                send!(sender, PreferencesMsg::AddButtonChord(ButtonChord {
+                                    buttons: vec![CHORD_BUTTONS[first.selected() as usize], CHORD_BUTTONS[second.selected() as usize]],
+                                    action: ChordAction::iter().nth(action.selected() as usize).unwrap(),
+                                }));
+                            },
+                        },
+                    },
                 },
             },
             add = &PreferencesPage {
-                set_title: "视频",
+                set_title: tr("视频"),
                 set_icon_name: Some("video-display-symbolic"),
                 add = &PreferencesGroup {
-                    set_title: "显示",
-                    set_description: Some("上位机的显示的画面设置"),
+                    set_title: tr("显示"),
+                    set_description: Some(tr("上位机的显示的画面设置")),
                     add = &ActionRow {
-                        set_title: "默认保持长宽比",
-                        set_subtitle: "在改变窗口大小的时是否保持画面比例，这可能导致画面无法全屏",
+                        set_title: tr("默认保持长宽比"),
+                        set_subtitle: tr("在改变窗口大小的时是否保持画面比例，这可能导致画面无法全屏"),
                         add_suffix: default_keep_video_display_ratio_switch = &Switch {
                             set_active: track!(model.changed(PreferencesModel::default_keep_video_display_ratio()), model.default_keep_video_display_ratio),
                             set_valign: Align::Center,
@@ -319,42 +1065,73 @@ impl Widgets<PreferencesModel, AppModel> for PreferencesWidgets {
                         },
                         set_activatable_widget: Some(&default_keep_video_display_ratio_switch),
                     },
+                    add = &ActionRow {
+                        set_title: tr("画面边框颜色"),
+                        set_subtitle: tr("保持长宽比时画面两侧空白区域的填充颜色"),
+                        add_suffix = &ColorButton {
+                            set_rgba: track!(model.changed(PreferencesModel::video_letterbox_color()), &gdk::RGBA::parse(model.get_video_letterbox_color()).unwrap_or_else(|_| gdk::RGBA::new(0.0, 0.0, 0.0, 1.0))),
+                            set_valign: Align::Center,
+                            connect_color_set(sender) => move |button| {
+                                send!(sender, PreferencesMsg::SetVideoLetterboxColor(button.rgba().to_string()));
+                            }
+                        },
+                    },
+                    add = &ActionRow {
+                        set_title: tr("使用 GTK 视频输出"),
+                        set_subtitle: tr("未启用图像增强、直方图、测量、裁剪与旋转时由 GTK 直接渲染视频流，省去画面的复制与转换，需要 gtk4paintablesink 元素，下次启动拉流时生效"),
+                        add_suffix: native_video_sink_enabled_switch = &Switch {
+                            set_active: track!(model.changed(PreferencesModel::native_video_sink_enabled()), *model.get_native_video_sink_enabled()),
+                            set_valign: Align::Center,
+                            connect_state_set(sender) => move |_switch, state| {
+                                send!(sender, PreferencesMsg::SetNativeVideoSinkEnabled(state));
+                                Inhibit(false)
+                            }
+                        },
+                        set_activatable_widget: Some(&native_video_sink_enabled_switch),
+                    },
                 },
                 add = &PreferencesGroup {
-                    set_title: "管道",
-                    set_description: Some("配置拉流以及录制所使用的管道"),
+                    set_title: tr("管道"),
+                    set_description: Some(tr("配置拉流以及录制所使用的管道")),
                     add = &ActionRow {
-                        set_title: "默认视频 URL",
-                        set_subtitle: "第一机位使用的视频 URL，其他机位会自动累加端口",
+                        set_title: tr("默认视频 URL"),
+                        set_subtitle: &format!("{}{}", tr("第一机位使用的视频 URL，其他机位会自动累加端口，"), tr(VIDEO_URL_FORMAT_HINT)),
                         add_suffix = &Entry {
                             set_text: track!(model.changed(PreferencesModel::default_video_url()), model.get_default_video_url().to_string().as_str()),
                             set_valign: Align::Center,
                             set_width_request: 200,
                             connect_changed(sender) => move |entry| {
-                                if let Ok(url) = Url::from_str(&entry.text()) {
-                                    send!(sender, PreferencesMsg::SetDefaultVideoUrl(url));
-                                    entry.remove_css_class("error");
-                                } else {
-                                    entry.add_css_class("error");
+                                match parse_video_url(&entry.text()) {
+                                    Ok(url) => {
+                                        send!(sender, PreferencesMsg::SetDefaultVideoUrl(url));
+                                        entry.remove_css_class("error");
+                                        entry.set_tooltip_text(None);
+                                    },
+                                    Err(err) => {
+                                        entry.add_css_class("error");
+                                        entry.set_tooltip_text(Some(err));
+                                    },
                                 }
                             }
                         },
                     },
-                    add = &ActionRow {
-                        set_title: "默认启用画面自动跳帧",
-                        set_subtitle: "默认启用自动跳帧，当机位画面与视频流延迟过大时避免延迟提升",
-                        add_suffix: appsink_queue_leaky_enabled_switch = &Switch {
-                            set_active: track!(model.changed(PreferencesModel::default_appsink_queue_leaky_enabled()), *model.get_default_appsink_queue_leaky_enabled()),
-                            set_valign: Align::Center,
-                            connect_state_set(sender) => move |_switch, state| {
-                                send!(sender, PreferencesMsg::SetDefaultAppSinkQueueLeakyEnabled(state));
-                                Inhibit(false)
+                    add = &ComboRow {
+                        set_title: tr("默认画面缓存策略"),
+                        set_subtitle: tr("低延迟优先时画面落后于视频流会自动跳帧，完整性优先时不跳帧并缓存最多 120 帧，会占用更多内存并可能增大延迟"),
+                        set_model: Some(&{
+                            let model = StringList::new(&[]);
+                            for value in AppSinkQueueMode::iter() {
+                                model.append(&value.to_string());
                             }
-                        },
-                        set_activatable_widget: Some(&appsink_queue_leaky_enabled_switch),
+                            model
+                        }),
+                        set_selected: track!(model.changed(PreferencesModel::default_appsink_queue_mode()), AppSinkQueueMode::iter().position(|x| x == model.default_appsink_queue_mode).unwrap() as u32),
+                        connect_selected_notify(sender) => move |row| {
+                            send!(sender, PreferencesMsg::SetDefaultAppSinkQueueMode(AppSinkQueueMode::iter().nth(row.selected() as usize).unwrap()));
+                        }
                     },
                     add = &ExpanderRow {
-                        set_title: "默认手动配置管道",
+                        set_title: tr("默认手动配置管道"),
                         set_show_enable_switch: true,
                         set_expanded: !*model.get_default_use_decodebin(),
                         set_enable_expansion: track!(model.changed(PreferencesModel::default_use_decodebin()), !*model.get_default_use_decodebin()),
@@ -362,9 +1139,9 @@ impl Widgets<PreferencesModel, AppModel> for PreferencesWidgets {
                             send!(sender, PreferencesMsg::SetDefaultUseDecodebin(!expander.enables_expansion()));
                         },
                         add_row = &ActionRow {
-                            set_title: "默认接收缓冲区延迟",
-                            set_subtitle: "若接收的视频流出现卡顿、花屏等现象，可以增加接收缓冲区延迟，牺牲视频的实时性来换取流畅度的提升",
-                            add_suffix = &SpinButton::with_range(0.0, 60000.0, 50.0) {
+                            set_title: tr("默认接收缓冲区延迟"),
+                            set_subtitle: tr("若接收的视频流出现卡顿、花屏等现象，可以增加接收缓冲区延迟，牺牲视频的实时性来换取流畅度的提升"),
+                            add_suffix = &SpinButton::with_range(0.0, VIDEO_LATENCY_MAX as f64, 50.0) {
                                 set_value: track!(model.changed(PreferencesModel::default_video_latency()), model.default_video_latency as f64),
                                 set_digits: 0,
                                 set_valign: Align::Center,
@@ -374,12 +1151,12 @@ impl Widgets<PreferencesModel, AppModel> for PreferencesWidgets {
                                 }
                             },
                             add_suffix = &Label {
-                                set_label: "毫秒",
+                                set_label: tr("毫秒"),
                             },
                         },
                         add_row = &ComboRow {
-                            set_title: "默认解码器",
-                            set_subtitle: "指定解码视频流默认使用的解码器",
+                            set_title: tr("默认解码器"),
+                            set_subtitle: tr("指定解码视频流默认使用的解码器"),
                             set_model: Some(&{
                                 let model = StringList::new(&[]);
                                 for value in VideoCodec::iter() {
@@ -393,8 +1170,8 @@ impl Widgets<PreferencesModel, AppModel> for PreferencesWidgets {
                             }
                         },
                         add_row = &ComboRow {
-                            set_title: "默认解码器接口",
-                            set_subtitle: "指定解码视频流默认使用的解码器接口",
+                            set_title: tr("默认解码器接口"),
+                            set_subtitle: tr("指定解码视频流默认使用的解码器接口"),
                             set_model: Some(&{
                                 let model = StringList::new(&[]);
                                 for value in VideoCodecProvider::iter() {
@@ -408,8 +1185,8 @@ impl Widgets<PreferencesModel, AppModel> for PreferencesWidgets {
                             }
                         },
                         add_row = &ComboRow {
-                            set_title: "默认色彩空间转换",
-                            set_subtitle: "设置视频编解码、视频流显示要求的色彩空间转换所使用的默认硬件",
+                            set_title: tr("默认色彩空间转换"),
+                            set_subtitle: tr("设置视频编解码、视频流显示要求的色彩空间转换所使用的默认硬件"),
                             set_model: Some(&{
                                 let model = StringList::new(&[]);
                                 for value in ColorspaceConversion::iter() {
@@ -424,8 +1201,8 @@ impl Widgets<PreferencesModel, AppModel> for PreferencesWidgets {
                         },
                     },
                     add = &ActionRow {
-                        set_title: "管道等待超时",
-                        set_subtitle: "由于网络等原因，管道可能失去响应，超过设定时间后上位机将强制终止管道，设置为 0 以禁用等待超时（需要重启管道以应用设置）",
+                        set_title: tr("管道等待超时"),
+                        set_subtitle: tr("由于网络等原因，管道可能失去响应，超过设定时间后上位机将强制终止管道，设置为 0 以禁用等待超时（需要重启管道以应用设置）"),
                         add_suffix = &SpinButton::with_range(0.0, 99.0, 1.0) {
                             set_value: track!(model.changed(PreferencesModel::pipeline_timeout()), model.pipeline_timeout.as_secs() as f64),
                             set_digits: 0,
@@ -436,24 +1213,85 @@ impl Widgets<PreferencesModel, AppModel> for PreferencesWidgets {
                             }
                         },
                         add_suffix = &Label {
-                            set_label: "秒",
+                            set_label: tr("秒"),
                         },
                     },
                 },
                 add = &PreferencesGroup {
-                    set_title: "截图",
-                    set_description: Some("画面的截图选项"),
+                    set_title: tr("文件名"),
+                    set_description: Some(tr("截图与录制文件的命名方式")),
                     add = &ActionRow {
-                        set_title: "图片保存目录",
-                        set_subtitle: track!(model.changed(PreferencesModel::image_save_path()), model.image_save_path.to_str().unwrap()),
+                        set_title: tr("文件名模板"),
+                        set_subtitle: tr("可用的占位符有 {date}（日期）、{time}（时间）、{datetime}（完整时间戳）、{slave}（机位连接地址）与 {counter}（本次启动以来该机位保存文件的序号），模板无效时使用时间戳作为文件名"),
+                        add_suffix = &Entry {
+                            set_text: track!(model.changed(PreferencesModel::file_name_template()), model.get_file_name_template().as_str()),
+                            set_valign: Align::Center,
+                            set_width_request: 200,
+                            connect_changed(sender) => move |entry| {
+                                match validate_file_name_template(&entry.text()) {
+                                    Ok(()) => {
+                                        entry.remove_css_class("error");
+                                        entry.set_tooltip_text(None);
+                                    },
+                                    Err(err) => {
+                                        entry.add_css_class("error");
+                                        entry.set_tooltip_text(Some(&err.to_string()));
+                                    },
+                                }
+                                send!(sender, PreferencesMsg::SetFileNameTemplate(entry.text().to_string()));
+                            }
+                        },
+                    },
+                    add = &ComboRow {
+                        set_title: tr("子文件夹"),
+                        set_subtitle: tr("将截图与录像按日期或按日期与本次启动时间保存到保存目录下的子文件夹中，子文件夹在保存第一个文件时创建"),
+                        set_model: Some(&{
+                            let model = StringList::new(&[]);
+                            for value in SaveDirectoryScheme::iter() {
+                                model.append(&value.to_string());
+                            }
+                            model
+                        }),
+                        set_selected: track!(model.changed(PreferencesModel::save_directory_scheme()), SaveDirectoryScheme::iter().position(|x| x == model.save_directory_scheme).unwrap() as u32),
+                        connect_selected_notify(sender) => move |row| {
+                            send!(sender, PreferencesMsg::SetSaveDirectoryScheme(SaveDirectoryScheme::iter().nth(row.selected() as usize).unwrap()));
+                        }
+                    },
+                },
+                add = &PreferencesGroup {
+                    set_title: tr("截图"),
+                    set_description: Some(tr("画面的截图选项")),
+                    add = &ActionRow {
+                        set_title: tr("图片保存目录"),
+                        set_subtitle: track!(model.changed(PreferencesModel::image_save_path()), &model.image_save_directory().to_string_lossy()),
                         set_activatable: true,
                         connect_activated(sender) => move |_row| {
                             send!(sender, PreferencesMsg::OpenImageDirectory);
-                        }
+                        },
+                        add_suffix = &Button {
+                            set_icon_name: "edit-undo-symbolic",
+                            set_tooltip_text: Some(tr("恢复默认目录")),
+                            set_valign: Align::Center,
+                            set_visible: track!(model.changed(PreferencesModel::image_save_path()), model.image_save_path.is_some()),
+                            connect_clicked(sender) => move |_button| {
+                                send!(sender, PreferencesMsg::SetImageSavePath(None));
+                            },
+                        },
+                        add_suffix = &Button {
+                            set_label: tr("更改"),
+                            set_valign: Align::Center,
+                            connect_clicked(sender, window) => move |_button| {
+                                std::mem::forget(select_path(FileChooserAction::SelectFolder, &[], &window, clone!(@strong sender => move |path| {
+                                    if let Some(path) = path {
+                                        send!(sender, PreferencesMsg::SetImageSavePath(Some(path)));
+                                    }
+                                })));
+                            },
+                        },
                     },
                     add = &ComboRow {
-                        set_title: "图片保存格式",
-                        set_subtitle: "截图保存的图片格式",
+                        set_title: tr("图片保存格式"),
+                        set_subtitle: tr("截图保存的图片格式"),
                         set_model: Some(&{
                             let model = StringList::new(&[]);
                             for value in ImageFormat::iter() {
@@ -466,21 +1304,117 @@ impl Widgets<PreferencesModel, AppModel> for PreferencesWidgets {
                             send!(sender, PreferencesMsg::SetImageSaveFormat(ImageFormat::iter().nth(row.selected() as usize).unwrap()))
                         }
                     },
+                    add = &ActionRow {
+                        set_title: tr("网络存储写入重试次数"),
+                        set_subtitle: tr("图片保存目录位于网络存储上且截图写入失败时，将画面保留在内存中并重试，每次重试的间隔翻倍"),
+                        add_suffix = &SpinButton::with_range(0.0, 10.0, 1.0) {
+                            set_value: track!(model.changed(PreferencesModel::network_write_retries()), model.network_write_retries as f64),
+                            set_digits: 0,
+                            set_valign: Align::Center,
+                            set_can_focus: false,
+                            connect_value_changed(sender) => move |button| {
+                                send!(sender, PreferencesMsg::SetNetworkWriteRetries(button.value() as u8));
+                            }
+                        },
+                        add_suffix = &Label {
+                            set_label: tr("次"),
+                        },
+                    },
+                    add = &ActionRow {
+                        set_title: tr("截图时添加标注"),
+                        set_subtitle: tr("通过工具栏按钮截图时打开标注窗口，可在画面上绘制矩形与箭头并添加说明文字后再保存"),
+                        add_suffix: annotate_screenshots_switch = &Switch {
+                            set_active: track!(model.changed(PreferencesModel::annotate_screenshots()), *model.get_annotate_screenshots()),
+                            set_valign: Align::Center,
+                            connect_state_set(sender) => move |_switch, state| {
+                                send!(sender, PreferencesMsg::SetAnnotateScreenshots(state));
+                                Inhibit(false)
+                            }
+                        },
+                        set_activatable_widget: Some(&annotate_screenshots_switch),
+                    },
                 },
                 add = &PreferencesGroup {
-                    set_title: "录制",
-                    set_description: Some("视频流的录制选项"),
+                    set_title: tr("采集报告"),
+                    set_description: Some(tr("将当前画面与采集时刻的完整状态信息、机位信息一并保存，便于整理巡检结果")),
                     add = &ActionRow {
-                        set_title: "视频保存目录",
-                        set_subtitle: track!(model.changed(PreferencesModel::video_save_path()), model.video_save_path.to_str().unwrap()),
+                        set_title: tr("报告保存目录"),
+                        set_subtitle: track!(model.changed(PreferencesModel::capture_report_save_path()), &model.capture_report_save_directory().to_string_lossy()),
                         set_activatable: true,
                         connect_activated(sender) => move |_row| {
-                            send!(sender, PreferencesMsg::OpenVideoDirectory);
+                            send!(sender, PreferencesMsg::OpenCaptureReportDirectory);
+                        },
+                        add_suffix = &Button {
+                            set_icon_name: "edit-undo-symbolic",
+                            set_tooltip_text: Some(tr("恢复默认目录")),
+                            set_valign: Align::Center,
+                            set_visible: track!(model.changed(PreferencesModel::capture_report_save_path()), model.capture_report_save_path.is_some()),
+                            connect_clicked(sender) => move |_button| {
+                                send!(sender, PreferencesMsg::SetCaptureReportSavePath(None));
+                            },
+                        },
+                        add_suffix = &Button {
+                            set_label: tr("更改"),
+                            set_valign: Align::Center,
+                            connect_clicked(sender, window) => move |_button| {
+                                std::mem::forget(select_path(FileChooserAction::SelectFolder, &[], &window, clone!(@strong sender => move |path| {
+                                    if let Some(path) = path {
+                                        send!(sender, PreferencesMsg::SetCaptureReportSavePath(Some(path)));
+                                    }
+                                })));
+                            },
+                        },
+                    },
+                    add = &ComboRow {
+                        set_title: tr("报告格式"),
+                        set_subtitle: tr("保存为截图与同名的 JSON 文件（截图使用图片保存格式），或内嵌截图的单个 HTML 文件"),
+                        set_model: Some(&{
+                            let model = StringList::new(&[]);
+                            for value in CaptureReportFormat::iter() {
+                                model.append(&value.to_string());
+                            }
+                            model
+                        }),
+                        set_selected: track!(model.changed(PreferencesModel::capture_report_format()), CaptureReportFormat::iter().position(|x| x == model.capture_report_format).unwrap() as u32),
+                        connect_selected_notify(sender) => move |row| {
+                            send!(sender, PreferencesMsg::SetCaptureReportFormat(CaptureReportFormat::iter().nth(row.selected() as usize).unwrap()));
                         }
                     },
+                },
+                add = &PreferencesGroup {
+                    set_title: tr("录制"),
+                    set_description: Some(tr("视频流的录制选项")),
                     add = &ActionRow {
-                        set_title: "同步录制时使用单独文件夹",
-                        set_subtitle: "每次进行同步录制时，都在视频保存目录下创建新的文件夹，并在其中保存录制的视频文件",
+                        set_title: tr("视频保存目录"),
+                        set_subtitle: track!(model.changed(PreferencesModel::video_save_path()), &model.video_save_directory().to_string_lossy()),
+                        set_activatable: true,
+                        connect_activated(sender) => move |_row| {
+                            send!(sender, PreferencesMsg::OpenVideoDirectory);
+                        },
+                        add_suffix = &Button {
+                            set_icon_name: "edit-undo-symbolic",
+                            set_tooltip_text: Some(tr("恢复默认目录")),
+                            set_valign: Align::Center,
+                            set_visible: track!(model.changed(PreferencesModel::video_save_path()), model.video_save_path.is_some()),
+                            connect_clicked(sender) => move |_button| {
+                                send!(sender, PreferencesMsg::SetVideoSavePath(None));
+                            },
+                        },
+                        add_suffix = &Button {
+                            set_label: tr("更改"),
+                            set_valign: Align::Center,
+                            connect_clicked(sender, window) => move |_button| {
+                                std::mem::forget(select_path(FileChooserAction::SelectFolder, &[], &window, clone!(@strong sender => move |path| {
+                                    if let Some(path) = path {
+                                        send!(sender, PreferencesMsg::SetVideoSavePath(Some(path)));
+                                    }
+                                })));
+                            },
+                        },
+                    },
+                    add = &ActionRow {
+                        set_title: tr("同步录制时使用单独文件夹"),
+                        set_subtitle: tr("每次进行同步录制时，都在视频保存目录下创建新的文件夹，并在其中保存录制的视频文件"),
                         add_suffix: video_sync_record_use_separate_directory_switch = &Switch {
                             set_active: track!(model.changed(PreferencesModel::video_sync_record_use_separate_directory()), *model.get_video_sync_record_use_separate_directory()),
                             set_valign: Align::Center,
@@ -491,8 +1425,37 @@ impl Widgets<PreferencesModel, AppModel> for PreferencesWidgets {
                         },
                         set_activatable_widget: Some(&video_sync_record_use_separate_directory_switch),
                     },
+                    add = &ActionRow {
+                        set_title: tr("通讯中断时继续录制"),
+                        set_subtitle: tr("与下位机的通讯意外中断时继续录制视频流，视频流中断时录制总会停止"),
+                        add_suffix: keep_recording_on_connection_lost_switch = &Switch {
+                            set_active: track!(model.changed(PreferencesModel::keep_recording_on_connection_lost()), *model.get_keep_recording_on_connection_lost()),
+                            set_valign: Align::Center,
+                            connect_state_set(sender) => move |_switch, state| {
+                                send!(sender, PreferencesMsg::SetKeepRecordingOnConnectionLost(state));
+                                Inhibit(false)
+                            }
+                        },
+                        set_activatable_widget: Some(&keep_recording_on_connection_lost_switch),
+                    },
+                    add = &ActionRow {
+                        set_title: tr("最低可用空间"),
+                        set_subtitle: tr("视频保存目录所在磁盘的可用空间低于该值时无法开始录制，录制过程中低于该值的一半时自动停止录制"),
+                        add_suffix = &SpinButton::with_range(100.0, 100000.0, 100.0) {
+                            set_value: track!(model.changed(PreferencesModel::recording_min_free_space()), model.recording_min_free_space as f64),
+                            set_digits: 0,
+                            set_valign: Align::Center,
+                            set_can_focus: false,
+                            connect_value_changed(sender) => move |button| {
+                                send!(sender, PreferencesMsg::SetRecordingMinFreeSpace(button.value() as u32));
+                            }
+                        },
+                        add_suffix = &Label {
+                            set_label: "MB",
+                        },
+                    },
                     add = &ExpanderRow {
-                        set_title: "默认录制时重新编码",
+                        set_title: tr("默认录制时重新编码"),
                         set_show_enable_switch: true,
                         set_expanded: *model.get_default_reencode_recording_video(),
                         set_enable_expansion: track!(model.changed(PreferencesModel::default_reencode_recording_video()), *model.get_default_reencode_recording_video()),
@@ -500,8 +1463,8 @@ impl Widgets<PreferencesModel, AppModel> for PreferencesWidgets {
                             send!(sender, PreferencesMsg::SetDefaultReencodeRecordingVideo(expander.enables_expansion()));
                         },
                         add_row = &ComboRow {
-                            set_title: "默认编码器",
-                            set_subtitle: "视频录制时默认使用的编码器",
+                            set_title: tr("默认编码器"),
+                            set_subtitle: tr("视频录制时默认使用的编码器"),
                             set_model: Some(&{
                                 let model = StringList::new(&[]);
                                 for value in VideoCodec::iter() {
@@ -515,8 +1478,8 @@ impl Widgets<PreferencesModel, AppModel> for PreferencesWidgets {
                             }
                         },
                         add_row = &ComboRow {
-                            set_title: "默认编码器接口",
-                            set_subtitle: "视频录制时默认调用的编码器接口",
+                            set_title: tr("默认编码器接口"),
+                            set_subtitle: tr("视频录制时默认调用的编码器接口"),
                             set_model: Some(&{
                                 let model = StringList::new(&[]);
                                 for value in VideoCodecProvider::iter() {
@@ -533,14 +1496,14 @@ impl Widgets<PreferencesModel, AppModel> for PreferencesWidgets {
                 },
             },
             add = &PreferencesPage {
-                set_title: "调试",
+                set_title: tr("调试"),
                 set_icon_name: Some("preferences-other-symbolic"),
                 add = &PreferencesGroup {
-                    set_title: "控制环",
-                    set_description: Some("配置控制环调试选项"),
+                    set_title: tr("控制环"),
+                    set_description: Some(tr("配置控制环调试选项")),
                     add = &ActionRow {
-                        set_title: "反馈曲线最大点数",
-                        set_subtitle: "绘制控制环反馈曲线时使用最多使用点数，这将影响最多能观测的历史数据",
+                        set_title: tr("反馈曲线最大点数"),
+                        set_subtitle: tr("绘制控制环反馈曲线时使用最多使用点数，这将影响最多能观测的历史数据"),
                         add_suffix = &SpinButton::with_range(1.0, 255.0, 1.0) {
                             set_value: track!(model.changed(PreferencesModel::param_tuner_graph_view_point_num_limit()), model.param_tuner_graph_view_point_num_limit as f64),
                             set_digits: 0,
@@ -552,8 +1515,8 @@ impl Widgets<PreferencesModel, AppModel> for PreferencesWidgets {
                         },
                     },
                     add = &ActionRow {
-                        set_title: "反馈曲线更新时间间隔",
-                        set_subtitle: "控制环反馈曲线的更新速率，这将影响最多能观测的历史数据",
+                        set_title: tr("反馈曲线更新时间间隔"),
+                        set_subtitle: tr("控制环反馈曲线的更新速率，这将影响最多能观测的历史数据"),
                         add_suffix = &SpinButton::with_range(50.0, 10000.0, 50.0) {
                             set_value: track!(model.changed(PreferencesModel::param_tuner_graph_view_update_interval()), model.param_tuner_graph_view_update_interval as f64),
                             set_digits: 0,
@@ -564,10 +1527,27 @@ impl Widgets<PreferencesModel, AppModel> for PreferencesWidgets {
                             }
                         },
                         add_suffix = &Label {
-                            set_label: "毫秒",
+                            set_label: tr("毫秒"),
                         },
                     },
                 },
+                add = &PreferencesGroup {
+                    set_title: tr("视频管道"),
+                    set_description: Some(tr("配置视频管道调试选项")),
+                    add = &ActionRow {
+                        set_title: tr("导出管道结构图"),
+                        set_subtitle: tr("在机位的诊断信息中提供导出当前视频管道结构图（DOT 格式）的按钮，用于排查元素与连接问题"),
+                        add_suffix: pipeline_graph_export_enabled_switch = &Switch {
+                            set_active: track!(model.changed(PreferencesModel::pipeline_graph_export_enabled()), *model.get_pipeline_graph_export_enabled()),
+                            set_valign: Align::Center,
+                            connect_state_set(sender) => move |_switch, state| {
+                                send!(sender, PreferencesMsg::SetPipelineGraphExportEnabled(state));
+                                Inhibit(false)
+                            }
+                        },
+                        set_activatable_widget: Some(&pipeline_graph_export_enabled_switch),
+                    },
+                },
             },
         }
     }
@@ -589,35 +1569,86 @@ impl ComponentUpdate<AppModel> for PreferencesModel {
     ) {
         self.reset();
         match msg {
-            PreferencesMsg::SetVideoSavePath(path) => self.set_video_save_path(path),
+            PreferencesMsg::SetVideoSavePath(path) => match path
+                .as_deref()
+                .map(prepare_directory)
+                .unwrap_or(Ok(()))
+            {
+                Ok(_) => self.set_video_save_path(path),
+                Err(msg) => self.get_mut_toast_messages().borrow_mut().push_back(msg),
+            },
             PreferencesMsg::SetInitialSlaveNum(num) => self.set_initial_slave_num(num),
             PreferencesMsg::SetInputSendingRate(rate) => self.set_default_input_sending_rate(rate),
+            PreferencesMsg::SetControlKeepAliveInterval(interval) => {
+                self.set_control_keep_alive_interval(interval)
+            }
+            PreferencesMsg::SetControlLogEnabled(enabled) => self.set_control_log_enabled(enabled),
             PreferencesMsg::SetDefaultKeepVideoDisplayRatio(value) => {
                 self.set_default_keep_video_display_ratio(value)
             }
-            PreferencesMsg::SaveToFile => serde_json::to_string_pretty(&self)
-                .ok()
-                .and_then(|json| fs::write(get_preference_path(), json).ok())
-                .unwrap(),
-            PreferencesMsg::SetImageSavePath(path) => self.set_image_save_path(path),
+            PreferencesMsg::RestoreDefaults => {
+                self.restore_defaults();
+                self.save_to_file().unwrap();
+                send!(
+                    parent_sender,
+                    AppMsg::SetColorScheme(*self.get_application_color_scheme())
+                );
+            }
+            PreferencesMsg::SetVideoLetterboxColor(color) => self.set_video_letterbox_color(color),
+            PreferencesMsg::SetNativeVideoSinkEnabled(enabled) => self.set_native_video_sink_enabled(enabled),
+            PreferencesMsg::SaveToFile => self.save_to_file().unwrap(),
+            PreferencesMsg::SetImageSavePath(path) => match path
+                .as_deref()
+                .map(prepare_directory)
+                .unwrap_or(Ok(()))
+            {
+                Ok(_) => self.set_image_save_path(path),
+                Err(msg) => self.get_mut_toast_messages().borrow_mut().push_back(msg),
+            },
             PreferencesMsg::SetImageSaveFormat(format) => self.set_image_save_format(format),
+            PreferencesMsg::SetFileNameTemplate(template) => self.set_file_name_template(template),
+            PreferencesMsg::SetSaveDirectoryScheme(scheme) => self.set_save_directory_scheme(scheme),
+            PreferencesMsg::SetCaptureReportSavePath(path) => match path
+                .as_deref()
+                .map(prepare_directory)
+                .unwrap_or(Ok(()))
+            {
+                Ok(_) => self.set_capture_report_save_path(path),
+                Err(msg) => self.get_mut_toast_messages().borrow_mut().push_back(msg),
+            },
+            PreferencesMsg::SetCaptureReportFormat(format) => self.set_capture_report_format(format),
+            PreferencesMsg::OpenCaptureReportDirectory => {
+                if let Err(msg) = open_directory(&self.capture_report_save_directory()) {
+                    self.get_mut_toast_messages().borrow_mut().push_back(msg);
+                }
+            }
             PreferencesMsg::SetParameterTunerGraphViewPointNumberLimit(limit) => {
                 self.set_param_tuner_graph_view_point_num_limit(limit)
             }
-            PreferencesMsg::OpenVideoDirectory => gtk::show_uri(
-                None as Option<&PreferencesWindow>,
-                glib::filename_to_uri(self.get_video_save_path().to_str().unwrap(), None)
-                    .unwrap()
-                    .as_str(),
-                gdk::CURRENT_TIME,
-            ),
-            PreferencesMsg::OpenImageDirectory => gtk::show_uri(
-                None as Option<&PreferencesWindow>,
-                glib::filename_to_uri(self.get_image_save_path().to_str().unwrap(), None)
-                    .unwrap()
-                    .as_str(),
-                gdk::CURRENT_TIME,
-            ),
+            PreferencesMsg::OpenVideoDirectory => {
+                if let Err(msg) = open_directory(&self.video_save_directory()) {
+                    self.get_mut_toast_messages().borrow_mut().push_back(msg);
+                }
+            }
+            PreferencesMsg::OpenControlLogDirectory => {
+                if let Err(msg) = open_directory(&get_control_log_path()) {
+                    self.get_mut_toast_messages().borrow_mut().push_back(msg);
+                }
+            }
+            PreferencesMsg::OpenLogDirectory => {
+                if let Err(msg) = open_directory(&get_log_path()) {
+                    self.get_mut_toast_messages().borrow_mut().push_back(msg);
+                }
+            }
+            PreferencesMsg::SetLogLevel(level) => {
+                logger::set_level(level);
+                self.set_log_level(level);
+            }
+            PreferencesMsg::OpenImageDirectory => {
+                if let Err(msg) = open_directory(&self.image_save_directory()) {
+                    self.get_mut_toast_messages().borrow_mut().push_back(msg);
+                }
+            }
             PreferencesMsg::SetDefaultColorspaceConversion(conversion) => {
                 self.set_default_colorspace_conversion(conversion)
             }
@@ -642,9 +1673,7 @@ impl ComponentUpdate<AppModel> for PreferencesModel {
                 self.get_mut_default_video_encoder().1 = provider
             }
             PreferencesMsg::SetPipelineTimeout(timeout) => self.set_pipeline_timeout(timeout),
-            PreferencesMsg::SetDefaultAppSinkQueueLeakyEnabled(leaky) => {
-                self.set_default_appsink_queue_leaky_enabled(leaky)
-            }
+            PreferencesMsg::SetDefaultAppSinkQueueMode(mode) => self.set_default_appsink_queue_mode(mode),
             PreferencesMsg::SetDefaultUseDecodebin(use_decodebin) => {
                 if use_decodebin {
                     self.set_default_reencode_recording_video(true);
@@ -654,6 +1683,14 @@ impl ComponentUpdate<AppModel> for PreferencesModel {
             PreferencesMsg::SetVideoSyncRecordUseSeparateDirectory(use_separate_directory) => {
                 self.set_video_sync_record_use_separate_directory(use_separate_directory)
             }
+            PreferencesMsg::SetKeepRecordingOnConnectionLost(keep) => {
+                self.set_keep_recording_on_connection_lost(keep)
+            }
+            PreferencesMsg::SetRecordingMinFreeSpace(space) => {
+                self.set_recording_min_free_space(space)
+            }
+            PreferencesMsg::SetNetworkWriteRetries(retries) => self.set_network_write_retries(retries),
+            PreferencesMsg::SetAnnotateScreenshots(annotate) => self.set_annotate_screenshots(annotate),
             PreferencesMsg::SetDefaultVideoLatency(latency) => {
                 self.set_default_video_latency(latency)
             }
@@ -666,12 +1703,82 @@ impl ComponentUpdate<AppModel> for PreferencesModel {
                     AppMsg::SetColorScheme(*self.get_application_color_scheme())
                 );
             }
+            PreferencesMsg::SetLanguage(language) => {
+                if language != self.language {
+                    self.set_language(language);
+                    self.get_mut_toast_messages()
+                        .borrow_mut()
+                        .push_back(tr("语言设置将在重启上位机后生效").to_string());
+                }
+            }
             PreferencesMsg::SetDefaultStatusInfoUpdateInterval(interval) => {
                 self.set_default_status_info_update_interval(interval)
             }
+            PreferencesMsg::SetDepthLockedInfoKey(key) => self.set_depth_locked_info_key(key),
+            PreferencesMsg::SetDirectionLockedInfoKey(key) => self.set_direction_locked_info_key(key),
+            PreferencesMsg::SetPinnedInfoKeys(keys) => self.set_pinned_info_keys(keys),
+            PreferencesMsg::SetHiddenInfoKeys(keys) => self.set_hidden_info_keys(keys),
+            PreferencesMsg::SetAxisInverted(axis, inverted) => {
+                self.get_mut_input_axis_settings().set_inverted(axis, inverted)
+            }
+            PreferencesMsg::SetTriggerThreshold(threshold) => {
+                self.get_mut_input_axis_settings().trigger_threshold = threshold.min(100)
+            }
+            PreferencesMsg::SetResponseCurve(group, curve) => {
+                self.get_mut_input_axis_settings().set_response_curve(group, curve)
+            }
+            PreferencesMsg::SetResponseExponent(group, exponent) => {
+                self.get_mut_input_axis_settings().set_response_exponent(group, exponent)
+            }
+            PreferencesMsg::SetControllerMappingPath(path) => {
+                if let Some(path) = &path {
+                    send!(parent_sender, AppMsg::LoadControllerMappings(path.clone()));
+                }
+                self.set_controller_mapping_path(path);
+            }
+            PreferencesMsg::ReloadControllerMappings => match &self.controller_mapping_path {
+                Some(path) => send!(parent_sender, AppMsg::LoadControllerMappings(path.clone())),
+                None => self
+                    .get_mut_toast_messages()
+                    .borrow_mut()
+                    .push_back(String::from(tr("未设置手柄映射文件"))),
+            },
+            PreferencesMsg::ShowToastMessage(msg) => {
+                self.get_mut_toast_messages().borrow_mut().push_back(msg)
+            }
+            PreferencesMsg::SetJoystickDisplayThreshold(threshold) => {
+                self.set_joystick_display_threshold(threshold)
+            }
+            PreferencesMsg::AddButtonChord(chord) => {
+                if chord.buttons.iter().enumerate().any(|(index, button)| chord.buttons[..index].contains(button)) {
+                    self.get_mut_toast_messages().borrow_mut().push_back(String::from(tr("组合键中的按键不能重复。")));
+                } else {
+                    // 按键相同的组合键只保留最新的设置
+                    let chords = self.get_mut_button_chords();
+                    chords.retain(|other| {
+                        other.buttons.len() != chord.buttons.len()
+                            || !other.buttons.iter().all(|button| chord.buttons.contains(button))
+                    });
+                    chords.push(chord);
+                }
+            }
+            PreferencesMsg::RemoveButtonChord(index) => {
+                if index < self.button_chords.len() {
+                    self.get_mut_button_chords().remove(index);
+                }
+            }
+            PreferencesMsg::SetVirtualJoystickEnabled(enabled) => {
+                self.set_virtual_joystick_enabled(enabled)
+            }
+            PreferencesMsg::SetInputDispatchMode(mode) => self.set_input_dispatch_mode(mode),
+            PreferencesMsg::SetRemoteApiEnabled(enabled) => self.set_remote_api_enabled(enabled),
+            PreferencesMsg::SetRemoteApiPort(port) => self.set_remote_api_port(port),
             PreferencesMsg::SetParamTunerGraphViewUpdateInterval(interval) => {
                 self.set_param_tuner_graph_view_update_interval(interval)
             }
+            PreferencesMsg::SetPipelineGraphExportEnabled(enabled) => {
+                self.set_pipeline_graph_export_enabled(enabled)
+            }
         }
         send!(parent_sender, AppMsg::PreferencesUpdated(self.clone()));
     }
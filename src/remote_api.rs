@@ -0,0 +1,264 @@
+/* remote_api.rs
+ *
+ * Copyright 2021-2022 Bohong Huang
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::{collections::BTreeMap, net::Ipv4Addr, time::Duration};
+
+use async_std::{
+    io::{self, BufReader},
+    net::{TcpListener, TcpStream},
+    prelude::*,
+    task,
+};
+use glib::Sender;
+use relm4::{factory::FactoryVec, send};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::slave::{MyComponent, SlaveDiagnostics, SlaveModel, SlaveMsg};
+
+const REQUEST_SIZE_LIMIT: u64 = 8192;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+// 远程控制接口的请求与返回数据格式见 README.org 中“远程控制接口”一节
+pub enum RemoteApiCommand {
+    ListSlaves,
+    GetSlave(usize),
+    Connect(usize),
+    Disconnect(usize),
+    StartRecording(usize),
+    StopRecording(usize),
+    TakeScreenshot(usize),
+}
+
+pub type RemoteApiResult = Result<Value, (u16, String)>;
+
+// 服务器在后台线程中运行，请求经由通道交给主线程处理后再将结果返回
+pub struct RemoteApiRequest {
+    pub command: RemoteApiCommand,
+    pub responder: async_std::channel::Sender<RemoteApiResult>,
+}
+
+#[derive(Serialize)]
+pub struct SlaveState {
+    pub index: usize,
+    pub slave_url: String,
+    pub video_url: String,
+    pub connected: Option<bool>, // 为 null 时表示正在切换状态
+    pub polling: Option<bool>,
+    pub recording: Option<bool>,
+    pub telemetry: BTreeMap<String, String>,
+    pub diagnostics: SlaveDiagnostics,
+}
+
+impl SlaveState {
+    fn from_slave(index: usize, slave: &SlaveModel) -> SlaveState {
+        let config = slave.get_config().model().unwrap();
+        SlaveState {
+            index,
+            slave_url: config.get_slave_url().to_string(),
+            video_url: config.get_video_url().to_string(),
+            connected: *slave.get_connected(),
+            polling: *slave.get_polling(),
+            recording: *slave.get_recording(),
+            telemetry: slave.telemetry(),
+            diagnostics: slave.collect_diagnostics(),
+        }
+    }
+}
+
+fn parse_command(method: &str, path: &str) -> Result<RemoteApiCommand, (u16, String)> {
+    let segments: Vec<&str> = path
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    let not_found = || (404, format!("未知的路径：{}", path));
+    let index = match segments.get(1) {
+        Some(index) => Some(index.parse::<usize>().map_err(|_| not_found())?),
+        None => None,
+    };
+    let command = match (segments.first().copied(), index, segments.get(2).copied()) {
+        (Some("slaves"), None, None) => (RemoteApiCommand::ListSlaves, "GET"),
+        (Some("slaves"), Some(index), None) => (RemoteApiCommand::GetSlave(index), "GET"),
+        (Some("slaves"), Some(index), Some("connect")) => (RemoteApiCommand::Connect(index), "POST"),
+        (Some("slaves"), Some(index), Some("disconnect")) => (RemoteApiCommand::Disconnect(index), "POST"),
+        (Some("slaves"), Some(index), Some("start-recording")) => (RemoteApiCommand::StartRecording(index), "POST"),
+        (Some("slaves"), Some(index), Some("stop-recording")) => (RemoteApiCommand::StopRecording(index), "POST"),
+        (Some("slaves"), Some(index), Some("screenshot")) => (RemoteApiCommand::TakeScreenshot(index), "POST"),
+        _ => return Err(not_found()),
+    };
+    match command {
+        (_, expected) if segments.len() > 3 || expected != method => {
+            Err((405, format!("该路径仅接受 {} 请求", expected)))
+        }
+        (command, _) => Ok(command),
+    }
+}
+
+pub fn handle_command(slaves: &FactoryVec<MyComponent<SlaveModel>>, command: RemoteApiCommand) -> RemoteApiResult {
+    let slave_component = |index: usize| {
+        slaves
+            .get(index)
+            .ok_or_else(|| (404, format!("不存在序号为 {} 的机位", index)))
+    };
+    let conflict = |message: &str| Err((409, String::from(message)));
+    let accepted = || Ok(json!({ "accepted": true }));
+    match command {
+        RemoteApiCommand::ListSlaves => Ok(json!(slaves
+            .iter()
+            .enumerate()
+            .map(|(index, slave)| SlaveState::from_slave(index, &slave.model().unwrap()))
+            .collect::<Vec<_>>())),
+        RemoteApiCommand::GetSlave(index) => Ok(json!(SlaveState::from_slave(
+            index,
+            &slave_component(index)?.model().unwrap()
+        ))),
+        RemoteApiCommand::Connect(index) | RemoteApiCommand::Disconnect(index) => {
+            let component = slave_component(index)?;
+            let target = matches!(command, RemoteApiCommand::Connect(_));
+            match *component.model().unwrap().get_connected() {
+                None => conflict("机位正在连接或断开连接"),
+                Some(connected) if connected == target => accepted(),
+                Some(_) => {
+                    send!(component.sender(), if target { SlaveMsg::Connect } else { SlaveMsg::Disconnect });
+                    accepted()
+                }
+            }
+        }
+        RemoteApiCommand::StartRecording(index) | RemoteApiCommand::StopRecording(index) => {
+            let component = slave_component(index)?;
+            let target = matches!(command, RemoteApiCommand::StartRecording(_));
+            let slave = component.model().unwrap();
+            if *slave.get_sync_recording() {
+                return conflict("机位正在同步录制，请在上位机中停止同步录制");
+            }
+            match *slave.get_recording() {
+                None => conflict("机位正在开始或停止录制"),
+                Some(recording) if recording == target => accepted(),
+                Some(_) if target && *slave.get_polling() != Some(true) => {
+                    conflict("机位未在拉流，无法开始录制")
+                }
+                Some(_) => {
+                    send!(component.sender(), SlaveMsg::ToggleRecord);
+                    accepted()
+                }
+            }
+        }
+        RemoteApiCommand::TakeScreenshot(index) => {
+            let component = slave_component(index)?;
+            if !component.model().unwrap().get_video().model().unwrap().has_frame() {
+                return conflict("当前没有画面，无法截图");
+            }
+            send!(component.sender(), SlaveMsg::TakeScreenshot);
+            accepted()
+        }
+    }
+}
+
+fn status_reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        _ => "Service Unavailable",
+    }
+}
+
+async fn dispatch(command: RemoteApiCommand, request_sender: Sender<RemoteApiRequest>) -> RemoteApiResult {
+    let unavailable = || (503, String::from("上位机正在退出"));
+    let (responder, response_receiver) = async_std::channel::bounded(1);
+    request_sender
+        .send(RemoteApiRequest { command, responder })
+        .map_err(|_| unavailable())?;
+    response_receiver.recv().await.map_err(|_| unavailable())?
+}
+
+async fn read_request(stream: &TcpStream) -> io::Result<(String, String, bool)> {
+    let mut reader = BufReader::new(stream.take(REQUEST_SIZE_LIMIT));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    let mut cross_origin = false;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).await? == 0 || header.trim().is_empty() {
+            break;
+        }
+        // 浏览器发起的跨域请求会携带 Origin，拒绝此类请求以防网页借此操控机器人
+        if header.to_ascii_lowercase().starts_with("origin:") {
+            cross_origin = true;
+        }
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+    Ok((method, path, cross_origin))
+}
+
+async fn handle_connection(stream: TcpStream, request_sender: Sender<RemoteApiRequest>) -> io::Result<()> {
+    let result = match io::timeout(REQUEST_TIMEOUT, read_request(&stream)).await {
+        Ok((_, _, true)) => Err((403, String::from("不接受跨域请求"))),
+        Ok((method, path, false)) => match parse_command(&method, &path) {
+            Ok(command) => dispatch(command, request_sender).await,
+            Err(err) => Err(err),
+        },
+        Err(err) => Err((400, err.to_string())),
+    };
+    let (status, body) = match result {
+        Ok(value) => (200, value),
+        Err((status, message)) => (status, json!({ "error": message })),
+    };
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        status_reason(status),
+        body.len(),
+        body
+    );
+    let mut stream = stream;
+    stream.write_all(response.as_bytes()).await?;
+    stream.flush().await
+}
+
+// 仅监听本机回环地址，其他主机无法访问
+pub fn start(port: u16, request_sender: Sender<RemoteApiRequest>) {
+    task::spawn(async move {
+        let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, port)).await {
+            Ok(listener) => listener,
+            Err(err) => {
+                log::error!("无法启动远程控制接口：{}", err);
+                return;
+            }
+        };
+        log::info!("远程控制接口已启动：http://{}:{}", Ipv4Addr::LOCALHOST, port);
+        let mut incoming = listener.incoming();
+        while let Some(stream) = incoming.next().await {
+            match stream {
+                Ok(stream) => {
+                    task::spawn(handle_connection(stream, request_sender.clone()));
+                }
+                Err(err) => log::warn!("远程控制接口无法接受连接：{}", err),
+            }
+        }
+    });
+}
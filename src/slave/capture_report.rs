@@ -0,0 +1,152 @@
+/* capture_report.rs
+ *
+ * Copyright 2021-2022 Bohong Huang
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::{collections::BTreeMap, fs, path::{Path, PathBuf}};
+
+use gdk_pixbuf::Pixbuf;
+use glib::DateTime;
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
+
+use super::{slave_video::write_pixbuf, video::FrameTimestamp};
+use crate::i18n::tr;
+
+#[derive(EnumIter, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum CaptureReportFormat {
+    JsonSidecar, // 截图与同名的 JSON 文件
+    Html,        // 内嵌截图的单个 HTML 文件
+}
+
+impl ToString for CaptureReportFormat {
+    fn to_string(&self) -> String {
+        match self {
+            CaptureReportFormat::JsonSidecar => tr("图片与 JSON 文件"),
+            CaptureReportFormat::Html => tr("HTML 文件"),
+        }.to_string()
+    }
+}
+
+impl Default for CaptureReportFormat {
+    fn default() -> Self {
+        CaptureReportFormat::JsonSidecar
+    }
+}
+
+// 采集报告中的机位信息与采集时刻的完整状态信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureReport {
+    pub time: String,
+    pub slave_url: String,
+    pub video_url: String,
+    pub video_format: Option<String>,
+    pub frame_timestamp: Option<FrameTimestamp>, // 采集时最近一次显示的帧，与截图可能相差一帧
+    pub image: Option<String>, // 截图的文件名，HTML 报告中截图直接内嵌，不单独保存
+    pub telemetry: BTreeMap<String, String>,
+}
+
+impl CaptureReport {
+    pub fn new(slave_url: String, video_url: String, video_format: Option<String>, frame_timestamp: Option<FrameTimestamp>, telemetry: BTreeMap<String, String>) -> CaptureReport {
+        CaptureReport {
+            time: DateTime::now_local()
+                .ok()
+                .and_then(|time| time.format_iso8601().ok())
+                .map(|time| time.to_string())
+                .unwrap_or_default(),
+            slave_url,
+            video_url,
+            video_format,
+            frame_timestamp,
+            image: None,
+            telemetry,
+        }
+    }
+
+    // image_path 为截图的保存路径，报告使用相同的文件名，返回报告文件的路径
+    pub fn save(mut self, pixbuf: &Pixbuf, image_path: &Path, format: CaptureReportFormat) -> Result<PathBuf, String> {
+        match format {
+            CaptureReportFormat::JsonSidecar => {
+                write_pixbuf(pixbuf, &image_path.to_path_buf()).map_err(|err| err.to_string())?;
+                self.image = image_path.file_name().map(|name| name.to_string_lossy().to_string());
+                let report_path = image_path.with_extension("json");
+                let json = serde_json::to_string_pretty(&self).map_err(|err| err.to_string())?;
+                fs::write(&report_path, json).map_err(|err| err.to_string())?;
+                Ok(report_path)
+            }
+            CaptureReportFormat::Html => {
+                let png = pixbuf.save_to_bufferv("png", &[]).map_err(|err| err.to_string())?;
+                let report_path = image_path.with_extension("html");
+                fs::write(&report_path, self.to_html(&base64::encode(png))).map_err(|err| err.to_string())?;
+                Ok(report_path)
+            }
+        }
+    }
+
+    fn to_html(&self, image_base64: &str) -> String {
+        let row = |key: &str, value: &str| format!("<tr><th>{}</th><td>{}</td></tr>\n", escape_html(key), escape_html(value));
+        let mut metadata = String::new();
+        metadata.push_str(&row(tr("采集时间"), &self.time));
+        metadata.push_str(&row(tr("连接 URL"), &self.slave_url));
+        metadata.push_str(&row(tr("视频 URL"), &self.video_url));
+        metadata.push_str(&row(tr("视频格式"), self.video_format.as_deref().unwrap_or("—")));
+        if let Some(timestamp) = self.frame_timestamp {
+            metadata.push_str(&row(tr("画面 PTS（微秒）"), &timestamp.pts_us.map_or(String::from("—"), |pts| pts.to_string())));
+            metadata.push_str(&row(tr("画面到达时间（Unix 微秒）"), &timestamp.arrival_unix_us.to_string()));
+        }
+        let telemetry: String = self.telemetry.iter().map(|(key, value)| row(key, value)).collect();
+        format!(
+            "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{title} {time}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+img {{ max-width: 100%; }}
+table {{ border-collapse: collapse; margin-top: 1em; }}
+th, td {{ border: 1px solid #ccc; padding: 0.3em 0.8em; text-align: left; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<img src=\"data:image/png;base64,{image}\">
+<h2>{metadata_title}</h2>
+<table>
+{metadata}</table>
+<h2>{telemetry_title}</h2>
+<table>
+{telemetry}</table>
+</body>
+</html>
+",
+            title = escape_html(tr("采集报告")),
+            time = escape_html(&self.time),
+            image = image_base64,
+            metadata_title = escape_html(tr("机位信息")),
+            metadata = metadata,
+            telemetry_title = escape_html(tr("状态信息")),
+            telemetry = telemetry,
+        )
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
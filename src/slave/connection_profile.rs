@@ -0,0 +1,292 @@
+/* connection_profile.rs
+ *
+ * Copyright 2021-2022 Bohong Huang
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::{cell::RefCell, fs, path::PathBuf, rc::Rc};
+
+use adw::{prelude::*, HeaderBar, Window};
+use glib::{clone, Sender};
+use gtk::{Align, Box as GtkBox, Button, Entry, Label, ListBox, Orientation, ScrolledWindow, SelectionMode, Widget};
+use relm4::send;
+use derivative::*;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{
+    slave_config::{parse_slave_url, parse_video_url, SlaveConfigMsg},
+    video::{RtspProtocol, VideoDecoder},
+    ControlSendingMode, ControlValueEncoding, SlaveMsg,
+};
+use crate::{
+    i18n::tr,
+    preferences::{get_data_path, PreferencesModel},
+};
+
+pub fn get_connection_profile_path() -> PathBuf {
+    let mut path = get_data_path();
+    path.push("profiles.json");
+    path
+}
+
+// 连接档案，保存常用的下位机与视频地址及其解码、通讯设置，用于快速配置或新建机位
+#[derive(Serialize, Deserialize, Derivative, Clone, Debug, PartialEq)]
+#[derivative(Default)]
+#[serde(default)]
+pub struct ConnectionProfile {
+    pub name: String,
+    #[derivative(Default(value = "PreferencesModel::default().default_slave_url"))]
+    pub slave_url: Url,
+    #[derivative(Default(value = "PreferencesModel::default().default_video_url"))]
+    pub video_url: Url,
+    pub video_decoder: VideoDecoder,
+    pub use_decodebin: bool,
+    pub rtsp_protocol: RtspProtocol,
+    #[derivative(Default(value = "PreferencesModel::default().default_video_latency"))]
+    pub video_latency: u32,
+    pub control_sending_mode: ControlSendingMode,
+    pub control_value_encoding: ControlValueEncoding,
+}
+
+impl ConnectionProfile {
+    pub fn load_all() -> Vec<ConnectionProfile> {
+        fs::read_to_string(get_connection_profile_path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save_all(profiles: &[ConnectionProfile]) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(profiles)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+        fs::write(get_connection_profile_path(), json)
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "{} {} · {}",
+            self.video_decoder.0.to_string(),
+            self.video_decoder.1.to_string(),
+            self.control_value_encoding.to_string()
+        )
+    }
+}
+
+// 机位设置中应用档案的下拉列表
+pub fn connection_profiles_list_box(profiles: &[ConnectionProfile], sender: &Sender<SlaveConfigMsg>) -> Widget {
+    if profiles.is_empty() {
+        return Label::builder()
+            .label(tr("未保存连接档案"))
+            .margin_top(4)
+            .margin_bottom(4)
+            .margin_start(4)
+            .margin_end(4)
+            .build()
+            .upcast();
+    }
+    let list_box = ListBox::builder().selection_mode(SelectionMode::None).build();
+    for profile in profiles {
+        let button = Button::builder()
+            .label(&profile.name)
+            .tooltip_text(&format!("{}\n{}", profile.slave_url, profile.video_url))
+            .build();
+        button.add_css_class("flat");
+        button.connect_clicked(clone!(@strong sender, @strong profile => move |_button| {
+            send!(sender, SlaveConfigMsg::ApplyConnectionProfile(profile.clone()));
+        }));
+        list_box.append(&button);
+    }
+    list_box.upcast()
+}
+
+struct ConnectionProfileRow {
+    row: GtkBox,
+    name_entry: Entry,
+    slave_url_entry: Entry,
+    video_url_entry: Entry,
+    profile: ConnectionProfile, // 名称与地址以外的设置保持添加时的值
+}
+
+// 逐项检查输入，有误的输入框标记为错误并在提示中说明原因
+fn collect_connection_profile(row: &ConnectionProfileRow) -> Option<ConnectionProfile> {
+    let ConnectionProfileRow { name_entry, slave_url_entry, video_url_entry, .. } = row;
+    for entry in [name_entry, slave_url_entry, video_url_entry] {
+        entry.remove_css_class("error");
+        entry.set_tooltip_text(None);
+    }
+    let name = name_entry.text().trim().to_string();
+    if name.is_empty() {
+        name_entry.add_css_class("error");
+        name_entry.set_tooltip_text(Some(tr("档案名称不能为空")));
+    }
+    let slave_url = parse_slave_url(&slave_url_entry.text()).map_err(|err| {
+        slave_url_entry.add_css_class("error");
+        slave_url_entry.set_tooltip_text(Some(err));
+    });
+    let video_url = parse_video_url(&video_url_entry.text()).map_err(|err| {
+        video_url_entry.add_css_class("error");
+        video_url_entry.set_tooltip_text(Some(err));
+    });
+    match (name.is_empty(), slave_url, video_url) {
+        (false, Ok(slave_url), Ok(video_url)) => Some(ConnectionProfile {
+            name,
+            slave_url,
+            video_url,
+            ..row.profile.clone()
+        }),
+        _ => None,
+    }
+}
+
+fn append_connection_profile_row(
+    list_box: &ListBox,
+    rows: &Rc<RefCell<Vec<ConnectionProfileRow>>>,
+    profile: ConnectionProfile,
+    sender: &Sender<SlaveMsg>,
+) {
+    let row = GtkBox::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(5)
+        .margin_top(5)
+        .margin_bottom(5)
+        .margin_start(5)
+        .margin_end(5)
+        .build();
+    let name_entry = Entry::builder().placeholder_text(tr("名称")).text(&profile.name).build();
+    let slave_url_entry = Entry::builder()
+        .placeholder_text(tr("连接 URL"))
+        .text(profile.slave_url.as_str())
+        .hexpand(true)
+        .build();
+    let video_url_entry = Entry::builder()
+        .placeholder_text(tr("视频 URL"))
+        .text(profile.video_url.as_str())
+        .hexpand(true)
+        .build();
+    let summary_label = Label::new(Some(&profile.summary()));
+    summary_label.add_css_class("dim-label");
+    let apply_button = Button::builder()
+        .label(tr("应用"))
+        .tooltip_text(tr("将档案应用到当前机位"))
+        .valign(Align::Center)
+        .build();
+    let new_slave_button = Button::builder()
+        .icon_name("window-new-symbolic")
+        .tooltip_text(tr("以此档案新建机位"))
+        .valign(Align::Center)
+        .build();
+    let remove_button = Button::builder()
+        .icon_name("list-remove-symbolic")
+        .tooltip_text(tr("移除"))
+        .valign(Align::Center)
+        .build();
+    remove_button.add_css_class("flat");
+    for widget in [name_entry.upcast_ref::<Widget>(), slave_url_entry.upcast_ref(), video_url_entry.upcast_ref(), summary_label.upcast_ref(), apply_button.upcast_ref(), new_slave_button.upcast_ref(), remove_button.upcast_ref()] {
+        row.append(widget);
+    }
+    rows.borrow_mut().push(ConnectionProfileRow { row: row.clone(), name_entry, slave_url_entry, video_url_entry, profile });
+    apply_button.connect_clicked(clone!(@strong rows, @strong sender, @weak row => move |_button| {
+        let rows = rows.borrow();
+        if let Some(profile) = rows.iter().find(|profile_row| profile_row.row == row).and_then(collect_connection_profile) {
+            send!(sender, SlaveMsg::ApplyConnectionProfile(profile));
+        }
+    }));
+    new_slave_button.connect_clicked(clone!(@strong rows, @strong sender, @weak row => move |_button| {
+        let rows = rows.borrow();
+        if let Some(profile) = rows.iter().find(|profile_row| profile_row.row == row).and_then(collect_connection_profile) {
+            send!(sender, SlaveMsg::NewSlaveFromConnectionProfile(profile));
+        }
+    }));
+    remove_button.connect_clicked(clone!(@weak list_box, @weak row, @strong rows => move |_button| {
+        rows.borrow_mut().retain(|profile_row| profile_row.row != row);
+        if let Some(parent) = row.parent() {
+            list_box.remove(&parent);
+        }
+    }));
+    list_box.append(&row);
+}
+
+// 管理连接档案，current 为以当前机位配置新建档案时使用的设置，保存后通知各机位重新读取档案
+pub fn connection_profile_manager<T>(current: ConnectionProfile, parent_window: Option<&T>, sender: Sender<SlaveMsg>) -> Window
+where
+    T: IsA<gtk::Window>,
+{
+    let window = Window::builder()
+        .title(tr("连接档案"))
+        .modal(true)
+        .default_width(960)
+        .default_height(400)
+        .build();
+    window.set_transient_for(parent_window);
+
+    let rows: Rc<RefCell<Vec<ConnectionProfileRow>>> = Rc::new(RefCell::new(Vec::new()));
+    let list_box = ListBox::builder()
+        .selection_mode(SelectionMode::None)
+        .margin_top(10)
+        .margin_bottom(10)
+        .margin_start(10)
+        .margin_end(10)
+        .valign(Align::Start)
+        .build();
+    list_box.add_css_class("boxed-list");
+    for profile in ConnectionProfile::load_all() {
+        append_connection_profile_row(&list_box, &rows, profile, &sender);
+    }
+
+    let header_bar = HeaderBar::new();
+    let add_button = Button::builder()
+        .icon_name("list-add-symbolic")
+        .tooltip_text(tr("以当前机位的配置添加档案"))
+        .build();
+    add_button.connect_clicked(clone!(@weak list_box, @strong rows, @strong sender => move |_button| {
+        append_connection_profile_row(&list_box, &rows, current.clone(), &sender);
+    }));
+    let save_button = Button::with_label(tr("保存"));
+    save_button.add_css_class("suggested-action");
+    save_button.connect_clicked(clone!(@weak window, @strong rows, @strong sender => move |_button| {
+        let profiles: Vec<Option<ConnectionProfile>> = rows.borrow().iter().map(collect_connection_profile).collect();
+        if profiles.iter().any(Option::is_none) {
+            return;
+        }
+        let profiles: Vec<ConnectionProfile> = profiles.into_iter().flatten().collect();
+        match ConnectionProfile::save_all(&profiles) {
+            Ok(_) => {
+                send!(sender, SlaveMsg::ConnectionProfilesSaved);
+                window.close();
+            }
+            Err(err) => send!(sender, SlaveMsg::ErrorMessage(format!("{}{}", tr("无法保存连接档案："), err))),
+        }
+    }));
+    header_bar.pack_start(&add_button);
+    header_bar.pack_end(&save_button);
+
+    let hint = Label::builder()
+        .label(tr("连接档案保存下位机与视频地址以及添加时机位的解码与通讯设置，可应用到当前机位或以此新建机位。"))
+        .wrap(true)
+        .xalign(0.0)
+        .margin_top(10)
+        .margin_start(10)
+        .margin_end(10)
+        .build();
+    hint.add_css_class("dim-label");
+    let content = GtkBox::new(Orientation::Vertical, 0);
+    content.append(&header_bar);
+    content.append(&hint);
+    content.append(&ScrolledWindow::builder().vexpand(true).child(&list_box).build());
+    window.set_content(Some(&content));
+    window.present();
+    window
+}
@@ -0,0 +1,109 @@
+/* control_log.rs
+ *
+ * Copyright 2021-2022 Bohong Huang
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use async_std::task;
+use glib::Sender;
+use relm4::send;
+use serde::{Deserialize, Serialize};
+
+use super::{ControlPacket, SlaveCommunicationMsg, SlaveMsg};
+use crate::i18n::tr;
+
+// 控制指令记录文件为 JSON Lines 格式，每行记录一个实际发送的控制数据包
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlLogEntry {
+    pub time: u64, // 相对开始记录时的毫秒数
+    pub control: ControlPacket,
+}
+
+pub struct ControlLogger {
+    writer: BufWriter<File>,
+    start_time: Instant,
+}
+
+impl ControlLogger {
+    pub fn create(path: &Path) -> io::Result<ControlLogger> {
+        Ok(ControlLogger {
+            writer: BufWriter::new(File::create(path)?),
+            start_time: Instant::now(),
+        })
+    }
+
+    // 每条记录写入后立即刷新，上位机异常退出时也不会丢失已发送的数据包
+    pub fn log(&mut self, control: &ControlPacket) -> io::Result<()> {
+        let entry = ControlLogEntry {
+            time: self.start_time.elapsed().as_millis() as u64,
+            control: control.clone(),
+        };
+        serde_json::to_writer(&mut self.writer, &entry)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()
+    }
+}
+
+// 读取控制指令记录，并将时间调整为相对第一个数据包的毫秒数
+pub fn read_control_log(path: &Path) -> Result<Vec<ControlLogEntry>, String> {
+    let content = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let mut entries = content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str::<ControlLogEntry>(line)
+                .map_err(|err| format!("{}{}：{}", tr("控制指令记录格式有误，行号："), index + 1, err))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(first_time) = entries.first().map(|entry| entry.time) {
+        for entry in entries.iter_mut() {
+            entry.time = entry.time.saturating_sub(first_time);
+        }
+    }
+    Ok(entries)
+}
+
+// 按照记录时的时间间隔依次发送控制数据包，连接断开或回放结束后通知机位
+// 记录文件可能经过手动修改，发送前同样限制各控制量的范围
+pub async fn replay_control_log(
+    entries: Vec<ControlLogEntry>,
+    communication_sender: async_std::channel::Sender<SlaveCommunicationMsg>,
+    slave_sender: Sender<SlaveMsg>,
+) {
+    let start_time = Instant::now();
+    for entry in entries {
+        let elapsed = start_time.elapsed();
+        let time = Duration::from_millis(entry.time);
+        if time > elapsed {
+            task::sleep(time - elapsed).await;
+        }
+        if communication_sender
+            .send(SlaveCommunicationMsg::ControlUpdated(entry.control.clamped()))
+            .await
+            .is_err()
+        {
+            break;
+        }
+    }
+    send!(slave_sender, SlaveMsg::ControlReplayFinished);
+}
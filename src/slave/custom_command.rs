@@ -0,0 +1,236 @@
+/* custom_command.rs
+ *
+ * Copyright 2021-2022 Bohong Huang
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::{cell::RefCell, rc::Rc};
+
+use adw::{prelude::*, HeaderBar, Window};
+use glib::{clone, Sender};
+use gtk::{Align, Box as GtkBox, Button, CheckButton, Entry, FlowBox, Label, ListBox, Orientation, ScrolledWindow, SelectionMode, Widget};
+use relm4::send;
+use serde::{Deserialize, Serialize};
+
+use super::{RpcParamsStyle, SlaveMsg};
+use crate::i18n::tr;
+
+// 调用下位机自定义 RPC 方法的命令，参数为 JSON 数组或对象，为 None 时不附带参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomCommand {
+    pub label: String,
+    pub method: String,
+    #[serde(default)]
+    pub params: Option<serde_json::Value>,
+    #[serde(default)]
+    pub params_style: RpcParamsStyle,
+}
+
+pub fn parse_custom_command_params(text: &str) -> Result<Option<serde_json::Value>, &'static str> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    match serde_json::from_str(text) {
+        Ok(value @ (serde_json::Value::Array(_) | serde_json::Value::Object(_))) => Ok(Some(value)),
+        Ok(_) => Err(tr("参数须为 JSON 数组或对象")),
+        Err(_) => Err(tr("无法解析 JSON")),
+    }
+}
+
+// 状态信息面板中的命令按钮，未连接时不可用
+pub fn custom_commands_box(commands: &[CustomCommand], connected: bool, sender: Sender<SlaveMsg>) -> Widget {
+    let flow_box = FlowBox::builder()
+        .selection_mode(SelectionMode::None)
+        .column_spacing(5)
+        .row_spacing(5)
+        .max_children_per_line(3)
+        .build();
+    for (index, command) in commands.iter().enumerate() {
+        let button = Button::builder()
+            .label(&command.label)
+            .tooltip_text(&command.method)
+            .sensitive(connected)
+            .build();
+        button.connect_clicked(clone!(@strong sender => move |_button| {
+            send!(sender, SlaveMsg::RunCustomCommand(index));
+        }));
+        flow_box.insert(&button, -1);
+    }
+    flow_box.upcast()
+}
+
+struct CustomCommandRow {
+    row: GtkBox,
+    label_entry: Entry,
+    method_entry: Entry,
+    params_entry: Entry,
+    positional_check: CheckButton,
+}
+
+fn custom_command_row(command: Option<&CustomCommand>) -> CustomCommandRow {
+    let row = GtkBox::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(5)
+        .margin_top(5)
+        .margin_bottom(5)
+        .margin_start(5)
+        .margin_end(5)
+        .build();
+    let label_entry = Entry::builder().placeholder_text(tr("名称")).build();
+    let method_entry = Entry::builder().placeholder_text(tr("RPC 方法")).build();
+    let params_entry = Entry::builder()
+        .placeholder_text(tr("参数（可选，JSON 数组或对象）"))
+        .hexpand(true)
+        .build();
+    let positional_check = CheckButton::builder()
+        .label(tr("按位置传参"))
+        .tooltip_text(tr("将对象参数作为唯一的位置参数传递，而不是按名称传递其各字段"))
+        .build();
+    if let Some(command) = command {
+        label_entry.set_text(&command.label);
+        method_entry.set_text(&command.method);
+        if let Some(params) = &command.params {
+            params_entry.set_text(&params.to_string());
+        }
+        positional_check.set_active(command.params_style == RpcParamsStyle::Positional);
+    }
+    row.append(&label_entry);
+    row.append(&method_entry);
+    row.append(&params_entry);
+    row.append(&positional_check);
+    CustomCommandRow { row, label_entry, method_entry, params_entry, positional_check }
+}
+
+fn append_custom_command_row(list_box: &ListBox, rows: &Rc<RefCell<Vec<CustomCommandRow>>>, command: Option<&CustomCommand>) {
+    let command_row = custom_command_row(command);
+    let remove_button = Button::builder()
+        .icon_name("list-remove-symbolic")
+        .tooltip_text(tr("移除"))
+        .valign(Align::Center)
+        .build();
+    remove_button.add_css_class("flat");
+    let row = &command_row.row;
+    remove_button.connect_clicked(clone!(@weak list_box, @weak row, @strong rows => move |_button| {
+        rows.borrow_mut().retain(|command_row| command_row.row != row);
+        if let Some(parent) = row.parent() {
+            list_box.remove(&parent);
+        }
+    }));
+    row.append(&remove_button);
+    list_box.append(row);
+    rows.borrow_mut().push(command_row);
+}
+
+// 逐行检查输入，有误的输入框标记为错误并在提示中说明原因
+fn collect_custom_commands(rows: &[CustomCommandRow]) -> Option<Vec<CustomCommand>> {
+    let mut commands = Vec::new();
+    let mut valid = true;
+    for CustomCommandRow { label_entry, method_entry, params_entry, positional_check, .. } in rows {
+        for entry in [label_entry, method_entry, params_entry] {
+            entry.remove_css_class("error");
+            entry.set_tooltip_text(None);
+        }
+        let method = method_entry.text().trim().to_string();
+        if method.is_empty() {
+            method_entry.add_css_class("error");
+            method_entry.set_tooltip_text(Some(tr("RPC 方法不能为空")));
+            valid = false;
+        }
+        let params = match parse_custom_command_params(&params_entry.text()) {
+            Ok(params) => params,
+            Err(err) => {
+                params_entry.add_css_class("error");
+                params_entry.set_tooltip_text(Some(err));
+                valid = false;
+                None
+            }
+        };
+        let label = match label_entry.text().trim() {
+            "" => method.clone(),
+            label => label.to_string(),
+        };
+        let params_style = if positional_check.is_active() {
+            RpcParamsStyle::Positional
+        } else {
+            RpcParamsStyle::Named
+        };
+        commands.push(CustomCommand { label, method, params, params_style });
+    }
+    valid.then(|| commands)
+}
+
+// 编辑机位的自定义命令，保存时回调新的命令列表，随机位配置保存
+pub fn custom_command_editor<T>(commands: &[CustomCommand], parent_window: Option<&T>, sender: Sender<SlaveMsg>) -> Window
+where
+    T: IsA<gtk::Window>,
+{
+    let window = Window::builder()
+        .title(tr("自定义命令"))
+        .modal(true)
+        .default_width(720)
+        .default_height(400)
+        .build();
+    window.set_transient_for(parent_window);
+
+    let rows: Rc<RefCell<Vec<CustomCommandRow>>> = Rc::new(RefCell::new(Vec::new()));
+    let list_box = ListBox::builder()
+        .selection_mode(SelectionMode::None)
+        .margin_top(10)
+        .margin_bottom(10)
+        .margin_start(10)
+        .margin_end(10)
+        .valign(Align::Start)
+        .build();
+    list_box.add_css_class("boxed-list");
+    for command in commands {
+        append_custom_command_row(&list_box, &rows, Some(command));
+    }
+
+    let header_bar = HeaderBar::new();
+    let add_button = Button::builder()
+        .icon_name("list-add-symbolic")
+        .tooltip_text(tr("添加命令"))
+        .build();
+    add_button.connect_clicked(clone!(@weak list_box, @strong rows => move |_button| {
+        append_custom_command_row(&list_box, &rows, None);
+    }));
+    let save_button = Button::with_label(tr("保存"));
+    save_button.add_css_class("suggested-action");
+    save_button.connect_clicked(clone!(@weak window, @strong rows, @strong sender => move |_button| {
+        if let Some(commands) = collect_custom_commands(&rows.borrow()) {
+            send!(sender, SlaveMsg::SetCustomCommands(commands));
+            window.close();
+        }
+    }));
+    header_bar.pack_start(&add_button);
+    header_bar.pack_end(&save_button);
+
+    let hint = Label::builder()
+        .label(tr("命令按钮显示在状态信息面板中，点击时在暂停状态信息请求与控制指令发送的情况下调用一次对应的 RPC 方法，结果以通知的形式显示。"))
+        .wrap(true)
+        .xalign(0.0)
+        .margin_top(10)
+        .margin_start(10)
+        .margin_end(10)
+        .build();
+    hint.add_css_class("dim-label");
+    let content = GtkBox::new(Orientation::Vertical, 0);
+    content.append(&header_bar);
+    content.append(&hint);
+    content.append(&ScrolledWindow::builder().vexpand(true).child(&list_box).build());
+    window.set_content(Some(&content));
+    window.present();
+    window
+}
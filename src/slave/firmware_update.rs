@@ -16,16 +16,18 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use async_std::{io::ReadExt, task};
+use async_std::{io::ReadExt, task::{self, JoinHandle}};
 use std::error::Error;
 use std::fmt::Display;
-use std::{fmt::Debug, path::PathBuf};
+use std::{fmt::Debug, path::PathBuf, sync::{atomic::{AtomicBool, Ordering}, Arc}};
 
 use adw::{prelude::*, ActionRow, Carousel, HeaderBar, PreferencesGroup, StatusPage, Window};
+use sha2::{Digest, Sha256};
 use glib::Sender;
 use glib_macros::clone;
-use gtk::{Align, Box as GtkBox, Button, FileChooserAction, FileFilter, Orientation, ProgressBar};
-use once_cell::unsync::OnceCell;
+use gtk::{
+    Align, Box as GtkBox, Button, Entry, FileChooserAction, FileFilter, Inhibit, Label, Orientation, ProgressBar,
+};
 use relm4::{send, MicroModel, MicroWidgets};
 use relm4_macros::micro_widget;
 
@@ -33,9 +35,10 @@ use derivative::*;
 
 use jsonrpsee_core::client::ClientT;
 
+use crate::i18n::tr;
 use crate::prelude::*;
 use crate::slave::{protocol::*, AsRpcParams, RpcClient, SlaveCommunicationMsg};
-use crate::ui::generic::select_path;
+use crate::ui::generic::{confirm_message, select_path};
 
 use super::SlaveMsg;
 
@@ -43,8 +46,13 @@ pub enum SlaveFirmwareUpdaterMsg {
     StartUpload,
     NextStep,
     FirmwareFileSelected(PathBuf),
+    FirmwareFileHashProgressUpdated(PathBuf, f32),
+    FirmwareFileHashed(PathBuf, Result<(String, u64), String>),
+    Cancel, // 关闭窗口时取消校验以及尚未开始写入的上传
+    SetExpectedChecksum(String),
     FirmwareUploadProgressUpdated(f32),
     FirmwareUploadFailed(SlaveFirmwareUpdateError),
+    SetRpcClient(Option<RpcClient>), // 机位重新连接或断开连接时更新
 }
 
 #[tracker::track]
@@ -53,9 +61,17 @@ pub enum SlaveFirmwareUpdaterMsg {
 pub struct SlaveFirmwareUpdaterModel {
     current_page: u32,
     firmware_file_path: Option<PathBuf>,
+    firmware_file_digest: Option<Result<(String, u64), String>>, // SHA-256 与文件大小，为 None 时表示正在计算
+    firmware_hashing_progress: f32,
+    #[no_eq]
+    hashing_task: Option<JoinHandle<()>>, // 关闭窗口或重新选择文件时取消
+    #[no_eq]
+    upload_cancelled: Option<Arc<AtomicBool>>, // 上传任务在开始写入固件前检查，关闭窗口时置位
+    expected_checksum: String,
     firmware_uploading_progress: f32,
     #[no_eq]
-    _rpc_client: OnceCell<RpcClient>,
+    rpc_client: Option<RpcClient>,
+    connection_interrupted: bool, // 上传期间机位断开或重新连接，原有的连接已失效
     #[no_eq]
     #[derivative(Default(value = "Ok(())"))]
     firmware_update_result: Result<(), SlaveFirmwareUpdateError>,
@@ -67,6 +83,44 @@ impl SlaveFirmwareUpdaterModel {
             && self.firmware_uploading_progress < 1.0
             && self.firmware_update_result.is_ok()
     }
+
+    fn checksum(&self) -> Option<&str> {
+        match &self.firmware_file_digest {
+            Some(Ok((checksum, _))) => Some(checksum),
+            _ => None,
+        }
+    }
+
+    fn is_checksum_matched(&self) -> bool {
+        let expected = self.expected_checksum.trim();
+        self.checksum()
+            .map_or(false, |checksum| expected.is_empty() || checksum.eq_ignore_ascii_case(expected))
+    }
+}
+
+const FILE_BUFFER_SIZE: usize = 64 * 1024;
+
+// 在后台任务中分块读取，每读取约百分之一报告一次进度
+async fn hash_file(path: PathBuf, sender: Sender<SlaveFirmwareUpdaterMsg>) -> std::io::Result<(String, u64)> {
+    let mut file = async_std::fs::File::open(&path).await?;
+    let total = file.metadata().await?.len();
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; FILE_BUFFER_SIZE];
+    let mut size = 0u64;
+    let mut reported = 0u64;
+    loop {
+        let len = file.read(&mut buffer).await?;
+        if len == 0 {
+            break;
+        }
+        hasher.update(&buffer[..len]);
+        size += len as u64;
+        if total > 0 && (size - reported) * 100 >= total {
+            reported = size;
+            send!(sender, SlaveFirmwareUpdaterMsg::FirmwareFileHashProgressUpdated(path.clone(), (size as f64 / total as f64) as f32));
+        }
+    }
+    Ok((format!("{:x}", hasher.finalize()), size))
 }
 
 #[derive(Debug)]
@@ -74,6 +128,8 @@ pub enum SlaveFirmwareUpdateError {
     IOError(std::io::Error),
     RpcError(jsonrpsee_core::Error),
     VerificationError(usize, usize),
+    ChecksumError(String, String),
+    ConnectionLost(jsonrpsee_core::Error),
 }
 
 impl Display for SlaveFirmwareUpdateError {
@@ -86,6 +142,16 @@ impl Display for SlaveFirmwareUpdateError {
                 "Verification error: The returned length {} doesn't match the expected {}",
                 given, expect
             ),
+            SlaveFirmwareUpdateError::ChecksumError(expect, given) => write!(
+                f,
+                "Checksum error: The file checksum {} doesn't match the verified {}",
+                given, expect
+            ),
+            SlaveFirmwareUpdateError::ConnectionLost(error) => write!(
+                f,
+                "Connection lost during the update, please reconnect and try again: {}",
+                error
+            ),
         }
     }
 }
@@ -95,14 +161,10 @@ impl Error for SlaveFirmwareUpdateError {}
 impl SlaveFirmwareUpdaterModel {
     pub fn new(rpc_client: RpcClient) -> SlaveFirmwareUpdaterModel {
         SlaveFirmwareUpdaterModel {
-            _rpc_client: OnceCell::from(rpc_client),
+            rpc_client: Some(rpc_client),
             ..Default::default()
         }
     }
-
-    pub fn get_rpc_client(&self) -> &RpcClient {
-        self._rpc_client.get().unwrap()
-    }
 }
 
 impl MicroModel for SlaveFirmwareUpdaterModel {
@@ -122,8 +184,39 @@ impl MicroModel for SlaveFirmwareUpdaterModel {
                 self.set_current_page(self.get_current_page().wrapping_add(1))
             }
             SlaveFirmwareUpdaterMsg::FirmwareFileSelected(path) => {
+                if let Some(handle) = self.hashing_task.take() {
+                    task::spawn(handle.cancel());
+                }
+                self.set_firmware_file_digest(None);
+                self.set_firmware_hashing_progress(0.0);
+                self.hashing_task = Some(task::spawn(clone!(@strong sender, @strong path => async move {
+                    let result = hash_file(path.clone(), sender.clone()).await.map_err(|err| err.to_string());
+                    send!(sender, SlaveFirmwareUpdaterMsg::FirmwareFileHashed(path, result));
+                })));
                 self.set_firmware_file_path(Some(path))
             }
+            SlaveFirmwareUpdaterMsg::FirmwareFileHashProgressUpdated(path, progress) => {
+                if self.get_firmware_file_path().as_ref() == Some(&path) && self.firmware_file_digest.is_none() {
+                    self.set_firmware_hashing_progress(progress)
+                }
+            }
+            SlaveFirmwareUpdaterMsg::FirmwareFileHashed(path, result) => {
+                if self.get_firmware_file_path().as_ref() == Some(&path) { // 忽略先前所选文件的结果
+                    self.hashing_task = None;
+                    self.set_firmware_file_digest(Some(result))
+                }
+            }
+            SlaveFirmwareUpdaterMsg::Cancel => {
+                if let Some(handle) = self.hashing_task.take() {
+                    task::spawn(handle.cancel());
+                }
+                if let Some(cancelled) = self.upload_cancelled.take() {
+                    cancelled.store(true, Ordering::SeqCst);
+                }
+            }
+            SlaveFirmwareUpdaterMsg::SetExpectedChecksum(checksum) => {
+                self.set_expected_checksum(checksum)
+            }
             SlaveFirmwareUpdaterMsg::FirmwareUploadProgressUpdated(progress) => {
                 self.set_firmware_uploading_progress(progress);
                 if progress >= 1.0 || progress < 0.0 {
@@ -131,15 +224,45 @@ impl MicroModel for SlaveFirmwareUpdaterModel {
                 }
             }
             SlaveFirmwareUpdaterMsg::StartUpload => {
-                if let Some(path) = self.get_firmware_file_path() {
+                if let (Some(path), Some(checksum), true, Some(rpc_client)) = (
+                    self.get_firmware_file_path(),
+                    self.checksum().map(str::to_string),
+                    self.is_checksum_matched(),
+                    self.get_rpc_client().clone(),
+                ) {
                     const CHUNK_SIZE: usize = 1024;
+                    self.set_connection_interrupted(false);
                     send!(sender, SlaveFirmwareUpdaterMsg::NextStep);
-                    let rpc_client = self.get_rpc_client().clone();
-                    let handle = task::spawn(clone!(@strong sender, @strong path => async move {
+                    let cancelled = Arc::new(AtomicBool::new(false));
+                    self.upload_cancelled = Some(cancelled.clone());
+                    let upload = clone!(@strong sender, @strong path => async move {
                         match async_std::fs::File::open(path).await {
                             Ok(mut file) => {
+                                // 与校验时相同，分块读取并计算校验和，读取期间关闭窗口则放弃上传
                                 let mut bytes = Vec::new();
-                                file.read_to_end(&mut bytes).await.map_err(SlaveFirmwareUpdateError::IOError)?;
+                                let mut hasher = Sha256::new();
+                                let mut buffer = vec![0u8; FILE_BUFFER_SIZE];
+                                loop {
+                                    if cancelled.load(Ordering::SeqCst) {
+                                        log::info!("已取消固件上传");
+                                        return Ok(());
+                                    }
+                                    let len = file.read(&mut buffer).await.map_err(SlaveFirmwareUpdateError::IOError)?;
+                                    if len == 0 {
+                                        break;
+                                    }
+                                    hasher.update(&buffer[..len]);
+                                    bytes.extend_from_slice(&buffer[..len]);
+                                }
+                                let checksum_read = format!("{:x}", hasher.finalize());
+                                if checksum_read != checksum {
+                                    return Err(SlaveFirmwareUpdateError::ChecksumError(checksum, checksum_read)); // 文件在校验后被修改
+                                }
+                                // 开始写入后不再响应取消，以免下位机中残留不完整的固件
+                                if cancelled.load(Ordering::SeqCst) {
+                                    log::info!("已取消固件上传");
+                                    return Ok(());
+                                }
                                 let len_total = bytes.len();
                                 let mut len_remain = len_total;
                                 for chunk in bytes.chunks(CHUNK_SIZE) {
@@ -162,24 +285,37 @@ impl MicroModel for SlaveFirmwareUpdaterModel {
                             },
                             Err(err) => Err(SlaveFirmwareUpdateError::IOError(err)),
                         }
-                    }));
-                    let handle = task::spawn(async move {
-                        let result = handle.await;
+                    });
+                    // 上传在通讯主循环暂停状态信息请求与控制指令发送后才开始，避免与其争用连接
+                    let blocker = Box::pin(async move {
+                        let result = upload.await;
                         if let Err(err) = result {
                             send!(sender, SlaveFirmwareUpdaterMsg::FirmwareUploadFailed(err));
                         }
-                        Ok(())
+                        Ok::<(), Box<dyn Error + Send>>(())
                     });
                     send!(
                         parent_sender,
-                        SlaveMsg::CommunicationMessage(SlaveCommunicationMsg::Block(handle))
+                        SlaveMsg::CommunicationMessage(SlaveCommunicationMsg::Block(blocker))
                     );
                 }
             }
             SlaveFirmwareUpdaterMsg::FirmwareUploadFailed(err) => {
+                let err = match err {
+                    SlaveFirmwareUpdateError::RpcError(err) if self.connection_interrupted => {
+                        SlaveFirmwareUpdateError::ConnectionLost(err)
+                    }
+                    err => err,
+                };
                 self.set_firmware_update_result(Err(err));
                 send!(sender, SlaveFirmwareUpdaterMsg::NextStep);
             }
+            SlaveFirmwareUpdaterMsg::SetRpcClient(rpc_client) => {
+                if self.is_uploading() {
+                    self.set_connection_interrupted(true);
+                }
+                self.set_rpc_client(rpc_client);
+            }
         }
     }
 }
@@ -188,11 +324,15 @@ impl MicroModel for SlaveFirmwareUpdaterModel {
 impl MicroWidgets<SlaveFirmwareUpdaterModel> for SlaveFirmwareUpdaterWidgets {
     view! {
         window = Window {
-            set_title: Some("固件更新向导"),
+            set_title: Some(tr("固件更新向导")),
             set_width_request: 480,
             set_height_request: 480,
             set_destroy_with_parent: true,
             set_modal: true,
+            connect_close_request(sender) => move |_window| {
+                send!(sender, SlaveFirmwareUpdaterMsg::Cancel);
+                Inhibit(false)
+            },
             set_content = Some(&GtkBox) {
                 set_orientation: Orientation::Vertical,
                 append = &HeaderBar {
@@ -205,14 +345,14 @@ impl MicroWidgets<SlaveFirmwareUpdaterModel> for SlaveFirmwareUpdaterWidgets {
                     scroll_to_page: track!(model.changed(SlaveFirmwareUpdaterModel::current_page()), model.current_page, true),
                     append = &StatusPage {
                         set_icon_name: Some("software-update-available-symbolic"),
-                        set_title: "欢迎使用固件更新向导",
+                        set_title: tr("欢迎使用固件更新向导"),
                         set_hexpand: true,
                         set_vexpand: true,
-                        set_description: Some("请确保固件更新期间机器人有充足的电量供应。"),
+                        set_description: Some(tr("请确保固件更新期间机器人有充足的电量供应。")),
                         set_child = Some(&Button) {
                             set_css_classes: &["suggested-action", "pill"],
                             set_halign: Align::Center,
-                            set_label: "下一步",
+                            set_label: tr("下一步"),
                             connect_clicked(sender) => move |_button| {
                                 send!(sender, SlaveFirmwareUpdaterMsg::NextStep);
                             },
@@ -220,24 +360,24 @@ impl MicroWidgets<SlaveFirmwareUpdaterModel> for SlaveFirmwareUpdaterWidgets {
                     },
                     append = &StatusPage {
                         set_icon_name: Some("folder-open-symbolic"),
-                        set_title: "请选择固件文件",
+                        set_title: tr("请选择固件文件"),
                         set_hexpand: true,
                         set_vexpand: true,
-                        set_description: Some("选择的固件文件必须为下位机的可执行文件。"),
+                        set_description: Some(tr("选择的固件文件必须为下位机的可执行文件。")),
                         set_child = Some(&GtkBox) {
                             set_orientation: Orientation::Vertical,
                             set_spacing: 50,
                             append = &PreferencesGroup {
                                 add = &ActionRow {
-                                    set_title: "固件文件",
-                                    set_subtitle: track!(model.changed(SlaveFirmwareUpdaterModel::firmware_file_path()), &model.firmware_file_path.as_ref().map_or("请选择文件".to_string(), |path| path.to_str().unwrap().to_string())),
+                                    set_title: tr("固件文件"),
+                                    set_subtitle: track!(model.changed(SlaveFirmwareUpdaterModel::firmware_file_path()), &model.firmware_file_path.as_ref().map_or(tr("请选择文件").to_string(), |path| path.to_str().unwrap().to_string())),
                                     add_suffix: browse_firmware_file_button = &Button {
-                                        set_label: "浏览",
+                                        set_label: tr("浏览"),
                                         set_valign: Align::Center,
                                         connect_clicked(sender, window) => move |_button| {
                                             let filter = FileFilter::new();
                                             filter.add_suffix("tar.gz");
-                                            filter.set_name(Some("固件文件"));
+                                            filter.set_name(Some(tr("固件文件")));
                                             std::mem::forget(select_path(FileChooserAction::Open, &[filter], &window, clone!(@strong sender => move |path| {
                                                 match path {
                                                     Some(path) => {
@@ -250,24 +390,55 @@ impl MicroWidgets<SlaveFirmwareUpdaterModel> for SlaveFirmwareUpdaterWidgets {
                                     },
                                     set_activatable_widget: Some(&browse_firmware_file_button),
                                 },
+                                add: checksum_row = &ActionRow {
+                                    set_title: "SHA-256",
+                                    set_visible: track!(model.changed(SlaveFirmwareUpdaterModel::firmware_file_path()), model.firmware_file_path.is_some()),
+                                    set_subtitle_selectable: true,
+                                    set_subtitle: track!(model.changed(SlaveFirmwareUpdaterModel::firmware_file_digest()) || model.changed(SlaveFirmwareUpdaterModel::firmware_hashing_progress()), &match model.get_firmware_file_digest() {
+                                        Some(Ok((checksum, size))) => format!("{}\n{}{}", checksum, size, tr(" 字节")),
+                                        Some(Err(err)) => format!("{}{}", tr("无法读取文件："), err),
+                                        None => format!("{}{:.0}%", tr("正在计算... "), model.firmware_hashing_progress * 100.0),
+                                    }),
+                                },
+                                add = &ActionRow {
+                                    set_title: tr("预期校验和"),
+                                    set_subtitle: tr("可选，填写后仅在文件的 SHA-256 与之相符时允许更新"),
+                                    add_suffix = &Entry {
+                                        set_valign: Align::Center,
+                                        set_width_request: 160,
+                                        connect_changed(sender) => move |entry| {
+                                            send!(sender, SlaveFirmwareUpdaterMsg::SetExpectedChecksum(entry.text().to_string()));
+                                        },
+                                        add_css_class?: track!(model.changed(SlaveFirmwareUpdaterModel::expected_checksum()) || model.changed(SlaveFirmwareUpdaterModel::firmware_file_digest()), (!model.is_checksum_matched() && model.checksum().is_some()).then(|| "error")),
+                                        remove_css_class?: track!(model.changed(SlaveFirmwareUpdaterModel::expected_checksum()) || model.changed(SlaveFirmwareUpdaterModel::firmware_file_digest()), (model.is_checksum_matched() || model.checksum().is_none()).then(|| "error")),
+                                    },
+                                },
+                            },
+                            append = &Label {
+                                set_label: tr("下位机已断开连接，请在重新连接后继续。"),
+                                set_css_classes: &["error"],
+                                set_visible: track!(model.changed(SlaveFirmwareUpdaterModel::rpc_client()), model.rpc_client.is_none()),
                             },
                             append = &Button {
                                 set_css_classes: &["suggested-action", "pill"],
                                 set_halign: Align::Center,
-                                set_label: "开始更新",
-                                set_sensitive: track!(model.changed(SlaveFirmwareUpdaterModel::firmware_file_path()), model.get_firmware_file_path().as_ref().map_or(false, |pathbuf| pathbuf.exists() && pathbuf.is_file())),
-                                connect_clicked(sender) => move |_button| {
-                                    send!(sender, SlaveFirmwareUpdaterMsg::StartUpload);
+                                set_label: tr("开始更新"),
+                                set_sensitive: track!(model.changed(SlaveFirmwareUpdaterModel::firmware_file_path()) || model.changed(SlaveFirmwareUpdaterModel::firmware_file_digest()) || model.changed(SlaveFirmwareUpdaterModel::expected_checksum()) || model.changed(SlaveFirmwareUpdaterModel::rpc_client()), model.get_firmware_file_path().as_ref().map_or(false, |pathbuf| pathbuf.exists() && pathbuf.is_file()) && model.is_checksum_matched() && model.rpc_client.is_some()),
+                                connect_clicked(sender, window, checksum_row) => move |_button| {
+                                    let detail = checksum_row.subtitle().map(|x| x.to_string()).unwrap_or_default();
+                                    confirm_message(tr("开始更新"), &format!("{}\n\nSHA-256: {}", tr("确定要使用该固件文件进行更新吗？"), detail), Some(&window), clone!(@strong sender => move || {
+                                        send!(sender, SlaveFirmwareUpdaterMsg::StartUpload);
+                                    }));
                                 },
                             }
                         },
                     },
                     append = &StatusPage {
                         set_icon_name: Some("folder-download-symbolic"),
-                        set_title: "正在更新固件...",
+                        set_title: tr("正在更新固件..."),
                         set_hexpand: true,
                         set_vexpand: true,
-                        set_description: Some("请不要切断连接或电源。"),
+                        set_description: Some(tr("请不要切断连接或电源。")),
                         set_child = Some(&GtkBox) {
                             set_orientation: Orientation::Vertical,
                             set_spacing: 50,
@@ -278,17 +449,17 @@ impl MicroWidgets<SlaveFirmwareUpdaterModel> for SlaveFirmwareUpdaterWidgets {
                     },
                     append = &StatusPage {
                         set_icon_name: track!(model.changed(SlaveFirmwareUpdaterModel::firmware_update_result()), if model.get_firmware_update_result().is_ok() { Some("emblem-ok-symbolic") } else { Some("dialog-warning-symbolic") }),
-                        set_title: track!(model.changed(SlaveFirmwareUpdaterModel::firmware_update_result()), if model.get_firmware_update_result().is_ok() { "固件更新成功" } else { "固件更新失败" }),
+                        set_title: track!(model.changed(SlaveFirmwareUpdaterModel::firmware_update_result()), if model.get_firmware_update_result().is_ok() { tr("固件更新成功") } else { tr("固件更新失败") }),
                         set_hexpand: true,
                         set_vexpand: true,
                         set_description: track!(model.changed(SlaveFirmwareUpdaterModel::firmware_update_result()), Some(
                             match model.get_firmware_update_result() {
-                                Ok(_) => "机器人将自动重启，请稍后手动进行连接。".to_string(),
-                                Err(err) => format!("请检查文件与网络连接是否正常。\n\n{}", err)}).as_deref()),
+                                Ok(_) => tr("机器人将自动重启，请稍后手动进行连接。").to_string(),
+                                Err(err) => format!("{}\n\n{}", tr("请检查文件与网络连接是否正常。"), err)}).as_deref()),
                         set_child = Some(&Button) {
                             set_css_classes: &["suggested-action", "pill"],
                             set_halign: Align::Center,
-                            set_label: "完成",
+                            set_label: tr("完成"),
                             connect_clicked(window) => move |_button| {
                                 window.destroy();
                             },
@@ -16,11 +16,16 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+pub mod capture_report;
+pub mod connection_profile;
+pub mod control_log;
+pub mod custom_command;
 pub mod firmware_update;
 pub mod param_tuner;
 pub mod protocol;
 pub mod slave_config;
 pub mod slave_video;
+pub mod thruster_mix;
 pub mod video;
 
 use async_std::task::{self, JoinHandle};
@@ -29,19 +34,24 @@ use std::{
     collections::{BTreeMap, HashMap, HashSet, VecDeque},
     error::Error,
     fmt::Debug,
+    fs,
     ops::Deref,
+    path::{Path, PathBuf},
+    pin::Pin,
     rc::Rc,
     sync::{Arc, Mutex},
-    time::{Duration, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
 
-use adw::{ApplicationWindow, Flap, FlapFoldPolicy, Toast, ToastOverlay};
-use glib::{DateTime, MainContext, Sender, WeakRef, PRIORITY_DEFAULT};
+use adw::{ApplicationWindow, Flap, FlapFoldPolicy, StatusPage, Toast, ToastOverlay};
+use gdk_pixbuf::Pixbuf;
+use glib::{DateTime, MainContext, Sender, SourceId, WeakRef, PRIORITY_DEFAULT};
 use glib_macros::clone;
 use gtk::{
-    prelude::*, Align, Box as GtkBox, Button as GtkButton, CenterBox, CheckButton, Frame, Grid,
-    Image, Inhibit, Label, ListBox, MenuButton, Orientation, Overlay, PackType, Popover, Revealer,
-    Separator, Switch, ToggleButton, Widget,
+    prelude::*, Align, Box as GtkBox, Button as GtkButton, CenterBox, CheckButton,
+    FileChooserAction, FileFilter, Frame, GestureClick, Grid, Image, Inhibit, Label, ListBox, MenuButton, Orientation, Overlay, PackType,
+    PolicyType, Popover, PropagationPhase, Revealer, ScrolledWindow, SelectionMode, Separator, Spinner,
+    Switch, ToggleButton, Widget, Window, WindowHandle,
 };
 use relm4::{
     factory::{positions::GridPosition, FactoryPrototype, FactoryVec},
@@ -54,19 +64,40 @@ use jsonrpsee_http_client::{HttpClient, HttpClientBuilder};
 
 use derivative::*;
 use serde::{Deserialize, Serialize};
+use once_cell::sync::Lazy;
+use strum_macros::EnumIter;
 
 use self::{
-    firmware_update::SlaveFirmwareUpdaterModel,
+    capture_report::CaptureReport,
+    control_log::{read_control_log, replay_control_log, ControlLogger},
+    connection_profile::{connection_profile_manager, ConnectionProfile},
+    custom_command::{custom_command_editor, custom_commands_box, CustomCommand},
+    firmware_update::{SlaveFirmwareUpdaterModel, SlaveFirmwareUpdaterMsg},
     param_tuner::SlaveParameterTunerModel,
     protocol::*,
-    slave_config::{SlaveConfigModel, SlaveConfigMsg},
+    slave_config::{check_slave_url_scheme, SlaveConfigModel, SlaveConfigMsg},
     slave_video::{SlaveVideoModel, SlaveVideoMsg},
+    thruster_mix::{thruster_mix_window, ThrusterMix},
+    video::{StreamStatistics, VideoFormat},
+};
+use crate::async_glib::{Future, Promise};
+use crate::file_name::{
+    create_directory, expand_file_name_template, sanitize_file_name, timestamp_file_name,
+    unique_file_path, FileNameContext,
+};
+use crate::i18n::tr;
+use crate::preferences::{get_control_log_path, PreferencesModel};
+use crate::ui::{
+    annotation::annotate_screenshot,
+    generic::{confirm_message, error_message, select_path},
+    virtual_joystick::virtual_joystick,
 };
-use crate::preferences::PreferencesModel;
-use crate::ui::generic::error_message;
 use crate::AppMsg;
 use crate::{
-    input::{Axis, Button, InputSource, InputSourceEvent, InputSystem},
+    input::{
+        Axis, Button, ButtonChord, ChordAction, InputSource, InputSourceEvent, InputSystem,
+        PowerLevel,
+    },
     slave::param_tuner::SlaveParameterTunerMsg,
 };
 
@@ -74,6 +105,37 @@ pub type RpcClient = HttpClient;
 pub type RpcClientBuilder = HttpClientBuilder;
 pub type RpcParams = jsonrpsee_http_client::types::ParamsSer<'static>;
 
+const DIAGNOSTICS_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+// 用于排查现场问题的计数，显示在诊断信息弹出框中，也可通过远程控制接口获取
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SlaveDiagnostics {
+    pub frames_displayed: u64,
+    pub frames_dropped: u64,
+    pub control_packets_sent: u64,
+    pub rpc_errors: u64,
+    pub video_reconnects: u64,
+    pub connected_secs: Option<u64>, // 本次连接以来的秒数，未连接时为 None
+}
+
+impl SlaveDiagnostics {
+    fn entries(&self) -> Vec<(&'static str, String)> {
+        vec![
+            (tr("显示帧数"), self.frames_displayed.to_string()),
+            (tr("丢弃帧数"), self.frames_dropped.to_string()),
+            (tr("已发送控制指令"), self.control_packets_sent.to_string()),
+            (tr("通讯错误"), self.rpc_errors.to_string()),
+            (tr("视频流重连次数"), self.video_reconnects.to_string()),
+            (
+                tr("连接时长"),
+                self.connected_secs
+                    .map(|secs| format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60))
+                    .unwrap_or_else(|| String::from("—")),
+            ),
+        ]
+    }
+}
+
 #[tracker::track]
 #[derive(Debug, Derivative)]
 #[derivative(Default)]
@@ -93,11 +155,33 @@ pub struct SlaveModel {
     #[derivative(Default(value = "Some(false)"))]
     pub polling: Option<bool>,
     #[derivative(Default(value = "Some(false)"))]
+    pub secondary_polling: Option<bool>, // 副摄像头画面是否正在显示，正在启动或停止时为 None
+    #[derivative(Default(value = "Some(false)"))]
     pub recording: Option<bool>,
     pub sync_recording: bool,
+    pub timelapse_directory: Option<PathBuf>,
+    #[no_eq]
+    pub timelapse_timer: Option<SourceId>,
+    pub timelapse_frame_count: u32,
+    #[no_eq]
+    pub control_replay: Option<JoinHandle<()>>, // 正在回放的控制指令记录，回放期间忽略输入设备的控制输入
+    pub timelapse_paused: bool,
+    pub timelapse_last_frame: u64,
+    #[no_eq]
+    pub disk_space_timer: Option<SourceId>,
+    pub file_counter: u32, // 本次启动以来截图与录制的文件数，用于文件名模板中的 {counter}
     #[no_eq]
     pub preferences: Rc<RefCell<PreferencesModel>>,
     pub input_sources: HashSet<InputSource>,
+    pub input_focused: bool, // 以跟随焦点方式分配输入时是否由该机位接收输入
+    #[no_eq]
+    pub pending_chord_buttons: Vec<(Button, bool)>, // 等待判断是否构成组合键的按键及其是否已松开
+    #[no_eq]
+    pub suppressed_buttons: HashSet<Button>,
+    #[no_eq]
+    pub latched_chord_buttons: HashSet<Button>, // 等待结束时已松开、需保持按下至少一个控制包的按键
+    #[no_eq]
+    pub active_chords: Vec<ButtonChord>,
     #[no_eq]
     pub input_system: Rc<InputSystem>,
     #[no_eq]
@@ -111,11 +195,45 @@ pub struct SlaveModel {
     pub communication_msg_sender: Option<async_std::channel::Sender<SlaveCommunicationMsg>>,
     #[no_eq]
     pub rpc_client: Option<async_std::sync::Arc<RpcClient>>,
+    #[no_eq]
+    pub firmware_updater_sender: Option<Sender<SlaveFirmwareUpdaterMsg>>, // 连接变化时通知已打开的子窗口，以免其继续使用失效的连接
+    #[no_eq]
+    pub parameter_tuner_sender: Option<Sender<SlaveParameterTunerMsg>>,
     pub toast_messages: Rc<RefCell<VecDeque<String>>>,
+    pub toast_history: VecDeque<(String, String)>, // 通知时间及内容
     #[no_eq]
     #[derivative(Default(value = "FactoryVec::new()"))]
     pub infos: FactoryVec<SlaveInfoModel>,
+    #[no_eq]
+    pub info_map: BTreeMap<String, String>, // 完整的状态信息，包括未显示的项
+    pub lock_setpoints: HashMap<SlaveStatusClass, Option<String>>, // 仅包含已锁定的项
+    #[no_eq]
+    pub lock_changed_times: HashMap<SlaveStatusClass, Instant>, // 最近一次切换锁定开关的时间
+    pub control_latency: Option<Duration>, // 最近一次发送控制指令的往返耗时
+    #[no_eq]
+    pub last_motion_trim: MotionPacket, // 用于在配平变化时重新发送控制指令
+    #[no_eq]
+    pub last_communication_rates: (u16, u16), // 通讯主循环正在使用的输入发送率与状态信息更新时间间隔
+    pub stream_statistics: Option<StreamStatistics>, // 最近一秒的视频流码率及丢包率，未拉流时为 None
+    pub video_format: Option<VideoFormat>, // 显示分支协商得到的画面格式，未拉流时为 None
     pub config_presented: bool,
+    pub control_packets_sent: u64,
+    pub rpc_errors: u64, // 通讯中断及下位机拒绝请求的次数
+    #[no_eq]
+    pub connected_time: Option<Instant>,
+    pub dive_secs: Option<u64>, // 连接以来经过的秒数，未连接时为 None
+    #[no_eq]
+    pub dive_timer: Option<SourceId>,
+    #[no_eq]
+    pub last_idle_timeout: Option<u16>, // 通讯主循环正在使用的空闲超时时间
+    pub info_polling_paused: bool, // 暂停状态信息请求以便优先发送控制指令，控制指令照常发送
+    #[no_eq]
+    pub last_info_time: Option<Instant>, // 最近一次收到状态信息的时刻
+    pub diagnostics: SlaveDiagnostics,
+    #[no_eq]
+    pub diagnostics_timer: Option<SourceId>, // 诊断信息弹出框显示期间定时刷新
+    #[no_eq]
+    pub video_window: Option<(Window, WindowHandle, Overlay)>, // 弹出的画面窗口、其中容纳画面的控件以及主窗口中原本容纳画面的控件
 }
 
 #[tracker::track]
@@ -188,7 +306,8 @@ impl SlaveStatusClass {
     }
 }
 
-const JOYSTICK_DISPLAY_THRESHOLD: i16 = 500;
+const CHORD_WINDOW: Duration = Duration::from_millis(150);
+const CHORD_TAP_LATCH_PERIODS: u64 = 2; // 短按的组合键按键保持按下的输入发送周期数
 
 impl SlaveModel {
     pub fn new(
@@ -223,6 +342,593 @@ impl SlaveModel {
         let mut status = self.get_mut_status().lock().unwrap();
         *status.entry(status_class.clone()).or_insert(0) = new_status;
     }
+    fn apply_button(&mut self, button: Button, pressed: bool) {
+        match SlaveStatusClass::from_button(button) {
+            Some(status_class @ SlaveStatusClass::RoboticArmOpen)
+            | Some(status_class @ SlaveStatusClass::LightOpen) => {
+                self.set_target_status(&status_class, if pressed { 1 } else { 0 });
+            }
+            Some(status_class) => {
+                if pressed {
+                    self.set_target_status(
+                        &status_class,
+                        !(self.get_target_status(&status_class) != 0) as i16,
+                    );
+                }
+            }
+            None => (),
+        }
+    }
+
+    // 属于组合键的按键先等待一段时间，期间组合键的其余按键均被按下则触发组合键，否则按原功能处理
+    fn handle_chord_button(&mut self, button: Button, pressed: bool, sender: &Sender<SlaveMsg>) {
+        let chords: Vec<ButtonChord> = self
+            .preferences
+            .borrow()
+            .get_button_chords()
+            .iter()
+            .filter(|chord| chord.buttons.len() >= 2)
+            .cloned()
+            .collect();
+        if pressed {
+            self.latched_chord_buttons.remove(&button); // 再次按下时不再补发上次短按的松开
+            let completed_chord = chords
+                .iter()
+                .find(|chord| {
+                    chord.buttons.contains(&button)
+                        && chord.buttons.iter().all(|other| {
+                            *other == button
+                                || self
+                                    .pending_chord_buttons
+                                    .iter()
+                                    .any(|(pending, released)| pending == other && !released)
+                        })
+                })
+                .cloned();
+            match completed_chord {
+                Some(chord) => {
+                    self.pending_chord_buttons
+                        .retain(|(pending, _)| !chord.buttons.contains(pending));
+                    self.suppressed_buttons.extend(chord.buttons.iter().cloned());
+                    log::info!(
+                        "[{}] 触发组合键 {}：{}",
+                        self.config.model().get_slave_url(),
+                        chord.to_string(),
+                        chord.action.to_string()
+                    );
+                    self.apply_chord_action(chord.action, true, sender);
+                    self.active_chords.push(chord);
+                }
+                None if chords.iter().any(|chord| chord.buttons.contains(&button)) => {
+                    self.pending_chord_buttons.push((button, false));
+                    glib::timeout_add_local_once(
+                        CHORD_WINDOW,
+                        clone!(@strong sender => move || {
+                            sender.send(SlaveMsg::ChordTimeout(button)).unwrap_or_default();
+                        }),
+                    );
+                }
+                None => self.apply_button(button, true),
+            }
+        } else if self.suppressed_buttons.remove(&button) {
+            let (released_chords, active_chords): (Vec<_>, Vec<_>) = self
+                .active_chords
+                .drain(..)
+                .partition(|chord| chord.buttons.contains(&button));
+            self.active_chords = active_chords;
+            for chord in released_chords {
+                self.apply_chord_action(chord.action, false, sender);
+            }
+        } else if let Some((_, released)) = self
+            .pending_chord_buttons
+            .iter_mut()
+            .find(|(pending, _)| *pending == button)
+        {
+            *released = true; // 等待结束后补发按下与松开
+        } else {
+            self.apply_button(button, false);
+        }
+    }
+
+    fn apply_chord_action(&mut self, action: ChordAction, pressed: bool, sender: &Sender<SlaveMsg>) {
+        match (action, pressed) {
+            (ChordAction::EmergencyStop, true) => send!(sender, SlaveMsg::EmergencyStop),
+            (ChordAction::EmergencySurface, pressed) => {
+                self.set_target_status(&SlaveStatusClass::MotionZ, if pressed { i16::MAX } else { 0 })
+            }
+            (ChordAction::ToggleRecord, true) => send!(sender, SlaveMsg::ToggleRecord),
+            (ChordAction::TakeScreenshot, true) => send!(sender, SlaveMsg::TakeScreenshot),
+            (ChordAction::ToggleDepthLocked, true) => self.apply_button(Button::LeftStick, true),
+            (ChordAction::ToggleDirectionLocked, true) => self.apply_button(Button::RightStick, true),
+            (ChordAction::SetTrim, true) => send!(sender, SlaveMsg::SetTrim),
+            (ChordAction::ResetTrim, true) => send!(sender, SlaveMsg::ResetTrim),
+            (_, false) => (),
+        }
+    }
+
+    fn clear_motion(&mut self) {
+        for status_class in [
+            SlaveStatusClass::MotionX,
+            SlaveStatusClass::MotionY,
+            SlaveStatusClass::MotionZ,
+            SlaveStatusClass::MotionRotate,
+        ] {
+            self.set_target_status(&status_class, 0);
+        }
+    }
+
+    // 在当前输入上叠加配平偏移量，再按机位配置交换 X/Y 轴
+    fn control_packet(&self, trim: &MotionPacket) -> ControlPacket {
+        let mut control_packet =
+            ControlPacket::from_status_map(&self.get_status().lock().unwrap()).trimmed(trim);
+        if *self.config.model().get_swap_xy() {
+            std::mem::swap(&mut control_packet.motion.x, &mut control_packet.motion.y);
+        }
+        control_packet
+    }
+
+    // 机位设置中未单独指定时使用首选项中的设置
+    fn communication_rates(&self) -> (u16, u16) {
+        let config = self.config.model();
+        let preferences = self.preferences.borrow();
+        (
+            config.get_input_sending_rate().unwrap_or(*preferences.get_default_input_sending_rate()),
+            config
+                .get_status_info_update_interval()
+                .unwrap_or(*preferences.get_default_status_info_update_interval()),
+        )
+    }
+
+    fn send_control_packet(&self) {
+        self.send_control_packet_with_trim(self.config.model().get_motion_trim());
+    }
+
+    fn send_control_packet_with_trim(&self, trim: &MotionPacket) {
+        if self.control_replay.is_some() {
+            return;
+        }
+        if let Some(sender) = self.get_communication_msg_sender() {
+            match sender.try_send(SlaveCommunicationMsg::ControlUpdated(self.control_packet(trim))) {
+                Ok(_) => (),
+                Err(err) => log::warn!("无法发送控制输入：{}", err),
+            }
+        }
+    }
+
+    // 仅用于状态面板的方向指示，不影响实际发送的控制量
+    pub fn joystick_display_threshold(&self) -> i16 {
+        *self.preferences.borrow().get_joystick_display_threshold()
+    }
+
+    fn info_value(&self, keys: &[&str]) -> Option<String> {
+        keys.iter().find_map(|key| self.info_map.get(*key).cloned())
+    }
+
+    fn lock_setpoint_keys(status_class: &SlaveStatusClass) -> Option<(&'static [&'static str], &'static str)> {
+        match status_class {
+            SlaveStatusClass::DepthLocked => Some((&INFO_KEYS_DEPTH, INFO_KEY_DEPTH_SETPOINT)),
+            SlaveStatusClass::DirectionLocked => Some((&INFO_KEYS_HEADING, INFO_KEY_HEADING_SETPOINT)),
+            _ => None,
+        }
+    }
+
+    // 快捷键对应的消息，仅在对应按钮可用时返回
+    pub fn shortcut_message(&self, shortcut: SlaveShortcut) -> Option<SlaveMsg> {
+        match shortcut {
+            SlaveShortcut::ToggleConnect => self.connected.is_some().then(|| SlaveMsg::RequestToggleConnect),
+            SlaveShortcut::TogglePolling => self.polling_disabled_reason().is_none().then(|| SlaveMsg::TogglePolling),
+            SlaveShortcut::ToggleRecord => self.record_disabled_reason().is_none().then(|| SlaveMsg::ToggleRecord),
+            SlaveShortcut::TakeScreenshot => self.screenshot_disabled_reason().is_none().then(|| SlaveMsg::RequestScreenshot),
+        }
+    }
+
+    // 以下按钮不可用时的原因，同时决定按钮与对应快捷键是否可用，并作为按钮的提示向用户说明
+    fn polling_disabled_reason(&self) -> Option<&'static str> {
+        if self.sync_recording {
+            Some(tr("同步录制进行中，无法停止拉流"))
+        } else if self.recording.is_none() {
+            Some(tr("正在开始或停止录制…"))
+        } else {
+            None
+        }
+    }
+
+    fn record_disabled_reason(&self) -> Option<&'static str> {
+        if self.sync_recording {
+            Some(tr("同步录制进行中，请使用标题栏中的按钮停止"))
+        } else if self.recording.is_none() {
+            Some(tr("正在开始或停止录制…"))
+        } else if self.polling != Some(true) {
+            Some(tr("需要先启动拉流"))
+        } else {
+            None
+        }
+    }
+
+    fn screenshot_disabled_reason(&self) -> Option<&'static str> {
+        if self.video.model().has_frame() {
+            None
+        } else if self.polling == Some(true) {
+            Some(tr("等待视频画面…"))
+        } else {
+            Some(tr("需要先启动拉流"))
+        }
+    }
+
+    // 关闭与通讯主循环之间的通道，仍在运行的通讯主循环会随之退出，不再向机位发送消息
+    fn close_communication(&mut self) {
+        if let Some(sender) = self.get_mut_communication_msg_sender().take() {
+            sender.close();
+        }
+    }
+
+    // 开启锁定时记录当时的深度或航向角作为设定值，关闭锁定时清除
+    fn update_lock_setpoints(&mut self) {
+        for status_class in [SlaveStatusClass::DepthLocked, SlaveStatusClass::DirectionLocked] {
+            let locked = self.get_target_status(&status_class) != 0;
+            match (locked, self.lock_setpoints.contains_key(&status_class)) {
+                (true, false) => {
+                    let (keys, _) = Self::lock_setpoint_keys(&status_class).unwrap();
+                    let value = self.info_value(keys);
+                    self.get_mut_lock_setpoints().insert(status_class.clone(), value);
+                    self.lock_changed_times.insert(status_class, Instant::now());
+                }
+                (false, true) => {
+                    self.get_mut_lock_setpoints().remove(&status_class);
+                    self.lock_changed_times.insert(status_class, Instant::now());
+                }
+                _ => (),
+            }
+        }
+    }
+
+    // 下位机报告的锁定状态与开关不一致时以下位机为准，切换开关后的一段时间内等待下位机响应而不校正
+    fn reconcile_lock_states(&mut self, sender: &Sender<SlaveMsg>) {
+        let info_keys = {
+            let preferences = self.preferences.borrow();
+            [
+                (SlaveStatusClass::DepthLocked, preferences.get_depth_locked_info_key().trim().to_string()),
+                (SlaveStatusClass::DirectionLocked, preferences.get_direction_locked_info_key().trim().to_string()),
+            ]
+        };
+        let mut reconciled = false;
+        for (status_class, info_key) in info_keys {
+            if info_key.is_empty() {
+                continue;
+            }
+            let reported = match self.info_value(&[info_key.as_str()]).as_deref().and_then(parse_lock_state) {
+                Some(reported) => reported,
+                None => continue,
+            };
+            let locked = self.get_target_status(&status_class) != 0;
+            let pending = self
+                .lock_changed_times
+                .get(&status_class)
+                .map_or(false, |time| time.elapsed() < LOCK_CONFIRMATION_TIMEOUT);
+            if reported == locked || pending {
+                continue;
+            }
+            self.set_target_status(&status_class, reported as i16);
+            reconciled = true;
+            let (name, rejected_message) = match status_class {
+                SlaveStatusClass::DepthLocked => ("深度锁定", "下位机未能开启深度锁定，已关闭深度锁定开关。"),
+                _ => ("方向锁定", "下位机未能开启方向锁定，已关闭方向锁定开关。"),
+            };
+            log::warn!("下位机报告的{}状态与上位机不一致，已校正为{}", name, if reported { "开启" } else { "关闭" });
+            if locked {
+                send!(sender, SlaveMsg::ShowToastMessage(String::from(tr(rejected_message))));
+            }
+        }
+        if reconciled {
+            self.update_lock_setpoints();
+            if let Some(communication_sender) = self.get_communication_msg_sender() {
+                let depth_locked = self.get_target_status(&SlaveStatusClass::DepthLocked) != 0;
+                let direction_locked = self.get_target_status(&SlaveStatusClass::DirectionLocked) != 0;
+                match communication_sender.try_send(SlaveCommunicationMsg::LockStatesReconciled(depth_locked, direction_locked)) {
+                    Ok(_) => (),
+                    Err(err) => log::warn!("无法更新锁定状态：{}", err),
+                }
+            }
+        }
+    }
+
+    pub fn lock_setpoint_text(&self, status_class: &SlaveStatusClass) -> String {
+        self.lock_setpoints
+            .get(status_class)
+            .cloned()
+            .flatten()
+            .unwrap_or_else(|| String::from("—"))
+    }
+
+    // 已关闭的子窗口不再接收消息，发送失败时丢弃其发送端
+    fn notify_child_windows(&mut self, rpc_client: Option<&RpcClient>) {
+        if let Some(sender) = &self.firmware_updater_sender {
+            if sender.send(SlaveFirmwareUpdaterMsg::SetRpcClient(rpc_client.cloned())).is_err() {
+                self.firmware_updater_sender = None;
+            }
+        }
+        if rpc_client.is_none() {
+            if let Some(sender) = self.parameter_tuner_sender.take() {
+                sender.send(SlaveParameterTunerMsg::ConnectionClosed).unwrap_or_default();
+            }
+        }
+    }
+
+    // 按照首选项确定保存文件的子文件夹并在写入前创建，创建失败时以通知提示并返回 None
+    fn save_directory(&self, base: &Path, sender: &Sender<SlaveMsg>) -> Option<PathBuf> {
+        let scheme = *self.preferences.borrow().get_save_directory_scheme();
+        match scheme.prepare_directory(base) {
+            Ok(directory) => Some(directory),
+            Err(msg) => {
+                send!(sender, SlaveMsg::ShowToastMessage(msg));
+                None
+            }
+        }
+    }
+
+    // 按照首选项中的文件名模板生成文件路径，模板无效时使用时间戳作为文件名
+    fn file_name_path(
+        &mut self,
+        directory: &Path,
+        extension: &str,
+        sender: &Sender<SlaveMsg>,
+    ) -> PathBuf {
+        self.set_file_counter(self.file_counter + 1);
+        let template = self.preferences.borrow().get_file_name_template().clone();
+        let slave_url = self.config.model().get_slave_url().clone();
+        let context = FileNameContext {
+            slave_url: &slave_url,
+            counter: self.file_counter,
+        };
+        let name = match expand_file_name_template(&template, &context) {
+            Ok(name) => name,
+            Err(err) => {
+                send!(
+                    sender,
+                    SlaveMsg::ShowToastMessage(format!(
+                        "{}{}",
+                        tr("文件名模板无效，已使用时间戳作为文件名："),
+                        err.to_string()
+                    ))
+                );
+                timestamp_file_name()
+            }
+        };
+        unique_file_path(directory, &name, extension)
+    }
+
+    // 断开连接或退出会中断的操作，用于在断开连接或退出前提示确认，退出时录制也会停止
+    pub fn disconnect_interruptions(&self, shutdown: bool) -> Vec<&'static str> {
+        let mut interruptions = Vec::new();
+        let connected = *self.get_connected() == Some(true);
+        if *self.get_recording() == Some(true) {
+            if shutdown {
+                interruptions.push(tr("正在进行的录制将停止"));
+            } else if connected {
+                interruptions.push(tr("正在进行的录制此后将缺少遥测数据"));
+            }
+        }
+        if connected && !self.lock_setpoints.is_empty() {
+            interruptions.push(tr("已启用的深度或方向锁定将不再收到上位机的控制指令"));
+        }
+        interruptions
+    }
+
+    pub fn collect_diagnostics(&self) -> SlaveDiagnostics {
+        let video = self.video.model();
+        SlaveDiagnostics {
+            frames_displayed: *video.get_frame_count(),
+            frames_dropped: video.dropped_frame_count(),
+            control_packets_sent: self.control_packets_sent,
+            rpc_errors: self.rpc_errors,
+            video_reconnects: video.video_reconnects,
+            connected_secs: self.connected_time.map(|time| time.elapsed().as_secs()),
+        }
+    }
+
+    pub fn control_latency_text(&self) -> String {
+        self.control_latency
+            .map(|latency| format!("{} ms", latency.as_millis()))
+            .unwrap_or_else(|| String::from("—"))
+    }
+
+    pub fn stream_statistics_text(&self) -> String {
+        self.stream_statistics
+            .map(|statistics| statistics.to_string())
+            .unwrap_or_else(|| String::from("—"))
+    }
+
+    pub fn dive_time_text(&self) -> String {
+        self.dive_secs
+            .map(|secs| format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60))
+            .unwrap_or_else(|| String::from("—"))
+    }
+
+    pub fn info_polling_text(&self) -> String {
+        match (self.info_polling_paused, self.last_info_time) {
+            (false, _) => String::new(),
+            (true, Some(time)) => format!("{}{}{}", tr("已暂停，数据已过时 "), time.elapsed().as_secs(), tr(" 秒")),
+            (true, None) => String::from(tr("已暂停")),
+        }
+    }
+
+    pub fn video_format_text(&self) -> String {
+        self.video_format
+            .map(|format| format.to_string())
+            .unwrap_or_else(|| String::from("—"))
+    }
+
+    pub fn telemetry(&self) -> BTreeMap<String, String> {
+        self.info_map.clone()
+    }
+
+    // 将画面移至单独的无边框窗口中，便于拖动到其他显示器上，控制与状态信息仍保留在主窗口中
+    fn pop_out_video(&mut self, sender: &Sender<SlaveMsg>) {
+        let video = self.video.root_widget();
+        let main_overlay = match video.parent().and_then(|parent| parent.downcast::<Overlay>().ok()) {
+            Some(overlay) => overlay,
+            None => return,
+        };
+        let placeholder = StatusPage::builder()
+            .icon_name("video-display-symbolic")
+            .title(tr("画面已在单独窗口中显示"))
+            .build();
+        let dock_button = GtkButton::with_label(tr("收回画面"));
+        dock_button.set_halign(Align::Center);
+        dock_button.add_css_class("pill");
+        dock_button.connect_clicked(clone!(@strong sender => move |_button| {
+            send!(sender, SlaveMsg::DockVideo);
+        }));
+        placeholder.set_child(Some(&dock_button));
+        main_overlay.set_child(Some(&placeholder));
+
+        let window = Window::builder()
+            .title(&format!("{} - {}", tr("机位画面"), self.config.model().get_slave_url()))
+            .decorated(false)
+            .default_width(960)
+            .default_height(540)
+            .build();
+        let handle = WindowHandle::new(); // 拖动画面即可移动窗口
+        handle.set_child(Some(video));
+        let overlay = Overlay::new();
+        overlay.set_child(Some(&handle));
+        let button = GtkButton::from_icon_name("view-restore-symbolic");
+        button.set_css_classes(&["circular", "osd"]);
+        button.set_tooltip_text(Some(tr("收回画面")));
+        button.set_halign(Align::End);
+        button.set_valign(Align::Start);
+        button.set_margin_top(8);
+        button.set_margin_end(8);
+        button.connect_clicked(clone!(@strong sender => move |_button| {
+            send!(sender, SlaveMsg::DockVideo);
+        }));
+        overlay.add_overlay(&button);
+        window.set_child(Some(&overlay));
+        // 点击画面时获取输入焦点，双击时在当前显示器上切换全屏
+        let gesture = GestureClick::new();
+        gesture.set_propagation_phase(PropagationPhase::Capture);
+        gesture.connect_pressed(clone!(@strong sender, @weak window => move |_gesture, n_press, _x, _y| {
+            send!(sender, SlaveMsg::RequestInputFocus);
+            if n_press == 2 {
+                if window.is_fullscreened() {
+                    window.unfullscreen();
+                } else {
+                    window.fullscreen();
+                }
+            }
+        }));
+        window.add_controller(&gesture);
+        window.connect_close_request(clone!(@strong sender => move |_window| {
+            send!(sender, SlaveMsg::DockVideo);
+            Inhibit(true)
+        }));
+        window.present();
+        self.set_video_window(Some((window, handle, main_overlay)));
+    }
+
+    fn dock_video(&mut self) {
+        if let Some((window, handle, main_overlay)) = self.get_mut_video_window().take() {
+            handle.set_child(None::<&Widget>);
+            main_overlay.set_child(Some(self.video.root_widget()));
+            window.destroy();
+        }
+    }
+
+    // 置顶的信息项按首选项中列出的顺序排在最前，其余按名称排序，隐藏的信息项不显示
+    fn displayed_infos(&self) -> Vec<(String, String)> {
+        let preferences = self.preferences.borrow();
+        let pinned_keys = preferences.get_pinned_info_keys();
+        let hidden_keys = preferences.get_hidden_info_keys();
+        let pinned = pinned_keys
+            .iter()
+            .filter(|key| !hidden_keys.contains(key))
+            .filter_map(|key| self.info_map.get(key).map(|value| (key.clone(), value.clone())));
+        let rest = self
+            .info_map
+            .iter()
+            .filter(|(key, _)| !pinned_keys.contains(key) && !hidden_keys.contains(key))
+            .map(|(key, value)| (key.clone(), value.clone()));
+        pinned.chain(rest).collect()
+    }
+}
+
+const TOAST_HISTORY_LIMIT: usize = 50;
+const DISK_SPACE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+const LOCK_CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(2);
+const CONTROL_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+const DIVE_TIMER_INTERVAL: Duration = Duration::from_secs(1);
+
+fn parse_lock_state(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" | "on" => Some(true),
+        "false" | "0" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+pub fn diagnostics_list_box(diagnostics: &SlaveDiagnostics) -> Widget {
+    let list_box = ListBox::builder().selection_mode(SelectionMode::None).build();
+    for (name, value) in diagnostics.entries() {
+        let row = CenterBox::builder()
+            .margin_top(4)
+            .margin_bottom(4)
+            .margin_start(8)
+            .margin_end(8)
+            .build();
+        row.set_start_widget(Some(&Label::new(Some(name))));
+        let value_label = Label::builder().label(&value).selectable(true).margin_start(20).build();
+        value_label.add_css_class("numeric");
+        row.set_end_widget(Some(&value_label));
+        list_box.append(&row);
+    }
+    list_box.upcast()
+}
+
+pub fn toast_history_list_box(toast_history: &VecDeque<(String, String)>) -> Widget {
+    if toast_history.is_empty() {
+        return Label::builder()
+            .label(tr("暂无通知"))
+            .margin_top(4)
+            .margin_bottom(4)
+            .margin_start(4)
+            .margin_end(4)
+            .build()
+            .upcast();
+    }
+    let list_box = ListBox::builder().selection_mode(SelectionMode::None).build();
+    for (time, message) in toast_history.iter().rev() {
+        let label = Label::builder()
+            .label(&format!("{}  {}", time, message))
+            .xalign(0.0)
+            .wrap(true)
+            .max_width_chars(48)
+            .selectable(true)
+            .margin_top(4)
+            .margin_bottom(4)
+            .margin_start(4)
+            .margin_end(4)
+            .build();
+        list_box.append(&label);
+    }
+    ScrolledWindow::builder()
+        .hscrollbar_policy(PolicyType::Never)
+        .propagate_natural_height(true)
+        .max_content_height(320)
+        .child(&list_box)
+        .build()
+        .upcast()
+}
+
+fn power_level_indicator(level: PowerLevel) -> Option<(&'static str, &'static str)> {
+    match level {
+        PowerLevel::Empty => Some(("battery-empty-symbolic", tr("电量耗尽"))),
+        PowerLevel::Low => Some(("battery-caution-symbolic", tr("电量低"))),
+        PowerLevel::Medium => Some(("battery-good-symbolic", tr("电量中等"))),
+        PowerLevel::Full => Some(("battery-full-symbolic", tr("电量充足"))),
+        PowerLevel::Wired => Some(("ac-adapter-symbolic", tr("有线供电"))),
+        PowerLevel::Unknown => None,
+    }
 }
 
 pub fn input_sources_list_box(
@@ -233,7 +939,7 @@ pub fn input_sources_list_box(
     let sources = input_system.get_sources().unwrap();
     if sources.is_empty() {
         return Label::builder()
-            .label("无可用设备")
+            .label(tr("无可用设备"))
             .margin_top(4)
             .margin_bottom(4)
             .margin_start(4)
@@ -244,7 +950,24 @@ pub fn input_sources_list_box(
     let list_box = ListBox::builder().build();
     let mut radio_button_group: Option<CheckButton> = None;
     for (source, name) in sources {
-        let radio_button = CheckButton::builder().label(&name).build();
+        let radio_button = CheckButton::builder().label(&name).hexpand(true).build();
+        let row = GtkBox::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(5)
+            .build();
+        row.append(&radio_button);
+        if let Some((icon_name, description)) = input_system
+            .power_level(&source)
+            .and_then(power_level_indicator)
+        {
+            row.append(
+                &Image::builder()
+                    .icon_name(icon_name)
+                    .tooltip_text(description)
+                    .margin_end(4)
+                    .build(),
+            );
+        }
         let sender = sender.clone();
         radio_button.set_active(input_sources.contains(&source));
         radio_button.connect_toggled(move |button| {
@@ -261,7 +984,7 @@ pub fn input_sources_list_box(
                 None => radio_button_group = Some(radio_button),
             }
         }
-        list_box.append(&radio_button);
+        list_box.append(&row);
     }
     list_box.upcast()
 }
@@ -280,43 +1003,118 @@ impl MicroWidgets<SlaveModel> for SlaveWidgets {
                         set_hexpand: true,
                         set_halign: Align::Start,
                         set_spacing: 5,
+                        // 连接或断开连接的过程中显示旋转图标，而不是直接禁用按钮
                         append = &GtkButton {
-                            set_icon_name: "network-transmit-symbolic",
-                            set_sensitive: track!(model.changed(SlaveModel::connected()), model.connected != None),
-                            set_css_classes?: watch!(model.connected.map(|x| if x { vec!["circular", "suggested-action"] } else { vec!["circular"] }).as_ref()),
-                            set_tooltip_text: track!(model.changed(SlaveModel::connected()), model.connected.map(|x| if x { "断开连接" } else { "连接" })),
+                            set_css_classes: track!(model.changed(SlaveModel::connected()), &match model.connected { Some(true) => vec!["circular", "image-button", "suggested-action"], Some(false) => vec!["circular", "image-button"], None => vec!["circular", "image-button", "warning"] }),
+                            set_tooltip_text: track!(model.changed(SlaveModel::connected()), Some(match model.connected { Some(true) => tr("断开连接"), Some(false) => tr("连接"), None => tr("正在连接或断开连接…") })),
+                            set_child = Some(&GtkBox) {
+                                set_halign: Align::Center,
+                                append = &Image {
+                                    set_icon_name: Some("network-transmit-symbolic"),
+                                    set_visible: track!(model.changed(SlaveModel::connected()), model.connected.is_some()),
+                                },
+                                append = &Spinner {
+                                    set_spinning: true,
+                                    set_visible: track!(model.changed(SlaveModel::connected()), model.connected.is_none()),
+                                },
+                            },
                             connect_clicked(sender) => move |_button| {
-                                send!(sender, SlaveMsg::ToggleConnect);
+                                send!(sender, SlaveMsg::RequestToggleConnect);
                             },
                         },
                         append = &GtkButton {
-                            set_icon_name: "video-display-symbolic",
-                            set_sensitive: track!(model.changed(SlaveModel::recording()) || model.changed(SlaveModel::sync_recording()) || model.changed(SlaveModel::polling()), model.get_recording().is_some() && model.get_polling().is_some() && !model.sync_recording),
-                            set_css_classes?: watch!(model.polling.map(|x| if x { vec!["circular", "destructive-action"] } else { vec!["circular"] }).as_ref()),
-                            set_tooltip_text: track!(model.changed(SlaveModel::polling()), model.polling.map(|x| if x { "停止拉流" } else { "启动拉流" })),
+                            set_sensitive: track!(model.changed(SlaveModel::recording()) || model.changed(SlaveModel::sync_recording()), model.polling_disabled_reason().is_none()),
+                            set_css_classes: track!(model.changed(SlaveModel::polling()), &match model.polling { Some(true) => vec!["circular", "image-button", "destructive-action"], Some(false) => vec!["circular", "image-button"], None => vec!["circular", "image-button", "warning"] }),
+                            set_tooltip_text: track!(model.changed(SlaveModel::polling()) || model.changed(SlaveModel::recording()) || model.changed(SlaveModel::sync_recording()), Some(model.polling_disabled_reason().unwrap_or(match model.polling { Some(true) => tr("停止拉流"), Some(false) => tr("启动拉流"), None => tr("正在启动或停止拉流…") }))),
+                            set_child = Some(&GtkBox) {
+                                set_halign: Align::Center,
+                                append = &Image {
+                                    set_icon_name: Some("video-display-symbolic"),
+                                    set_visible: track!(model.changed(SlaveModel::polling()), model.polling.is_some()),
+                                },
+                                append = &Spinner {
+                                    set_spinning: true,
+                                    set_visible: track!(model.changed(SlaveModel::polling()), model.polling.is_none()),
+                                },
+                            },
                             connect_clicked(sender) => move |_button| {
                                 send!(sender, SlaveMsg::TogglePolling);
                             },
                         },
+                        append = &GtkButton {
+                            set_icon_name: "view-dual-symbolic",
+                            set_visible: track!(model.changed(SlaveModel::config()), model.config.model().get_secondary_video_url().is_some()),
+                            set_sensitive: track!(model.changed(SlaveModel::secondary_polling()), model.secondary_polling.is_some()),
+                            set_css_classes: track!(model.changed(SlaveModel::secondary_polling()), &if model.secondary_polling == Some(true) { vec!["circular", "suggested-action"] } else { vec!["circular"] }),
+                            set_tooltip_text: track!(model.changed(SlaveModel::secondary_polling()), Some(if model.secondary_polling == Some(true) { tr("停止副摄像头画面") } else { tr("显示副摄像头画面（画中画）") })),
+                            connect_clicked(sender) => move |_button| {
+                                send!(sender, SlaveMsg::ToggleSecondaryPolling);
+                            },
+                        },
+                        append = &GtkButton {
+                            set_icon_name: "view-refresh-symbolic",
+                            set_sensitive: track!(model.changed(SlaveModel::polling()), model.polling == Some(true)),
+                            set_css_classes: &["circular"],
+                            set_tooltip_text: Some(tr("刷新画面（请求关键帧）")),
+                            connect_clicked(sender) => move |_button| {
+                                send!(sender, SlaveMsg::RefreshFrame);
+                            },
+                        },
                         append = &Separator {},
                         append = &GtkButton {
                             set_icon_name: "camera-photo-symbolic",
-                            set_sensitive: watch!(model.video.model().get_pixbuf().is_some()),
+                            set_sensitive: watch!(model.screenshot_disabled_reason().is_none()),
+                            set_css_classes: &["circular"],
+                            set_tooltip_text: watch!(Some(model.screenshot_disabled_reason().unwrap_or(tr("画面截图")))),
+                            connect_clicked(sender) => move |_button| {
+                                send!(sender, SlaveMsg::RequestScreenshot);
+                            },
+                        },
+                        append = &GtkButton {
+                            set_icon_name: "document-save-symbolic",
+                            set_sensitive: watch!(model.screenshot_disabled_reason().is_none()),
                             set_css_classes: &["circular"],
-                            set_tooltip_text: Some("画面截图"),
+                            set_tooltip_text: watch!(Some(model.screenshot_disabled_reason().unwrap_or(tr("保存采集报告（画面与状态信息）")))),
                             connect_clicked(sender) => move |_button| {
-                                send!(sender, SlaveMsg::TakeScreenshot);
+                                send!(sender, SlaveMsg::CaptureReport);
                             },
                         },
                         append = &GtkButton {
                             set_icon_name: "camera-video-symbolic",
-                            set_sensitive: track!(model.changed(SlaveModel::sync_recording()) || model.changed(SlaveModel::polling()) || model.changed(SlaveModel::recording()), !model.sync_recording && model.recording != None &&  model.polling == Some(true)),
+                            set_sensitive: track!(model.changed(SlaveModel::sync_recording()) || model.changed(SlaveModel::polling()) || model.changed(SlaveModel::recording()), model.record_disabled_reason().is_none()),
                             set_css_classes?: watch!(model.recording.map(|x| if x { vec!["circular", "destructive-action"] } else { vec!["circular"] }).as_ref()),
-                            set_tooltip_text: track!(model.changed(SlaveModel::recording()), model.recording.map(|x| if x { "停止录制" } else { "开始录制" })),
+                            set_tooltip_text: track!(model.changed(SlaveModel::sync_recording()) || model.changed(SlaveModel::polling()) || model.changed(SlaveModel::recording()), Some(model.record_disabled_reason().unwrap_or(if model.recording == Some(true) { tr("停止录制") } else { tr("开始录制") }))),
                             connect_clicked(sender) => move |_button| {
                                 send!(sender, SlaveMsg::ToggleRecord);
                             },
                         },
+                        append = &GtkButton {
+                            set_icon_name: "alarm-symbolic",
+                            set_sensitive: track!(model.changed(SlaveModel::polling()) || model.changed(SlaveModel::timelapse_directory()), model.polling == Some(true) || model.timelapse_directory.is_some()),
+                            set_css_classes: track!(model.changed(SlaveModel::timelapse_directory()), &if model.timelapse_directory.is_some() { vec!["circular", "destructive-action"] } else { vec!["circular"] }),
+                            set_tooltip_text: track!(model.changed(SlaveModel::timelapse_directory()), Some(if model.timelapse_directory.is_some() { tr("停止定时截图") } else { tr("开始定时截图") })),
+                            connect_clicked(sender) => move |_button| {
+                                send!(sender, SlaveMsg::ToggleTimelapse);
+                            },
+                        },
+                        append = &Label {
+                            set_visible: track!(model.changed(SlaveModel::timelapse_directory()), model.timelapse_directory.is_some()),
+                            set_text: track!(model.changed(SlaveModel::timelapse_frame_count()) || model.changed(SlaveModel::timelapse_paused()), &format!(
+                                "{}{}{}",
+                                tr("已截图："),
+                                model.timelapse_frame_count,
+                                if model.timelapse_paused { tr("（无画面，已暂停）") } else { "" }
+                            )),
+                        },
+                        append = &GtkButton {
+                            set_icon_name: "media-playlist-repeat-symbolic",
+                            set_sensitive: track!(model.changed(SlaveModel::connected()) || model.changed(SlaveModel::control_replay()), model.connected == Some(true) || model.control_replay.is_some()),
+                            set_css_classes: track!(model.changed(SlaveModel::control_replay()), &if model.control_replay.is_some() { vec!["circular", "destructive-action"] } else { vec!["circular"] }),
+                            set_tooltip_text: track!(model.changed(SlaveModel::control_replay()), Some(if model.control_replay.is_some() { tr("停止回放控制指令") } else { tr("回放控制指令记录") })),
+                            connect_clicked(sender) => move |_button| {
+                                send!(sender, SlaveMsg::ToggleControlReplay);
+                            },
+                        },
                     },
                     set_center_widget = Some(&GtkBox) {
                         set_hexpand: true,
@@ -328,7 +1126,7 @@ impl MicroWidgets<SlaveModel> for SlaveWidgets {
                         append = &MenuButton {
                             set_icon_name: "input-gaming-symbolic",
                             set_css_classes: &["circular"],
-                            set_tooltip_text: Some("切换当前机位使用的输入设备"),
+                            set_tooltip_text: Some(tr("切换当前机位使用的输入设备")),
                             set_popover = Some(&Popover) {
                                 set_child = Some(&GtkBox) {
                                     set_spacing: 5,
@@ -337,12 +1135,12 @@ impl MicroWidgets<SlaveModel> for SlaveWidgets {
                                         set_center_widget = Some(&Label) {
                                             set_margin_start: 10,
                                             set_margin_end: 10,
-                                            set_markup: "<b>输入设备</b>"
+                                            set_markup: tr("<b>输入设备</b>")
                                         },
                                         set_end_widget = Some(&GtkButton) {
                                             set_icon_name: "view-refresh-symbolic",
                                             set_css_classes: &["circular"],
-                                            set_tooltip_text: Some("刷新输入设备"),
+                                            set_tooltip_text: Some(tr("刷新输入设备")),
                                             connect_clicked(sender) => move |_button| {
                                                 send!(sender, SlaveMsg::UpdateInputSources);
                                             },
@@ -361,10 +1159,18 @@ impl MicroWidgets<SlaveModel> for SlaveWidgets {
                         set_halign: Align::End,
                         set_spacing: 5,
                         set_margin_end: 5,
+                        append = &GtkButton {
+                            set_icon_name: "window-new-symbolic",
+                            set_css_classes: &["circular"],
+                            set_tooltip_text: track!(model.changed(SlaveModel::video_window()), Some(if model.video_window.is_some() { tr("收回画面") } else { tr("在单独窗口中显示画面") })),
+                            connect_clicked(sender) => move |_button| {
+                                send!(sender, SlaveMsg::ToggleVideoWindow);
+                            },
+                        },
                         append = &GtkButton {
                             set_icon_name: "software-update-available-symbolic",
                             set_css_classes: &["circular"],
-                            set_tooltip_text: Some("固件更新"),
+                            set_tooltip_text: Some(tr("固件更新")),
                             connect_clicked(sender) => move |_button| {
                                 send!(sender, SlaveMsg::OpenFirmwareUpater);
                             },
@@ -372,52 +1178,149 @@ impl MicroWidgets<SlaveModel> for SlaveWidgets {
                         append = &GtkButton {
                             set_icon_name: "preferences-other-symbolic",
                             set_css_classes: &["circular"],
-                            set_tooltip_text: Some("参数调校"),
+                            set_tooltip_text: Some(tr("参数调校")),
                             connect_clicked(sender) => move |_button| {
                                 send!(sender, SlaveMsg::OpenParameterTuner);
                             },
                         },
-                        append = &Separator {},
-                        append = &ToggleButton {
-                            set_icon_name: "emblem-system-symbolic",
+                        append = &GtkButton {
+                            set_icon_name: "view-grid-symbolic",
                             set_css_classes: &["circular"],
-                            set_tooltip_text: Some("机位设置"),
-                            set_active: track!(model.changed(SlaveModel::config_presented()), *model.get_config_presented()),
-                            connect_active_notify(sender) => move |button| {
-                                send!(sender, SlaveMsg::SetConfigPresented(button.is_active()));
+                            set_tooltip_text: Some(tr("推进器混控预览")),
+                            connect_clicked(sender) => move |_button| {
+                                send!(sender, SlaveMsg::OpenThrusterMixPreview);
                             },
                         },
-                        append = &ToggleButton {
-                            set_icon_name: "window-close-symbolic",
+                        append = &MenuButton {
+                            set_icon_name: "preferences-system-notifications-symbolic",
                             set_css_classes: &["circular"],
-                            set_tooltip_text: Some("移除机位"),
-                            set_visible: false,
-                            connect_active_notify(sender) => move |_button| {
-                                send!(sender, SlaveMsg::DestroySlave);
-                            },
-                        },
-                    },
-                },
-                append = &Flap {
-                    set_flap: Some(model.config.root_widget()),
-                    set_reveal_flap: track!(model.changed(SlaveModel::config_presented()), *model.get_config_presented()),
-                    set_fold_policy: FlapFoldPolicy::Auto,
-                    set_locked: true,
-                    set_flap_position: PackType::End,
-                    set_separator = Some(&Separator) {},
-                    set_content = Some(&Overlay) {
-                        set_width_request: 640,
-                        set_child: Some(model.video.root_widget()),
-                        add_overlay = &GtkBox {
-                            set_valign: Align::Start,
-                            set_halign: Align::End,
-                            set_hexpand: true,
-                            set_margin_all: 20,
-                            append = &Frame {
-                                add_css_class: "card",
+                            set_tooltip_text: Some(tr("通知历史")),
+                            set_popover = Some(&Popover) {
                                 set_child = Some(&GtkBox) {
+                                    set_spacing: 5,
                                     set_orientation: Orientation::Vertical,
-                                    set_margin_all: 5,
+                                    append = &CenterBox {
+                                        set_center_widget = Some(&Label) {
+                                            set_margin_start: 10,
+                                            set_margin_end: 10,
+                                            set_markup: tr("<b>通知历史</b>")
+                                        },
+                                        set_end_widget = Some(&GtkButton) {
+                                            set_icon_name: "edit-clear-all-symbolic",
+                                            set_css_classes: &["circular"],
+                                            set_tooltip_text: Some(tr("清空")),
+                                            connect_clicked(sender) => move |_button| {
+                                                send!(sender, SlaveMsg::ClearToastHistory);
+                                            },
+                                        },
+                                    },
+                                    append = &Frame {
+                                        set_child: track!(model.changed(SlaveModel::toast_history()), Some(&toast_history_list_box(&model.toast_history))),
+                                    },
+                                },
+                            },
+                        },
+                        append = &MenuButton {
+                            set_icon_name: "utilities-system-monitor-symbolic",
+                            set_css_classes: &["circular"],
+                            set_tooltip_text: Some(tr("诊断信息")),
+                            set_popover = Some(&Popover) {
+                                connect_show(sender) => move |_popover| {
+                                    send!(sender, SlaveMsg::SetDiagnosticsPresented(true));
+                                },
+                                connect_closed(sender) => move |_popover| {
+                                    send!(sender, SlaveMsg::SetDiagnosticsPresented(false));
+                                },
+                                set_child = Some(&GtkBox) {
+                                    set_spacing: 5,
+                                    set_orientation: Orientation::Vertical,
+                                    append = &Label {
+                                        set_margin_start: 10,
+                                        set_margin_end: 10,
+                                        set_markup: tr("<b>诊断信息</b>")
+                                    },
+                                    append = &Frame {
+                                        set_child: track!(model.changed(SlaveModel::diagnostics()), Some(&diagnostics_list_box(&model.diagnostics))),
+                                    },
+                                    append = &GtkButton {
+                                        set_label: tr("导出管道结构图"),
+                                        set_visible: watch!(model.preferences.borrow().pipeline_graph_export_available()),
+                                        connect_clicked(sender) => move |_button| {
+                                            send!(sender, SlaveMsg::ExportPipelineGraph);
+                                        },
+                                    },
+                                },
+                            },
+                        },
+                        append = &Separator {},
+                        append = &ToggleButton {
+                            set_icon_name: "emblem-system-symbolic",
+                            set_css_classes: &["circular"],
+                            set_tooltip_text: Some(tr("机位设置")),
+                            set_active: track!(model.changed(SlaveModel::config_presented()), *model.get_config_presented()),
+                            connect_active_notify(sender) => move |button| {
+                                send!(sender, SlaveMsg::SetConfigPresented(button.is_active()));
+                            },
+                        },
+                        append = &ToggleButton {
+                            set_icon_name: "window-close-symbolic",
+                            set_css_classes: &["circular"],
+                            set_tooltip_text: Some(tr("移除机位")),
+                            set_visible: false,
+                            connect_active_notify(sender) => move |_button| {
+                                send!(sender, SlaveMsg::DestroySlave);
+                            },
+                        },
+                    },
+                },
+                append = &Flap {
+                    set_flap: Some(model.config.root_widget()),
+                    set_reveal_flap: track!(model.changed(SlaveModel::config_presented()), *model.get_config_presented()),
+                    set_fold_policy: FlapFoldPolicy::Auto,
+                    set_locked: true,
+                    set_flap_position: PackType::End,
+                    set_separator = Some(&Separator) {},
+                    set_content = Some(&Overlay) {
+                        set_width_request: 640,
+                        set_child: Some(model.video.root_widget()),
+                        add_controller = &GestureClick {
+                            set_propagation_phase: PropagationPhase::Capture,
+                            connect_pressed(sender) => move |_gesture, _n_press, _x, _y| {
+                                send!(sender, SlaveMsg::RequestInputFocus);
+                            },
+                        },
+                        add_overlay = &GtkBox {
+                            set_valign: Align::Start,
+                            set_halign: Align::Center,
+                            set_margin_top: 8,
+                            set_spacing: 5,
+                            add_css_class: "osd",
+                            set_can_target: false,
+                            set_visible: track!(model.changed(SlaveModel::input_focused()), model.input_focused),
+                            append = &Image {
+                                set_margin_start: 6,
+                                set_icon_name: Some("input-gaming-symbolic"),
+                            },
+                            append = &Label {
+                                set_margin_end: 6,
+                                set_label: tr("控制中"),
+                            },
+                        },
+                        add_overlay = &GtkBox {
+                            set_valign: Align::End,
+                            set_visible: track!(model.changed(SlaveModel::input_sources()), model.input_sources.contains(&InputSource::Virtual)),
+                            append: &virtual_joystick(&model.input_system),
+                        },
+                        add_overlay = &GtkBox {
+                            set_valign: Align::Start,
+                            set_halign: Align::End,
+                            set_hexpand: true,
+                            set_margin_all: 20,
+                            append = &Frame {
+                                add_css_class: "card",
+                                set_child = Some(&GtkBox) {
+                                    set_orientation: Orientation::Vertical,
+                                    set_margin_all: 5,
                                     set_width_request: 50,
                                     set_spacing: 5,
                                     append = &GtkButton {
@@ -425,7 +1328,7 @@ impl MicroWidgets<SlaveModel> for SlaveWidgets {
                                             set_center_widget = Some(&Label) {
                                                 set_margin_start: 10,
                                                 set_margin_end: 10,
-                                                set_text: "状态信息",
+                                                set_text: tr("状态信息"),
                                             },
                                             set_end_widget = Some(&Image) {
                                                 set_icon_name: watch!(Some(if model.slave_info_displayed { "go-down-symbolic" } else { "go-next-symbolic" })),
@@ -471,49 +1374,49 @@ impl MicroWidgets<SlaveModel> for SlaveWidgets {
                                                         set_icon_name: "object-rotate-left-symbolic",
                                                         set_can_focus: false,
                                                         set_can_target: false,
-                                                        set_active: track!(model.changed(SlaveModel::status()), model.get_target_status(&SlaveStatusClass::MotionRotate) < -JOYSTICK_DISPLAY_THRESHOLD),
+                                                        set_active: track!(model.changed(SlaveModel::status()), model.get_target_status(&SlaveStatusClass::MotionRotate) < -model.joystick_display_threshold()),
                                                     },
                                                     attach(2, 1, 1, 1) = &ToggleButton {
                                                         set_icon_name: "object-rotate-right-symbolic",
                                                         set_can_focus: false,
                                                         set_can_target: false,
-                                                        set_active: track!(model.changed(SlaveModel::status()), model.get_target_status(&SlaveStatusClass::MotionRotate) > JOYSTICK_DISPLAY_THRESHOLD),
+                                                        set_active: track!(model.changed(SlaveModel::status()), model.get_target_status(&SlaveStatusClass::MotionRotate) > model.joystick_display_threshold()),
                                                     },
                                                     attach(0, 3, 1, 1) = &ToggleButton {
                                                         set_icon_name: "go-bottom-symbolic",
                                                         set_can_focus: false,
                                                         set_can_target: false,
-                                                        set_active: track!(model.changed(SlaveModel::status()), model.get_target_status(&SlaveStatusClass::MotionZ) < -JOYSTICK_DISPLAY_THRESHOLD),
+                                                        set_active: track!(model.changed(SlaveModel::status()), model.get_target_status(&SlaveStatusClass::MotionZ) < -model.joystick_display_threshold()),
                                                     },
                                                     attach(2, 3, 1, 1) = &ToggleButton {
                                                         set_icon_name: "go-top-symbolic",
                                                         set_can_focus: false,
                                                         set_can_target: false,
-                                                        set_active: track!(model.changed(SlaveModel::status()), model.get_target_status(&SlaveStatusClass::MotionZ) > JOYSTICK_DISPLAY_THRESHOLD),
+                                                        set_active: track!(model.changed(SlaveModel::status()), model.get_target_status(&SlaveStatusClass::MotionZ) > model.joystick_display_threshold()),
                                                     },
                                                     attach(1, 1, 1, 1) = &ToggleButton {
                                                         set_icon_name: "go-up-symbolic",
                                                         set_can_focus: false,
                                                         set_can_target: false,
-                                                        set_active: track!(model.changed(SlaveModel::status()), model.get_target_status(&SlaveStatusClass::MotionY) > JOYSTICK_DISPLAY_THRESHOLD),
+                                                        set_active: track!(model.changed(SlaveModel::status()), model.get_target_status(&SlaveStatusClass::MotionY) > model.joystick_display_threshold()),
                                                     },
                                                     attach(0, 2, 1, 1) = &ToggleButton {
                                                         set_icon_name: "go-previous-symbolic",
                                                         set_can_focus: false,
                                                         set_can_target: false,
-                                                        set_active: track!(model.changed(SlaveModel::status()), model.get_target_status(&SlaveStatusClass::MotionX) < -JOYSTICK_DISPLAY_THRESHOLD),
+                                                        set_active: track!(model.changed(SlaveModel::status()), model.get_target_status(&SlaveStatusClass::MotionX) < -model.joystick_display_threshold()),
                                                     },
                                                     attach(2, 2, 1, 1) = &ToggleButton {
                                                         set_icon_name: "go-next-symbolic",
                                                         set_can_focus: false,
                                                         set_can_target: false,
-                                                        set_active: track!(model.changed(SlaveModel::status()), model.get_target_status(&SlaveStatusClass::MotionX) > JOYSTICK_DISPLAY_THRESHOLD),
+                                                        set_active: track!(model.changed(SlaveModel::status()), model.get_target_status(&SlaveStatusClass::MotionX) > model.joystick_display_threshold()),
                                                     },
                                                     attach(1, 3, 1, 1) = &ToggleButton {
                                                         set_icon_name: "go-down-symbolic",
                                                         set_can_focus: false,
                                                         set_can_target: false,
-                                                        set_active: track!(model.changed(SlaveModel::status()), model.get_target_status(&SlaveStatusClass::MotionY) < -JOYSTICK_DISPLAY_THRESHOLD),
+                                                        set_active: track!(model.changed(SlaveModel::status()), model.get_target_status(&SlaveStatusClass::MotionY) < -model.joystick_display_threshold()),
                                                     },
                                                 },
                                             },
@@ -526,7 +1429,78 @@ impl MicroWidgets<SlaveModel> for SlaveWidgets {
                                             append = &CenterBox {
                                                 set_hexpand: true,
                                                 set_start_widget = Some(&Label) {
-                                                    set_markup: "<b>深度锁定</b>",
+                                                    set_markup: tr("<b>状态信息更新</b>"),
+                                                },
+                                                set_center_widget = Some(&Label) {
+                                                    add_css_class: "warning",
+                                                    add_css_class: "numeric",
+                                                    set_visible: track!(model.changed(SlaveModel::info_polling_paused()), model.info_polling_paused),
+                                                    set_label: watch!(&model.info_polling_text()),
+                                                },
+                                                set_end_widget = Some(&Switch) {
+                                                    set_tooltip_text: Some(tr("带宽紧张时可暂停请求状态信息以优先发送控制指令")),
+                                                    set_active: track!(model.changed(SlaveModel::info_polling_paused()), !model.info_polling_paused),
+                                                    connect_state_set(sender) => move |_switch, state| {
+                                                        send!(sender, SlaveMsg::SetInfoPollingPaused(!state));
+                                                        Inhibit(false)
+                                                    }
+                                                },
+                                            },
+                                            append = &CenterBox {
+                                                set_hexpand: true,
+                                                set_start_widget = Some(&Label) {
+                                                    set_markup: tr("<b>潜水时间</b>"),
+                                                },
+                                                set_end_widget = Some(&Label) {
+                                                    add_css_class: "numeric",
+                                                    set_tooltip_text: Some(tr("连接下位机以来经过的时间")),
+                                                    set_label: track!(model.changed(SlaveModel::dive_secs()), &model.dive_time_text()),
+                                                },
+                                            },
+                                            append = &CenterBox {
+                                                set_hexpand: true,
+                                                set_start_widget = Some(&Label) {
+                                                    set_markup: tr("<b>控制延迟</b>"),
+                                                },
+                                                set_end_widget = Some(&Label) {
+                                                    add_css_class: "numeric",
+                                                    set_tooltip_text: Some(tr("最近一次发送控制指令的往返耗时")),
+                                                    set_label: track!(model.changed(SlaveModel::control_latency()), &model.control_latency_text()),
+                                                },
+                                            },
+                                            append = &CenterBox {
+                                                set_hexpand: true,
+                                                set_start_widget = Some(&Label) {
+                                                    set_markup: tr("<b>视频码率</b>"),
+                                                },
+                                                set_end_widget = Some(&Label) {
+                                                    add_css_class: "numeric",
+                                                    set_tooltip_text: Some(tr("最近一秒接收视频流的码率，RTP 视频流同时显示根据包序号估计的丢包率")),
+                                                    set_label: track!(model.changed(SlaveModel::stream_statistics()), &model.stream_statistics_text()),
+                                                },
+                                            },
+                                            append = &CenterBox {
+                                                set_hexpand: true,
+                                                set_start_widget = Some(&Label) {
+                                                    set_markup: tr("<b>视频格式</b>"),
+                                                },
+                                                set_end_widget = Some(&Label) {
+                                                    set_tooltip_text: Some(tr("显示的视频画面的分辨率、宽高比与帧率，分辨率过低时以警告颜色显示")),
+                                                    set_css_classes: track!(model.changed(SlaveModel::video_format()), &if model.video_format.map_or(false, |format| format.is_low_resolution()) { vec!["numeric", "warning"] } else { vec!["numeric"] }),
+                                                    set_label: track!(model.changed(SlaveModel::video_format()), &model.video_format_text()),
+                                                },
+                                            },
+                                            append = &CenterBox {
+                                                set_hexpand: true,
+                                                set_start_widget = Some(&Label) {
+                                                    set_markup: tr("<b>深度锁定</b>"),
+                                                },
+                                                set_center_widget = Some(&Label) {
+                                                    add_css_class: "dim-label",
+                                                    add_css_class: "numeric",
+                                                    set_tooltip_text: Some(tr("锁定的设定值")),
+                                                    set_visible: track!(model.changed(SlaveModel::lock_setpoints()), model.lock_setpoints.contains_key(&SlaveStatusClass::DepthLocked)),
+                                                    set_label: track!(model.changed(SlaveModel::lock_setpoints()), &model.lock_setpoint_text(&SlaveStatusClass::DepthLocked)),
                                                 },
                                                 set_end_widget = Some(&Switch) {
                                                     set_active: track!(model.changed(SlaveModel::status()), model.get_target_status(&SlaveStatusClass::DepthLocked) != 0),
@@ -539,7 +1513,14 @@ impl MicroWidgets<SlaveModel> for SlaveWidgets {
                                             append = &CenterBox {
                                                 set_hexpand: true,
                                                 set_start_widget = Some(&Label) {
-                                                    set_markup: "<b>方向锁定</b>",
+                                                    set_markup: tr("<b>方向锁定</b>"),
+                                                },
+                                                set_center_widget = Some(&Label) {
+                                                    add_css_class: "dim-label",
+                                                    add_css_class: "numeric",
+                                                    set_tooltip_text: Some(tr("锁定的设定值")),
+                                                    set_visible: track!(model.changed(SlaveModel::lock_setpoints()), model.lock_setpoints.contains_key(&SlaveStatusClass::DirectionLocked)),
+                                                    set_label: track!(model.changed(SlaveModel::lock_setpoints()), &model.lock_setpoint_text(&SlaveStatusClass::DirectionLocked)),
                                                 },
                                                 set_end_widget = Some(&Switch) {
                                                     set_active: track!(model.changed(SlaveModel::status()), model.get_target_status(&SlaveStatusClass::DirectionLocked) != 0),
@@ -549,6 +1530,43 @@ impl MicroWidgets<SlaveModel> for SlaveWidgets {
                                                     },
                                                 },
                                             },
+                                            append = &CenterBox {
+                                                set_hexpand: true,
+                                                set_start_widget = Some(&Label) {
+                                                    set_markup: tr("<b>配平</b>"),
+                                                },
+                                                set_center_widget = Some(&Label) {
+                                                    add_css_class: "numeric",
+                                                    set_margin_start: 5,
+                                                    set_margin_end: 5,
+                                                    set_tooltip_text: Some(tr("叠加到运动输出上的偏移量，用于抵消浮力不平衡等造成的漂移")),
+                                                    set_label: track!(model.changed(SlaveModel::config()), &model.config.model().get_motion_trim().to_string()),
+                                                },
+                                                set_end_widget = Some(&GtkBox) {
+                                                    set_spacing: 5,
+                                                    append = &GtkButton {
+                                                        set_icon_name: "object-select-symbolic",
+                                                        set_css_classes: &["circular", "flat"],
+                                                        set_tooltip_text: Some(tr("将当前运动输出设为配平")),
+                                                        connect_clicked(sender) => move |_button| {
+                                                            send!(sender, SlaveMsg::SetTrim);
+                                                        },
+                                                    },
+                                                    append = &GtkButton {
+                                                        set_icon_name: "edit-clear-symbolic",
+                                                        set_css_classes: &["circular", "flat"],
+                                                        set_tooltip_text: Some(tr("清除配平")),
+                                                        set_sensitive: track!(model.changed(SlaveModel::config()), !model.config.model().get_motion_trim().is_zero()),
+                                                        connect_clicked(sender) => move |_button| {
+                                                            send!(sender, SlaveMsg::ResetTrim);
+                                                        },
+                                                    },
+                                                },
+                                            },
+                                            append = &Revealer {
+                                                set_reveal_child: track!(model.changed(SlaveModel::config()), !model.config.model().get_custom_commands().is_empty()),
+                                                set_child: track!(model.changed(SlaveModel::config()) || model.changed(SlaveModel::connected()), Some(&custom_commands_box(model.config.model().get_custom_commands(), model.connected == Some(true), sender.clone()))),
+                                            },
                                         },
                                     },
                                 },
@@ -570,37 +1588,144 @@ impl std::fmt::Debug for SlaveWidgets {
     }
 }
 
+// 可通过键盘快捷键对获得焦点的机位执行的操作
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SlaveShortcut {
+    ToggleConnect,
+    TogglePolling,
+    ToggleRecord,
+    TakeScreenshot,
+}
+
 pub enum SlaveMsg {
     ConfigUpdated,
+    Shutdown(Promise<()>),
     ToggleRecord,
     ToggleConnect,
+    Connect,
+    Disconnect,
+    RequestToggleConnect, // 正在录制或已锁定深度、方向时先确认再断开连接
     TogglePolling,
     PollingChanged(bool),
     RecordingChanged(bool),
     TakeScreenshot,
+    RequestScreenshot,
+    RefreshFrame,
+    ToggleSecondaryPolling,
+    SecondaryPollingChanged(bool),
+    CaptureReport,
+    DiveTimerTick,
+    IdleTimeoutReached(u64), // 没有控制输入的秒数
+    SaveAnnotatedScreenshot(PathBuf, Pixbuf, Option<Pixbuf>), // 保存路径、标注后的画面与需要一并保存的原始画面
+    ToggleTimelapse,
+    ToggleControlReplay,
+    StartControlReplay(PathBuf),
+    ControlReplayFinished,
+    TimelapseTick,
+    CheckDiskSpace,
+    TimelapseFrameSaved,
     AddInputSource(InputSource),
     RemoveInputSource(InputSource),
     SetSlaveStatus(SlaveStatusClass, i16),
     UpdateInputSources,
+    InputPowerLevelChanged(InputSource, PowerLevel),
     ToggleDisplayInfo,
     InputReceived(InputSourceEvent),
+    ChordTimeout(Button),
+    ChordTapReleased(Button),
+    EmergencyStop,
+    SetTrim,
+    ResetTrim,
+    RequestInputFocus,
+    SetInputFocused(bool),
     OpenFirmwareUpater,
     OpenParameterTuner,
+    OpenThrusterMixPreview,
+    OpenCustomCommandEditor,
+    OpenConnectionProfileManager,
+    ApplyConnectionProfile(ConnectionProfile),
+    NewSlaveFromConnectionProfile(ConnectionProfile),
+    ConnectionProfilesSaved,
+    ReloadConnectionProfiles,
+    SetCustomCommands(Vec<CustomCommand>),
+    RunCustomCommand(usize),
+    SetThrusterMix(ThrusterMix),
     DestroySlave,
     ErrorMessage(String),
     CommunicationError(String),
+    RequestRejected(String),
     ConnectionChanged(Option<async_std::sync::Arc<RpcClient>>),
     ShowToastMessage(String),
+    ClearToastHistory,
     CommunicationMessage(SlaveCommunicationMsg),
     InformationsReceived(HashMap<String, String>),
+    SetInfoPollingPaused(bool),
+    ControlLatencyMeasured(Duration),
+    StreamStatisticsUpdated(Option<StreamStatistics>),
+    VideoFormatChanged(Option<VideoFormat>),
     SetConfigPresented(bool),
+    SetDiagnosticsPresented(bool),
+    ExportPipelineGraph,
+    RefreshDiagnostics,
+    ToggleVideoWindow,
+    DockVideo,
 }
 
 pub enum SlaveCommunicationMsg {
     ConnectionLost(RpcError),
     Disconnect,
     ControlUpdated(ControlPacket),
-    Block(JoinHandle<Result<(), Box<dyn Error + Send>>>),
+    LockStatesReconciled(bool, bool), // 校正后的深度锁定与方向锁定状态，仅更新发送基准而不发送
+    RatesChanged(u16, u16), // 输入发送率与状态信息更新时间间隔（毫秒），连接期间修改时无需重新连接
+    IdleTimeoutChanged(Option<u16>), // 空闲超时时间（秒），为 None 时不检测
+    InfoPollingPaused(bool), // 暂停或恢复状态信息请求，恢复时立即请求
+    Block(BlockingTask),
+}
+
+// 需要独占与下位机连接的任务（固件更新、参数调试），在通讯主循环暂停状态信息请求与控制指令发送后才开始执行，结束后恢复
+pub type BlockingTask = Pin<Box<dyn std::future::Future<Output = Result<(), Box<dyn Error + Send>>> + Send>>;
+
+// 下位机返回的 JSON-RPC 错误仅表示该请求被拒绝，连接本身仍然可用，其余错误视为连接中断
+fn is_request_rejected(err: &RpcError) -> bool {
+    matches!(err, RpcError::Call(_))
+}
+
+pub async fn request_informations(rpc_client: &RpcClient) -> Result<HashMap<String, String>, RpcError> {
+    rpc_client.request::<HashMap<String, String>>(METHOD_GET_INFO, None).await
+}
+
+// 按照指定的方式发送相对 last 发生变化的控制指令，返回是否实际发送了请求
+async fn transmit_control_packet(
+    rpc_client: &RpcClient,
+    sending_mode: ControlSendingMode,
+    encoding: ControlValueEncoding,
+    control: &ControlPacket,
+    last: Option<&ControlPacket>,
+) -> Result<bool, RpcError> {
+    if last == Some(control) {
+        return Ok(false);
+    }
+    match sending_mode {
+        ControlSendingMode::Separate => {
+            for (method, params) in control.changed_commands(last, encoding) {
+                rpc_client.request::<()>(method, Some(params)).await?;
+            }
+        }
+        ControlSendingMode::Batch => {
+            let batch = control
+                .changed_commands(last, encoding)
+                .into_iter()
+                .map(|(method, params)| (method, Some(params)))
+                .collect();
+            rpc_client.batch_request::<()>(batch).await?;
+        }
+        ControlSendingMode::Combined => {
+            rpc_client
+                .request::<()>(METHOD_CONTROL, Some(control.encoded(encoding).to_rpc_params()))
+                .await?;
+        }
+    }
+    Ok(true)
 }
 
 async fn communication_main_loop(
@@ -609,7 +1734,13 @@ async fn communication_main_loop(
     communication_sender: async_std::channel::Sender<SlaveCommunicationMsg>,
     communication_receiver: async_std::channel::Receiver<SlaveCommunicationMsg>,
     slave_sender: Sender<SlaveMsg>,
-    status_info_udpate_interval: u64,
+    status_info_udpate_interval: u16,
+    keep_alive_interval: Duration,
+    control_sending_mode: ControlSendingMode,
+    control_value_encoding: ControlValueEncoding,
+    control_log: Option<PathBuf>,
+    idle_timeout: Option<u16>,
+    info_polling_paused: bool,
 ) -> Result<(), RpcError> {
     fn current_millis() -> u128 {
         SystemTime::now()
@@ -623,92 +1754,225 @@ async fn communication_main_loop(
     );
 
     let idle = async_std::sync::Arc::new(async_std::sync::Mutex::new(true));
+    let exclusive = async_std::sync::Arc::new(async_std::sync::Mutex::new(())); // 保证独占连接的任务依次执行
     let last_action_timestamp =
         async_std::sync::Arc::new(async_std::sync::Mutex::new(current_millis()));
     let control_packet =
         async_std::sync::Arc::new(async_std::sync::Mutex::new(None as Option<ControlPacket>));
+    let reconciled_locks =
+        async_std::sync::Arc::new(async_std::sync::Mutex::new(None as Option<(bool, bool)>));
+    let rates = async_std::sync::Arc::new(async_std::sync::Mutex::new((input_rate, status_info_udpate_interval)));
+    let idle_timeout = async_std::sync::Arc::new(async_std::sync::Mutex::new(idle_timeout));
+    let info_polling_paused = async_std::sync::Arc::new(async_std::sync::Mutex::new(info_polling_paused));
+    let (info_polling_resume_sender, info_polling_resume_receiver) = async_std::channel::bounded::<()>(1);
 
     let receive_task = task::spawn(
-        clone!(@strong communication_sender, @strong idle, @strong slave_sender, @strong rpc_client => async move {
+        clone!(@strong communication_sender, @strong idle, @strong slave_sender, @strong rpc_client, @strong rates, @strong info_polling_paused => async move {
+            let mut rejected = false;
             loop {
                 if communication_sender.is_closed() {
                     return;
                 }
-                if *idle.lock().await {
-                    match rpc_client.request::<HashMap<String, String>>(METHOD_GET_INFO, None).await {
-                        Ok(info) => send!(slave_sender, SlaveMsg::InformationsReceived(info)),
+                let paused = *info_polling_paused.lock().await;
+                // 请求期间持有锁，使暂停通讯时等待正在进行的请求完成
+                let idle_guard = idle.lock().await;
+                if *idle_guard && !paused {
+                    match request_informations(&rpc_client).await {
+                        Ok(info) => {
+                            rejected = false;
+                            send!(slave_sender, SlaveMsg::InformationsReceived(info));
+                        },
+                        Err(error) if is_request_rejected(&error) => {
+                            if !rejected {
+                                send!(slave_sender, SlaveMsg::RequestRejected(error.to_string()));
+                            }
+                            rejected = true;
+                        },
                         Err(error) => {
+                            drop(idle_guard);
                             communication_sender.send(SlaveCommunicationMsg::ConnectionLost(error)).await.unwrap_or_default();
                             break;
                         },
                     }
                 }
-                task::sleep(Duration::from_millis(status_info_udpate_interval)).await;
+                drop(idle_guard);
+                let (_, status_info_udpate_interval) = *rates.lock().await;
+                // 恢复状态信息请求时立即唤醒，无需等待本次间隔结束
+                async_std::future::timeout(
+                    Duration::from_millis(status_info_udpate_interval as u64),
+                    info_polling_resume_receiver.recv(),
+                )
+                .await
+                .ok();
             }
         }),
     ); // 定时请求数据
     let control_send_task = task::spawn(
-        clone!(@strong idle, @strong communication_sender, @strong rpc_client, @strong control_packet => async move {
+        clone!(@strong idle, @strong communication_sender, @strong slave_sender, @strong rpc_client, @strong control_packet, @strong reconciled_locks, @strong rates => async move {
+            let mut last_sent: Option<ControlPacket> = None;
+            let mut last_sent_time = Instant::now();
+            let mut rejected = false;
+            let mut control_logger = control_log.and_then(|path| match ControlLogger::create(&path) {
+                Ok(logger) => Some(logger),
+                Err(err) => {
+                    log::error!("无法创建控制指令记录文件 {}：{}", path.to_string_lossy(), err);
+                    None
+                }
+            });
             loop {
                 if communication_sender.is_closed() {
                     return;
                 }
-                if *idle.lock().await {
+                let (input_rate, _) = *rates.lock().await;
+                let mut interval = Duration::from_millis(1000 / input_rate.max(1) as u64);
+                let idle_guard = idle.lock().await;
+                if *idle_guard {
                     let mut control_mutex = control_packet.lock().await;
-                    if let Some(control) = control_mutex.as_ref() {
-                        for (method, params) in vec![(METHOD_MOVE, Some(control.motion.to_rpc_params())),
-                                                     (METHOD_SET_DEPTH_LOCKED, Some(control.depth_locked.to_rpc_params())),
-                                                     (METHOD_SET_DIRECTION_LOCKED, Some(control.direction_locked.to_rpc_params())),
-                                                     (METHOD_CATCH, Some(control.catch.to_rpc_params())),
-                                                     (METHOD_LIGHT, Some(control.light.to_rpc_params())),
-                        ].into_iter() {
-                            match rpc_client.request::<()>(method, params).await {
-                                Ok(_) => *control_mutex = None,
-                                Err(err) => {
-                                    communication_sender.send(SlaveCommunicationMsg::ConnectionLost(err)).await.unwrap_or_default();
+                    // 锁定状态已按下位机的报告校正，视作已发送，避免保活或增量发送再次发出被拒绝的锁定命令
+                    if let Some((depth_locked, direction_locked)) = reconciled_locks.lock().await.take() {
+                        for control in last_sent.iter_mut().chain(control_mutex.iter_mut()) {
+                            control.depth_locked = depth_locked;
+                            control.direction_locked = direction_locked;
+                        }
+                    }
+                    // 仅发送相对上次发送发生变化的子命令，超过保活间隔未发送时重新发送全部子命令
+                    let keep_alive = last_sent.is_some() && last_sent_time.elapsed() >= keep_alive_interval;
+                    let control = match control_mutex.take() {
+                        Some(control) => Some(control),
+                        None if keep_alive => last_sent.clone(),
+                        None => None,
+                    };
+                    if let Some(control) = control {
+                        let baseline = if keep_alive { None } else { last_sent.as_ref() };
+                        let start_time = Instant::now();
+                        match transmit_control_packet(&rpc_client, control_sending_mode, control_value_encoding, &control, baseline).await {
+                            Ok(transmitted) => {
+                                if transmitted {
+                                    send!(slave_sender, SlaveMsg::ControlLatencyMeasured(start_time.elapsed()));
+                                    last_sent_time = Instant::now();
+                                    if let Some(Err(err)) = control_logger.as_mut().map(|logger| logger.log(&control)) {
+                                        log::warn!("无法写入控制指令记录：{}", err);
+                                        control_logger = None;
+                                    }
+                                }
+                                last_sent = Some(control);
+                                rejected = false;
+                            }
+                            // 被拒绝的控制指令稍后重试，期间有新的控制输入时改为发送新的输入
+                            Err(err) if is_request_rejected(&err) => {
+                                if control_mutex.is_none() {
+                                    *control_mutex = Some(control);
+                                }
+                                if !rejected {
+                                    send!(slave_sender, SlaveMsg::RequestRejected(err.to_string()));
                                 }
+                                rejected = true;
+                                interval = CONTROL_RETRY_INTERVAL;
+                            }
+                            Err(err) => {
+                                drop(idle_guard);
+                                communication_sender.send(SlaveCommunicationMsg::ConnectionLost(err)).await.unwrap_or_default();
+                                break;
                             }
                         }
-
                     }
                 }
-                task::sleep(Duration::from_millis(1000 / input_rate as u64)).await;
+                drop(idle_guard);
+                task::sleep(interval).await;
             }
         }),
     );
 
+    let idle_watch_task = task::spawn(
+        clone!(@strong communication_sender, @strong slave_sender, @strong last_action_timestamp, @strong idle_timeout => async move {
+            let mut reported_timestamp = None; // 每段空闲期间只报告一次
+            loop {
+                if communication_sender.is_closed() {
+                    return;
+                }
+                task::sleep(IDLE_CHECK_INTERVAL).await;
+                let timestamp = *last_action_timestamp.lock().await;
+                if let Some(timeout) = *idle_timeout.lock().await {
+                    let idle_millis = current_millis().saturating_sub(timestamp);
+                    if idle_millis >= timeout as u128 * 1000 && reported_timestamp != Some(timestamp) {
+                        reported_timestamp = Some(timestamp);
+                        send!(slave_sender, SlaveMsg::IdleTimeoutReached((idle_millis / 1000) as u64));
+                    }
+                }
+            }
+        }),
+    ); // 检测长时间没有控制输入
+
     loop {
         match communication_receiver.recv().await {
-            Ok(msg) if *idle.lock().await => match msg {
-                SlaveCommunicationMsg::Disconnect => {
-                    control_send_task.cancel().await;
-                    receive_task.cancel().await;
-                    send!(slave_sender, SlaveMsg::ConnectionChanged(None));
-                    communication_receiver.close();
-                    break;
-                }
-                SlaveCommunicationMsg::ConnectionLost(err) => {
-                    control_send_task.cancel().await;
-                    receive_task.cancel().await;
-                    send!(slave_sender, SlaveMsg::CommunicationError(err.to_string()));
-                    communication_receiver.close();
-                    return Err(err);
+            // 独占连接的任务在暂停期间同样需要接收，依次排队执行，以免被丢弃而永不执行
+            Ok(SlaveCommunicationMsg::Block(blocker)) => {
+                task::spawn(clone!(@strong idle, @strong exclusive => async move {
+                    let _exclusive = exclusive.lock().await;
+                    // 等待正在进行的请求完成后再开始执行，避免与状态信息请求及控制指令争用连接
+                    *idle.lock().await = false;
+                    if let Err(err) = blocker.await {
+                        log::error!("模块异常退出：{}", err);
+                    }
+                    *idle.lock().await = true;
+                }));
+            }
+            // 暂停期间收到的断开连接请求等待独占连接的任务执行完毕后再处理，以免被丢弃
+            Ok(SlaveCommunicationMsg::Disconnect) => {
+                let _exclusive = exclusive.lock().await;
+                control_send_task.cancel().await;
+                receive_task.cancel().await;
+                idle_watch_task.cancel().await;
+                send!(slave_sender, SlaveMsg::ConnectionChanged(None));
+                communication_receiver.close();
+                break;
+            }
+            // 接收与控制任务报告连接中断后已退出，同样需等待独占连接的任务执行完毕后处理，不能丢弃
+            Ok(SlaveCommunicationMsg::ConnectionLost(err)) => {
+                let _exclusive = exclusive.lock().await;
+                control_send_task.cancel().await;
+                receive_task.cancel().await;
+                idle_watch_task.cancel().await;
+                send!(slave_sender, SlaveMsg::CommunicationError(err.to_string()));
+                communication_receiver.close();
+                return Err(err);
+            }
+            Ok(SlaveCommunicationMsg::RatesChanged(input_rate, status_info_udpate_interval)) => {
+                *rates.lock().await = (input_rate, status_info_udpate_interval);
+            }
+            Ok(SlaveCommunicationMsg::IdleTimeoutChanged(timeout)) => {
+                *idle_timeout.lock().await = timeout;
+                *last_action_timestamp.lock().await = current_millis(); // 从修改时起重新计时
+            }
+            Ok(SlaveCommunicationMsg::InfoPollingPaused(paused)) => {
+                *info_polling_paused.lock().await = paused;
+                if !paused {
+                    info_polling_resume_sender.try_send(()).unwrap_or_default();
                 }
+            }
+            Ok(msg) if *idle.lock().await => match msg {
                 SlaveCommunicationMsg::ControlUpdated(control) => {
                     *control_packet.lock().await = Some(control);
                     *last_action_timestamp.lock().await = current_millis();
                 }
-                SlaveCommunicationMsg::Block(blocker) => {
-                    *idle.lock().await = false;
-                    task::spawn(clone!(@strong idle => async move {
-                        if let Err(err) = blocker.await {
-                            eprintln!("模块异常退出：{}", err);
-                        }
-                        *idle.lock().await = true;
-                    }));
+                SlaveCommunicationMsg::LockStatesReconciled(depth_locked, direction_locked) => {
+                    *reconciled_locks.lock().await = Some((depth_locked, direction_locked));
                 }
+                SlaveCommunicationMsg::Block(_)
+                | SlaveCommunicationMsg::Disconnect
+                | SlaveCommunicationMsg::ConnectionLost(_)
+                | SlaveCommunicationMsg::RatesChanged(..)
+                | SlaveCommunicationMsg::IdleTimeoutChanged(_)
+                | SlaveCommunicationMsg::InfoPollingPaused(_) => unreachable!(),
             },
-            _ => (),
+            Ok(_) => (),
+            // 通道已被机位关闭，说明该连接已被放弃，直接退出而不再报告连接状态
+            Err(_) => {
+                control_send_task.cancel().await;
+                receive_task.cancel().await;
+                idle_watch_task.cancel().await;
+                break;
+            }
         }
     }
     Ok(())
@@ -728,71 +1992,156 @@ impl MicroModel for SlaveModel {
         match msg {
             SlaveMsg::ConfigUpdated => {
                 let config = self.get_mut_config().model().clone();
+                if config.get_motion_trim() != &self.last_motion_trim {
+                    self.last_motion_trim = config.get_motion_trim().clone();
+                    self.send_control_packet(); // 配平变化后立即生效，不必等待下一次输入
+                }
+                let rates = self.communication_rates();
+                if rates != self.last_communication_rates {
+                    self.last_communication_rates = rates;
+                    if let Some(communication_sender) = self.get_communication_msg_sender() {
+                        match communication_sender.try_send(SlaveCommunicationMsg::RatesChanged(rates.0, rates.1)) {
+                            Ok(_) => (),
+                            Err(err) => log::warn!("无法更新通讯频率：{}", err),
+                        }
+                    }
+                }
+                let idle_timeout = *config.get_idle_timeout();
+                if idle_timeout != self.last_idle_timeout {
+                    self.last_idle_timeout = idle_timeout;
+                    if let Some(communication_sender) = self.get_communication_msg_sender() {
+                        match communication_sender.try_send(SlaveCommunicationMsg::IdleTimeoutChanged(idle_timeout)) {
+                            Ok(_) => (),
+                            Err(err) => log::warn!("无法更新空闲超时时间：{}", err),
+                        }
+                    }
+                }
                 send!(self.video.sender(), SlaveVideoMsg::ConfigUpdated(config));
+                send!(parent_sender, AppMsg::SaveSlaveSessions);
+            }
+            SlaveMsg::RequestToggleConnect => {
+                let interruptions = self.disconnect_interruptions(false);
+                if *self.get_connected() != Some(true) || interruptions.is_empty() {
+                    send!(sender, SlaveMsg::ToggleConnect);
+                } else {
+                    confirm_message(
+                        tr("断开连接"),
+                        &format!("{}\n\n{}", tr("断开连接将中断以下操作，确定要断开连接吗？"), interruptions.iter().map(|x| format!("• {}", x)).collect::<Vec<_>>().join("\n")),
+                        app_window.upgrade().as_ref(),
+                        clone!(@strong sender => move || {
+                            send!(sender, SlaveMsg::Disconnect); // 确认期间连接可能已经中断，此时不应重新连接
+                        }),
+                    )
+                    .present();
+                }
             }
-            SlaveMsg::ToggleConnect => {
-                match self.get_connected() {
-                    Some(true) => {
-                        // 断开连接
-                        self.set_connected(None);
-                        self.config
-                            .send(SlaveConfigMsg::SetConnected(None))
-                            .unwrap();
-                        let sender = self.get_communication_msg_sender().clone().unwrap();
+            // 连接状态为 None 时正在连接或断开连接，此时忽略切换请求，保证每个机位同时只有一个通讯主循环
+            SlaveMsg::ToggleConnect => match self.get_connected() {
+                Some(true) => send!(sender, SlaveMsg::Disconnect),
+                Some(false) => send!(sender, SlaveMsg::Connect),
+                None => log::debug!(
+                    "[{}] 正在连接或断开连接，忽略切换连接的请求",
+                    self.config.model().get_slave_url()
+                ),
+            },
+            SlaveMsg::Disconnect => {
+                if *self.get_connected() != Some(true) {
+                    return;
+                }
+                // 断开连接
+                log::info!(
+                    "[{}] 正在断开连接",
+                    self.config.model().get_slave_url()
+                );
+                self.set_connected(None);
+                self.config
+                    .send(SlaveConfigMsg::SetConnected(None))
+                    .unwrap();
+                match self.get_communication_msg_sender().clone() {
+                    Some(communication_sender) => {
+                        let sender = sender.clone();
                         task::spawn(async move {
-                            sender
+                            // 通讯主循环已经因通讯错误退出时直接视作已断开连接
+                            if communication_sender
                                 .send(SlaveCommunicationMsg::Disconnect)
                                 .await
-                                .expect("Communication main loop should be running");
-                        });
-                    }
-                    Some(false) => {
-                        // 连接
-                        let url = self.config.model().get_slave_url().clone();
-                        if let ("http", url_str) = (url.scheme(), url.as_str()) {
-                            if let Ok(rpc_client) = RpcClientBuilder::default().build(url_str) {
-                                let (comm_sender, comm_receiver) =
-                                    async_std::channel::bounded::<SlaveCommunicationMsg>(128);
-                                self.set_communication_msg_sender(Some(comm_sender.clone()));
-                                let sender = sender.clone();
-                                let control_sending_rate =
-                                    *self.preferences.borrow().get_default_input_sending_rate();
-                                self.set_connected(None);
-                                self.config
-                                    .send(SlaveConfigMsg::SetConnected(None))
-                                    .unwrap();
-                                let status_info_update_interval = *self
-                                    .preferences
-                                    .borrow()
-                                    .get_default_status_info_update_interval();
-                                async_std::task::spawn(async move {
-                                    communication_main_loop(
-                                        control_sending_rate,
-                                        Arc::new(rpc_client),
-                                        comm_sender,
-                                        comm_receiver,
-                                        sender.clone(),
-                                        status_info_update_interval as u64,
-                                    )
-                                    .await
-                                    .unwrap_or_default();
-                                });
-                            } else {
-                                error_message(
-                                    "错误",
-                                    "无法创建 RPC 客户端。",
-                                    app_window.upgrade().as_ref(),
-                                );
+                                .is_err()
+                            {
+                                send!(sender, SlaveMsg::ConnectionChanged(None));
                             }
-                        } else {
-                            error_message(
-                                "错误",
-                                "连接 URL 有误，请检查并修改后重试 。",
-                                app_window.upgrade().as_ref(),
-                            );
-                        }
+                        });
                     }
-                    None => (),
+                    None => send!(sender, SlaveMsg::ConnectionChanged(None)),
+                }
+            }
+            SlaveMsg::Connect => {
+                if *self.get_connected() != Some(false) {
+                    return;
+                }
+                // 连接
+                let url = self.config.model().get_slave_url().clone();
+                log::info!("[{}] 正在连接", url);
+                if let Err(msg) = check_slave_url_scheme(&url) {
+                    error_message(tr("错误"), &msg, app_window.upgrade().as_ref());
+                } else if let Ok(rpc_client) = RpcClientBuilder::default().build(url.as_str()) {
+                    self.close_communication();
+                    let (comm_sender, comm_receiver) =
+                        async_std::channel::bounded::<SlaveCommunicationMsg>(128);
+                    self.set_communication_msg_sender(Some(comm_sender.clone()));
+                    let sender = sender.clone();
+                    let (control_sending_rate, status_info_update_interval) =
+                        self.communication_rates();
+                    self.last_communication_rates = (control_sending_rate, status_info_update_interval);
+                    let idle_timeout = *self.config.model().get_idle_timeout();
+                    self.last_idle_timeout = idle_timeout;
+                    let info_polling_paused = self.info_polling_paused;
+                    self.set_connected(None);
+                    self.config
+                        .send(SlaveConfigMsg::SetConnected(None))
+                        .unwrap();
+                    let keep_alive_interval = Duration::from_millis(
+                        *self.preferences.borrow().get_control_keep_alive_interval()
+                            as u64,
+                    );
+                    let control_sending_mode =
+                        *self.config.model().get_control_sending_mode();
+                    let control_value_encoding =
+                        *self.config.model().get_control_value_encoding();
+                    let control_log = self
+                        .preferences
+                        .borrow()
+                        .control_log_enabled
+                        .then(|| {
+                            get_control_log_path().join(format!(
+                                "{}_{}.jsonl",
+                                timestamp_file_name(),
+                                sanitize_file_name(url.host_str().unwrap_or_default())
+                            ))
+                        });
+                    async_std::task::spawn(async move {
+                        communication_main_loop(
+                            control_sending_rate,
+                            Arc::new(rpc_client),
+                            comm_sender,
+                            comm_receiver,
+                            sender.clone(),
+                            status_info_update_interval,
+                            keep_alive_interval,
+                            control_sending_mode,
+                            control_value_encoding,
+                            control_log,
+                            idle_timeout,
+                            info_polling_paused,
+                        )
+                        .await
+                        .unwrap_or_default();
+                    });
+                } else {
+                    error_message(
+                        tr("错误"),
+                        tr("无法创建 RPC 客户端。"),
+                        app_window.upgrade().as_ref(),
+                    );
                 }
             }
             SlaveMsg::TogglePolling => match self.get_polling() {
@@ -809,35 +2158,55 @@ impl MicroModel for SlaveModel {
                 None => (),
             },
             SlaveMsg::AddInputSource(source) => {
+                log::debug!("添加输入设备：{:?}", source);
+                if source == InputSource::Virtual {
+                    send!(parent_sender, AppMsg::VirtualJoystickAssigned(self as *const Self));
+                }
                 self.get_mut_input_sources().insert(source);
             }
             SlaveMsg::RemoveInputSource(source) => {
+                log::debug!("移除输入设备：{:?}", source);
                 self.get_mut_input_sources().remove(&source);
             }
             SlaveMsg::UpdateInputSources => {
                 let _unuse = self.get_mut_input_system();
             }
+            SlaveMsg::InputPowerLevelChanged(source, level) => {
+                if self.input_sources.contains(&source)
+                    && matches!(level, PowerLevel::Low | PowerLevel::Empty)
+                {
+                    let name = self
+                        .input_system
+                        .get_sources()
+                        .unwrap_or_default()
+                        .into_iter()
+                        .find(|(other, _)| *other == source)
+                        .map(|(_, name)| name)
+                        .unwrap_or_else(|| tr("未知设备").to_string());
+                    log::warn!(
+                        "[{}] 输入设备电量不足：{}",
+                        self.config.model().get_slave_url(),
+                        name
+                    );
+                    send!(
+                        sender,
+                        SlaveMsg::ShowToastMessage(format!(
+                            "{}{}",
+                            tr("手柄电量不足，请及时充电或更换电池："),
+                            name
+                        ))
+                    );
+                }
+                let _unuse = self.get_mut_input_system(); // 刷新输入设备列表中的电量显示
+            }
             SlaveMsg::ToggleDisplayInfo => {
                 self.set_slave_info_displayed(!*self.get_slave_info_displayed());
             }
             SlaveMsg::InputReceived(event) => {
+                log::trace!("收到输入事件：{:?}", event);
                 match event {
                     InputSourceEvent::ButtonChanged(button, pressed) => {
-                        match SlaveStatusClass::from_button(button) {
-                            Some(status_class @ SlaveStatusClass::RoboticArmOpen)
-                            | Some(status_class @ SlaveStatusClass::LightOpen) => {
-                                self.set_target_status(&status_class, if pressed { 1 } else { 0 });
-                            }
-                            Some(status_class) => {
-                                if pressed {
-                                    self.set_target_status(
-                                        &status_class,
-                                        !(self.get_target_status(&status_class) != 0) as i16,
-                                    );
-                                }
-                            }
-                            None => (),
-                        }
+                        self.handle_chord_button(button, pressed, &sender);
                     }
                     InputSourceEvent::AxisChanged(axis, value) => {
                         match SlaveStatusClass::from_axis(axis) {
@@ -862,32 +2231,102 @@ impl MicroModel for SlaveModel {
                         }
                     }
                 }
-                if let Some(sender) = self.get_communication_msg_sender() {
-                    let mut control_packet =
-                        ControlPacket::from_status_map(&self.get_status().lock().unwrap());
-                    if *self.config.model().get_swap_xy() {
-                        std::mem::swap(&mut control_packet.motion.x, &mut control_packet.motion.y);
-                    }
-                    match sender.try_send(SlaveCommunicationMsg::ControlUpdated(control_packet)) {
-                        Ok(_) => (),
-                        Err(err) => println!("无法发送控制输入：{}", err.to_string()),
+                self.update_lock_setpoints();
+                self.send_control_packet();
+            }
+            SlaveMsg::ChordTimeout(button) => {
+                if let Some(index) = self
+                    .pending_chord_buttons
+                    .iter()
+                    .position(|(pending, _)| *pending == button)
+                {
+                    let (_, released) = self.pending_chord_buttons.remove(index);
+                    self.apply_button(button, true);
+                    self.update_lock_setpoints();
+                    self.send_control_packet();
+                    if released {
+                        // 按下与松开若在同一次处理中应用，短按不会出现在任何控制包中，因此保持按下若干发送周期后再松开
+                        self.latched_chord_buttons.insert(button);
+                        let period = 1000 / u64::from(self.communication_rates().0.max(1));
+                        glib::timeout_add_local_once(
+                            Duration::from_millis(period * CHORD_TAP_LATCH_PERIODS),
+                            clone!(@strong sender => move || {
+                                sender.send(SlaveMsg::ChordTapReleased(button)).unwrap_or_default();
+                            }),
+                        );
                     }
                 }
             }
+            SlaveMsg::ChordTapReleased(button) => {
+                if self.latched_chord_buttons.remove(&button) {
+                    self.apply_button(button, false);
+                    self.update_lock_setpoints();
+                    self.send_control_packet();
+                }
+            }
+            SlaveMsg::EmergencyStop => {
+                self.clear_motion();
+                log::warn!("[{}] 紧急停止", self.config.model().get_slave_url());
+                send!(
+                    sender,
+                    SlaveMsg::ShowToastMessage(String::from(tr("已紧急停止，所有运动输出已清零。")))
+                );
+                // 配平同样会产生运动输出，紧急停止时一并清除
+                if !self.config.model().get_motion_trim().is_zero() {
+                    self.config.send(SlaveConfigMsg::SetMotionTrim(MotionPacket::default())).unwrap();
+                }
+                self.send_control_packet_with_trim(&MotionPacket::default());
+            }
+            SlaveMsg::SetTrim => {
+                // 按住摇杆保持机器人静止时记录当前的运动输出，已有配平时在其基础上累加
+                let trim = ControlPacket::from_status_map(&self.get_status().lock().unwrap())
+                    .trimmed(self.config.model().get_motion_trim())
+                    .motion;
+                log::info!(
+                    "[{}] 设置配平：{}",
+                    self.config.model().get_slave_url(),
+                    trim.to_string()
+                );
+                send!(
+                    sender,
+                    SlaveMsg::ShowToastMessage(format!("{}{}", tr("已设置配平："), trim.to_string()))
+                );
+                self.config.send(SlaveConfigMsg::SetMotionTrim(trim)).unwrap();
+            }
+            SlaveMsg::ResetTrim => {
+                if self.config.model().get_motion_trim().is_zero() {
+                    return;
+                }
+                log::info!("[{}] 清除配平", self.config.model().get_slave_url());
+                send!(sender, SlaveMsg::ShowToastMessage(String::from(tr("已清除配平。"))));
+                self.config.send(SlaveConfigMsg::SetMotionTrim(MotionPacket::default())).unwrap();
+            }
+            SlaveMsg::RequestInputFocus => {
+                send!(parent_sender, AppMsg::FocusSlave(self as *const Self));
+            }
+            SlaveMsg::SetInputFocused(focused) => {
+                if *self.get_input_focused() && !focused {
+                    // 失去焦点后不再收到输入，清零运动输出以免机器人保持最后的输入继续运动
+                    self.clear_motion();
+                    self.send_control_packet();
+                }
+                self.set_input_focused(focused);
+            }
             SlaveMsg::OpenFirmwareUpater => match self.get_rpc_client() {
                 Some(rpc_client) => {
                     let component = MicroComponent::new(
                         SlaveFirmwareUpdaterModel::new(Deref::deref(rpc_client).clone()),
                         sender.clone(),
                     );
+                    self.firmware_updater_sender = Some(component.sender());
                     let window = component.root_widget();
                     window.set_transient_for(app_window.upgrade().as_ref());
                     window.set_visible(true);
                 }
                 None => {
                     error_message(
-                        "错误",
-                        "请确保下位机处于连接状态。",
+                        tr("错误"),
+                        tr("请确保下位机处于连接状态。"),
                         app_window.upgrade().as_ref(),
                     );
                 }
@@ -914,66 +2353,278 @@ impl MicroModel for SlaveModel {
                         component.sender(),
                         SlaveParameterTunerMsg::StartDebug(Deref::deref(rpc_client).clone())
                     );
+                    self.parameter_tuner_sender = Some(component.sender());
                 }
                 None => {
                     error_message(
-                        "错误",
-                        "请确保下位机处于连接状态。",
+                        tr("错误"),
+                        tr("请确保下位机处于连接状态。"),
                         app_window.upgrade().as_ref(),
                     );
                 }
             },
-            SlaveMsg::DestroySlave => {
-                if let Some(polling) = self.get_polling() {
-                    if *polling {
-                        send!(self.video.sender(), SlaveVideoMsg::StopPipeline);
-                    }
+            SlaveMsg::OpenThrusterMixPreview => {
+                let config = self.config.model();
+                thruster_mix_window(
+                    config.get_thruster_mix().clone(),
+                    self.status.clone(),
+                    *config.get_swap_xy(),
+                    app_window.upgrade().as_ref(),
+                    sender.clone(),
+                );
+            }
+            SlaveMsg::SetThrusterMix(mix) => {
+                self.config.send(SlaveConfigMsg::SetThrusterMix(mix)).unwrap();
+            }
+            SlaveMsg::OpenCustomCommandEditor => {
+                custom_command_editor(
+                    self.config.model().get_custom_commands(),
+                    app_window.upgrade().as_ref(),
+                    sender.clone(),
+                );
+            }
+            SlaveMsg::OpenConnectionProfileManager => {
+                connection_profile_manager(
+                    self.config.model().to_connection_profile(String::new()),
+                    app_window.upgrade().as_ref(),
+                    sender.clone(),
+                );
+            }
+            SlaveMsg::ApplyConnectionProfile(profile) => {
+                let config = self.config.model();
+                if config.get_connected() != &Some(false) || config.get_polling() != &Some(false) {
+                    send!(sender, SlaveMsg::ShowToastMessage(String::from(tr("请先断开连接并停止拉流再应用连接档案。"))));
+                    return;
+                }
+                log::info!("[{}] 应用连接档案 {}", config.get_slave_url(), profile.name);
+                drop(config);
+                self.config.send(SlaveConfigMsg::ApplyConnectionProfile(profile)).unwrap();
+            }
+            SlaveMsg::NewSlaveFromConnectionProfile(profile) => {
+                send!(parent_sender, AppMsg::NewSlaveFromConnectionProfile(app_window.clone(), profile));
+            }
+            SlaveMsg::ConnectionProfilesSaved => {
+                send!(parent_sender, AppMsg::ConnectionProfilesChanged);
+            }
+            SlaveMsg::ReloadConnectionProfiles => {
+                self.config.send(SlaveConfigMsg::SetConnectionProfiles(ConnectionProfile::load_all())).unwrap();
+            }
+            SlaveMsg::SetCustomCommands(commands) => {
+                self.config.send(SlaveConfigMsg::SetCustomCommands(commands)).unwrap();
+            }
+            SlaveMsg::RunCustomCommand(index) => {
+                let command = match self.config.model().get_custom_commands().get(index) {
+                    Some(command) => command.clone(),
+                    None => return,
+                };
+                let (rpc_client, communication_sender) = match (self.get_rpc_client(), self.get_communication_msg_sender()) {
+                    (Some(rpc_client), Some(communication_sender)) => (rpc_client.clone(), communication_sender.clone()),
+                    _ => {
+                        error_message(
+                            tr("错误"),
+                            tr("请确保下位机处于连接状态。"),
+                            app_window.upgrade().as_ref(),
+                        );
+                        return;
+                    }
+                };
+                log::info!(
+                    "[{}] 执行自定义命令 {}：{} {}",
+                    self.config.model().get_slave_url(),
+                    command.label,
+                    command.method,
+                    command.params.as_ref().map(|params| params.to_string()).unwrap_or_default()
+                );
+                let sender = sender.clone();
+                // 作为独占连接的任务执行，避免与状态信息请求及控制指令交错，执行结果以通知的形式显示
+                let blocker = Box::pin(async move {
+                    let params = command.params.as_ref().map(|params| params.to_rpc_params_with(command.params_style));
+                    let message = match rpc_client.request::<serde_json::Value>(&command.method, params).await {
+                        Ok(serde_json::Value::Null) => format!("{}{}", command.label, tr(" 执行成功")),
+                        Ok(result) => format!("{}{}{}", command.label, tr(" 执行成功："), result),
+                        Err(err) => format!("{}{}{}", command.label, tr(" 执行失败："), err),
+                    };
+                    send!(sender, SlaveMsg::ShowToastMessage(message));
+                    Ok::<(), Box<dyn Error + Send>>(())
+                });
+                if let Err(err) = communication_sender.try_send(SlaveCommunicationMsg::Block(blocker)) {
+                    log::warn!("无法执行自定义命令：{}", err);
+                }
+            }
+            SlaveMsg::DestroySlave => {
+                self.dock_video();
+                if let Some(timer) = self.diagnostics_timer.take() {
+                    timer.remove();
+                }
+                if let Some(timer) = self.dive_timer.take() {
+                    timer.remove();
+                }
+                if let Some(timer) = self.timelapse_timer.take() {
+                    timer.remove();
                 }
-                if let Some(connected) = self.get_connected() {
-                    if *connected {
-                        send!(sender, SlaveMsg::ToggleConnect);
+                if let Some(timer) = self.disk_space_timer.take() {
+                    timer.remove();
+                }
+                if let Some(polling) = self.get_polling() {
+                    if *polling {
+                        send!(self.video.sender(), SlaveVideoMsg::StopPipeline);
                     }
                 }
+                if self.video.model().is_secondary_running() {
+                    send!(self.video.sender(), SlaveVideoMsg::StopSecondaryPipeline);
+                }
+                send!(sender, SlaveMsg::Disconnect);
                 send!(parent_sender, AppMsg::DestroySlave(self as *const Self));
             }
+            SlaveMsg::Shutdown(promise) => {
+                let mut futures = Vec::<Future<()>>::new();
+                if self.video.model().is_recording() {
+                    let record_promise = Promise::new();
+                    futures.push(record_promise.future());
+                    send!(
+                        self.video.sender(),
+                        SlaveVideoMsg::StopRecord(None, Some(record_promise))
+                    );
+                    self.set_recording(None);
+                }
+                if self.video.model().is_secondary_running() {
+                    send!(self.video.sender(), SlaveVideoMsg::StopSecondaryPipeline); // 等待副摄像头的录像写入完成后停止
+                }
+                if let (Some(true), Some(communication_sender)) = (
+                    self.get_connected(),
+                    self.get_communication_msg_sender().clone(),
+                ) {
+                    log::info!(
+                        "[{}] 正在断开连接",
+                        self.config.model().get_slave_url()
+                    );
+                    let disconnect_promise = Promise::new();
+                    futures.push(disconnect_promise.future());
+                    self.set_connected(None);
+                    self.config
+                        .send(SlaveConfigMsg::SetConnected(None))
+                        .unwrap();
+                    task::spawn(async move {
+                        communication_sender
+                            .send(SlaveCommunicationMsg::Disconnect)
+                            .await
+                            .unwrap_or_default();
+                        while !communication_sender.is_closed() {
+                            task::sleep(Duration::from_millis(10)).await;
+                        } // 通讯主循环退出时会关闭通道
+                        disconnect_promise.success(());
+                    });
+                }
+                Future::sequence(futures.into_iter()).for_each(move |_| promise.success(()));
+            }
             SlaveMsg::ErrorMessage(msg) => {
-                error_message("错误", &msg, app_window.upgrade().as_ref());
+                log::error!("[{}] {}", self.config.model().get_slave_url(), msg);
+                error_message(tr("错误"), &msg, app_window.upgrade().as_ref());
             }
             SlaveMsg::CommunicationError(msg) => {
+                self.rpc_errors += 1;
+                log::warn!(
+                    "[{}] 下位机通讯错误：{}",
+                    self.config.model().get_slave_url(),
+                    msg
+                );
                 send!(
                     sender,
-                    SlaveMsg::ShowToastMessage(format!("下位机通讯错误：{}", msg))
+                    SlaveMsg::ShowToastMessage(format!("{}{}", tr("下位机通讯错误："), msg))
                 );
                 send!(sender, SlaveMsg::ConnectionChanged(None));
+                if stops_recording_on_connection_lost(self.video.model().is_recording(), &self.preferences.borrow()) {
+                    log::warn!(
+                        "[{}] 下位机通讯中断，停止录制",
+                        self.config.model().get_slave_url()
+                    );
+                    send!(self.video.sender(), SlaveVideoMsg::StopRecord(None, None));
+                    self.set_recording(None);
+                    send!(
+                        sender,
+                        SlaveMsg::ShowToastMessage(String::from(tr("下位机通讯中断，录制已停止。")))
+                    );
+                }
+            }
+            SlaveMsg::RequestRejected(msg) => {
+                self.rpc_errors += 1;
+                log::warn!(
+                    "[{}] 下位机拒绝了请求：{}",
+                    self.config.model().get_slave_url(),
+                    msg
+                );
+                send!(
+                    sender,
+                    SlaveMsg::ShowToastMessage(format!("{}{}", tr("下位机拒绝了请求："), msg))
+                );
             }
             SlaveMsg::ConnectionChanged(rpc_client) => {
+                // 已放弃的连接在退出前报告的连接成功不再生效
+                if rpc_client.is_some() && self.get_communication_msg_sender().is_none() {
+                    return;
+                }
+                log::info!(
+                    "[{}] {}",
+                    self.config.model().get_slave_url(),
+                    if rpc_client.is_some() { "已连接" } else { "已断开连接" }
+                );
                 self.set_connected(Some(rpc_client.is_some()));
+                self.connected_time = rpc_client.as_ref().map(|_| Instant::now());
+                if let Some(timer) = self.dive_timer.take() {
+                    timer.remove();
+                }
+                self.set_dive_secs(rpc_client.as_ref().map(|_| 0));
+                if rpc_client.is_some() {
+                    self.dive_timer = Some(glib::timeout_add_local(
+                        DIVE_TIMER_INTERVAL,
+                        clone!(@strong sender => move || {
+                            Continue(sender.send(SlaveMsg::DiveTimerTick).is_ok())
+                        }),
+                    ));
+                }
                 self.config
                     .send(SlaveConfigMsg::SetConnected(Some(rpc_client.is_some())))
                     .unwrap();
                 if rpc_client.is_none() {
-                    self.set_communication_msg_sender(None);
+                    self.close_communication();
+                    self.set_control_latency(None);
                 }
+                self.notify_child_windows(rpc_client.as_deref());
                 self.set_rpc_client(rpc_client);
             }
             SlaveMsg::ShowToastMessage(msg) => {
+                let time = DateTime::now_local()
+                    .and_then(|time| time.format("%T"))
+                    .map(|time| time.to_string())
+                    .unwrap_or_default();
+                let toast_history = self.get_mut_toast_history();
+                toast_history.push_back((time, msg.clone()));
+                while toast_history.len() > TOAST_HISTORY_LIMIT {
+                    toast_history.pop_front();
+                }
                 self.get_mut_toast_messages().borrow_mut().push_back(msg);
             }
             SlaveMsg::ToggleRecord => {
-                let video = &self.video;
-                if video.model().get_record_handle().is_none() {
-                    let mut pathbuf = self.preferences.borrow().get_video_save_path().clone();
-                    pathbuf.push(format!(
-                        "{}.mkv",
-                        DateTime::now_local()
-                            .unwrap()
-                            .format_iso8601()
-                            .unwrap()
-                            .replace(":", "-")
-                    ));
-                    send!(video.sender(), SlaveVideoMsg::StartRecord(pathbuf));
+                if !self.video.model().is_recording() {
+                    if let Some(space) = self.preferences.borrow().video_save_space_shortage(false) {
+                        send!(
+                            sender,
+                            SlaveMsg::ErrorMessage(format!(
+                                "{}{} MB",
+                                tr("视频保存目录所在磁盘的可用空间不足，无法开始录制。剩余空间："),
+                                space
+                            ))
+                        );
+                        return;
+                    }
+                    let directory = match self.save_directory(&self.preferences.borrow().video_save_directory(), &sender) {
+                        Some(directory) => directory,
+                        None => return,
+                    };
+                    let pathbuf = self.file_name_path(&directory, "mkv", &sender);
+                    send!(self.video.sender(), SlaveVideoMsg::StartRecord(pathbuf));
                 } else {
-                    send!(video.sender(), SlaveVideoMsg::StopRecord(None));
+                    send!(self.video.sender(), SlaveVideoMsg::StopRecord(None, None));
                 }
                 self.set_recording(None);
             }
@@ -994,48 +2645,399 @@ impl MicroModel for SlaveModel {
                     self.set_sync_recording(false);
                 }
                 self.set_recording(Some(recording));
+                // 录制期间定时检查磁盘可用空间，防止磁盘写满导致录像损坏
+                match (recording, self.disk_space_timer.take()) {
+                    (true, None) => {
+                        self.disk_space_timer = Some(glib::timeout_add_local(
+                            DISK_SPACE_CHECK_INTERVAL,
+                            clone!(@strong sender => move || {
+                                Continue(sender.send(SlaveMsg::CheckDiskSpace).is_ok())
+                            }),
+                        ));
+                    }
+                    (true, timer) => self.disk_space_timer = timer,
+                    (false, Some(timer)) => timer.remove(),
+                    (false, None) => (),
+                }
+            }
+            SlaveMsg::CheckDiskSpace => {
+                if *self.get_recording() == Some(true) {
+                    let shortage = self.preferences.borrow().video_save_space_shortage(true);
+                    if let Some(space) = shortage {
+                        log::warn!(
+                            "[{}] 磁盘可用空间过低（剩余 {} MB），自动停止录制",
+                            self.config.model().get_slave_url(),
+                            space
+                        );
+                        send!(self.video.sender(), SlaveVideoMsg::StopRecord(None, None));
+                        self.set_recording(None);
+                        send!(
+                            sender,
+                            SlaveMsg::ShowToastMessage(format!(
+                                "{}{} MB",
+                                tr("磁盘可用空间过低，已自动停止录制。剩余空间："),
+                                space
+                            ))
+                        );
+                    }
+                }
+            }
+            SlaveMsg::RefreshFrame => send!(self.video.sender(), SlaveVideoMsg::RequestFrame),
+            SlaveMsg::ToggleSecondaryPolling => match self.secondary_polling {
+                Some(true) => {
+                    send!(self.video.sender(), SlaveVideoMsg::StopSecondaryPipeline);
+                    self.set_secondary_polling(None);
+                }
+                Some(false) => {
+                    send!(self.video.sender(), SlaveVideoMsg::StartSecondaryPipeline);
+                    self.set_secondary_polling(None);
+                }
+                None => (),
+            },
+            SlaveMsg::SecondaryPollingChanged(polling) => self.set_secondary_polling(Some(polling)),
+            SlaveMsg::CaptureReport => {
+                let pixbuf = match self.video.model().current_pixbuf() {
+                    Some(pixbuf) => pixbuf,
+                    None => {
+                        send!(sender, SlaveMsg::ShowToastMessage(String::from(tr("当前没有画面，无法生成采集报告。"))));
+                        return;
+                    }
+                };
+                let directory = match self.save_directory(&self.preferences.borrow().capture_report_save_directory(), &sender) {
+                    Some(directory) => directory,
+                    None => return,
+                };
+                let (image_format, report_format) = {
+                    let preferences = self.preferences.borrow();
+                    (*preferences.get_image_save_format(), *preferences.get_capture_report_format())
+                };
+                let pathbuf = self.file_name_path(&directory, image_format.extension(), &sender);
+                let config = self.config.model().clone();
+                let report = CaptureReport::new(
+                    config.get_slave_url().to_string(),
+                    config.get_video_url().to_string(),
+                    self.video_format.map(|format| format.to_string()),
+                    *self.video.model().frame_timestamp.lock().unwrap(),
+                    self.telemetry(),
+                );
+                match report.save(&pixbuf, &pathbuf, report_format) {
+                    Ok(report_path) => send!(sender, SlaveMsg::ShowToastMessage(format!("{}{}", tr("采集报告保存成功："), report_path.to_string_lossy()))),
+                    Err(err) => {
+                        log::error!("[{}] 采集报告保存失败：{}", config.get_slave_url(), err);
+                        send!(sender, SlaveMsg::ShowToastMessage(format!("{}{}", tr("采集报告保存失败："), err)));
+                    }
+                }
             }
             SlaveMsg::TakeScreenshot => {
-                let mut pathbuf = self.preferences.borrow().get_image_save_path().clone();
+                let directory = match self.save_directory(&self.preferences.borrow().image_save_directory(), &sender) {
+                    Some(directory) => directory,
+                    None => return,
+                };
                 let format = self.preferences.borrow().get_image_save_format().clone();
-                pathbuf.push(format!(
-                    "{}.{}",
-                    DateTime::now_local()
-                        .unwrap()
-                        .format_iso8601()
-                        .unwrap()
-                        .replace(":", "-"),
-                    format.extension()
-                ));
-                send!(self.video.sender(), SlaveVideoMsg::SaveScreenshot(pathbuf));
+                let pathbuf = self.file_name_path(&directory, format.extension(), &sender);
+                send!(self.video.sender(), SlaveVideoMsg::SaveScreenshot(pathbuf, None));
+            }
+            SlaveMsg::RequestScreenshot => {
+                if !*self.preferences.borrow().get_annotate_screenshots() {
+                    send!(sender, SlaveMsg::TakeScreenshot);
+                    return;
+                }
+                let pixbuf = match self.video.model().current_pixbuf() {
+                    Some(pixbuf) => pixbuf,
+                    None => {
+                        send!(sender, SlaveMsg::ShowToastMessage(String::from(tr("当前没有画面，无法截图。"))));
+                        return;
+                    }
+                };
+                let directory = match self.save_directory(&self.preferences.borrow().image_save_directory(), &sender) {
+                    Some(directory) => directory,
+                    None => return,
+                };
+                let format = self.preferences.borrow().get_image_save_format().clone();
+                let pathbuf = self.file_name_path(&directory, format.extension(), &sender);
+                annotate_screenshot(
+                    pixbuf.clone(),
+                    app_window.upgrade().as_ref(),
+                    clone!(@strong sender => move |annotated| {
+                        if let Some((annotated, keep_raw)) = annotated {
+                            send!(sender, SlaveMsg::SaveAnnotatedScreenshot(pathbuf.clone(), annotated, keep_raw.then(|| pixbuf.clone())));
+                        }
+                    }),
+                );
+            }
+            SlaveMsg::SaveAnnotatedScreenshot(pathbuf, annotated, raw) => {
+                // 同时保存原始画面时，标注后的画面以 _annotated 后缀区分
+                let annotated_pathbuf = match &raw {
+                    Some(_) => pathbuf.with_file_name(format!(
+                        "{}_annotated.{}",
+                        pathbuf.file_stem().unwrap_or_default().to_string_lossy(),
+                        pathbuf.extension().unwrap_or_default().to_string_lossy()
+                    )),
+                    None => pathbuf.clone(),
+                };
+                if let Some(raw) = raw {
+                    send!(self.video.sender(), SlaveVideoMsg::SaveScreenshot(pathbuf, Some(raw)));
+                }
+                send!(self.video.sender(), SlaveVideoMsg::SaveScreenshot(annotated_pathbuf, Some(annotated)));
+            }
+            SlaveMsg::ToggleControlReplay => match self.get_mut_control_replay().take() {
+                Some(replay) => {
+                    task::spawn(replay.cancel());
+                    log::info!("[{}] 已停止回放控制指令", self.config.model().get_slave_url());
+                    self.send_control_packet(); // 恢复为输入设备当前的控制输入
+                }
+                None => {
+                    let filter = FileFilter::new();
+                    filter.add_suffix("jsonl");
+                    filter.set_name(Some(tr("控制指令记录")));
+                    if let Some(window) = app_window.upgrade() {
+                        std::mem::forget(select_path(FileChooserAction::Open, &[filter], &window, clone!(@strong sender => move |path| {
+                            if let Some(path) = path {
+                                send!(sender, SlaveMsg::StartControlReplay(path));
+                            }
+                        })));
+                    }
+                }
+            },
+            SlaveMsg::StartControlReplay(path) => {
+                let communication_sender = match self.get_communication_msg_sender() {
+                    Some(communication_sender) if self.control_replay.is_none() => communication_sender.clone(),
+                    _ => return,
+                };
+                match read_control_log(&path) {
+                    Ok(entries) => {
+                        log::info!(
+                            "[{}] 开始回放控制指令，共 {} 个数据包：{}",
+                            self.config.model().get_slave_url(),
+                            entries.len(),
+                            path.to_string_lossy()
+                        );
+                        self.set_control_replay(Some(task::spawn(replay_control_log(
+                            entries,
+                            communication_sender,
+                            sender.clone(),
+                        ))));
+                    }
+                    Err(err) => send!(
+                        sender,
+                        SlaveMsg::ErrorMessage(format!("{}{}", tr("无法读取控制指令记录："), err))
+                    ),
+                }
+            }
+            SlaveMsg::ControlReplayFinished => {
+                if self.get_mut_control_replay().take().is_some() {
+                    log::info!("[{}] 控制指令回放结束", self.config.model().get_slave_url());
+                    send!(sender, SlaveMsg::ShowToastMessage(String::from(tr("控制指令回放结束。"))));
+                    self.send_control_packet();
+                }
+            }
+            SlaveMsg::ToggleTimelapse => match self.timelapse_directory.clone() {
+                Some(directory) => {
+                    if let Some(timer) = self.timelapse_timer.take() {
+                        timer.remove();
+                    }
+                    log::info!(
+                        "[{}] 定时截图已停止，共截图 {} 张：{}",
+                        self.config.model().get_slave_url(),
+                        self.timelapse_frame_count,
+                        directory.to_string_lossy()
+                    );
+                    self.set_timelapse_directory(None);
+                    self.set_timelapse_paused(false);
+                }
+                None => {
+                    let scheme = *self.preferences.borrow().get_save_directory_scheme();
+                    let mut directory = scheme.directory(&self.preferences.borrow().image_save_directory());
+                    directory.push(
+                        DateTime::now_local()
+                            .unwrap()
+                            .format_iso8601()
+                            .unwrap()
+                            .replace(":", "-"),
+                    );
+                    if let Err(msg) = create_directory(&directory) {
+                        send!(sender, SlaveMsg::ErrorMessage(msg));
+                        return;
+                    }
+                    let interval = *self.config.model().get_timelapse_interval();
+                    log::info!(
+                        "[{}] 开始定时截图，间隔 {} 秒：{}",
+                        self.config.model().get_slave_url(),
+                        interval,
+                        directory.to_string_lossy()
+                    );
+                    self.set_timelapse_directory(Some(directory));
+                    self.set_timelapse_frame_count(0);
+                    self.set_timelapse_paused(false);
+                    self.set_timelapse_last_frame(0);
+                    self.timelapse_timer = Some(glib::timeout_add_local(
+                        Duration::from_secs(interval as u64),
+                        clone!(@strong sender => move || {
+                            Continue(sender.send(SlaveMsg::TimelapseTick).is_ok())
+                        }),
+                    ));
+                    send!(sender, SlaveMsg::TimelapseTick);
+                }
+            },
+            SlaveMsg::TimelapseTick => {
+                if let Some(directory) = self.timelapse_directory.clone() {
+                    let frame_count = *self.video.model().get_frame_count();
+                    // 视频流中断或停止拉流时不再有新画面，暂停截图直至画面恢复
+                    let stalled = !self.video.model().has_frame()
+                        || frame_count == self.timelapse_last_frame;
+                    if stalled != self.timelapse_paused {
+                        log::info!(
+                            "[{}] {}",
+                            self.config.model().get_slave_url(),
+                            if stalled { "视频流无新画面，定时截图已暂停" } else { "视频流已恢复，定时截图继续" }
+                        );
+                        self.set_timelapse_paused(stalled);
+                    }
+                    if !stalled {
+                        self.set_timelapse_last_frame(frame_count);
+                        let mut pathbuf = directory;
+                        pathbuf.push(format!(
+                            "{}.{}",
+                            DateTime::now_local()
+                                .unwrap()
+                                .format_iso8601()
+                                .unwrap()
+                                .replace(":", "-"),
+                            self.preferences.borrow().get_image_save_format().extension()
+                        ));
+                        send!(self.video.sender(), SlaveVideoMsg::SaveTimelapseFrame(pathbuf));
+                    }
+                }
+            }
+            SlaveMsg::TimelapseFrameSaved => {
+                if self.timelapse_directory.is_some() {
+                    self.set_timelapse_frame_count(self.timelapse_frame_count + 1);
+                }
             }
             SlaveMsg::CommunicationMessage(msg) => {
                 if let Some(sender) = self.get_communication_msg_sender().as_ref() {
                     sender.try_send(msg).unwrap_or_default();
                 }
             }
+            SlaveMsg::ControlLatencyMeasured(latency) => {
+                self.control_packets_sent += 1;
+                self.set_control_latency(Some(latency));
+            }
+            SlaveMsg::StreamStatisticsUpdated(statistics) => self.set_stream_statistics(statistics),
+            SlaveMsg::VideoFormatChanged(format) => self.set_video_format(format),
+            SlaveMsg::SetInfoPollingPaused(paused) => {
+                if paused == self.info_polling_paused {
+                    return;
+                }
+                log::info!(
+                    "[{}] {}",
+                    self.config.model().get_slave_url(),
+                    if paused { "已暂停状态信息请求" } else { "已恢复状态信息请求" }
+                );
+                self.set_info_polling_paused(paused);
+                if let Some(communication_sender) = self.get_communication_msg_sender() {
+                    if let Err(err) = communication_sender.try_send(SlaveCommunicationMsg::InfoPollingPaused(paused)) {
+                        log::warn!("无法切换状态信息请求：{}", err);
+                    }
+                }
+            }
             SlaveMsg::InformationsReceived(info_map) => {
+                self.last_info_time = Some(Instant::now());
+                self.info_map = info_map.into_iter().collect();
+                if self.video.model().telemetry_subtitle.is_some() {
+                    send!(
+                        self.video.sender(),
+                        SlaveVideoMsg::TelemetryReceived(self.info_map.clone().into_iter().collect())
+                    );
+                }
+                let displayed_infos = self.displayed_infos();
                 let infos = self.get_mut_infos();
-                let mut sorted_infos = info_map.into_iter().collect::<Vec<_>>();
-                sorted_infos.sort();
                 infos.clear();
-                for (key, value) in sorted_infos.into_iter() {
+                for (key, value) in displayed_infos.into_iter() {
                     infos.push(SlaveInfoModel {
                         key,
                         value,
                         ..Default::default()
                     });
                 }
+                for status_class in [SlaveStatusClass::DepthLocked, SlaveStatusClass::DirectionLocked] {
+                    let (_, setpoint_key) = Self::lock_setpoint_keys(&status_class).unwrap();
+                    if let (true, Some(setpoint)) = (
+                        self.lock_setpoints.contains_key(&status_class),
+                        self.info_value(&[setpoint_key]),
+                    ) {
+                        if self.lock_setpoints[&status_class].as_ref() != Some(&setpoint) {
+                            self.get_mut_lock_setpoints().insert(status_class, Some(setpoint));
+                        }
+                    }
+                }
+                self.reconcile_lock_states(&sender);
             }
             SlaveMsg::SetConfigPresented(presented) => self.set_config_presented(presented),
+            SlaveMsg::ExportPipelineGraph => {
+                send!(self.video.sender(), SlaveVideoMsg::ExportPipelineGraph);
+            }
+            SlaveMsg::SetDiagnosticsPresented(presented) => {
+                if let Some(timer) = self.diagnostics_timer.take() {
+                    timer.remove();
+                }
+                if presented {
+                    self.set_diagnostics(self.collect_diagnostics());
+                    self.diagnostics_timer = Some(glib::timeout_add_local(
+                        DIAGNOSTICS_UPDATE_INTERVAL,
+                        clone!(@strong sender => move || {
+                            Continue(sender.send(SlaveMsg::RefreshDiagnostics).is_ok())
+                        }),
+                    ));
+                }
+            }
+            SlaveMsg::RefreshDiagnostics => self.set_diagnostics(self.collect_diagnostics()),
+            SlaveMsg::DiveTimerTick => self.set_dive_secs(self.connected_time.map(|time| time.elapsed().as_secs())),
+            SlaveMsg::IdleTimeoutReached(idle_secs) => {
+                if *self.get_connected() != Some(true) {
+                    return;
+                }
+                let action = *self.config.model().get_idle_timeout_action();
+                log::warn!(
+                    "[{}] 已有 {} 秒没有控制输入，执行空闲超时操作：{}",
+                    self.config.model().get_slave_url(),
+                    idle_secs,
+                    action.to_string()
+                );
+                send!(
+                    sender,
+                    SlaveMsg::ShowToastMessage(format!(
+                        "{}{}{}",
+                        tr("长时间没有控制输入，已超过 "),
+                        idle_secs,
+                        match action {
+                            IdleTimeoutAction::Warn => tr(" 秒"),
+                            IdleTimeoutAction::EmergencyStop => tr(" 秒，已紧急停止"),
+                            IdleTimeoutAction::Disconnect => tr(" 秒，已断开连接"),
+                        }
+                    ))
+                );
+                match action {
+                    IdleTimeoutAction::Warn => (),
+                    IdleTimeoutAction::EmergencyStop => send!(sender, SlaveMsg::EmergencyStop),
+                    IdleTimeoutAction::Disconnect => send!(sender, SlaveMsg::Disconnect),
+                }
+            }
+            SlaveMsg::ToggleVideoWindow => match self.video_window {
+                Some(_) => self.dock_video(),
+                None => self.pop_out_video(&sender),
+            },
+            SlaveMsg::DockVideo => self.dock_video(),
+            SlaveMsg::ClearToastHistory => self.get_mut_toast_history().clear(),
             SlaveMsg::SetSlaveStatus(which, value) => {
                 self.set_target_status(&which, value);
+                self.update_lock_setpoints();
                 if let Some(sender) = self.get_communication_msg_sender() {
                     match sender.try_send(SlaveCommunicationMsg::ControlUpdated(
-                        ControlPacket::from_status_map(&self.get_status().lock().unwrap()),
+                        self.control_packet(self.config.model().get_motion_trim()),
                     )) {
                         Ok(_) => (),
-                        Err(err) => println!("无法更新机位状态：{}", err.to_string()),
+                        Err(err) => log::warn!("无法更新机位状态：{}", err),
                     }
                 }
             }
@@ -1148,6 +3150,50 @@ pub struct MotionPacket {
     rot: f32,
 }
 
+// 录像只依赖视频流，默认不因通讯中断而停止，之后重新连接也不会影响正在进行的录制
+fn stops_recording_on_connection_lost(recording: bool, preferences: &PreferencesModel) -> bool {
+    recording && !*preferences.get_keep_recording_on_connection_lost()
+}
+
+fn clamp_control_value(value: f32) -> f32 {
+    if value.is_finite() {
+        value.clamp(-1.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+impl MotionPacket {
+    pub fn clamped(self) -> MotionPacket {
+        MotionPacket {
+            x: clamp_control_value(self.x),
+            y: clamp_control_value(self.y),
+            z: clamp_control_value(self.z),
+            rot: clamp_control_value(self.rot),
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        *self == MotionPacket::default()
+    }
+}
+
+// 仅显示非零的分量，全部为零时显示“无”
+impl ToString for MotionPacket {
+    fn to_string(&self) -> String {
+        let components: Vec<String> = [("X", self.x), ("Y", self.y), ("Z", self.z), (tr("旋转"), self.rot)]
+            .into_iter()
+            .filter(|(_, value)| *value != 0.0)
+            .map(|(name, value)| format!("{} {:+.2}", name, value))
+            .collect();
+        if components.is_empty() {
+            String::from(tr("无"))
+        } else {
+            components.join(" ")
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct ControlPacket {
     motion: MotionPacket,
@@ -1166,6 +3212,10 @@ impl ControlPacket {
                 i16::MIN..=-1 => *value as f32 / i16::MIN as f32 * -1.0,
             }
         }
+        // 按钮状态正常情况下为 0 或 1，在 i32 中求差以免异常的状态值溢出
+        fn button_pair_value(status_map: &HashMap<SlaveStatusClass, i16>, positive: SlaveStatusClass, negative: SlaveStatusClass) -> f32 {
+            (*status_map.get(&positive).unwrap_or(&0) as i32 - *status_map.get(&negative).unwrap_or(&0) as i32) as f32
+        }
         ControlPacket {
             motion: MotionPacket {
                 x: map_value(status_map.get(&SlaveStatusClass::MotionX).unwrap_or(&0)),
@@ -1177,17 +3227,8 @@ impl ControlPacket {
                         .unwrap_or(&0),
                 ),
             },
-            catch: (*status_map
-                .get(&SlaveStatusClass::RoboticArmOpen)
-                .unwrap_or(&0)
-                * 1
-                + *status_map
-                    .get(&SlaveStatusClass::RoboticArmClose)
-                    .unwrap_or(&0)
-                    * -1) as f32,
-            light: (*status_map.get(&SlaveStatusClass::LightOpen).unwrap_or(&0) * 1
-                + *status_map.get(&SlaveStatusClass::LightClose).unwrap_or(&0) * -1)
-                as f32,
+            catch: button_pair_value(status_map, SlaveStatusClass::RoboticArmOpen, SlaveStatusClass::RoboticArmClose),
+            light: button_pair_value(status_map, SlaveStatusClass::LightOpen, SlaveStatusClass::LightClose),
             depth_locked: status_map
                 .get(&SlaveStatusClass::DepthLocked)
                 .map(|x| *x >= 1)
@@ -1197,6 +3238,65 @@ impl ControlPacket {
                 .map(|x| *x >= 1)
                 .unwrap_or(false),
         }
+        .clamped()
+    }
+
+    // 下位机固件假定各控制量均已归一化：运动分量、机械臂与灯光均位于 [-1, 1]，非有限值视为 0
+    pub fn clamped(mut self) -> ControlPacket {
+        self.motion = self.motion.clamped();
+        self.catch = clamp_control_value(self.catch);
+        self.light = clamp_control_value(self.light);
+        self
+    }
+
+    // 在运动输出上叠加配平偏移量，叠加后仍限制在 [-1, 1] 内
+    pub fn trimmed(mut self, trim: &MotionPacket) -> ControlPacket {
+        self.motion.x += trim.x;
+        self.motion.y += trim.y;
+        self.motion.z += trim.z;
+        self.motion.rot += trim.rot;
+        self.clamped()
+    }
+
+    // 按照下位机固件要求的编码方式转换运动分量、机械臂与灯光，锁定状态保持为布尔值
+    fn encoded_motion(&self, encoding: ControlValueEncoding) -> serde_json::Value {
+        serde_json::json!({
+            "x": encoding.encode(self.motion.x),
+            "y": encoding.encode(self.motion.y),
+            "z": encoding.encode(self.motion.z),
+            "rot": encoding.encode(self.motion.rot),
+        })
+    }
+
+    pub fn encoded(&self, encoding: ControlValueEncoding) -> serde_json::Value {
+        serde_json::json!({
+            "motion": self.encoded_motion(encoding),
+            "catch": encoding.encode(self.catch),
+            "light": encoding.encode(self.light),
+            "depth_locked": self.depth_locked,
+            "direction_locked": self.direction_locked,
+        })
+    }
+
+    // 与 last 相比发生变化的子命令及其参数，last 为 None 时返回全部子命令
+    pub fn changed_commands(&self, last: Option<&ControlPacket>, encoding: ControlValueEncoding) -> Vec<(&'static str, RpcParams)> {
+        let mut commands = Vec::new();
+        if last.map_or(true, |last| last.motion != self.motion) {
+            commands.push((METHOD_MOVE, self.encoded_motion(encoding).to_rpc_params()));
+        }
+        if last.map_or(true, |last| last.depth_locked != self.depth_locked) {
+            commands.push((METHOD_SET_DEPTH_LOCKED, self.depth_locked.to_rpc_params()));
+        }
+        if last.map_or(true, |last| last.direction_locked != self.direction_locked) {
+            commands.push((METHOD_SET_DIRECTION_LOCKED, self.direction_locked.to_rpc_params()));
+        }
+        if last.map_or(true, |last| last.catch != self.catch) {
+            commands.push((METHOD_CATCH, encoding.encode(self.catch).to_rpc_params()));
+        }
+        if last.map_or(true, |last| last.light != self.light) {
+            commands.push((METHOD_LIGHT, encoding.encode(self.light).to_rpc_params()));
+        }
+        commands
     }
 }
 
@@ -1206,20 +3306,445 @@ impl ToString for ControlPacket {
     }
 }
 
+// 控制指令的发送方式，合并请求需要下位机固件支持 control 方法
+#[derive(EnumIter, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum ControlSendingMode {
+    Separate,
+    Batch,
+    Combined,
+}
+
+impl ToString for ControlSendingMode {
+    fn to_string(&self) -> String {
+        match self {
+            ControlSendingMode::Separate => tr("逐条请求"),
+            ControlSendingMode::Batch => tr("批量请求"),
+            ControlSendingMode::Combined => tr("合并请求"),
+        }
+        .to_string()
+    }
+}
+
+impl Default for ControlSendingMode {
+    fn default() -> Self {
+        Self::Separate
+    }
+}
+
+// 控制量发送给下位机前的编码方式，内部始终使用 [-1, 1] 的浮点数
+#[derive(EnumIter, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum ControlValueEncoding {
+    Float,   // [-1, 1] 的浮点数
+    Percent, // [0, 100] 的整数，50 为中立
+    Pwm,     // [1000, 2000] 微秒的 PWM 脉宽，1500 为中立
+    Int16,   // [-32768, 32767] 的整数，与输入的轴值范围相同
+}
+
+impl ToString for ControlValueEncoding {
+    fn to_string(&self) -> String {
+        match self {
+            ControlValueEncoding::Float => tr("浮点数 [-1, 1]"),
+            ControlValueEncoding::Percent => tr("百分比 [0, 100]"),
+            ControlValueEncoding::Pwm => tr("PWM 脉宽 [1000, 2000]"),
+            ControlValueEncoding::Int16 => tr("16 位整数 [-32768, 32767]"),
+        }
+        .to_string()
+    }
+}
+
+impl Default for ControlValueEncoding {
+    fn default() -> Self {
+        Self::Float
+    }
+}
+
+impl ControlValueEncoding {
+    pub fn encode(&self, value: f32) -> serde_json::Value {
+        let value = clamp_control_value(value);
+        match self {
+            ControlValueEncoding::Float => value.into(),
+            ControlValueEncoding::Percent => (((value + 1.0) * 50.0).round() as u8).into(),
+            ControlValueEncoding::Pwm => ((1500.0 + value * 500.0).round() as u16).into(),
+            // 与 from_status_map 中轴值的换算互逆，负值按 i16::MIN 缩放，使 -1 对应 -32768
+            ControlValueEncoding::Int16 if value < 0.0 => ((value * -(i16::MIN as f32)).round() as i16).into(),
+            ControlValueEncoding::Int16 => ((value * i16::MAX as f32).round() as i16).into(),
+        }
+    }
+}
+
+// 超过空闲超时时间没有控制输入时执行的操作
+#[derive(EnumIter, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum IdleTimeoutAction {
+    Warn,
+    EmergencyStop,
+    Disconnect,
+}
+
+impl ToString for IdleTimeoutAction {
+    fn to_string(&self) -> String {
+        match self {
+            IdleTimeoutAction::Warn => tr("仅提示"),
+            IdleTimeoutAction::EmergencyStop => tr("紧急停止"),
+            IdleTimeoutAction::Disconnect => tr("断开连接"),
+        }
+        .to_string()
+    }
+}
+
+impl Default for IdleTimeoutAction {
+    fn default() -> Self {
+        Self::Warn
+    }
+}
+
+// 对象参数的传递方式：按名称传递各字段，或将整个对象作为唯一的位置参数传递，数组始终按位置传递
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum RpcParamsStyle {
+    Named,
+    Positional,
+}
+
+impl Default for RpcParamsStyle {
+    fn default() -> Self {
+        Self::Named
+    }
+}
+
+// 命名参数的键需要 'static 生命周期，相同的键只分配一次，避免每次请求都泄漏内存
+static RPC_PARAM_KEYS: Lazy<Mutex<HashSet<&'static str>>> = Lazy::new(Default::default);
+
+fn rpc_param_key(key: String) -> &'static str {
+    let mut keys = RPC_PARAM_KEYS.lock().unwrap();
+    match keys.get(key.as_str()) {
+        Some(key) => key,
+        None => {
+            let key: &'static str = Box::leak(key.into_boxed_str());
+            keys.insert(key);
+            key
+        }
+    }
+}
+
 pub trait AsRpcParams {
-    fn to_rpc_params(&self) -> RpcParams;
+    fn to_rpc_params_with(&self, style: RpcParamsStyle) -> RpcParams;
+
+    fn to_rpc_params(&self) -> RpcParams {
+        self.to_rpc_params_with(RpcParamsStyle::Named)
+    }
 }
 
 impl<T: Serialize> AsRpcParams for T {
-    fn to_rpc_params(&self) -> RpcParams {
-        match serde_json::to_value(self).unwrap() {
-            serde_json::Value::Object(map) => map
+    fn to_rpc_params_with(&self, style: RpcParamsStyle) -> RpcParams {
+        match (serde_json::to_value(self).unwrap(), style) {
+            (serde_json::Value::Object(map), RpcParamsStyle::Named) => map
                 .into_iter()
-                .map(|(key, value)| ((Box::leak(Box::new(key)) as &'static str), value))
+                .map(|(key, value)| (rpc_param_key(key), value))
                 .collect::<BTreeMap<_, _>>()
                 .into(),
-            serde_json::Value::Array(vec) => vec.into(),
-            x => vec![x].into(),
+            (serde_json::Value::Array(vec), _) => vec.into(),
+            (x, _) => vec![x].into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::{TcpListener, TcpStream},
+    };
+
+    // 记录收到的方法名的模拟下位机，对所有请求返回空对象，返回其地址
+    fn spawn_mock_slave() -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let methods = Arc::new(Mutex::new(Vec::new()));
+        let served_methods = methods.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let methods = served_methods.clone();
+                std::thread::spawn(move || serve_mock_slave(stream, methods));
+            }
+        });
+        (address, methods)
+    }
+
+    fn serve_mock_slave(mut stream: TcpStream, methods: Arc<Mutex<Vec<String>>>) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        loop {
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                    return;
+                }
+                match line.trim_end().split_once(':') {
+                    Some((name, value)) if name.eq_ignore_ascii_case("content-length") => {
+                        content_length = value.trim().parse().unwrap_or(0);
+                    }
+                    Some(_) => (),
+                    None if line.trim_end().is_empty() => break,
+                    None => (),
+                }
+            }
+            let mut body = vec![0; content_length];
+            if reader.read_exact(&mut body).is_err() {
+                return;
+            }
+            let request: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+            methods
+                .lock()
+                .unwrap()
+                .push(request["method"].as_str().unwrap_or_default().to_string());
+            let response = serde_json::json!({ "jsonrpc": "2.0", "result": {}, "id": request["id"] }).to_string();
+            if write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                response.len(),
+                response
+            )
+            .is_err()
+            {
+                return;
+            }
+        }
+    }
+
+    fn count_method(methods: &Mutex<Vec<String>>, method: &str) -> usize {
+        methods.lock().unwrap().iter().filter(|x| *x == method).count()
+    }
+
+    // 以 20 毫秒的状态信息更新间隔启动通讯主循环，返回的 glib 接收端需保持到通讯主循环退出
+    fn start_communication_main_loop(
+        address: &str,
+    ) -> (
+        async_std::channel::Sender<SlaveCommunicationMsg>,
+        JoinHandle<Result<(), RpcError>>,
+        glib::Receiver<SlaveMsg>,
+    ) {
+        let rpc_client = Arc::new(RpcClientBuilder::default().build(address).unwrap());
+        let (communication_sender, communication_receiver) = async_std::channel::bounded::<SlaveCommunicationMsg>(128);
+        let (slave_sender, slave_receiver) = MainContext::channel(PRIORITY_DEFAULT);
+        let handle = task::spawn(communication_main_loop(
+            50,
+            rpc_client,
+            communication_sender.clone(),
+            communication_receiver,
+            slave_sender,
+            20,
+            Duration::from_secs(1),
+            ControlSendingMode::default(),
+            ControlValueEncoding::default(),
+            None,
+            None,
+            false,
+        ));
+        (communication_sender, handle, slave_receiver)
+    }
+
+    // 独占连接的任务开始执行时已暂停状态信息请求，执行期间不应再请求状态信息，结束后恢复
+    #[test]
+    fn blocked_loop_does_not_request_informations() {
+        let (address, methods) = spawn_mock_slave();
+        let (communication_sender, handle, _slave_receiver) = start_communication_main_loop(&address);
+        task::block_on(async {
+            task::sleep(Duration::from_millis(100)).await;
+            assert!(count_method(&methods, METHOD_GET_INFO) > 0);
+            let counts = Arc::new(Mutex::new(None));
+            let blocker_methods = methods.clone();
+            let blocker_counts = counts.clone();
+            let blocker: BlockingTask = Box::pin(async move {
+                let before = count_method(&blocker_methods, METHOD_GET_INFO);
+                task::sleep(Duration::from_millis(300)).await;
+                let after = count_method(&blocker_methods, METHOD_GET_INFO);
+                *blocker_counts.lock().unwrap() = Some((before, after));
+                Ok(())
+            });
+            communication_sender.send(SlaveCommunicationMsg::Block(blocker)).await.unwrap();
+            task::sleep(Duration::from_millis(600)).await;
+            let (before, after) = counts.lock().unwrap().expect("独占连接的任务未执行");
+            assert_eq!(before, after);
+            assert!(count_method(&methods, METHOD_GET_INFO) > after);
+            communication_sender.send(SlaveCommunicationMsg::Disconnect).await.unwrap();
+            assert!(handle.await.is_ok());
+        });
+    }
+
+    // 再次连接时关闭原有的通道，原有的通讯主循环应随之退出并结束全部任务，且不再报告连接状态
+    #[test]
+    fn reconnecting_leaves_a_single_communication_loop() {
+        let (first_address, first_methods) = spawn_mock_slave();
+        let (second_address, second_methods) = spawn_mock_slave();
+        let (first_sender, first_handle, first_slave_receiver) = start_communication_main_loop(&first_address);
+        task::block_on(async {
+            task::sleep(Duration::from_millis(100)).await;
+            first_sender.close();
+            let (second_sender, second_handle, _second_slave_receiver) = start_communication_main_loop(&second_address);
+            let result = async_std::future::timeout(Duration::from_secs(1), first_handle)
+                .await
+                .expect("原有的通讯主循环未能退出");
+            assert!(result.is_ok());
+            let first_count = first_methods.lock().unwrap().len();
+            let second_count = count_method(&second_methods, METHOD_GET_INFO);
+            task::sleep(Duration::from_millis(200)).await;
+            assert_eq!(first_methods.lock().unwrap().len(), first_count);
+            assert!(count_method(&second_methods, METHOD_GET_INFO) > second_count);
+            second_sender.send(SlaveCommunicationMsg::Disconnect).await.unwrap();
+            assert!(second_handle.await.is_ok());
+        });
+
+        let context = MainContext::new();
+        let messages = Rc::new(RefCell::new(Vec::new()));
+        let received_messages = messages.clone();
+        first_slave_receiver.attach(Some(&context), move |msg| {
+            received_messages.borrow_mut().push(msg);
+            glib::Continue(true)
+        });
+        while context.iteration(false) {}
+        assert!(!messages.borrow().is_empty());
+        assert!(!messages
+            .borrow()
+            .iter()
+            .any(|msg| matches!(msg, SlaveMsg::ConnectionChanged(None) | SlaveMsg::CommunicationError(_))));
+    }
+
+    #[test]
+    fn connection_loss_stops_recording_only_when_not_kept() {
+        let mut preferences = PreferencesModel::default();
+        assert!(*preferences.get_keep_recording_on_connection_lost());
+        assert!(!stops_recording_on_connection_lost(true, &preferences));
+        assert!(!stops_recording_on_connection_lost(false, &preferences));
+        preferences.set_keep_recording_on_connection_lost(false);
+        assert!(stops_recording_on_connection_lost(true, &preferences));
+        assert!(!stops_recording_on_connection_lost(false, &preferences));
+    }
+
+    // 重复构造参数时不应再分配新的键
+    #[test]
+    fn rpc_param_keys_are_interned_once() {
+        let packet = ControlPacket {
+            motion: MotionPacket { x: 0.5, ..Default::default() },
+            ..Default::default()
+        };
+        packet.encoded(ControlValueEncoding::Float).to_rpc_params();
+        let interned = RPC_PARAM_KEYS.lock().unwrap().len();
+        for _ in 0..1000 {
+            packet.encoded(ControlValueEncoding::Pwm).to_rpc_params();
+            packet.to_rpc_params_with(RpcParamsStyle::Positional);
         }
+        assert_eq!(RPC_PARAM_KEYS.lock().unwrap().len(), interned);
+        assert!(std::ptr::eq(
+            rpc_param_key(String::from("motion")),
+            rpc_param_key(String::from("motion"))
+        ));
+    }
+
+    #[test]
+    fn control_value_encoding_edges() {
+        let cases = [
+            (ControlValueEncoding::Float, -1.0, serde_json::json!(-1.0)),
+            (ControlValueEncoding::Float, 0.0, serde_json::json!(0.0)),
+            (ControlValueEncoding::Float, 1.0, serde_json::json!(1.0)),
+            (ControlValueEncoding::Percent, -1.0, serde_json::json!(0)),
+            (ControlValueEncoding::Percent, 0.0, serde_json::json!(50)),
+            (ControlValueEncoding::Percent, 1.0, serde_json::json!(100)),
+            (ControlValueEncoding::Pwm, -1.0, serde_json::json!(1000)),
+            (ControlValueEncoding::Pwm, 0.0, serde_json::json!(1500)),
+            (ControlValueEncoding::Pwm, 1.0, serde_json::json!(2000)),
+            (ControlValueEncoding::Int16, -1.0, serde_json::json!(-32768)),
+            (ControlValueEncoding::Int16, 0.0, serde_json::json!(0)),
+            (ControlValueEncoding::Int16, 1.0, serde_json::json!(32767)),
+        ];
+        for (encoding, value, expected) in cases {
+            assert_eq!(encoding.encode(value), expected, "{:?} {}", encoding, value);
+        }
+    }
+
+    // 超出范围与非有限的输入先限制到 [-1, 1]，16 位整数在两端饱和而不会溢出
+    #[test]
+    fn control_value_encoding_saturates() {
+        for encoding in [
+            ControlValueEncoding::Float,
+            ControlValueEncoding::Percent,
+            ControlValueEncoding::Pwm,
+            ControlValueEncoding::Int16,
+        ] {
+            assert_eq!(encoding.encode(2.0), encoding.encode(1.0), "{:?}", encoding);
+            assert_eq!(encoding.encode(-2.0), encoding.encode(-1.0), "{:?}", encoding);
+            assert_eq!(encoding.encode(f32::NAN), encoding.encode(0.0), "{:?}", encoding);
+        }
+        assert_eq!(ControlValueEncoding::Int16.encode(f32::MIN), serde_json::json!(i16::MIN));
+        assert_eq!(ControlValueEncoding::Int16.encode(f32::MAX), serde_json::json!(i16::MAX));
+    }
+
+    // 输入的轴值编码为 16 位整数后应还原为原值
+    #[test]
+    fn int16_encoding_round_trips_axis_values() {
+        for axis in [i16::MIN, -16384, -1, 0, 1, 16384, i16::MAX] {
+            let status_map = HashMap::from([(SlaveStatusClass::MotionX, axis)]);
+            let packet = ControlPacket::from_status_map(&status_map);
+            assert_eq!(
+                ControlValueEncoding::Int16.encode(packet.motion.x),
+                serde_json::json!(axis),
+                "{}",
+                axis
+            );
+        }
+    }
+
+    #[test]
+    fn clamp_control_value_matrix() {
+        let cases = [
+            (0.0, 0.0),
+            (0.5, 0.5),
+            (-0.5, -0.5),
+            (1.0, 1.0),
+            (-1.0, -1.0),
+            (1.5, 1.0),
+            (-1.5, -1.0),
+            (f32::MAX, 1.0),
+            (f32::MIN, -1.0),
+            (f32::NAN, 0.0),
+            (f32::INFINITY, 0.0),
+            (f32::NEG_INFINITY, 0.0),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(clamp_control_value(value), expected, "{}", value);
+        }
+    }
+
+    #[test]
+    fn control_packet_clamped_limits_every_component() {
+        let packet = ControlPacket {
+            motion: MotionPacket { x: 0.25, y: -2.0, z: 3.0, rot: f32::NAN },
+            catch: -1.5,
+            light: f32::INFINITY,
+            depth_locked: true,
+            direction_locked: false,
+        };
+        assert_eq!(
+            packet.clamped(),
+            ControlPacket {
+                motion: MotionPacket { x: 0.25, y: -1.0, z: 1.0, rot: 0.0 },
+                catch: -1.0,
+                light: 0.0,
+                depth_locked: true,
+                direction_locked: false,
+            }
+        );
+    }
+
+    #[test]
+    fn control_packet_trimmed_stays_in_range() {
+        let packet = ControlPacket {
+            motion: MotionPacket { x: 0.9, y: -0.9, z: 0.0, rot: 0.5 },
+            ..Default::default()
+        };
+        let trim = MotionPacket { x: 0.2, y: -0.2, z: f32::NAN, rot: -0.25 };
+        assert_eq!(
+            packet.trimmed(&trim).motion,
+            MotionPacket { x: 1.0, y: -1.0, z: 0.0, rot: 0.25 }
+        );
     }
 }
@@ -18,22 +18,27 @@
 
 use async_std::task;
 use std::{
+    cell::RefCell,
     cmp::{max, min},
-    collections::{HashMap, VecDeque},
+    collections::{BTreeSet, HashMap, VecDeque},
     error::Error,
     fmt::Debug,
+    fs,
     ops::Deref,
-    time::{Duration, SystemTime},
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{Duration, Instant, SystemTime},
 };
 
 use adw::{
-    prelude::*, ActionRow, Clamp, ExpanderRow, HeaderBar, Leaflet, PreferencesGroup,
-    PreferencesPage, PreferencesWindow, ToastOverlay,
+    prelude::*, ActionRow, Clamp, ComboRow, ExpanderRow, HeaderBar, Leaflet, PreferencesGroup,
+    PreferencesPage, PreferencesWindow, Toast, ToastOverlay,
 };
 use glib::{clone, Sender};
 use gtk::{
-    Align, Box as GtkBox, Button, FlowBox, Image, Inhibit, Label, Orientation, Scale,
-    SelectionMode, SpinButton, Switch,
+    Align, Box as GtkBox, Button, DrawingArea, Entry, FileChooserAction, FileFilter, FlowBox,
+    Image, Inhibit, Label, Orientation, Scale, SelectionMode, SpinButton, SpinButtonUpdatePolicy,
+    StringList, Switch, ToggleButton,
 };
 use relm4::{
     factory::{FactoryPrototype, FactoryVec},
@@ -45,8 +50,12 @@ use derivative::*;
 use jsonrpsee_core::client::ClientT;
 use serde::{Deserialize, Serialize};
 
+use crate::file_name::create_directory;
 use crate::function::*;
-use crate::slave::{protocol::*, AsRpcParams, RpcClient, SlaveCommunicationMsg};
+use crate::i18n::tr;
+use crate::preferences::get_data_path;
+use crate::slave::{protocol::*, AsRpcParams, RpcClient, RpcParams, SlaveCommunicationMsg};
+use crate::ui::generic::{confirm_message, select_path};
 use crate::ui::graph_view::{GraphView, Point as GraphPoint};
 
 use super::SlaveMsg;
@@ -66,8 +75,31 @@ pub enum SlaveParameterTunerMsg {
     ApplyParameters,
     StartDebug(RpcClient),
     StopDebug(Option<SlaveParameterTunerError>),
+    ConnectionClosed, // 机位断开连接，调试所使用的连接已失效
     FeedbacksReceived(SlaveParameterTunerFeedbackPacket),
     ParametersReceived(SlaveParameterTunerParameterPacket),
+    ParameterRangesReceived(ParameterRanges),
+    ResponseSignalsUpdated(Vec<String>),
+    ResponsePlotUpdated(Option<ResponsePlot>),
+    SelectResponseSignal(u32),
+    SetResponsePlotPaused(bool),
+    ClearResponsePlot,
+    SetProfileName(String),
+    SelectProfile(u32),
+    SaveProfile,
+    LoadProfile,
+    DeleteProfile,
+    ExportProfile(PathBuf),
+    ImportProfile(PathBuf),
+    ProfileApplied(Vec<ParameterKey>, bool), // 被拒绝的参数及下位机是否拒绝保存参数
+}
+
+// 用于标识下位机拒绝的参数
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParameterKey {
+    PropellerPwmFreqCalibration,
+    Propeller(String),
+    ControlLoop(String),
 }
 
 #[derive(Debug)]
@@ -99,6 +131,7 @@ pub struct PropellerModel {
     #[derivative(Default(value = "true"))]
     enabled: bool,
     reversed: bool,
+    ranges: ParameterRanges,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -122,6 +155,309 @@ const DEFAULT_PROPELLERS: [&'static str; 6] = [
 const DEFAULT_CONTROL_LOOPS: [&'static str; 2] = ["depth_lock", "direction_lock"];
 const CARD_MIN_WIDTH: i32 = 300;
 
+const RESPONSE_PLOT_WINDOW_SECS: f64 = 30.0;
+const RESPONSE_PLOT_COLUMNS: usize = 512;
+
+// 降采样后的响应曲线，在后台任务中计算，主线程仅负责绘制
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponsePlot {
+    signal: Vec<(f64, f32)>,
+    setpoint: Vec<(f64, f32)>,
+    time_end: f64,
+    lower_value: f32,
+    upper_value: f32,
+}
+
+#[derive(Debug, Default)]
+struct ResponseRecorder {
+    samples: VecDeque<(f64, HashMap<String, f32>)>,
+    signal: Option<String>,
+    paused: bool,
+    dirty: bool,
+}
+
+impl ResponseRecorder {
+    fn push(&mut self, time: f64, values: HashMap<String, f32>) {
+        if self.paused {
+            return;
+        }
+        while self
+            .samples
+            .front()
+            .map_or(false, |(first, _)| time - first > RESPONSE_PLOT_WINDOW_SECS)
+        {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((time, values));
+        self.dirty = true;
+    }
+
+    fn clear(&mut self) {
+        self.samples.clear();
+        self.dirty = true;
+    }
+
+    fn signal_names(&self) -> Vec<String> {
+        self.samples
+            .iter()
+            .flat_map(|(_, values)| values.keys().cloned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    // 每列仅保留最小值与最大值，避免降采样时丢失超调
+    fn decimate(&self, key: &str, time_end: f64) -> Vec<(f64, f32)> {
+        fn flush(points: &mut Vec<(f64, f32)>, min: (f64, f32), max: (f64, f32)) {
+            if min == max {
+                points.push(min);
+            } else if min.0 <= max.0 {
+                points.extend([min, max]);
+            } else {
+                points.extend([max, min]);
+            }
+        }
+        let time_begin = time_end - RESPONSE_PLOT_WINDOW_SECS;
+        let mut points = Vec::new();
+        let mut column: Option<(usize, (f64, f32), (f64, f32))> = None;
+        for (time, values) in &self.samples {
+            if let Some(&value) = values.get(key) {
+                let point = (*time, value);
+                let index = ((time - time_begin) / RESPONSE_PLOT_WINDOW_SECS
+                    * RESPONSE_PLOT_COLUMNS as f64) as usize;
+                column = match column {
+                    Some((current, min, max)) if current == index => Some((
+                        index,
+                        if value < min.1 { point } else { min },
+                        if value > max.1 { point } else { max },
+                    )),
+                    previous => {
+                        if let Some((_, min, max)) = previous {
+                            flush(&mut points, min, max);
+                        }
+                        Some((index, point, point))
+                    }
+                };
+            }
+        }
+        if let Some((_, min, max)) = column {
+            flush(&mut points, min, max);
+        }
+        points
+    }
+
+    fn plot(&self) -> Option<ResponsePlot> {
+        let signal = self.signal.as_ref()?;
+        let time_end = self.samples.back()?.0;
+        let signal_points = self.decimate(signal, time_end);
+        let setpoint_points = self.decimate(&format!("{}_setpoint", signal), time_end);
+        let (lower_value, upper_value) = signal_points
+            .iter()
+            .chain(setpoint_points.iter())
+            .fold(None, |range: Option<(f32, f32)>, &(_, value)| match range {
+                Some((lower, upper)) => Some((lower.min(value), upper.max(value))),
+                None => Some((value, value)),
+            })?;
+        let margin = ((upper_value - lower_value) * 0.1).max(0.5);
+        Some(ResponsePlot {
+            signal: signal_points,
+            setpoint: setpoint_points,
+            time_end,
+            lower_value: lower_value - margin,
+            upper_value: upper_value + margin,
+        })
+    }
+}
+
+fn draw_response_plot(cr: &gtk::cairo::Context, plot: &ResponsePlot, width: f64, height: f64) {
+    const PADDING: f64 = 24.0;
+    let plot_width = width - PADDING * 2.0;
+    let plot_height = height - PADDING * 2.0;
+    let value_span = (plot.upper_value - plot.lower_value) as f64;
+    let to_x = |time: f64| {
+        PADDING + (time - plot.time_end + RESPONSE_PLOT_WINDOW_SECS) / RESPONSE_PLOT_WINDOW_SECS * plot_width
+    };
+    let to_y = |value: f32| PADDING + (plot.upper_value - value) as f64 / value_span * plot_height;
+
+    cr.set_source_rgba(0.5, 0.5, 0.5, 0.6);
+    cr.set_line_width(0.5);
+    cr.set_font_size(10.0);
+    for i in 0..=4 {
+        let y = PADDING + plot_height * i as f64 / 4.0;
+        cr.move_to(PADDING, y);
+        cr.line_to(PADDING + plot_width, y);
+        let value = plot.upper_value as f64 - value_span * i as f64 / 4.0;
+        cr.move_to(2.0, y - 2.0);
+        cr.show_text(&format!("{:.2}", value))
+            .expect("Couldn't show text on Cairo Context");
+    }
+    cr.move_to(PADDING + plot_width - 24.0, height - 6.0);
+    cr.show_text(&format!("{:.0}s", plot.time_end))
+        .expect("Couldn't show text on Cairo Context");
+    cr.stroke().expect("Couldn't stroke on Cairo Context");
+
+    let series = [
+        (&plot.setpoint, (0.96, 0.47, 0.0), true),
+        (&plot.signal, (0.21, 0.52, 0.89), false),
+    ];
+    for (points, (r, g, b), dashed) in series {
+        cr.set_source_rgb(r, g, b);
+        cr.set_line_width(2.0);
+        let dashes: &[f64] = if dashed { &[6.0, 4.0] } else { &[] };
+        cr.set_dash(dashes, 0.0);
+        for (index, &(time, value)) in points.iter().enumerate() {
+            if index == 0 {
+                cr.move_to(to_x(time), to_y(value));
+            } else {
+                cr.line_to(to_x(time), to_y(value));
+            }
+        }
+        cr.stroke().expect("Couldn't stroke on Cairo Context");
+    }
+    cr.set_dash(&[], 0.0);
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct ParameterRange {
+    pub min: f64,
+    pub max: f64,
+    pub step: f64,
+}
+
+impl ParameterRange {
+    const fn new(min: f64, max: f64, step: f64) -> Self {
+        ParameterRange { min, max, step }
+    }
+
+    fn is_valid(&self) -> bool {
+        self.min.is_finite() && self.max.is_finite() && self.min < self.max && self.step > 0.0
+    }
+
+    fn contains(&self, value: f64) -> bool {
+        value >= self.min && value <= self.max
+    }
+
+    // 根据步长确定显示的小数位数
+    fn digits(&self) -> u32 {
+        (-self.step.log10()).ceil().clamp(0.0, 6.0) as u32
+    }
+}
+
+// 各参数的取值范围，下位机未提供时使用默认值
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct ParameterRanges {
+    pub propeller_pwm_freq_calibration: ParameterRange,
+    pub power_positive: ParameterRange,
+    pub power_negative: ParameterRange,
+    pub deadzone_upper: ParameterRange,
+    pub deadzone_lower: ParameterRange,
+    pub p: ParameterRange,
+    pub i: ParameterRange,
+    pub d: ParameterRange,
+}
+
+impl Default for ParameterRanges {
+    fn default() -> Self {
+        ParameterRanges {
+            propeller_pwm_freq_calibration: ParameterRange::new(-0.1, 0.1, 0.0001),
+            power_positive: ParameterRange::new(0.01, 1.0, 0.01),
+            power_negative: ParameterRange::new(0.01, 1.0, 0.01),
+            deadzone_upper: ParameterRange::new(-128.0, 127.0, 1.0),
+            deadzone_lower: ParameterRange::new(-128.0, 127.0, 1.0),
+            p: ParameterRange::new(0.0, 100.0, 0.01),
+            i: ParameterRange::new(0.0, 100.0, 0.01),
+            d: ParameterRange::new(0.0, 100.0, 0.01),
+        }
+    }
+}
+
+impl ParameterRanges {
+    // 将下位机提供的无效范围替换为默认值
+    fn sanitize(mut self) -> Self {
+        let default = Self::default();
+        for (range, default) in [
+            (&mut self.propeller_pwm_freq_calibration, default.propeller_pwm_freq_calibration),
+            (&mut self.power_positive, default.power_positive),
+            (&mut self.power_negative, default.power_negative),
+            (&mut self.deadzone_upper, default.deadzone_upper),
+            (&mut self.deadzone_lower, default.deadzone_lower),
+            (&mut self.p, default.p),
+            (&mut self.i, default.i),
+            (&mut self.d, default.d),
+        ] {
+            if !range.is_valid() {
+                *range = default;
+            }
+        }
+        self
+    }
+
+    fn accepts(&self, parameters: &SlaveParameterTunerParameterPacket) -> bool {
+        self.propeller_pwm_freq_calibration
+            .contains(parameters.propeller_pwm_freq_calibration)
+            && parameters.propeller_parameters.values().all(|propeller| {
+                self.power_positive.contains(propeller.power_positive)
+                    && self.power_negative.contains(propeller.power_negative)
+                    && self.deadzone_upper.contains(propeller.deadzone_upper as f64)
+                    && self.deadzone_lower.contains(propeller.deadzone_lower as f64)
+            })
+            && parameters.control_loop_parameters.values().all(|control_loop| {
+                self.p.contains(control_loop.p)
+                    && self.i.contains(control_loop.i)
+                    && self.d.contains(control_loop.d)
+            })
+    }
+}
+
+// 数据目录只读或已满时无法创建文件夹，返回错误信息以便提示而不是崩溃
+fn get_parameter_profile_path() -> Result<PathBuf, String> {
+    let mut profile_path = get_data_path();
+    profile_path.push("ParameterProfiles");
+    create_directory(&profile_path)?;
+    Ok(profile_path)
+}
+
+fn parameter_profile_file(name: &str) -> Result<PathBuf, String> {
+    let mut path = get_parameter_profile_path()?;
+    path.push(format!("{}.json", name));
+    Ok(path)
+}
+
+fn list_parameter_profiles() -> Result<Vec<String>, String> {
+    let mut names: Vec<String> = fs::read_dir(get_parameter_profile_path()?)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+                .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    Ok(names)
+}
+
+fn is_valid_profile_name(name: &str) -> bool {
+    !name.trim().is_empty()
+        && !name.starts_with('.')
+        && !name.contains(|c: char| matches!(c, '/' | '\\' | ':'))
+}
+
+fn read_parameter_profile(path: &Path) -> Result<SlaveParameterTunerParameterPacket, String> {
+    let json = fs::read_to_string(path).map_err(|err| format!("{}{}", tr("无法读取参数档案："), err))?;
+    serde_json::from_str(&json).map_err(|err| format!("{}{}", tr("参数档案格式错误："), err))
+}
+
+fn write_parameter_profile(
+    path: &Path,
+    profile: &SlaveParameterTunerParameterPacket,
+) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(profile).unwrap();
+    fs::write(path, json).map_err(|err| format!("{}{}", tr("无法写入参数档案："), err))
+}
+
 trait SlaveParameterTunerWindowExt {
     fn set_destroy(&self, destroy: bool);
 }
@@ -174,12 +510,12 @@ impl PropellerModel {
 
     fn key_to_string<'a, 'b: 'a>(key: &'b str) -> &'a str {
         match key {
-            "front_left" => "左前",
-            "front_right" => "右前",
-            "back_left" => "左后",
-            "back_right" => "右后",
-            "center_left" => "左中",
-            "center_right" => "右中",
+            "front_left" => tr("左前"),
+            "front_right" => tr("右前"),
+            "back_left" => tr("左后"),
+            "back_right" => tr("右后"),
+            "center_left" => tr("左中"),
+            "center_right" => tr("右中"),
             key => key,
         }
     }
@@ -197,6 +533,7 @@ pub struct ControlLoopModel {
     #[derivative(Default(value = "1.0"))]
     d: f64,
     feedbacks: VecDeque<f32>,
+    ranges: ParameterRanges,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -223,8 +560,8 @@ impl ControlLoopModel {
 
     fn key_to_string<'a, 'b: 'a>(key: &'b str) -> &'a str {
         match key {
-            "depth_lock" => "深度锁定",
-            "direction_lock" => "方向锁定",
+            "depth_lock" => tr("深度锁定"),
+            "direction_lock" => tr("方向锁定"),
             key => key,
         }
     }
@@ -253,6 +590,19 @@ pub struct SlaveParameterTunerModel {
     graph_view_point_num_limit: u16,
     graph_view_update_interval: u16,
     stopped: bool,
+    parameter_ranges: ParameterRanges,
+    response_signals: Vec<String>,
+    response_signal: Option<String>,
+    response_plot_paused: bool,
+    #[no_eq]
+    response_plot: Rc<RefCell<Option<ResponsePlot>>>,
+    profile_name: String,
+    profile_names: Vec<String>,
+    #[derivative(Default(value = "gtk::INVALID_LIST_POSITION"))]
+    selected_profile: u32,
+    previous_parameters: Option<SlaveParameterTunerParameterPacket>, // 载入档案前的参数，用于回退被拒绝的参数
+    #[no_eq]
+    toast_messages: Rc<RefCell<VecDeque<String>>>,
 }
 
 #[relm4::factory_prototype(pub)]
@@ -270,7 +620,7 @@ impl FactoryPrototype for PropellerModel {
                 set_spacing: 12,
                 append = &PreferencesGroup {
                     add = &ExpanderRow {
-                        set_title: "启用",
+                        set_title: tr("启用"),
                         set_show_enable_switch: true,
                         set_expanded: *self.get_enabled(),
                         set_enable_expansion: track!(self.changed(PropellerModel::enabled()), *self.get_enabled()),
@@ -278,7 +628,7 @@ impl FactoryPrototype for PropellerModel {
                             send!(sender, SlaveParameterTunerMsg::SetPropellerEnabled(key, expander.enables_expansion()));
                         },
                         add_row = &ActionRow {
-                            set_title: "反转",
+                            set_title: tr("反转"),
                             add_suffix: reversed_switch = &Switch {
                                 set_valign: Align::Center,
                                 set_active: track!(self.changed(PropellerModel::reversed()), *self.get_reversed()),
@@ -290,10 +640,14 @@ impl FactoryPrototype for PropellerModel {
                             set_activatable_widget: Some(&reversed_switch),
                         },
                         add_row = &ActionRow {
-                            set_title: "正向动力",
+                            set_title: tr("正向动力"),
                             add_suffix = &SpinButton::with_range(0.01, 1.0, 0.01) {
+                                set_snap_to_ticks: true,
+                                set_update_policy: SpinButtonUpdatePolicy::IfValid,
+                                set_range: track!(self.changed(PropellerModel::ranges()), self.ranges.power_positive.min, self.ranges.power_positive.max),
+                                set_increments: track!(self.changed(PropellerModel::ranges()), self.ranges.power_positive.step, self.ranges.power_positive.step * 10.0),
                                 set_value: track!(self.changed(PropellerModel::power_positive()), *self.get_power_positive()),
-                                set_digits: 2,
+                                set_digits: track!(self.changed(PropellerModel::ranges()), self.ranges.power_positive.digits()),
                                 set_valign: Align::Center,
                                 connect_value_changed(key, sender) => move |button| {
                                     send!(sender, SlaveParameterTunerMsg::SetPropellerPowerPositive(key, button.value()));
@@ -303,7 +657,9 @@ impl FactoryPrototype for PropellerModel {
                         add_row = &ActionRow {
                             set_child = Some(&Scale::with_range(Orientation::Horizontal, 0.01, 1.0, 0.01)) {
                                 set_width_request: CARD_MIN_WIDTH,
-                                set_round_digits: 2,
+                                set_round_digits: track!(self.changed(PropellerModel::ranges()), self.ranges.power_positive.digits() as i32),
+                                set_range: track!(self.changed(PropellerModel::ranges()), self.ranges.power_positive.min, self.ranges.power_positive.max),
+                                set_increments: track!(self.changed(PropellerModel::ranges()), self.ranges.power_positive.step, self.ranges.power_positive.step * 10.0),
                                 set_value: track!(self.changed(PropellerModel::power_positive()), *self.get_power_positive() as f64),
                                 connect_value_changed(key, sender) => move |scale| {
                                     send!(sender, SlaveParameterTunerMsg::SetPropellerPowerPositive(key, scale.value()));
@@ -311,10 +667,14 @@ impl FactoryPrototype for PropellerModel {
                             }
                         },
                         add_row = &ActionRow {
-                            set_title: "反向动力",
+                            set_title: tr("反向动力"),
                             add_suffix = &SpinButton::with_range(0.01, 1.0, 0.01) {
+                                set_snap_to_ticks: true,
+                                set_update_policy: SpinButtonUpdatePolicy::IfValid,
+                                set_range: track!(self.changed(PropellerModel::ranges()), self.ranges.power_negative.min, self.ranges.power_negative.max),
+                                set_increments: track!(self.changed(PropellerModel::ranges()), self.ranges.power_negative.step, self.ranges.power_negative.step * 10.0),
                                 set_value: track!(self.changed(PropellerModel::power_negative()), *self.get_power_negative()),
-                                set_digits: 2,
+                                set_digits: track!(self.changed(PropellerModel::ranges()), self.ranges.power_negative.digits()),
                                 set_valign: Align::Center,
                                 connect_value_changed(key, sender) => move |button| {
                                     send!(sender, SlaveParameterTunerMsg::SetPropellerPowerNegative(key, button.value()));
@@ -324,7 +684,9 @@ impl FactoryPrototype for PropellerModel {
                         add_row = &ActionRow {
                             set_child = Some(&Scale::with_range(Orientation::Horizontal, 0.01, 1.0, 0.01)) {
                                 set_width_request: CARD_MIN_WIDTH,
-                                set_round_digits: 2,
+                                set_round_digits: track!(self.changed(PropellerModel::ranges()), self.ranges.power_negative.digits() as i32),
+                                set_range: track!(self.changed(PropellerModel::ranges()), self.ranges.power_negative.min, self.ranges.power_negative.max),
+                                set_increments: track!(self.changed(PropellerModel::ranges()), self.ranges.power_negative.step, self.ranges.power_negative.step * 10.0),
                                 set_value: track!(self.changed(PropellerModel::power_negative()), *self.get_power_negative() as f64),
                                 connect_value_changed(key, sender) => move |scale| {
                                     send!(sender, SlaveParameterTunerMsg::SetPropellerPowerNegative(key, scale.value()));
@@ -332,10 +694,14 @@ impl FactoryPrototype for PropellerModel {
                             }
                         },
                         add_row = &ActionRow {
-                            set_title: "死区上限",
+                            set_title: tr("死区上限"),
                             add_suffix = &SpinButton::with_range(-128.0, 127.0, 1.0) {
+                                set_snap_to_ticks: true,
+                                set_update_policy: SpinButtonUpdatePolicy::IfValid,
+                                set_range: track!(self.changed(PropellerModel::ranges()), self.ranges.deadzone_upper.min, self.ranges.deadzone_upper.max),
+                                set_increments: track!(self.changed(PropellerModel::ranges()), self.ranges.deadzone_upper.step, self.ranges.deadzone_upper.step * 10.0),
                                 set_value: track!(self.changed(PropellerModel::deadzone_upper()), *self.get_deadzone_upper() as f64),
-                                set_digits: 0,
+                                set_digits: track!(self.changed(PropellerModel::ranges()), self.ranges.deadzone_upper.digits()),
                                 set_valign: Align::Center,
                                 connect_value_changed(key, sender) => move |button| {
                                     send!(sender, SlaveParameterTunerMsg::SetPropellerUpperDeadzone(key, button.value() as i8));
@@ -345,7 +711,9 @@ impl FactoryPrototype for PropellerModel {
                         add_row = &ActionRow {
                             set_child = Some(&Scale::with_range(Orientation::Horizontal, -128.0, 127.0, 1.0)) {
                                 set_width_request: CARD_MIN_WIDTH,
-                                set_round_digits: 0,
+                                set_round_digits: track!(self.changed(PropellerModel::ranges()), self.ranges.deadzone_upper.digits() as i32),
+                                set_range: track!(self.changed(PropellerModel::ranges()), self.ranges.deadzone_upper.min, self.ranges.deadzone_upper.max),
+                                set_increments: track!(self.changed(PropellerModel::ranges()), self.ranges.deadzone_upper.step, self.ranges.deadzone_upper.step * 10.0),
                                 set_value: track!(self.changed(PropellerModel::deadzone_upper()), *self.get_deadzone_upper() as f64),
                                 connect_value_changed(key, sender) => move |scale| {
                                     send!(sender, SlaveParameterTunerMsg::SetPropellerUpperDeadzone(key, scale.value() as i8));
@@ -353,10 +721,14 @@ impl FactoryPrototype for PropellerModel {
                             }
                         },
                         add_row = &ActionRow {
-                            set_title: "死区下限",
+                            set_title: tr("死区下限"),
                             add_suffix = &SpinButton::with_range(-128.0, 127.0, 1.0) {
+                                set_snap_to_ticks: true,
+                                set_update_policy: SpinButtonUpdatePolicy::IfValid,
+                                set_range: track!(self.changed(PropellerModel::ranges()), self.ranges.deadzone_lower.min, self.ranges.deadzone_lower.max),
+                                set_increments: track!(self.changed(PropellerModel::ranges()), self.ranges.deadzone_lower.step, self.ranges.deadzone_lower.step * 10.0),
                                 set_value: track!(self.changed(PropellerModel::deadzone_lower()), *self.get_deadzone_lower() as f64),
-                                set_digits: 0,
+                                set_digits: track!(self.changed(PropellerModel::ranges()), self.ranges.deadzone_lower.digits()),
                                 set_valign: Align::Center,
                                 connect_value_changed(key, sender) => move |button| {
                                     send!(sender, SlaveParameterTunerMsg::SetPropellerLowerDeadzone(key, button.value() as i8));
@@ -366,7 +738,9 @@ impl FactoryPrototype for PropellerModel {
                         add_row = &ActionRow {
                             set_child = Some(&Scale::with_range(Orientation::Horizontal, -128.0, 127.0, 1.0)) {
                                 set_width_request: CARD_MIN_WIDTH,
-                                set_round_digits: 0,
+                                set_round_digits: track!(self.changed(PropellerModel::ranges()), self.ranges.deadzone_lower.digits() as i32),
+                                set_range: track!(self.changed(PropellerModel::ranges()), self.ranges.deadzone_lower.min, self.ranges.deadzone_lower.max),
+                                set_increments: track!(self.changed(PropellerModel::ranges()), self.ranges.deadzone_lower.step, self.ranges.deadzone_lower.step * 10.0),
                                 set_value: track!(self.changed(PropellerModel::deadzone_lower()), *self.get_deadzone_lower() as f64),
                                 connect_value_changed(key, sender) => move |scale| {
                                     send!(sender, SlaveParameterTunerMsg::SetPropellerLowerDeadzone(key, scale.value() as i8));
@@ -410,8 +784,12 @@ impl FactoryPrototype for ControlLoopModel {
                     add = &ActionRow {
                         set_title: "P",
                         add_suffix = &SpinButton::with_range(0.0, 100.0, 0.01) {
+                            set_snap_to_ticks: true,
+                            set_update_policy: SpinButtonUpdatePolicy::IfValid,
+                            set_range: track!(self.changed(ControlLoopModel::ranges()), self.ranges.p.min, self.ranges.p.max),
+                            set_increments: track!(self.changed(ControlLoopModel::ranges()), self.ranges.p.step, self.ranges.p.step * 10.0),
                             set_value: track!(self.changed(ControlLoopModel::p()), *self.get_p()),
-                            set_digits: 2,
+                            set_digits: track!(self.changed(ControlLoopModel::ranges()), self.ranges.p.digits()),
                             set_valign: Align::Center,
                             connect_value_changed(key, sender) => move |button| {
                                 send!(sender, SlaveParameterTunerMsg::SetP(key, button.value()));
@@ -421,7 +799,9 @@ impl FactoryPrototype for ControlLoopModel {
                     add = &ActionRow {
                         set_child = Some(&Scale::with_range(Orientation::Horizontal, 0.0, 100.0, 0.01)) {
                             set_width_request: CARD_MIN_WIDTH,
-                            set_round_digits: 2,
+                            set_round_digits: track!(self.changed(ControlLoopModel::ranges()), self.ranges.p.digits() as i32),
+                            set_range: track!(self.changed(ControlLoopModel::ranges()), self.ranges.p.min, self.ranges.p.max),
+                            set_increments: track!(self.changed(ControlLoopModel::ranges()), self.ranges.p.step, self.ranges.p.step * 10.0),
                             set_value: track!(self.changed(ControlLoopModel::p()), *self.get_p()),
                             connect_value_changed(key, sender) => move |scale| {
                                 send!(sender, SlaveParameterTunerMsg::SetP(key, scale.value()));
@@ -433,8 +813,12 @@ impl FactoryPrototype for ControlLoopModel {
                     add = &ActionRow {
                         set_title: "I",
                         add_suffix = &SpinButton::with_range(0.0, 100.0, 0.01) {
+                            set_snap_to_ticks: true,
+                            set_update_policy: SpinButtonUpdatePolicy::IfValid,
+                            set_range: track!(self.changed(ControlLoopModel::ranges()), self.ranges.i.min, self.ranges.i.max),
+                            set_increments: track!(self.changed(ControlLoopModel::ranges()), self.ranges.i.step, self.ranges.i.step * 10.0),
                             set_value: track!(self.changed(ControlLoopModel::i()), *self.get_i()),
-                            set_digits: 2,
+                            set_digits: track!(self.changed(ControlLoopModel::ranges()), self.ranges.i.digits()),
                             set_valign: Align::Center,
                             connect_value_changed(key, sender) => move |button| {
                                 send!(sender, SlaveParameterTunerMsg::SetI(key, button.value()));
@@ -444,7 +828,9 @@ impl FactoryPrototype for ControlLoopModel {
                     add = &ActionRow {
                         set_child = Some(&Scale::with_range(Orientation::Horizontal, 0.0, 100.0, 0.01)) {
                             set_width_request: CARD_MIN_WIDTH,
-                            set_round_digits: 2,
+                            set_round_digits: track!(self.changed(ControlLoopModel::ranges()), self.ranges.i.digits() as i32),
+                            set_range: track!(self.changed(ControlLoopModel::ranges()), self.ranges.i.min, self.ranges.i.max),
+                            set_increments: track!(self.changed(ControlLoopModel::ranges()), self.ranges.i.step, self.ranges.i.step * 10.0),
                             set_value: track!(self.changed(ControlLoopModel::i()), *self.get_i()),
                             connect_value_changed(key, sender) => move |scale| {
                                 send!(sender, SlaveParameterTunerMsg::SetI(key, scale.value()));
@@ -456,8 +842,12 @@ impl FactoryPrototype for ControlLoopModel {
                     add = &ActionRow {
                         set_title: "D",
                         add_suffix = &SpinButton::with_range(0.0, 100.0, 0.01) {
+                            set_snap_to_ticks: true,
+                            set_update_policy: SpinButtonUpdatePolicy::IfValid,
+                            set_range: track!(self.changed(ControlLoopModel::ranges()), self.ranges.d.min, self.ranges.d.max),
+                            set_increments: track!(self.changed(ControlLoopModel::ranges()), self.ranges.d.step, self.ranges.d.step * 10.0),
                             set_value: track!(self.changed(ControlLoopModel::d()), *self.get_d()),
-                            set_digits: 2,
+                            set_digits: track!(self.changed(ControlLoopModel::ranges()), self.ranges.d.digits()),
                             set_valign: Align::Center,
                             connect_value_changed(key, sender) => move |button| {
                                 send!(sender, SlaveParameterTunerMsg::SetD(key, button.value()));
@@ -467,7 +857,9 @@ impl FactoryPrototype for ControlLoopModel {
                     add = &ActionRow {
                         set_child = Some(&Scale::with_range(Orientation::Horizontal, 0.0, 100.0, 0.01)) {
                             set_width_request: CARD_MIN_WIDTH,
-                            set_round_digits: 2,
+                            set_round_digits: track!(self.changed(ControlLoopModel::ranges()), self.ranges.d.digits() as i32),
+                            set_range: track!(self.changed(ControlLoopModel::ranges()), self.ranges.d.min, self.ranges.d.max),
+                            set_increments: track!(self.changed(ControlLoopModel::ranges()), self.ranges.d.step, self.ranges.d.step * 10.0),
                             set_value: track!(self.changed(ControlLoopModel::d()), *self.get_d()),
                             connect_value_changed(key, sender) => move |scale| {
                                 send!(sender, SlaveParameterTunerMsg::SetD(key, scale.value()));
@@ -484,7 +876,7 @@ impl FactoryPrototype for ControlLoopModel {
 
 impl SlaveParameterTunerModel {
     pub fn new(graph_view_point_num_limit: u16, graph_view_update_interval: u16) -> Self {
-        SlaveParameterTunerModel {
+        let mut model = SlaveParameterTunerModel {
             propellers: FactoryVec::from_vec(
                 DEFAULT_PROPELLERS
                     .iter()
@@ -500,7 +892,96 @@ impl SlaveParameterTunerModel {
             graph_view_point_num_limit,
             graph_view_update_interval,
             ..Default::default()
+        };
+        model.refresh_profile_names(None);
+        model
+    }
+
+    fn set_response_signal_and_notify(&mut self, signal: Option<String>) {
+        if let Some(msg_sender) = self.get_communication_msg_sender() {
+            msg_sender
+                .try_send(SlaveParameterTunerCommunicationMsg::SetResponseSignal(
+                    signal.clone(),
+                ))
+                .unwrap_or_default();
+        }
+        self.set_response_signal(signal);
+    }
+
+    fn selected_profile_name(&self) -> Option<&String> {
+        self.profile_names.get(self.selected_profile as usize)
+    }
+
+    fn current_parameters(&self) -> SlaveParameterTunerParameterPacket {
+        SlaveParameterTunerParameterPacket {
+            propeller_pwm_freq_calibration: self.propeller_pwm_frequency_calibration,
+            propeller_parameters: PropellerModel::vec_to_map(self.propellers.iter().collect()),
+            control_loop_parameters: ControlLoopModel::vec_to_map(
+                self.control_loops.iter().collect(),
+            ),
+        }
+    }
+
+    fn apply_parameters(&mut self, parameters: &SlaveParameterTunerParameterPacket) {
+        self.set_propeller_pwm_frequency_calibration(parameters.propeller_pwm_freq_calibration);
+        for index in 0..self.propellers.len() {
+            let propeller_model = self.propellers.get_mut(index).unwrap();
+            if let Some(propeller) = parameters.propeller_parameters.get(propeller_model.get_key()) {
+                propeller_model.set_deadzone_lower(
+                    propeller.deadzone_lower.min(propeller.deadzone_upper),
+                );
+                propeller_model.set_deadzone_upper(
+                    propeller.deadzone_upper.max(propeller.deadzone_lower),
+                );
+                propeller_model.set_power_positive(propeller.power_positive);
+                propeller_model.set_power_negative(propeller.power_negative);
+                propeller_model.set_reversed(propeller.reversed);
+                propeller_model.set_enabled(propeller.enabled);
+            }
+        }
+        for index in 0..self.control_loops.len() {
+            let control_loop_model = self.control_loops.get_mut(index).unwrap();
+            if let Some(control_loop) = parameters
+                .control_loop_parameters
+                .get(control_loop_model.get_key())
+            {
+                control_loop_model.set_p(control_loop.p);
+                control_loop_model.set_i(control_loop.i);
+                control_loop_model.set_d(control_loop.d);
+            }
+        }
+    }
+
+    fn refresh_profile_names(&mut self, selected: Option<&str>) {
+        let names = match list_parameter_profiles() {
+            Ok(names) => names,
+            Err(msg) => {
+                self.show_toast(msg);
+                Vec::new()
+            }
+        };
+        let position = selected
+            .and_then(|selected| names.iter().position(|name| name == selected))
+            .map_or(gtk::INVALID_LIST_POSITION, |position| position as u32);
+        self.set_profile_names(names);
+        self.set_selected_profile(position);
+    }
+
+    fn show_toast(&mut self, msg: String) {
+        self.get_mut_toast_messages().borrow_mut().push_back(msg);
+    }
+
+    fn check_range(&mut self, range: ParameterRange, value: f64) -> bool {
+        let accepted = range.contains(value);
+        if !accepted {
+            self.show_toast(format!(
+                "{}{} ~ {}",
+                tr("参数超出允许范围，未发送："),
+                range.min,
+                range.max
+            ));
         }
+        accepted
     }
 }
 
@@ -512,13 +993,13 @@ impl MicroWidgets<SlaveParameterTunerModel> for SlaveParameterTunerWidgets {
             set_modal: true,
             set_search_enabled: false,
             add = &PreferencesPage {
-                set_title: "推进器",
+                set_title: tr("推进器"),
                 set_icon_name: Some("weather-windy-symbolic"),
                 set_hexpand: true,
                 set_vexpand: true,
                 set_can_focus: false,
                 add: group_pwm = &PreferencesGroup {
-                    set_title: "PWM 控制器",
+                    set_title: tr("PWM 控制器"),
                     add = &FlowBox {
                         set_activate_on_single_click: false,
                         set_valign: Align::Start,
@@ -526,10 +1007,14 @@ impl MicroWidgets<SlaveParameterTunerModel> for SlaveParameterTunerWidgets {
                         set_selection_mode: SelectionMode::None,
                         insert(-1) = &PreferencesGroup {
                             add = &ActionRow {
-                                set_title: "频率校准",
+                                set_title: tr("频率校准"),
                                 add_suffix = &SpinButton::with_range(-0.1, 0.1, 0.0001) {
+                                    set_snap_to_ticks: true,
+                                    set_update_policy: SpinButtonUpdatePolicy::IfValid,
+                                    set_range: track!(model.changed(SlaveParameterTunerModel::parameter_ranges()), model.parameter_ranges.propeller_pwm_freq_calibration.min, model.parameter_ranges.propeller_pwm_freq_calibration.max),
+                                    set_increments: track!(model.changed(SlaveParameterTunerModel::parameter_ranges()), model.parameter_ranges.propeller_pwm_freq_calibration.step, model.parameter_ranges.propeller_pwm_freq_calibration.step * 10.0),
                                     set_value: track!(model.changed(SlaveParameterTunerModel::propeller_pwm_frequency_calibration()), *model.get_propeller_pwm_frequency_calibration() as f64),
-                                    set_digits: 4,
+                                    set_digits: track!(model.changed(SlaveParameterTunerModel::parameter_ranges()), model.parameter_ranges.propeller_pwm_freq_calibration.digits()),
                                     set_valign: Align::Center,
                                     connect_value_changed(sender) => move |button| {
                                         send!(sender, SlaveParameterTunerMsg::SetPropellerPwmFreqCalibration(button.value()));
@@ -539,7 +1024,9 @@ impl MicroWidgets<SlaveParameterTunerModel> for SlaveParameterTunerWidgets {
                             add = &ActionRow {
                                 set_child = Some(&Scale::with_range(Orientation::Horizontal, -0.1, 0.1, 0.0001)) {
                                     set_width_request: CARD_MIN_WIDTH,
-                                    set_round_digits: 4,
+                                    set_round_digits: track!(model.changed(SlaveParameterTunerModel::parameter_ranges()), model.parameter_ranges.propeller_pwm_freq_calibration.digits() as i32),
+                                    set_range: track!(model.changed(SlaveParameterTunerModel::parameter_ranges()), model.parameter_ranges.propeller_pwm_freq_calibration.min, model.parameter_ranges.propeller_pwm_freq_calibration.max),
+                                    set_increments: track!(model.changed(SlaveParameterTunerModel::parameter_ranges()), model.parameter_ranges.propeller_pwm_freq_calibration.step, model.parameter_ranges.propeller_pwm_freq_calibration.step * 10.0),
                                     set_value: track!(model.changed(SlaveParameterTunerModel::propeller_pwm_frequency_calibration()), *model.get_propeller_pwm_frequency_calibration() as f64),
                                     connect_value_changed(sender) => move |scale| {
                                         send!(sender, SlaveParameterTunerMsg::SetPropellerPwmFreqCalibration(scale.value()));
@@ -550,7 +1037,7 @@ impl MicroWidgets<SlaveParameterTunerModel> for SlaveParameterTunerWidgets {
                     },
                 },
                 add: group_propeller = &PreferencesGroup {
-                    set_title: "推进器参数",
+                    set_title: tr("推进器参数"),
                     add = &FlowBox {
                         set_activate_on_single_click: false,
                         set_valign: Align::Start,
@@ -561,13 +1048,13 @@ impl MicroWidgets<SlaveParameterTunerModel> for SlaveParameterTunerWidgets {
                 },
             },
             add = &PreferencesPage {
-                set_title: "控制环",
+                set_title: tr("控制环"),
                 set_icon_name: Some("media-playlist-repeat-symbolic"),
                 set_hexpand: true,
                 set_vexpand: true,
                 set_can_focus: false,
                 add: group_pid = &PreferencesGroup {
-                    set_title: "PID 参数",
+                    set_title: tr("PID 参数"),
                     add = &FlowBox {
                         set_activate_on_single_click: false,
                         set_valign: Align::Start,
@@ -576,9 +1063,145 @@ impl MicroWidgets<SlaveParameterTunerModel> for SlaveParameterTunerWidgets {
                         factory!(model.control_loops)
                     },
                 },
+                add = &PreferencesGroup {
+                    set_title: tr("响应曲线"),
+                    set_description: Some(tr("实时绘制所选反馈信号的变化，下位机提供对应的设定值（信号名加 _setpoint）时将以虚线一并绘制")),
+                    add = &ComboRow {
+                        set_title: tr("信号"),
+                        set_model: track!(model.changed(SlaveParameterTunerModel::response_signals()), Some(&StringList::new(&model.get_response_signals().iter().map(|x| ControlLoopModel::key_to_string(x)).collect::<Vec<_>>()))),
+                        set_selected: track!(model.changed(SlaveParameterTunerModel::response_signals()) || model.changed(SlaveParameterTunerModel::response_signal()), model.get_response_signal().as_ref().and_then(|signal| model.get_response_signals().iter().position(|x| x == signal)).map_or(gtk::INVALID_LIST_POSITION, |x| x as u32)),
+                        connect_selected_notify(sender) => move |row| {
+                            send!(sender, SlaveParameterTunerMsg::SelectResponseSignal(row.selected()));
+                        },
+                    },
+                    add: response_plot_area = &DrawingArea {
+                        set_hexpand: true,
+                        set_content_height: 240,
+                        set_draw_func: {
+                            let response_plot = model.response_plot.clone();
+                            move |_area, cr, width, height| {
+                                if let Some(plot) = response_plot.borrow().as_ref() {
+                                    draw_response_plot(cr, plot, width as f64, height as f64);
+                                }
+                            }
+                        },
+                    },
+                    add = &ActionRow {
+                        set_title: tr("曲线控制"),
+                        add_suffix = &ToggleButton {
+                            set_valign: Align::Center,
+                            set_icon_name: "media-playback-pause-symbolic",
+                            set_tooltip_text: Some(tr("暂停")),
+                            set_active: track!(model.changed(SlaveParameterTunerModel::response_plot_paused()), *model.get_response_plot_paused()),
+                            connect_toggled(sender) => move |button| {
+                                send!(sender, SlaveParameterTunerMsg::SetResponsePlotPaused(button.is_active()));
+                            },
+                        },
+                        add_suffix = &Button {
+                            set_valign: Align::Center,
+                            set_icon_name: "edit-clear-all-symbolic",
+                            set_tooltip_text: Some(tr("清空")),
+                            connect_clicked(sender) => move |_button| {
+                                send!(sender, SlaveParameterTunerMsg::ClearResponsePlot);
+                            },
+                        },
+                    },
+                },
             },
+            add = &PreferencesPage {
+                set_title: tr("档案"),
+                set_icon_name: Some("document-properties-symbolic"),
+                set_can_focus: false,
+                add = &PreferencesGroup {
+                    set_title: tr("保存档案"),
+                    set_description: Some(tr("将当前的参数保存为档案，以便之后重新载入")),
+                    add = &ActionRow {
+                        set_title: tr("档案名称"),
+                        add_suffix = &Entry {
+                            set_valign: Align::Center,
+                            connect_changed(sender) => move |entry| {
+                                send!(sender, SlaveParameterTunerMsg::SetProfileName(entry.text().to_string()));
+                            },
+                        },
+                        add_suffix = &Button {
+                            set_label: tr("保存"),
+                            set_valign: Align::Center,
+                            set_sensitive: track!(model.changed(SlaveParameterTunerModel::profile_name()), is_valid_profile_name(model.get_profile_name())),
+                            connect_clicked(sender) => move |_button| {
+                                send!(sender, SlaveParameterTunerMsg::SaveProfile);
+                            },
+                        },
+                    },
+                },
+                add = &PreferencesGroup {
+                    set_title: tr("已保存的档案"),
+                    set_description: Some(tr("载入档案会将其中的参数逐项发送至下位机，被拒绝的参数将恢复原值")),
+                    add = &ComboRow {
+                        set_title: tr("档案"),
+                        set_model: track!(model.changed(SlaveParameterTunerModel::profile_names()), Some(&StringList::new(&model.get_profile_names().iter().map(String::as_str).collect::<Vec<_>>()))),
+                        set_selected: track!(model.changed(SlaveParameterTunerModel::selected_profile()), *model.get_selected_profile()),
+                        connect_selected_notify(sender) => move |row| {
+                            send!(sender, SlaveParameterTunerMsg::SelectProfile(row.selected()));
+                        },
+                    },
+                    add = &ActionRow {
+                        set_title: tr("操作"),
+                        add_suffix = &Button {
+                            set_label: tr("载入"),
+                            set_valign: Align::Center,
+                            set_css_classes: &["suggested-action"],
+                            set_sensitive: track!(model.changed(SlaveParameterTunerModel::selected_profile()) || model.changed(SlaveParameterTunerModel::profile_names()), model.selected_profile_name().is_some()),
+                            connect_clicked(sender) => move |_button| {
+                                send!(sender, SlaveParameterTunerMsg::LoadProfile);
+                            },
+                        },
+                        add_suffix = &Button {
+                            set_label: tr("导出"),
+                            set_valign: Align::Center,
+                            set_sensitive: track!(model.changed(SlaveParameterTunerModel::selected_profile()) || model.changed(SlaveParameterTunerModel::profile_names()), model.selected_profile_name().is_some()),
+                            connect_clicked(sender, window) => move |_button| {
+                                std::mem::forget(select_path(FileChooserAction::Save, &[], &window, clone!(@strong sender => move |path| {
+                                    if let Some(path) = path {
+                                        send!(sender, SlaveParameterTunerMsg::ExportProfile(path));
+                                    }
+                                })));
+                            },
+                        },
+                        add_suffix = &Button {
+                            set_label: tr("删除"),
+                            set_valign: Align::Center,
+                            set_css_classes: &["destructive-action"],
+                            set_sensitive: track!(model.changed(SlaveParameterTunerModel::selected_profile()) || model.changed(SlaveParameterTunerModel::profile_names()), model.selected_profile_name().is_some()),
+                            connect_clicked(sender, window) => move |_button| {
+                                confirm_message(tr("删除档案"), tr("确定要删除所选的参数档案吗？"), Some(&window), clone!(@strong sender => move || {
+                                    send!(sender, SlaveParameterTunerMsg::DeleteProfile);
+                                }));
+                            },
+                        },
+                    },
+                    add = &ActionRow {
+                        set_title: tr("导入档案"),
+                        set_subtitle: tr("从 JSON 文件导入他人分享的参数档案"),
+                        add_suffix = &Button {
+                            set_label: tr("浏览"),
+                            set_valign: Align::Center,
+                            connect_clicked(sender, window) => move |_button| {
+                                let filter = FileFilter::new();
+                                filter.add_suffix("json");
+                                filter.set_name(Some(tr("参数档案")));
+                                std::mem::forget(select_path(FileChooserAction::Open, &[filter], &window, clone!(@strong sender => move |path| {
+                                    if let Some(path) = path {
+                                        send!(sender, SlaveParameterTunerMsg::ImportProfile(path));
+                                    }
+                                })));
+                            },
+                        },
+                    },
+                },
+            },
+            add_toast?: watch!(model.get_toast_messages().borrow_mut().pop_front().map(|x| Toast::new(&x)).as_ref()),
             set_title: {
-                Some("参数调校")
+                Some(tr("参数调校"))
             },
             set_destroy: track!(model.changed(SlaveParameterTunerModel::stopped()), *model.get_stopped()),
             connect_close_request(sender) => move |_window| {
@@ -587,6 +1210,12 @@ impl MicroWidgets<SlaveParameterTunerModel> for SlaveParameterTunerWidgets {
             },
         }
     }
+    fn post_view() {
+        if model.changed(SlaveParameterTunerModel::response_plot()) {
+            self.response_plot_area.queue_draw();
+        }
+    }
+
     fn post_init() {
         let groups = [&group_propeller, &group_pid];
         let clamps = groups
@@ -619,7 +1248,7 @@ impl MicroWidgets<SlaveParameterTunerModel> for SlaveParameterTunerWidgets {
                             set_icon_name: Some("document-save-symbolic"),
                         },
                         append = &Label {
-                            set_label: "保存",
+                            set_label: tr("保存"),
                         },
                     },
                     connect_clicked(sender) => move |_button| {
@@ -635,7 +1264,7 @@ impl MicroWidgets<SlaveParameterTunerModel> for SlaveParameterTunerWidgets {
                             set_icon_name: Some("view-refresh-symbolic"),
                         },
                         append = &Label {
-                            set_label: "读取",
+                            set_label: tr("读取"),
                         },
                     },
                     connect_clicked(sender) => move |_button| {
@@ -663,12 +1292,29 @@ pub struct SlaveParameterTunerParameterPacket {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SlaveParameterTunerFeedbackPacket {
     control_loops: HashMap<String, f32>,
+    #[serde(default)]
+    signals: HashMap<String, f32>, // 其他遥测信号，例如深度及其设定值
+}
+
+impl SlaveParameterTunerFeedbackPacket {
+    fn values(&self) -> HashMap<String, f32> {
+        self.control_loops
+            .iter()
+            .chain(self.signals.iter())
+            .map(|(key, &value)| (key.clone(), value))
+            .collect()
+    }
 }
 
 #[derive(Debug)]
 enum SlaveParameterTunerCommunicationMsg {
     UploadParameters(SlaveParameterTunerParameterPacket),
+    ApplyProfile(SlaveParameterTunerParameterPacket),
     RequestParameters,
+    RequestParameterRanges,
+    SetResponseSignal(Option<String>),
+    SetResponsePlotPaused(bool),
+    ClearResponsePlot,
     SetDebugModeEnabled(bool),
     PreviewPropeller(String, i8),
     PreviewPropellers(HashMap<String, i8>),
@@ -700,11 +1346,17 @@ async fn parameter_tuner_main_loop(
         async_std::sync::Arc::new(async_std::sync::Mutex::new(
             HashMap::<String, ControlLoop>::new(),
         ));
+    let response_recorder =
+        async_std::sync::Arc::new(async_std::sync::Mutex::new(ResponseRecorder::default()));
+    let start_instant = Instant::now();
     let receive_task = task::spawn(
-        clone!(@strong rpc_client, @strong model_sender, @strong communication_sender => async move {
+        clone!(@strong rpc_client, @strong model_sender, @strong communication_sender, @strong response_recorder => async move {
             loop {
                 match rpc_client.request::<SlaveParameterTunerFeedbackPacket>(METHOD_GET_FEEDBACKS, None).await {
-                    Ok(packet) => send!(model_sender, SlaveParameterTunerMsg::FeedbacksReceived(packet)),
+                    Ok(packet) => {
+                        response_recorder.lock().await.push(start_instant.elapsed().as_secs_f64(), packet.values());
+                        send!(model_sender, SlaveParameterTunerMsg::FeedbacksReceived(packet))
+                    },
                     Err(err) => communication_sender.send(SlaveParameterTunerCommunicationMsg::ConnectionLost(err)).await.unwrap_or_default(),
                 }
                 task::sleep(Duration::from_millis(graph_view_update_interval)).await;
@@ -712,6 +1364,25 @@ async fn parameter_tuner_main_loop(
         }),
     );
 
+    let response_plot_task = task::spawn(
+        clone!(@strong model_sender, @strong response_recorder => async move {
+            let mut signal_names = Vec::new();
+            loop {
+                task::sleep(Duration::from_millis(graph_view_update_interval)).await;
+                let mut recorder = response_recorder.lock().await;
+                let names = recorder.signal_names();
+                if names != signal_names {
+                    signal_names = names.clone();
+                    send!(model_sender, SlaveParameterTunerMsg::ResponseSignalsUpdated(names));
+                }
+                if recorder.dirty {
+                    recorder.dirty = false;
+                    send!(model_sender, SlaveParameterTunerMsg::ResponsePlotUpdated(recorder.plot()));
+                }
+            }
+        }),
+    );
+
     let parameter_preview_task = task::spawn(
         clone!(@strong communication_sender, @strong preview_propellers_value, @strong preview_control_loops => async move {
             loop {
@@ -751,6 +1422,10 @@ async fn parameter_tuner_main_loop(
         }),
     );
 
+    communication_sender
+        .send(SlaveParameterTunerCommunicationMsg::RequestParameterRanges)
+        .await
+        .unwrap_or_default();
     communication_sender
         .send(SlaveParameterTunerCommunicationMsg::RequestParameters)
         .await
@@ -795,6 +1470,81 @@ async fn parameter_tuner_main_loop(
                         }
                     };
                 }
+                SlaveParameterTunerCommunicationMsg::SetResponseSignal(signal) => {
+                    let mut recorder = response_recorder.lock().await;
+                    recorder.signal = signal;
+                    recorder.dirty = true;
+                }
+                SlaveParameterTunerCommunicationMsg::SetResponsePlotPaused(paused) => {
+                    response_recorder.lock().await.paused = paused;
+                }
+                SlaveParameterTunerCommunicationMsg::ClearResponsePlot => {
+                    response_recorder.lock().await.clear();
+                }
+                SlaveParameterTunerCommunicationMsg::RequestParameterRanges => {
+                    match rpc_client
+                        .request::<ParameterRanges>(METHOD_GET_PARAMETER_RANGES, None)
+                        .await
+                    {
+                        Ok(ranges) => send!(
+                            model_sender,
+                            SlaveParameterTunerMsg::ParameterRangesReceived(ranges.sanitize())
+                        ),
+                        Err(jsonrpsee_core::Error::Call(_)) => (), // 下位机不支持时使用默认范围
+                        Err(err) => communication_sender
+                            .send(SlaveParameterTunerCommunicationMsg::ConnectionLost(err))
+                            .await
+                            .unwrap_or_default(),
+                    }
+                }
+                SlaveParameterTunerCommunicationMsg::ApplyProfile(profile) => {
+                    // 逐项发送参数，以确定下位机拒绝了哪些参数
+                    let mut requests: Vec<(ParameterKey, &'static str, RpcParams)> = vec![(
+                        ParameterKey::PropellerPwmFreqCalibration,
+                        METHOD_SET_PROPELLER_PWM_FREQ_CALIBRATION,
+                        profile.propeller_pwm_freq_calibration.to_rpc_params(),
+                    )];
+                    requests.extend(profile.propeller_parameters.into_iter().map(
+                        |(key, propeller)| {
+                            let params = HashMap::from([(key.clone(), propeller)]).to_rpc_params();
+                            (ParameterKey::Propeller(key), METHOD_SET_PROPELLER_PARAMETERS, params)
+                        },
+                    ));
+                    requests.extend(profile.control_loop_parameters.into_iter().map(
+                        |(key, control_loop)| {
+                            let params = HashMap::from([(key.clone(), control_loop)]).to_rpc_params();
+                            (ParameterKey::ControlLoop(key), METHOD_SET_CONTROL_LOOP_PARAMETERS, params)
+                        },
+                    ));
+                    let mut rejected = Vec::new();
+                    let mut connection_error = None;
+                    for (key, method, params) in requests {
+                        match rpc_client.request::<()>(method, Some(params)).await {
+                            Ok(_) => (),
+                            Err(jsonrpsee_core::Error::Call(_)) => rejected.push(key),
+                            Err(err) => {
+                                connection_error = Some(err);
+                                break;
+                            }
+                        }
+                    }
+                    // 与手动应用参数相同，保存参数以免下位机重启后丢失，下位机拒绝保存时连接仍然可用
+                    let result = match connection_error {
+                        Some(err) => Err(err),
+                        None => match rpc_client.request::<()>(METHOD_SAVE_PARAMETERS, None).await {
+                            Ok(_) => Ok(false),
+                            Err(jsonrpsee_core::Error::Call(_)) => Ok(true),
+                            Err(err) => Err(err),
+                        },
+                    };
+                    match result {
+                        Ok(save_rejected) => send!(model_sender, SlaveParameterTunerMsg::ProfileApplied(rejected, save_rejected)),
+                        Err(err) => communication_sender
+                            .send(SlaveParameterTunerCommunicationMsg::ConnectionLost(err))
+                            .await
+                            .unwrap_or_default(),
+                    }
+                }
                 SlaveParameterTunerCommunicationMsg::RequestParameters => {
                     match rpc_client
                         .request::<SlaveParameterTunerParameterPacket>(METHOD_LOAD_PARAMETERS, None)
@@ -816,6 +1566,7 @@ async fn parameter_tuner_main_loop(
                 }
                 SlaveParameterTunerCommunicationMsg::Terminate(error) => {
                     receive_task.cancel().await;
+                    response_plot_task.cancel().await;
                     parameter_preview_task.cancel().await;
                     stop_propeller_preview_task.cancel().await;
                     match error {
@@ -900,6 +1651,9 @@ impl MicroModel for SlaveParameterTunerModel {
 
         match msg {
             SlaveParameterTunerMsg::SetPropellerLowerDeadzone(index, value) => {
+                if !self.check_range(self.parameter_ranges.deadzone_lower, value as f64) {
+                    return;
+                }
                 if let Some(propeller) = self.propellers.get_mut(index) {
                     propeller.reset();
                     propeller.set_deadzone_lower(value);
@@ -918,6 +1672,9 @@ impl MicroModel for SlaveParameterTunerModel {
                 }
             }
             SlaveParameterTunerMsg::SetPropellerUpperDeadzone(index, value) => {
+                if !self.check_range(self.parameter_ranges.deadzone_upper, value as f64) {
+                    return;
+                }
                 if let Some(propeller) = self.propellers.get_mut(index) {
                     propeller.reset();
                     propeller.set_deadzone_upper(value);
@@ -936,12 +1693,18 @@ impl MicroModel for SlaveParameterTunerModel {
                 }
             }
             SlaveParameterTunerMsg::SetPropellerPowerPositive(index, value) => {
+                if !self.check_range(self.parameter_ranges.power_positive, value) {
+                    return;
+                }
                 if let Some(propeller) = self.propellers.get_mut(index) {
                     propeller.reset();
                     propeller.set_power_positive(value);
                 }
             }
             SlaveParameterTunerMsg::SetPropellerPowerNegative(index, value) => {
+                if !self.check_range(self.parameter_ranges.power_negative, value) {
+                    return;
+                }
                 if let Some(propeller) = self.propellers.get_mut(index) {
                     propeller.reset();
                     propeller.set_power_negative(value);
@@ -960,6 +1723,9 @@ impl MicroModel for SlaveParameterTunerModel {
                 }
             }
             SlaveParameterTunerMsg::SetP(index, value) => {
+                if !self.check_range(self.parameter_ranges.p, value) {
+                    return;
+                }
                 if let Some(pids) = self.control_loops.get_mut(index) {
                     pids.reset();
                     pids.set_p(value);
@@ -977,6 +1743,9 @@ impl MicroModel for SlaveParameterTunerModel {
                 }
             }
             SlaveParameterTunerMsg::SetI(index, value) => {
+                if !self.check_range(self.parameter_ranges.i, value) {
+                    return;
+                }
                 if let Some(pids) = self.control_loops.get_mut(index) {
                     pids.reset();
                     pids.set_i(value);
@@ -994,6 +1763,9 @@ impl MicroModel for SlaveParameterTunerModel {
                 }
             }
             SlaveParameterTunerMsg::SetD(index, value) => {
+                if !self.check_range(self.parameter_ranges.d, value) {
+                    return;
+                }
                 if let Some(pids) = self.control_loops.get_mut(index) {
                     pids.reset();
                     pids.set_d(value);
@@ -1021,16 +1793,7 @@ impl MicroModel for SlaveParameterTunerModel {
                 if let Some(msg_sender) = self.get_communication_msg_sender() {
                     msg_sender
                         .try_send(SlaveParameterTunerCommunicationMsg::UploadParameters(
-                            SlaveParameterTunerParameterPacket {
-                                propeller_pwm_freq_calibration: self
-                                    .propeller_pwm_frequency_calibration,
-                                propeller_parameters: PropellerModel::vec_to_map(
-                                    self.propellers.iter().collect(),
-                                ),
-                                control_loop_parameters: ControlLoopModel::vec_to_map(
-                                    self.control_loops.iter().collect(),
-                                ),
-                            },
+                            self.current_parameters(),
                         ))
                         .unwrap_or_default();
                 }
@@ -1046,7 +1809,8 @@ impl MicroModel for SlaveParameterTunerModel {
                     ))
                     .unwrap_or_default();
                 let graph_view_update_interval = self.graph_view_update_interval;
-                let handle = task::spawn(async move {
+                // 调试在通讯主循环暂停状态信息请求与控制指令发送后才开始，直到调试结束才恢复
+                let blocker = Box::pin(async move {
                     parameter_tuner_main_loop(
                         rpc_client,
                         communication_sender,
@@ -1059,7 +1823,7 @@ impl MicroModel for SlaveParameterTunerModel {
                 });
                 send!(
                     parent_sender,
-                    SlaveMsg::CommunicationMessage(SlaveCommunicationMsg::Block(handle))
+                    SlaveMsg::CommunicationMessage(SlaveCommunicationMsg::Block(blocker))
                 );
             }
             SlaveParameterTunerMsg::StopDebug(error) => {
@@ -1076,6 +1840,15 @@ impl MicroModel for SlaveParameterTunerModel {
                     self.set_stopped(true);
                 }
             }
+            SlaveParameterTunerMsg::ConnectionClosed => {
+                if self.get_communication_msg_sender().is_some() {
+                    send!(
+                        parent_sender,
+                        SlaveMsg::ShowToastMessage(String::from(tr("下位机已断开连接，参数调校已关闭，请在重新连接后重新打开。")))
+                    );
+                    send!(sender, SlaveParameterTunerMsg::StopDebug(None));
+                }
+            }
             SlaveParameterTunerMsg::FeedbacksReceived(SlaveParameterTunerFeedbackPacket {
                 control_loops,
             }) => {
@@ -1093,37 +1866,184 @@ impl MicroModel for SlaveParameterTunerModel {
                     }
                 }
             }
-            SlaveParameterTunerMsg::ParametersReceived(SlaveParameterTunerParameterPacket {
-                propeller_pwm_freq_calibration: pwm_freq_calibration,
-                propeller_parameters: propellers,
-                control_loop_parameters: control_loops,
-            }) => {
-                self.set_propeller_pwm_frequency_calibration(pwm_freq_calibration);
+            SlaveParameterTunerMsg::ParametersReceived(parameters) => {
+                self.apply_parameters(&parameters);
+            }
+            SlaveParameterTunerMsg::ParameterRangesReceived(ranges) => {
                 for index in 0..self.propellers.len() {
-                    let propeller_model = self.propellers.get_mut(index).unwrap();
-                    if let Some(propeller) = propellers.get(propeller_model.get_key()) {
-                        propeller_model.set_deadzone_lower(
-                            propeller.deadzone_lower.min(propeller.deadzone_upper),
-                        );
-                        propeller_model.set_deadzone_upper(
-                            propeller.deadzone_upper.max(propeller.deadzone_lower),
-                        );
-                        propeller_model.set_power_positive(propeller.power_positive);
-                        propeller_model.set_power_negative(propeller.power_negative);
-                        propeller_model.set_reversed(propeller.reversed);
-                        propeller_model.set_enabled(propeller.enabled);
-                    }
+                    self.propellers.get_mut(index).unwrap().set_ranges(ranges);
                 }
                 for index in 0..self.control_loops.len() {
-                    let control_loop_model = self.control_loops.get_mut(index).unwrap();
-                    if let Some(control_loop) = control_loops.get(control_loop_model.get_key()) {
-                        control_loop_model.set_p(control_loop.p);
-                        control_loop_model.set_i(control_loop.i);
-                        control_loop_model.set_d(control_loop.d);
+                    self.control_loops.get_mut(index).unwrap().set_ranges(ranges);
+                }
+                self.set_parameter_ranges(ranges);
+            }
+            SlaveParameterTunerMsg::ResponseSignalsUpdated(signals) => {
+                let keep_signal = self
+                    .get_response_signal()
+                    .as_ref()
+                    .map_or(false, |signal| signals.contains(signal));
+                let first_signal = signals.first().cloned();
+                self.set_response_signals(signals);
+                if !keep_signal {
+                    self.set_response_signal_and_notify(first_signal);
+                }
+            }
+            SlaveParameterTunerMsg::ResponsePlotUpdated(plot) => {
+                *self.get_mut_response_plot().borrow_mut() = plot;
+            }
+            SlaveParameterTunerMsg::SelectResponseSignal(position) => {
+                if let Some(signal) = self.get_response_signals().get(position as usize).cloned() {
+                    if self.get_response_signal().as_ref() != Some(&signal) {
+                        self.set_response_signal_and_notify(Some(signal));
+                    }
+                }
+            }
+            SlaveParameterTunerMsg::SetResponsePlotPaused(paused) => {
+                if let Some(msg_sender) = self.get_communication_msg_sender() {
+                    msg_sender
+                        .try_send(SlaveParameterTunerCommunicationMsg::SetResponsePlotPaused(
+                            paused,
+                        ))
+                        .unwrap_or_default();
+                }
+                self.set_response_plot_paused(paused);
+            }
+            SlaveParameterTunerMsg::ClearResponsePlot => {
+                if let Some(msg_sender) = self.get_communication_msg_sender() {
+                    msg_sender
+                        .try_send(SlaveParameterTunerCommunicationMsg::ClearResponsePlot)
+                        .unwrap_or_default();
+                }
+            }
+            SlaveParameterTunerMsg::SetProfileName(name) => {
+                self.set_profile_name(name);
+            }
+            SlaveParameterTunerMsg::SelectProfile(position) => {
+                self.set_selected_profile(position);
+            }
+            SlaveParameterTunerMsg::SaveProfile => {
+                let name = self.get_profile_name().trim().to_string();
+                if is_valid_profile_name(&name) {
+                    match parameter_profile_file(&name).and_then(|path| write_parameter_profile(&path, &self.current_parameters())) {
+                        Ok(_) => {
+                            self.show_toast(format!("{}{}", tr("参数档案已保存："), name));
+                            self.refresh_profile_names(Some(&name));
+                        }
+                        Err(msg) => self.show_toast(msg),
                     }
                 }
             }
+            SlaveParameterTunerMsg::LoadProfile => {
+                if let Some(name) = self.selected_profile_name().cloned() {
+                    match parameter_profile_file(&name).and_then(|path| read_parameter_profile(&path)) {
+                        Ok(profile) if !self.parameter_ranges.accepts(&profile) => {
+                            self.show_toast(tr("档案中的参数超出允许范围，已取消载入").to_string());
+                        }
+                        // 未开始调试时无法将参数发送至下位机，不更新界面以免显示下位机并未使用的参数
+                        Ok(_) if self.get_communication_msg_sender().is_none() => {
+                            self.show_toast(tr("请先开始调试再载入参数档案").to_string());
+                        }
+                        Ok(profile) => {
+                            if let Some(msg_sender) = self.get_communication_msg_sender() {
+                                msg_sender
+                                    .try_send(SlaveParameterTunerCommunicationMsg::ApplyProfile(
+                                        profile.clone(),
+                                    ))
+                                    .unwrap_or_default();
+                            }
+                            self.set_previous_parameters(Some(self.current_parameters()));
+                            self.apply_parameters(&profile);
+                        }
+                        Err(msg) => self.show_toast(msg),
+                    }
+                }
+            }
+            SlaveParameterTunerMsg::ProfileApplied(rejected, save_rejected) => {
+                if save_rejected {
+                    self.show_toast(tr("下位机拒绝保存参数，重启后将恢复原有参数").to_string());
+                }
+                if let Some(previous) = self.get_mut_previous_parameters().take() {
+                    if rejected.is_empty() {
+                        self.show_toast(tr("下位机已接受档案中的全部参数").to_string());
+                    } else {
+                        // 将被拒绝的参数恢复为载入档案前的值
+                        let mut revert = SlaveParameterTunerParameterPacket {
+                            propeller_pwm_freq_calibration: self.propeller_pwm_frequency_calibration,
+                            propeller_parameters: HashMap::new(),
+                            control_loop_parameters: HashMap::new(),
+                        };
+                        for key in &rejected {
+                            match key {
+                                ParameterKey::PropellerPwmFreqCalibration => {
+                                    revert.propeller_pwm_freq_calibration =
+                                        previous.propeller_pwm_freq_calibration
+                                }
+                                ParameterKey::Propeller(key) => {
+                                    if let Some(propeller) = previous.propeller_parameters.get(key) {
+                                        revert.propeller_parameters.insert(key.clone(), propeller.clone());
+                                    }
+                                }
+                                ParameterKey::ControlLoop(key) => {
+                                    if let Some(control_loop) = previous.control_loop_parameters.get(key) {
+                                        revert.control_loop_parameters.insert(key.clone(), control_loop.clone());
+                                    }
+                                }
+                            }
+                        }
+                        self.apply_parameters(&revert);
+                        self.show_toast(format!("{}{}", tr("下位机拒绝的参数数量："), rejected.len()));
+                    }
+                }
+            }
+            SlaveParameterTunerMsg::DeleteProfile => {
+                if let Some(name) = self.selected_profile_name().cloned() {
+                    let result = parameter_profile_file(&name).and_then(|path| {
+                        fs::remove_file(path).map_err(|err| format!("{}{}", tr("无法删除参数档案："), err))
+                    });
+                    if let Err(msg) = result {
+                        self.show_toast(msg);
+                    }
+                    self.refresh_profile_names(None);
+                }
+            }
+            SlaveParameterTunerMsg::ExportProfile(mut path) => {
+                if let Some(name) = self.selected_profile_name().cloned() {
+                    if path.extension().is_none() {
+                        path.set_extension("json");
+                    }
+                    match parameter_profile_file(&name)
+                        .and_then(|profile_path| read_parameter_profile(&profile_path))
+                        .and_then(|profile| write_parameter_profile(&path, &profile))
+                    {
+                        Ok(_) => self.show_toast(format!("{}{}", tr("参数档案已导出至："), path.to_string_lossy())),
+                        Err(msg) => self.show_toast(msg),
+                    }
+                }
+            }
+            SlaveParameterTunerMsg::ImportProfile(path) => {
+                let name = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                match read_parameter_profile(&path).and_then(|profile| {
+                    if is_valid_profile_name(&name) {
+                        parameter_profile_file(&name).and_then(|path| write_parameter_profile(&path, &profile))
+                    } else {
+                        Err(tr("无效的档案名称").to_string())
+                    }
+                }) {
+                    Ok(_) => {
+                        self.show_toast(format!("{}{}", tr("参数档案已导入："), name));
+                        self.refresh_profile_names(Some(&name));
+                    }
+                    Err(msg) => self.show_toast(msg),
+                }
+            }
             SlaveParameterTunerMsg::SetPropellerPwmFreqCalibration(cal) => {
+                if !self.check_range(self.parameter_ranges.propeller_pwm_freq_calibration, cal) {
+                    return;
+                }
                 self.set_propeller_pwm_frequency_calibration(cal);
             }
         }
@@ -23,6 +23,16 @@ pub const METHOD_SET_DEPTH_LOCKED: &'static str = "set_depth_locked"; // 开启/
 pub const METHOD_SET_DIRECTION_LOCKED: &'static str = "set_direction_locked"; // 开启/关闭方向锁定
 pub const METHOD_CATCH: &'static str = "catch"; // 控制机械臂张合
 pub const METHOD_LIGHT: &'static str = "light"; // 控制灯的亮灭
+pub const METHOD_CONTROL: &'static str = "control"; // 一次性发送完整的控制数据包
+
+// 锁定时用作设定值的信息项，下位机可通过 *_setpoint 信息项报告实际采用的设定值
+pub const INFO_KEYS_DEPTH: [&'static str; 2] = ["depth", "深度"];
+pub const INFO_KEYS_HEADING: [&'static str; 2] = ["heading", "航向角"];
+pub const INFO_KEY_DEPTH_SETPOINT: &'static str = "depth_setpoint";
+pub const INFO_KEY_HEADING_SETPOINT: &'static str = "heading_setpoint";
+// 下位机报告实际锁定状态的信息项，可在首选项中修改
+pub const INFO_KEY_DEPTH_LOCKED: &'static str = "depth_locked";
+pub const INFO_KEY_DIRECTION_LOCKED: &'static str = "direction_locked";
 
 // 调试界面
 pub const METHOD_SET_DEBUG_MODE_ENABLED: &'static str = "set_debug_mode_enabled"; // 开启/关闭调试模式
@@ -34,5 +44,6 @@ pub const METHOD_SET_CONTROL_LOOP_PARAMETERS: &'static str = "set_control_loop_p
 pub const METHOD_SAVE_PARAMETERS: &'static str = "save_parameters"; // 保存参数
 pub const METHOD_LOAD_PARAMETERS: &'static str = "load_parameters"; // 读取参数
 pub const METHOD_SET_PROPELLER_VALUES: &'static str = "set_propeller_values";
+pub const METHOD_GET_PARAMETER_RANGES: &'static str = "get_parameter_ranges"; // 获取参数的取值范围与步长
 // 固件更新界面
 pub const METHOD_UPDATE_FIRMWARE: &'static str = "update_firmware"; // 固件更新
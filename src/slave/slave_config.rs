@@ -16,30 +16,182 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use std::{fmt::Debug, str::FromStr};
+use std::{fmt::Debug, fs, path::PathBuf};
 
 use adw::{prelude::*, ActionRow, ComboRow, ExpanderRow, PreferencesGroup};
 use glib::Sender;
 use gtk::{
-    Align, Box as GtkBox, Entry, Inhibit, Label, Orientation, ScrolledWindow, Separator,
+    Align, Box as GtkBox, Button, Entry, Frame, Inhibit, Label, MenuButton, Orientation, Popover, ScrolledWindow, Separator,
     SpinButton, StringList, Switch, Viewport,
 };
 use relm4::{send, MicroModel, MicroWidgets, WidgetPlus};
 use relm4_macros::micro_widget;
 
 use derivative::*;
+use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 use url::Url;
 
 use super::{
-    video::{VideoAlgorithm, VideoEncoder},
-    SlaveMsg,
+    video::{
+        AppSinkQueueMode, DenoiseMode, RtspProtocol, SubtitleFormat, VideoAlgorithm, VideoEncoder, VideoFlip, VideoOrientation, VideoRegion, PROCESSING_HEIGHTS,
+        VideoRotation, VIDEO_LATENCY_MAX,
+    },
+    connection_profile::{connection_profiles_list_box, ConnectionProfile},
+    custom_command::CustomCommand,
+    thruster_mix::ThrusterMix,
+    ControlSendingMode, ControlValueEncoding, IdleTimeoutAction, MotionPacket, SlaveMsg,
 };
 use crate::{
-    preferences::PreferencesModel,
+    i18n::tr,
+    preferences::{get_data_path, PreferencesModel},
     slave::video::{ColorspaceConversion, VideoCodec, VideoCodecProvider, VideoDecoder},
 };
 
+pub const SLAVE_URL_FORMAT_HINT: &str = "格式为 http://主机:端口 或 https://主机[:端口]";
+// 与下位机通讯的 HTTP 客户端支持的协议，https 使用 TLS 加密
+pub const SLAVE_URL_SCHEMES: &[&str] = &["http", "https"];
+pub const VIDEO_URL_FORMAT_HINT: &str = "格式为 rtp://主机:端口、udp://主机:端口 或 rtsp://主机[:端口]/路径";
+
+// 连接 URL 的协议不受支持时返回指明该协议的错误信息
+pub fn check_slave_url_scheme(url: &Url) -> Result<(), String> {
+    if SLAVE_URL_SCHEMES.contains(&url.scheme()) {
+        Ok(())
+    } else {
+        Err(format!("{}{}{}", tr("连接 URL 不支持 "), url.scheme(), tr(" 协议，仅支持 http 与 https 协议")))
+    }
+}
+
+pub fn parse_slave_url(text: &str) -> Result<Url, &'static str> {
+    let url = Url::parse(text).map_err(|_| tr("无法解析 URL"))?;
+    if check_slave_url_scheme(&url).is_err() {
+        return Err(tr("连接 URL 仅支持 http 与 https 协议"));
+    }
+    if url.host_str().is_none() {
+        return Err(tr("连接 URL 缺少主机地址"));
+    }
+    if url.port_or_known_default().is_none() {
+        return Err(tr("连接 URL 缺少端口"));
+    }
+    Ok(url)
+}
+
+// 与首选项中的输入发送率及状态信息更新时间间隔使用相同的取值范围
+pub const INPUT_SENDING_RATE_RANGE: (u16, u16) = (1, 1000);
+pub const STATUS_INFO_UPDATE_INTERVAL_RANGE: (u16, u16) = (50, 10000);
+
+// 空闲超时时间的取值范围与启用时的默认值（秒）
+pub const IDLE_TIMEOUT_RANGE: (u16, u16) = (5, 3600);
+pub const DEFAULT_IDLE_TIMEOUT: u16 = 60;
+
+// 重新编码录制时目标码率的取值范围与默认值（kbps）
+pub const RECORDING_BITRATE_RANGE: (u32, u32) = (500, 100000);
+pub const DEFAULT_RECORDING_BITRATE: u32 = 8000;
+
+fn clamp_recording_bitrate(bitrate: u32) -> u32 {
+    bitrate.clamp(RECORDING_BITRATE_RANGE.0, RECORDING_BITRATE_RANGE.1)
+}
+
+fn clamp_idle_timeout(timeout: u16) -> u16 {
+    timeout.clamp(IDLE_TIMEOUT_RANGE.0, IDLE_TIMEOUT_RANGE.1)
+}
+
+fn clamp_input_sending_rate(rate: u16) -> u16 {
+    rate.clamp(INPUT_SENDING_RATE_RANGE.0, INPUT_SENDING_RATE_RANGE.1)
+}
+
+fn clamp_status_info_update_interval(interval: u16) -> u16 {
+    interval.clamp(STATUS_INFO_UPDATE_INTERVAL_RANGE.0, STATUS_INFO_UPDATE_INTERVAL_RANGE.1)
+}
+
+pub fn parse_video_url(text: &str) -> Result<Url, &'static str> {
+    let url = Url::parse(text).map_err(|_| tr("无法解析 URL"))?;
+    if url.host_str().is_none() {
+        return Err(tr("视频 URL 缺少主机地址"));
+    }
+    match url.scheme() {
+        "rtp" | "udp" if url.port().is_none() => Err(tr("视频 URL 缺少端口")),
+        "rtp" | "udp" | "rtsp" => Ok(url),
+        _ => Err(tr("视频 URL 仅支持 rtp、udp 与 rtsp 协议")),
+    }
+}
+
+pub fn get_slave_session_path() -> PathBuf {
+    let mut path = get_data_path();
+    path.push("slaves.json");
+    path
+}
+
+// 机位的持久化配置，用于在下次启动时恢复机位
+#[derive(Serialize, Deserialize, Derivative, Clone, Debug, PartialEq)]
+#[derivative(Default)]
+#[serde(default)]
+pub struct SlaveSession {
+    #[derivative(Default(value = "PreferencesModel::default().default_slave_url"))]
+    pub slave_url: Url,
+    #[derivative(Default(value = "PreferencesModel::default().default_video_url"))]
+    pub video_url: Url,
+    pub auto_connect: bool,
+    pub auto_polling: bool,
+    pub control_sending_mode: ControlSendingMode,
+    pub control_value_encoding: ControlValueEncoding,
+    #[serde(deserialize_with = "deserialize_video_algorithms")]
+    pub video_algorithms: Vec<VideoAlgorithm>,
+    #[derivative(Default(value = "70"))]
+    pub white_balance_strength: u8,
+    #[derivative(Default(value = "85"))]
+    pub dehaze_strength: u8,
+    pub denoise_mode: DenoiseMode,
+    pub processing_height: Option<u32>,
+    pub thruster_mix: ThrusterMix,
+    pub custom_commands: Vec<CustomCommand>,
+    pub motion_trim: MotionPacket,
+    pub input_sending_rate: Option<u16>,
+    pub status_info_update_interval: Option<u16>,
+    pub idle_timeout: Option<u16>,
+    pub idle_timeout_action: IdleTimeoutAction,
+    pub secondary_video_url: Option<Url>,
+    pub record_secondary_video: bool,
+    // 未保存录制编码设置时（如由较早版本保存的机位）使用首选项中的默认值
+    pub reencode_recording_video: Option<bool>,
+    pub video_encoder: Option<VideoEncoder>,
+    #[derivative(Default(value = "DEFAULT_RECORDING_BITRATE"))]
+    pub recording_bitrate: u32,
+}
+
+// 跳过无法识别的增强算法（如由较新版本保存的算法），而不是放弃整个机位配置
+fn deserialize_video_algorithms<'de, D>(deserializer: D) -> Result<Vec<VideoAlgorithm>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let values: Vec<serde_json::Value> = Deserialize::deserialize(deserializer)?;
+    Ok(values
+        .into_iter()
+        .filter_map(|value| match serde_json::from_value::<VideoAlgorithm>(value.clone()) {
+            Ok(algorithm) => Some(algorithm),
+            Err(_) => {
+                log::warn!("忽略无法识别的增强算法：{}", value);
+                None
+            }
+        })
+        .collect())
+}
+
+impl SlaveSession {
+    pub fn load_all() -> Vec<SlaveSession> {
+        fs::read_to_string(get_slave_session_path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save_all(sessions: &[SlaveSession]) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(sessions)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+        fs::write(get_slave_session_path(), json)
+    }
+}
+
 #[tracker::track]
 #[derive(Debug, Derivative, PartialEq, Clone)]
 #[derivative(Default)]
@@ -53,6 +205,21 @@ pub struct SlaveConfigModel {
     #[derivative(Default(value = "PreferencesModel::default().default_video_url"))]
     pub video_url: Url,
     pub video_algorithms: Vec<VideoAlgorithm>,
+    #[derivative(Default(value = "70"))]
+    pub white_balance_strength: u8, // 百分比
+    #[derivative(Default(value = "85"))]
+    pub dehaze_strength: u8, // 百分比
+    pub denoise_mode: DenoiseMode,
+    pub processing_height: Option<u32>, // 增强算法处理画面时使用的高度，为 None 时使用原始分辨率
+    pub thruster_mix: ThrusterMix, // 仅用于推进器混控预览
+    pub custom_commands: Vec<CustomCommand>,
+    pub motion_trim: MotionPacket, // 叠加到运动输出上的配平偏移量
+    pub input_sending_rate: Option<u16>, // 为 None 时使用首选项中的输入发送率
+    pub status_info_update_interval: Option<u16>, // 单位为毫秒，为 None 时使用首选项中的设置
+    pub idle_timeout: Option<u16>, // 单位为秒，为 None 时不检测空闲超时
+    pub idle_timeout_action: IdleTimeoutAction,
+    pub secondary_video_url: Option<Url>, // 画中画显示的副摄像头，为 None 时不使用
+    pub record_secondary_video: bool, // 录制时同时录制副摄像头
     #[derivative(Default(value = "PreferencesModel::default().default_keep_video_display_ratio"))]
     pub keep_video_display_ratio: bool,
     #[derivative(Default(value = "PreferencesModel::default().default_video_decoder"))]
@@ -65,12 +232,34 @@ pub struct SlaveConfigModel {
     pub use_decodebin: bool,
     pub video_encoder: VideoEncoder,
     pub reencode_recording_video: bool,
-    #[derivative(Default(
-        value = "PreferencesModel::default().default_appsink_queue_leaky_enabled"
-    ))]
-    pub appsink_queue_leaky_enabled: bool,
+    #[derivative(Default(value = "DEFAULT_RECORDING_BITRATE"))]
+    pub recording_bitrate: u32,
+    #[derivative(Default(value = "PreferencesModel::default().default_appsink_queue_mode"))]
+    pub appsink_queue_mode: AppSinkQueueMode,
     #[derivative(Default(value = "PreferencesModel::default().default_video_latency"))]
     pub video_latency: u32,
+    pub rtsp_protocol: RtspProtocol,
+    pub video_rotation: VideoRotation,
+    pub video_flip: VideoFlip,
+    pub orient_recording_video: bool,
+    pub proxy_recording_enabled: bool,
+    #[derivative(Default(value = "480"))]
+    pub proxy_recording_height: u32,
+    pub telemetry_subtitle_enabled: bool,
+    pub telemetry_subtitle_format: SubtitleFormat,
+    pub frame_timestamps_enabled: bool,
+    pub video_crop_region: Option<VideoRegion>,
+    pub measurement_enabled: bool,
+    pub histogram_enabled: bool,
+    #[derivative(Default(value = "10"))]
+    pub timelapse_interval: u32,
+    pub auto_connect: bool,
+    pub auto_polling: bool,
+    pub auto_reconnect_video: bool,
+    pub control_sending_mode: ControlSendingMode,
+    pub control_value_encoding: ControlValueEncoding,
+    #[no_eq]
+    pub connection_profiles: Vec<ConnectionProfile>,
 }
 
 impl SlaveConfigModel {
@@ -84,13 +273,123 @@ impl SlaveConfigModel {
             use_decodebin: preferences.get_default_use_decodebin().clone(),
             video_encoder: preferences.get_default_video_encoder().clone(),
             reencode_recording_video: preferences.get_default_reencode_recording_video().clone(),
-            appsink_queue_leaky_enabled: preferences
-                .get_default_appsink_queue_leaky_enabled()
-                .clone(),
+            appsink_queue_mode: preferences.get_default_appsink_queue_mode().clone(),
             video_latency: preferences.get_default_video_latency().clone(),
+            connection_profiles: ConnectionProfile::load_all(),
             ..Default::default()
         }
     }
+
+    pub fn to_connection_profile(&self, name: String) -> ConnectionProfile {
+        ConnectionProfile {
+            name,
+            slave_url: self.slave_url.clone(),
+            video_url: self.video_url.clone(),
+            video_decoder: self.video_decoder.clone(),
+            use_decodebin: self.use_decodebin,
+            rtsp_protocol: self.rtsp_protocol,
+            video_latency: self.video_latency,
+            control_sending_mode: self.control_sending_mode,
+            control_value_encoding: self.control_value_encoding,
+        }
+    }
+
+    pub fn apply_connection_profile(&mut self, profile: ConnectionProfile) {
+        self.set_slave_url(profile.slave_url);
+        self.set_video_url(profile.video_url);
+        self.set_video_decoder(profile.video_decoder);
+        self.set_use_decodebin(profile.use_decodebin);
+        self.set_rtsp_protocol(profile.rtsp_protocol);
+        self.set_video_latency(profile.video_latency.min(VIDEO_LATENCY_MAX));
+        self.set_control_sending_mode(profile.control_sending_mode);
+        self.set_control_value_encoding(profile.control_value_encoding);
+    }
+
+    pub fn video_orientation(&self) -> VideoOrientation {
+        VideoOrientation(self.video_rotation, self.video_flip)
+    }
+
+    // 图像增强、直方图、测量、裁剪与旋转均需要在 CPU 上访问画面，此时无法由 GTK 直接渲染视频流
+    pub fn requires_frame_processing(&self) -> bool {
+        !self.video_algorithms.is_empty()
+            || self.histogram_enabled
+            || self.measurement_enabled
+            || self.video_crop_region.is_some()
+            || self.video_orientation() != VideoOrientation::default()
+    }
+
+    // 副摄像头使用与主摄像头相同的解码设置，但不进行画面处理
+    pub fn secondary_config(&self) -> Option<SlaveConfigModel> {
+        let video_url = self.secondary_video_url.clone()?;
+        Some(SlaveConfigModel {
+            video_url,
+            video_algorithms: Vec::new(),
+            histogram_enabled: false,
+            measurement_enabled: false,
+            video_crop_region: None,
+            video_rotation: Default::default(),
+            video_flip: Default::default(),
+            ..self.clone()
+        })
+    }
+
+    pub fn to_session(&self) -> SlaveSession {
+        SlaveSession {
+            slave_url: self.slave_url.clone(),
+            video_url: self.video_url.clone(),
+            auto_connect: self.auto_connect,
+            auto_polling: self.auto_polling,
+            control_sending_mode: self.control_sending_mode,
+            control_value_encoding: self.control_value_encoding,
+            video_algorithms: self.video_algorithms.clone(),
+            white_balance_strength: self.white_balance_strength,
+            dehaze_strength: self.dehaze_strength,
+            denoise_mode: self.denoise_mode,
+            processing_height: self.processing_height,
+            thruster_mix: self.thruster_mix.clone(),
+            custom_commands: self.custom_commands.clone(),
+            motion_trim: self.motion_trim.clone(),
+            input_sending_rate: self.input_sending_rate,
+            status_info_update_interval: self.status_info_update_interval,
+            idle_timeout: self.idle_timeout,
+            idle_timeout_action: self.idle_timeout_action,
+            secondary_video_url: self.secondary_video_url.clone(),
+            record_secondary_video: self.record_secondary_video,
+            reencode_recording_video: Some(self.reencode_recording_video),
+            video_encoder: Some(self.video_encoder.clone()),
+            recording_bitrate: self.recording_bitrate,
+        }
+    }
+
+    pub fn apply_session(&mut self, session: SlaveSession) {
+        self.set_slave_url(session.slave_url);
+        self.set_video_url(session.video_url);
+        self.set_auto_connect(session.auto_connect);
+        self.set_auto_polling(session.auto_polling);
+        self.set_control_sending_mode(session.control_sending_mode);
+        self.set_control_value_encoding(session.control_value_encoding);
+        self.set_video_algorithms(session.video_algorithms);
+        self.set_white_balance_strength(session.white_balance_strength);
+        self.set_dehaze_strength(session.dehaze_strength);
+        self.set_denoise_mode(session.denoise_mode);
+        self.set_processing_height(session.processing_height);
+        self.set_thruster_mix(session.thruster_mix);
+        self.set_custom_commands(session.custom_commands);
+        self.set_motion_trim(session.motion_trim.clamped());
+        self.set_input_sending_rate(session.input_sending_rate.map(clamp_input_sending_rate));
+        self.set_status_info_update_interval(session.status_info_update_interval.map(clamp_status_info_update_interval));
+        self.set_idle_timeout(session.idle_timeout.map(clamp_idle_timeout));
+        self.set_idle_timeout_action(session.idle_timeout_action);
+        self.set_secondary_video_url(session.secondary_video_url);
+        self.set_record_secondary_video(session.record_secondary_video);
+        if let Some(reencode) = session.reencode_recording_video {
+            self.set_reencode_recording_video(reencode);
+        }
+        if let Some(encoder) = session.video_encoder {
+            self.set_video_encoder(encoder);
+        }
+        self.set_recording_bitrate(clamp_recording_bitrate(session.recording_bitrate));
+    }
 }
 
 impl MicroModel for SlaveConfigModel {
@@ -143,10 +442,132 @@ impl MicroModel for SlaveConfigModel {
                 }
                 self.set_reencode_recording_video(reencode)
             }
-            SlaveConfigMsg::SetAppSinkQueueLeakyEnabled(leaky) => {
-                self.set_appsink_queue_leaky_enabled(leaky)
+            SlaveConfigMsg::SetRecordingBitrate(bitrate) => {
+                self.set_recording_bitrate(clamp_recording_bitrate(bitrate))
+            }
+            SlaveConfigMsg::SetAppSinkQueueMode(mode) => self.set_appsink_queue_mode(mode),
+            SlaveConfigMsg::SetVideoLatency(latency) => {
+                self.set_video_latency(latency.min(VIDEO_LATENCY_MAX))
+            }
+            SlaveConfigMsg::SetRtspProtocol(protocol) => self.set_rtsp_protocol(protocol),
+            SlaveConfigMsg::SetHistogramEnabled(enabled) => self.set_histogram_enabled(enabled),
+            SlaveConfigMsg::SetWhiteBalanceStrength(strength) => self.set_white_balance_strength(strength),
+            SlaveConfigMsg::SetDehazeStrength(strength) => self.set_dehaze_strength(strength),
+            SlaveConfigMsg::SetDenoiseMode(mode) => self.set_denoise_mode(mode),
+            SlaveConfigMsg::SetProcessingHeight(height) => self.set_processing_height(height),
+            SlaveConfigMsg::SetThrusterMix(mix) => self.set_thruster_mix(mix),
+            SlaveConfigMsg::SetCustomCommands(commands) => self.set_custom_commands(commands),
+            SlaveConfigMsg::SetMotionTrim(trim) => self.set_motion_trim(trim),
+            SlaveConfigMsg::SetInputSendingRateEnabled(enabled) => {
+                self.set_input_sending_rate(enabled.then(|| PreferencesModel::default().default_input_sending_rate))
+            }
+            // 未启用单独设置时数值框的值仅用于显示，其变化不应启用单独设置
+            SlaveConfigMsg::SetInputSendingRate(rate) => {
+                if self.input_sending_rate.is_some() {
+                    self.set_input_sending_rate(Some(clamp_input_sending_rate(rate)));
+                }
+            }
+            SlaveConfigMsg::SetStatusInfoUpdateIntervalEnabled(enabled) => {
+                self.set_status_info_update_interval(enabled.then(|| PreferencesModel::default().default_status_info_update_interval))
+            }
+            SlaveConfigMsg::SetStatusInfoUpdateInterval(interval) => {
+                if self.status_info_update_interval.is_some() {
+                    self.set_status_info_update_interval(Some(clamp_status_info_update_interval(interval)));
+                }
+            }
+            SlaveConfigMsg::SetIdleTimeoutEnabled(enabled) => {
+                self.set_idle_timeout(enabled.then(|| DEFAULT_IDLE_TIMEOUT))
+            }
+            SlaveConfigMsg::SetIdleTimeout(timeout) => {
+                if self.idle_timeout.is_some() {
+                    self.set_idle_timeout(Some(clamp_idle_timeout(timeout)));
+                }
+            }
+            SlaveConfigMsg::SetIdleTimeoutAction(action) => self.set_idle_timeout_action(action),
+            // 启用时默认使用主摄像头视频 URL 的下一个端口
+            SlaveConfigMsg::SetSecondaryVideoEnabled(enabled) => {
+                self.set_secondary_video_url(enabled.then(|| {
+                    let mut url = self.video_url.clone();
+                    if let Some(port) = url.port() {
+                        url.set_port(Some(port.wrapping_add(1))).unwrap_or_default();
+                    }
+                    url
+                }))
+            }
+            SlaveConfigMsg::SetSecondaryVideoUrl(url) => {
+                if self.secondary_video_url.is_some() {
+                    self.set_secondary_video_url(Some(url));
+                }
+            }
+            SlaveConfigMsg::SetRecordSecondaryVideo(record) => self.set_record_secondary_video(record),
+            SlaveConfigMsg::OpenCustomCommandEditor => {
+                send!(parent_sender, SlaveMsg::OpenCustomCommandEditor);
+                return;
+            }
+            SlaveConfigMsg::OpenConnectionProfileManager => {
+                send!(parent_sender, SlaveMsg::OpenConnectionProfileManager);
+                return;
+            }
+            SlaveConfigMsg::ApplyConnectionProfile(profile) => self.apply_connection_profile(profile),
+            SlaveConfigMsg::SetConnectionProfiles(profiles) => self.set_connection_profiles(profiles),
+            SlaveConfigMsg::SetMeasurementEnabled(enabled) => self.set_measurement_enabled(enabled),
+            SlaveConfigMsg::SetVideoCropEnabled(enabled) => {
+                self.set_video_crop_region(if enabled {
+                    Some(self.video_crop_region.unwrap_or_default())
+                } else {
+                    None
+                })
+            }
+            SlaveConfigMsg::SetVideoCropX(x) => {
+                if let Some(region) = self.get_mut_video_crop_region() {
+                    region.x = x;
+                }
+            }
+            SlaveConfigMsg::SetVideoCropY(y) => {
+                if let Some(region) = self.get_mut_video_crop_region() {
+                    region.y = y;
+                }
+            }
+            SlaveConfigMsg::SetVideoCropWidth(width) => {
+                if let Some(region) = self.get_mut_video_crop_region() {
+                    region.width = width;
+                }
+            }
+            SlaveConfigMsg::SetVideoCropHeight(height) => {
+                if let Some(region) = self.get_mut_video_crop_region() {
+                    region.height = height;
+                }
+            }
+            SlaveConfigMsg::SetVideoRotation(rotation) => self.set_video_rotation(rotation),
+            SlaveConfigMsg::SetVideoFlip(flip) => self.set_video_flip(flip),
+            SlaveConfigMsg::SetOrientRecordingVideo(orient) => {
+                self.set_orient_recording_video(orient)
+            }
+            SlaveConfigMsg::SetProxyRecordingEnabled(enabled) => {
+                self.set_proxy_recording_enabled(enabled)
+            }
+            SlaveConfigMsg::SetProxyRecordingHeight(height) => {
+                self.set_proxy_recording_height(height)
+            }
+            SlaveConfigMsg::SetTimelapseInterval(interval) => {
+                self.set_timelapse_interval(interval.max(1))
+            }
+            SlaveConfigMsg::SetTelemetrySubtitleEnabled(enabled) => {
+                self.set_telemetry_subtitle_enabled(enabled)
+            }
+            SlaveConfigMsg::SetTelemetrySubtitleFormat(format) => {
+                self.set_telemetry_subtitle_format(format)
+            }
+            SlaveConfigMsg::SetFrameTimestampsEnabled(enabled) => {
+                self.set_frame_timestamps_enabled(enabled)
+            }
+            SlaveConfigMsg::SetAutoConnect(auto_connect) => self.set_auto_connect(auto_connect),
+            SlaveConfigMsg::SetControlSendingMode(mode) => self.set_control_sending_mode(mode),
+            SlaveConfigMsg::SetControlValueEncoding(encoding) => self.set_control_value_encoding(encoding),
+            SlaveConfigMsg::SetAutoPolling(auto_polling) => self.set_auto_polling(auto_polling),
+            SlaveConfigMsg::SetAutoReconnectVideo(auto_reconnect) => {
+                self.set_auto_reconnect_video(auto_reconnect)
             }
-            SlaveConfigMsg::SetVideoLatency(latency) => self.set_video_latency(latency),
         }
         send!(parent_sender, SlaveMsg::ConfigUpdated);
     }
@@ -159,6 +580,43 @@ impl std::fmt::Debug for SlaveConfigWidgets {
 }
 
 pub enum SlaveConfigMsg {
+    SetHistogramEnabled(bool),
+    SetWhiteBalanceStrength(u8),
+    SetDehazeStrength(u8),
+    SetDenoiseMode(DenoiseMode),
+    SetProcessingHeight(Option<u32>),
+    SetThrusterMix(ThrusterMix),
+    SetCustomCommands(Vec<CustomCommand>),
+    SetMotionTrim(MotionPacket),
+    SetInputSendingRateEnabled(bool),
+    SetInputSendingRate(u16),
+    SetStatusInfoUpdateIntervalEnabled(bool),
+    SetStatusInfoUpdateInterval(u16),
+    SetIdleTimeoutEnabled(bool),
+    SetIdleTimeout(u16),
+    SetIdleTimeoutAction(IdleTimeoutAction),
+    SetSecondaryVideoEnabled(bool),
+    SetSecondaryVideoUrl(Url),
+    SetRecordSecondaryVideo(bool),
+    OpenCustomCommandEditor,
+    OpenConnectionProfileManager,
+    ApplyConnectionProfile(ConnectionProfile),
+    SetConnectionProfiles(Vec<ConnectionProfile>),
+    SetMeasurementEnabled(bool),
+    SetVideoCropEnabled(bool),
+    SetVideoCropX(f64),
+    SetVideoCropY(f64),
+    SetVideoCropWidth(f64),
+    SetVideoCropHeight(f64),
+    SetVideoRotation(VideoRotation),
+    SetVideoFlip(VideoFlip),
+    SetOrientRecordingVideo(bool),
+    SetProxyRecordingEnabled(bool),
+    SetProxyRecordingHeight(u32),
+    SetTelemetrySubtitleEnabled(bool),
+    SetTimelapseInterval(u32),
+    SetTelemetrySubtitleFormat(SubtitleFormat),
+    SetFrameTimestampsEnabled(bool),
     SetVideoUrl(Url),
     SetSlaveUrl(Url),
     SetKeepVideoDisplayRatio(bool),
@@ -174,8 +632,15 @@ pub enum SlaveConfigMsg {
     SetVideoEncoderCodec(VideoCodec),
     SetVideoEncoderCodecProvider(VideoCodecProvider),
     SetReencodeRecordingVideo(bool),
-    SetAppSinkQueueLeakyEnabled(bool),
+    SetRecordingBitrate(u32),
+    SetAppSinkQueueMode(AppSinkQueueMode),
     SetVideoLatency(u32),
+    SetRtspProtocol(RtspProtocol),
+    SetAutoConnect(bool),
+    SetControlSendingMode(ControlSendingMode),
+    SetControlValueEncoding(ControlValueEncoding),
+    SetAutoPolling(bool),
+    SetAutoReconnectVideo(bool),
 }
 
 #[micro_widget(pub)]
@@ -197,32 +662,101 @@ impl MicroWidgets<SlaveConfigModel> for SlaveConfigWidgets {
                         set_orientation: Orientation::Vertical,
                         append = &PreferencesGroup {
                             set_sensitive: track!(model.changed(SlaveConfigModel::connected()), model.get_connected().eq(&Some(false))),
-                            set_title: "通讯",
-                            set_description: Some("设置下位机的通讯选项"),
+                            set_title: tr("通讯"),
+                            set_description: track!(model.changed(SlaveConfigModel::connected()), Some(if model.get_connected().is_none() { tr("正在连接或断开连接，暂时无法修改通讯选项") } else { tr("设置下位机的通讯选项") })),
                             add = &ActionRow {
-                                set_title: "连接 URL",
-                                set_subtitle: "连接下位机使用的 URL",
+                                set_title: tr("连接 URL"),
+                                set_subtitle: &format!("{}{}", tr("连接下位机使用的 URL，"), tr(SLAVE_URL_FORMAT_HINT)),
                                 add_suffix = &Entry {
-                                    set_text: model.get_slave_url().to_string().as_str(),
+                                    set_text: track!(model.changed(SlaveConfigModel::slave_url()), model.get_slave_url().to_string().as_str()),
                                     set_width_request: 160,
                                     set_valign: Align::Center,
                                     connect_changed(sender) => move |entry| {
-                                        if let Ok(url) = Url::from_str(&entry.text()) {
-                                            send!(sender, SlaveConfigMsg::SetSlaveUrl(url));
-                                            entry.remove_css_class("error");
-                                        } else {
-                                            entry.add_css_class("error");
+                                        match parse_slave_url(&entry.text()) {
+                                            Ok(url) => {
+                                                send!(sender, SlaveConfigMsg::SetSlaveUrl(url));
+                                                entry.remove_css_class("error");
+                                                entry.set_tooltip_text(None);
+                                            },
+                                            Err(err) => {
+                                                entry.add_css_class("error");
+                                                entry.set_tooltip_text(Some(err));
+                                            },
                                         }
                                     }
                                 },
                             },
+                            add = &ActionRow {
+                                set_title: tr("连接档案"),
+                                set_sensitive: track!(model.changed(SlaveConfigModel::polling()), model.get_polling().eq(&Some(false))),
+                                set_subtitle: tr("使用保存的地址、解码与通讯设置填充该机位的配置"),
+                                add_suffix = &MenuButton {
+                                    set_label: tr("应用"),
+                                    set_valign: Align::Center,
+                                    set_popover = Some(&Popover) {
+                                        set_child = Some(&Frame) {
+                                            set_child: track!(model.changed(SlaveConfigModel::connection_profiles()), Some(&connection_profiles_list_box(&model.connection_profiles, &sender))),
+                                        },
+                                    },
+                                },
+                                add_suffix = &Button {
+                                    set_label: tr("管理"),
+                                    set_valign: Align::Center,
+                                    connect_clicked(sender) => move |_button| {
+                                        send!(sender, SlaveConfigMsg::OpenConnectionProfileManager);
+                                    },
+                                },
+                            },
+                            add = &ActionRow {
+                                set_title: tr("自动连接"),
+                                set_subtitle: tr("启动上位机后自动连接该机位的下位机，连接失败时不会重试"),
+                                add_suffix: auto_connect_switch = &Switch {
+                                    set_active: track!(model.changed(SlaveConfigModel::auto_connect()), *model.get_auto_connect()),
+                                    set_valign: Align::Center,
+                                    connect_state_set(sender) => move |_switch, state| {
+                                        send!(sender, SlaveConfigMsg::SetAutoConnect(state));
+                                        Inhibit(false)
+                                    }
+                                },
+                                set_activatable_widget: Some(&auto_connect_switch),
+                            },
+                            add = &ComboRow {
+                                set_title: tr("控制指令发送方式"),
+                                set_subtitle: tr("下位机固件支持 control 方法时可选择合并请求，将全部控制指令合并为一次请求发送以降低延迟"),
+                                set_model: Some(&{
+                                    let model = StringList::new(&[]);
+                                    for value in ControlSendingMode::iter() {
+                                        model.append(&value.to_string());
+                                    }
+                                    model
+                                }),
+                                set_selected: track!(model.changed(SlaveConfigModel::control_sending_mode()), ControlSendingMode::iter().position(|x| x == model.control_sending_mode).unwrap() as u32),
+                                connect_selected_notify(sender) => move |row| {
+                                    send!(sender, SlaveConfigMsg::SetControlSendingMode(ControlSendingMode::iter().nth(row.selected() as usize).unwrap()));
+                                }
+                            },
+                            add = &ComboRow {
+                                set_title: tr("控制量编码"),
+                                set_subtitle: tr("发送运动、机械臂与灯光控制量时使用的数值格式，需与下位机固件一致，重新连接后生效"),
+                                set_model: Some(&{
+                                    let model = StringList::new(&[]);
+                                    for value in ControlValueEncoding::iter() {
+                                        model.append(&value.to_string());
+                                    }
+                                    model
+                                }),
+                                set_selected: track!(model.changed(SlaveConfigModel::control_value_encoding()), ControlValueEncoding::iter().position(|x| x == model.control_value_encoding).unwrap() as u32),
+                                connect_selected_notify(sender) => move |row| {
+                                    send!(sender, SlaveConfigMsg::SetControlValueEncoding(ControlValueEncoding::iter().nth(row.selected() as usize).unwrap()));
+                                }
+                            },
                         },
                         append = &PreferencesGroup {
-                            set_title: "控制",
-                            set_description: Some("调整机位控制选项"),
+                            set_title: tr("控制"),
+                            set_description: Some(tr("调整机位控制选项")),
                             add = &ActionRow {
-                                set_title: "交换 X/Y 轴",
-                                set_subtitle: "若下位机规定的 X/Y 轴与上位机不一致，可以使用此选项进行交换",
+                                set_title: tr("交换 X/Y 轴"),
+                                set_subtitle: tr("若下位机规定的 X/Y 轴与上位机不一致，可以使用此选项进行交换"),
                                 add_suffix: swap_xy_switch = &Switch {
                                     set_active: track!(model.changed(SlaveConfigModel::swap_xy()), *model.get_swap_xy()),
                                     set_valign: Align::Center,
@@ -233,14 +767,115 @@ impl MicroWidgets<SlaveConfigModel> for SlaveConfigWidgets {
                                 },
                                 set_activatable_widget: Some(&swap_xy_switch),
                             },
+                            add = &ActionRow {
+                                set_title: tr("自定义命令"),
+                                set_subtitle: track!(model.changed(SlaveConfigModel::custom_commands()), &if model.custom_commands.is_empty() { String::from(tr("在状态信息面板中添加调用下位机自定义 RPC 方法的按钮")) } else { model.custom_commands.iter().map(|command| command.label.as_str()).collect::<Vec<_>>().join(tr("、")) }),
+                                add_suffix: custom_commands_button = &Button {
+                                    set_label: tr("编辑"),
+                                    set_valign: Align::Center,
+                                    connect_clicked(sender) => move |_button| {
+                                        send!(sender, SlaveConfigMsg::OpenCustomCommandEditor);
+                                    },
+                                },
+                                set_activatable_widget: Some(&custom_commands_button),
+                            },
+                            add = &ExpanderRow {
+                                set_title: tr("单独设置输入发送率"),
+                                set_subtitle: tr("为该机位单独指定每秒钟发送的控制数据包的个数，未启用时使用首选项中的设置，连接期间修改立即生效"),
+                                set_show_enable_switch: true,
+                                set_expanded: model.input_sending_rate.is_some(),
+                                set_enable_expansion: track!(model.changed(SlaveConfigModel::input_sending_rate()), model.input_sending_rate.is_some()),
+                                connect_enable_expansion_notify(sender) => move |expander| {
+                                    send!(sender, SlaveConfigMsg::SetInputSendingRateEnabled(expander.enables_expansion()));
+                                },
+                                add_row = &ActionRow {
+                                    set_title: tr("输入发送率"),
+                                    add_suffix = &SpinButton::with_range(INPUT_SENDING_RATE_RANGE.0 as f64, INPUT_SENDING_RATE_RANGE.1 as f64, 1.0) {
+                                        set_value: track!(model.changed(SlaveConfigModel::input_sending_rate()), model.input_sending_rate.unwrap_or(PreferencesModel::default().default_input_sending_rate) as f64),
+                                        set_digits: 0,
+                                        set_valign: Align::Center,
+                                        set_can_focus: false,
+                                        connect_value_changed(sender) => move |button| {
+                                            send!(sender, SlaveConfigMsg::SetInputSendingRate(button.value() as u16));
+                                        }
+                                    },
+                                    add_suffix = &Label {
+                                        set_label: "Hz",
+                                    },
+                                },
+                            },
+                            add = &ExpanderRow {
+                                set_title: tr("单独设置状态信息更新时间间隔"),
+                                set_subtitle: tr("为该机位单独指定请求状态信息的时间间隔，未启用时使用首选项中的设置，连接期间修改立即生效"),
+                                set_show_enable_switch: true,
+                                set_expanded: model.status_info_update_interval.is_some(),
+                                set_enable_expansion: track!(model.changed(SlaveConfigModel::status_info_update_interval()), model.status_info_update_interval.is_some()),
+                                connect_enable_expansion_notify(sender) => move |expander| {
+                                    send!(sender, SlaveConfigMsg::SetStatusInfoUpdateIntervalEnabled(expander.enables_expansion()));
+                                },
+                                add_row = &ActionRow {
+                                    set_title: tr("状态信息更新时间间隔"),
+                                    add_suffix = &SpinButton::with_range(STATUS_INFO_UPDATE_INTERVAL_RANGE.0 as f64, STATUS_INFO_UPDATE_INTERVAL_RANGE.1 as f64, 50.0) {
+                                        set_value: track!(model.changed(SlaveConfigModel::status_info_update_interval()), model.status_info_update_interval.unwrap_or(PreferencesModel::default().default_status_info_update_interval) as f64),
+                                        set_digits: 0,
+                                        set_valign: Align::Center,
+                                        set_can_focus: false,
+                                        connect_value_changed(sender) => move |button| {
+                                            send!(sender, SlaveConfigMsg::SetStatusInfoUpdateInterval(button.value() as u16));
+                                        }
+                                    },
+                                    add_suffix = &Label {
+                                        set_label: tr("毫秒"),
+                                    },
+                                },
+                            },
+                            add = &ExpanderRow {
+                                set_title: tr("空闲超时"),
+                                set_subtitle: tr("连接期间超过指定时间没有控制输入变化时给出提示，并可选择紧急停止或断开连接"),
+                                set_show_enable_switch: true,
+                                set_expanded: model.idle_timeout.is_some(),
+                                set_enable_expansion: track!(model.changed(SlaveConfigModel::idle_timeout()), model.idle_timeout.is_some()),
+                                connect_enable_expansion_notify(sender) => move |expander| {
+                                    send!(sender, SlaveConfigMsg::SetIdleTimeoutEnabled(expander.enables_expansion()));
+                                },
+                                add_row = &ActionRow {
+                                    set_title: tr("超时时间"),
+                                    add_suffix = &SpinButton::with_range(IDLE_TIMEOUT_RANGE.0 as f64, IDLE_TIMEOUT_RANGE.1 as f64, 5.0) {
+                                        set_value: track!(model.changed(SlaveConfigModel::idle_timeout()), model.idle_timeout.unwrap_or(DEFAULT_IDLE_TIMEOUT) as f64),
+                                        set_digits: 0,
+                                        set_valign: Align::Center,
+                                        set_can_focus: false,
+                                        connect_value_changed(sender) => move |button| {
+                                            send!(sender, SlaveConfigMsg::SetIdleTimeout(button.value() as u16));
+                                        }
+                                    },
+                                    add_suffix = &Label {
+                                        set_label: tr("秒"),
+                                    },
+                                },
+                                add_row = &ComboRow {
+                                    set_title: tr("超时后的操作"),
+                                    set_model: Some(&{
+                                        let model = StringList::new(&[]);
+                                        for value in IdleTimeoutAction::iter() {
+                                            model.append(&value.to_string());
+                                        }
+                                        model
+                                    }),
+                                    set_selected: track!(model.changed(SlaveConfigModel::idle_timeout_action()), IdleTimeoutAction::iter().position(|x| x == model.idle_timeout_action).unwrap() as u32),
+                                    connect_selected_notify(sender) => move |row| {
+                                        send!(sender, SlaveConfigMsg::SetIdleTimeoutAction(IdleTimeoutAction::iter().nth(row.selected() as usize).unwrap()));
+                                    }
+                                },
+                            },
                         },
                         append = &PreferencesGroup {
-                            set_title: "画面",
-                            set_description: Some("上位机端对画面进行的处理选项"),
+                            set_title: tr("画面"),
+                            set_description: Some(tr("上位机端对画面进行的处理选项")),
 
                             add = &ActionRow {
-                                set_title: "保持长宽比",
-                                set_subtitle: "在改变窗口大小的时是否保持画面比例，这可能导致画面无法全屏",
+                                set_title: tr("保持长宽比"),
+                                set_subtitle: tr("在改变窗口大小的时是否保持画面比例，这可能导致画面无法全屏"),
                                 add_suffix: default_keep_video_display_ratio_switch = &Switch {
                                     set_active: track!(model.changed(SlaveConfigModel::keep_video_display_ratio()), *model.get_keep_video_display_ratio()),
                                     set_valign: Align::Center,
@@ -252,11 +887,11 @@ impl MicroWidgets<SlaveConfigModel> for SlaveConfigWidgets {
                                 set_activatable_widget: Some(&default_keep_video_display_ratio_switch),
                             },
                             add = &ComboRow {
-                                set_title: "增强算法",
-                                set_subtitle: "对画面使用的增强算法",
+                                set_title: tr("增强算法"),
+                                set_subtitle: tr("对画面使用的增强算法"),
                                 set_model: Some(&{
                                     let model = StringList::new(&[]);
-                                    model.append("无");
+                                    model.append(tr("无"));
                                     for value in VideoAlgorithm::iter() {
                                         model.append(&value.to_string());
                                     }
@@ -266,44 +901,338 @@ impl MicroWidgets<SlaveConfigModel> for SlaveConfigWidgets {
                                 connect_selected_notify(sender) => move |row| {
                                     send!(sender, SlaveConfigMsg::SetVideoAlgorithm(if row.selected() > 0 { Some(VideoAlgorithm::iter().nth(row.selected().wrapping_sub(1) as usize).unwrap()) } else { None }));
                                 }
-                            }
+                            },
+                            add = &ActionRow {
+                                set_title: tr("白平衡强度"),
+                                set_subtitle: tr("自动白平衡校正色偏的程度，越低画面越接近原始色彩"),
+                                set_visible: track!(model.changed(SlaveConfigModel::video_algorithms()), model.video_algorithms.contains(&VideoAlgorithm::WhiteBalance)),
+                                add_suffix = &SpinButton::with_range(0.0, 100.0, 5.0) {
+                                    set_value: track!(model.changed(SlaveConfigModel::white_balance_strength()), *model.get_white_balance_strength() as f64),
+                                    set_digits: 0,
+                                    set_valign: Align::Center,
+                                    set_can_focus: false,
+                                    connect_value_changed(sender) => move |button| {
+                                        send!(sender, SlaveConfigMsg::SetWhiteBalanceStrength(button.value() as u8));
+                                    }
+                                },
+                                add_suffix = &Label {
+                                    set_label: "%",
+                                },
+                            },
+                            add = &ActionRow {
+                                set_title: tr("去雾强度"),
+                                set_subtitle: tr("去除水中悬浮物造成的雾气的比例，过高时画面可能偏暗或出现噪点"),
+                                set_visible: track!(model.changed(SlaveConfigModel::video_algorithms()), model.video_algorithms.contains(&VideoAlgorithm::Dehaze)),
+                                add_suffix = &SpinButton::with_range(0.0, 100.0, 5.0) {
+                                    set_value: track!(model.changed(SlaveConfigModel::dehaze_strength()), *model.get_dehaze_strength() as f64),
+                                    set_digits: 0,
+                                    set_valign: Align::Center,
+                                    set_can_focus: false,
+                                    connect_value_changed(sender) => move |button| {
+                                        send!(sender, SlaveConfigMsg::SetDehazeStrength(button.value() as u8));
+                                    }
+                                },
+                                add_suffix = &Label {
+                                    set_label: "%",
+                                },
+                            },
+                            add = &ComboRow {
+                                set_title: tr("降噪方式"),
+                                set_subtitle: tr("快速方式适合实时操控，高质量方式细节保留更好，但在高分辨率画面上可能无法维持帧率"),
+                                set_visible: track!(model.changed(SlaveConfigModel::video_algorithms()), model.video_algorithms.contains(&VideoAlgorithm::Denoise)),
+                                set_model: Some(&{
+                                    let model = StringList::new(&[]);
+                                    for value in DenoiseMode::iter() {
+                                        model.append(&value.to_string());
+                                    }
+                                    model
+                                }),
+                                set_selected: track!(model.changed(SlaveConfigModel::denoise_mode()), DenoiseMode::iter().position(|x| x == model.denoise_mode).unwrap() as u32),
+                                connect_selected_notify(sender) => move |row| {
+                                    send!(sender, SlaveConfigMsg::SetDenoiseMode(DenoiseMode::iter().nth(row.selected() as usize).unwrap()));
+                                }
+                            },
+                            add = &ComboRow {
+                                set_title: tr("处理分辨率"),
+                                set_subtitle: tr("增强算法处理画面时使用的分辨率，降低分辨率可减少 CPU 占用，但画面会变得模糊，录像不受影响"),
+                                set_visible: track!(model.changed(SlaveConfigModel::video_algorithms()), !model.video_algorithms.is_empty()),
+                                set_model: Some(&{
+                                    let model = StringList::new(&[]);
+                                    model.append(tr("原始分辨率"));
+                                    for height in PROCESSING_HEIGHTS {
+                                        model.append(&format!("{}p", height));
+                                    }
+                                    model
+                                }),
+                                set_selected: track!(model.changed(SlaveConfigModel::processing_height()), model.processing_height.and_then(|height| PROCESSING_HEIGHTS.iter().position(|x| *x == height)).map_or(0, |x| x + 1) as u32),
+                                connect_selected_notify(sender) => move |row| {
+                                    send!(sender, SlaveConfigMsg::SetProcessingHeight(PROCESSING_HEIGHTS.get((row.selected() as usize).wrapping_sub(1)).copied()));
+                                }
+                            },
+                            add = &ActionRow {
+                                set_title: tr("直方图"),
+                                set_subtitle: tr("在画面右上角显示 RGB 直方图，用于检查曝光是否过度或不足"),
+                                add_suffix: histogram_enabled_switch = &Switch {
+                                    set_active: track!(model.changed(SlaveConfigModel::histogram_enabled()), *model.get_histogram_enabled()),
+                                    set_valign: Align::Center,
+                                    connect_state_set(sender) => move |_switch, state| {
+                                        send!(sender, SlaveConfigMsg::SetHistogramEnabled(state));
+                                        Inhibit(false)
+                                    }
+                                },
+                                set_activatable_widget: Some(&histogram_enabled_switch),
+                            },
+                            add = &ActionRow {
+                                set_title: tr("取样与测量"),
+                                set_subtitle: tr("点击画面以读取像素坐标与颜色，再次点击以测量两点间的像素距离"),
+                                add_suffix: measurement_enabled_switch = &Switch {
+                                    set_active: track!(model.changed(SlaveConfigModel::measurement_enabled()), *model.get_measurement_enabled()),
+                                    set_valign: Align::Center,
+                                    connect_state_set(sender) => move |_switch, state| {
+                                        send!(sender, SlaveConfigMsg::SetMeasurementEnabled(state));
+                                        Inhibit(false)
+                                    }
+                                },
+                                set_activatable_widget: Some(&measurement_enabled_switch),
+                            },
+                            add = &ExpanderRow {
+                                set_title: tr("裁剪画面"),
+                                set_subtitle: tr("仅显示画面中的指定区域，各项数值为相对画面宽高的比例，同样作用于截图"),
+                                set_show_enable_switch: true,
+                                set_expanded: model.video_crop_region.is_some(),
+                                set_enable_expansion: track!(model.changed(SlaveConfigModel::video_crop_region()), model.video_crop_region.is_some()),
+                                connect_enable_expansion_notify(sender) => move |expander| {
+                                    send!(sender, SlaveConfigMsg::SetVideoCropEnabled(expander.enables_expansion()));
+                                },
+                                add_row = &ActionRow {
+                                    set_title: tr("左边距"),
+                                    add_suffix = &SpinButton::with_range(0.0, 1.0, 0.01) {
+                                        set_value: track!(model.changed(SlaveConfigModel::video_crop_region()), model.video_crop_region.unwrap_or_default().x),
+                                        set_digits: 2,
+                                        set_valign: Align::Center,
+                                        set_can_focus: false,
+                                        connect_value_changed(sender) => move |button| {
+                                            send!(sender, SlaveConfigMsg::SetVideoCropX(button.value()));
+                                        }
+                                    },
+                                },
+                                add_row = &ActionRow {
+                                    set_title: tr("上边距"),
+                                    add_suffix = &SpinButton::with_range(0.0, 1.0, 0.01) {
+                                        set_value: track!(model.changed(SlaveConfigModel::video_crop_region()), model.video_crop_region.unwrap_or_default().y),
+                                        set_digits: 2,
+                                        set_valign: Align::Center,
+                                        set_can_focus: false,
+                                        connect_value_changed(sender) => move |button| {
+                                            send!(sender, SlaveConfigMsg::SetVideoCropY(button.value()));
+                                        }
+                                    },
+                                },
+                                add_row = &ActionRow {
+                                    set_title: tr("宽度"),
+                                    add_suffix = &SpinButton::with_range(0.0, 1.0, 0.01) {
+                                        set_value: track!(model.changed(SlaveConfigModel::video_crop_region()), model.video_crop_region.unwrap_or_default().width),
+                                        set_digits: 2,
+                                        set_valign: Align::Center,
+                                        set_can_focus: false,
+                                        connect_value_changed(sender) => move |button| {
+                                            send!(sender, SlaveConfigMsg::SetVideoCropWidth(button.value()));
+                                        }
+                                    },
+                                },
+                                add_row = &ActionRow {
+                                    set_title: tr("高度"),
+                                    add_suffix = &SpinButton::with_range(0.0, 1.0, 0.01) {
+                                        set_value: track!(model.changed(SlaveConfigModel::video_crop_region()), model.video_crop_region.unwrap_or_default().height),
+                                        set_digits: 2,
+                                        set_valign: Align::Center,
+                                        set_can_focus: false,
+                                        connect_value_changed(sender) => move |button| {
+                                            send!(sender, SlaveConfigMsg::SetVideoCropHeight(button.value()));
+                                        }
+                                    },
+                                },
+                            },
+                            add = &ComboRow {
+                                set_title: tr("旋转"),
+                                set_subtitle: tr("对画面进行的旋转，同样作用于截图"),
+                                set_model: Some(&{
+                                    let model = StringList::new(&[]);
+                                    for value in VideoRotation::iter() {
+                                        model.append(&value.to_string());
+                                    }
+                                    model
+                                }),
+                                set_selected: track!(model.changed(SlaveConfigModel::video_rotation()), VideoRotation::iter().position(|x| x == model.video_rotation).unwrap() as u32),
+                                connect_selected_notify(sender) => move |row| {
+                                    send!(sender, SlaveConfigMsg::SetVideoRotation(VideoRotation::iter().nth(row.selected() as usize).unwrap()));
+                                }
+                            },
+                            add = &ComboRow {
+                                set_title: tr("翻转"),
+                                set_subtitle: tr("在旋转之后对画面进行的翻转，同样作用于截图"),
+                                set_model: Some(&{
+                                    let model = StringList::new(&[]);
+                                    for value in VideoFlip::iter() {
+                                        model.append(&value.to_string());
+                                    }
+                                    model
+                                }),
+                                set_selected: track!(model.changed(SlaveConfigModel::video_flip()), VideoFlip::iter().position(|x| x == model.video_flip).unwrap() as u32),
+                                connect_selected_notify(sender) => move |row| {
+                                    send!(sender, SlaveConfigMsg::SetVideoFlip(VideoFlip::iter().nth(row.selected() as usize).unwrap()));
+                                }
+                            },
+                            add = &ActionRow {
+                                set_title: tr("定时截图间隔"),
+                                set_subtitle: tr("定时截图时每两张截图之间的时间间隔，截图保存在截图目录下以开始时间命名的文件夹中"),
+                                add_suffix = &SpinButton::with_range(1.0, 3600.0, 1.0) {
+                                    set_value: track!(model.changed(SlaveConfigModel::timelapse_interval()), model.timelapse_interval as f64),
+                                    set_digits: 0,
+                                    set_valign: Align::Center,
+                                    set_can_focus: false,
+                                    connect_value_changed(sender) => move |button| {
+                                        send!(sender, SlaveConfigMsg::SetTimelapseInterval(button.value() as u32));
+                                    }
+                                },
+                                add_suffix = &Label {
+                                    set_label: tr("秒"),
+                                },
+                            },
+                        },
+                        append = &PreferencesGroup {
+                            set_title: tr("副摄像头"),
+                            set_description: Some(tr("以画中画方式在画面上显示另一路视频流，修改在下次启动副摄像头画面时生效")),
+                            add = &ExpanderRow {
+                                set_title: tr("画中画副摄像头"),
+                                set_subtitle: tr("启用后可通过工具栏按钮单独启动或停止副摄像头画面，点击画中画可交换主画面与画中画"),
+                                set_show_enable_switch: true,
+                                set_expanded: model.secondary_video_url.is_some(),
+                                set_enable_expansion: track!(model.changed(SlaveConfigModel::secondary_video_url()), model.secondary_video_url.is_some()),
+                                connect_enable_expansion_notify(sender) => move |expander| {
+                                    send!(sender, SlaveConfigMsg::SetSecondaryVideoEnabled(expander.enables_expansion()));
+                                },
+                                add_row = &ActionRow {
+                                    set_title: tr("视频流 URL"),
+                                    set_subtitle: tr(VIDEO_URL_FORMAT_HINT),
+                                    add_suffix = &Entry {
+                                        set_text: track!(model.changed(SlaveConfigModel::secondary_video_url()), &model.secondary_video_url.as_ref().map(|url| url.to_string()).unwrap_or_default()),
+                                        set_valign: Align::Center,
+                                        set_width_request: 160,
+                                        connect_changed(sender) => move |entry| {
+                                            match parse_video_url(&entry.text()) {
+                                                Ok(url) => {
+                                                    send!(sender, SlaveConfigMsg::SetSecondaryVideoUrl(url));
+                                                    entry.remove_css_class("error");
+                                                    entry.set_tooltip_text(None);
+                                                },
+                                                Err(err) => {
+                                                    entry.add_css_class("error");
+                                                    entry.set_tooltip_text(Some(err));
+                                                },
+                                            }
+                                        }
+                                    },
+                                },
+                                add_row = &ActionRow {
+                                    set_title: tr("同时录制副摄像头"),
+                                    set_subtitle: tr("开始录制时副摄像头画面正在显示则一并录制，录像文件名带有 _secondary 后缀"),
+                                    add_suffix: record_secondary_video_switch = &Switch {
+                                        set_active: track!(model.changed(SlaveConfigModel::record_secondary_video()), *model.get_record_secondary_video()),
+                                        set_valign: Align::Center,
+                                        connect_state_set(sender) => move |_switch, state| {
+                                            send!(sender, SlaveConfigMsg::SetRecordSecondaryVideo(state));
+                                            Inhibit(false)
+                                        }
+                                    },
+                                    set_activatable_widget: Some(&record_secondary_video_switch),
+                                },
+                            },
                         },
                         append = &PreferencesGroup {
                             set_sensitive: track!(model.changed(SlaveConfigModel::polling()), model.get_polling().eq(&Some(false))),
-                            set_title: "管道",
-                            set_description: Some("配置视频流接收以及录制所使用的管道"),
+                            set_title: tr("管道"),
+                            set_description: track!(model.changed(SlaveConfigModel::polling()), Some(if model.get_polling().is_none() { tr("正在启动或停止拉流，暂时无法修改管道") } else { tr("配置视频流接收以及录制所使用的管道") })),
                             add = &ActionRow {
-                                set_title: "视频流 URL",
-                                set_subtitle: "配置机位视频流的 URL",
+                                set_title: tr("视频流 URL"),
+                                set_subtitle: &format!("{}{}", tr("配置机位视频流的 URL，"), tr(VIDEO_URL_FORMAT_HINT)),
                                 add_suffix = &Entry {
                                     set_text: track!(model.changed(SlaveConfigModel::video_url()), model.get_video_url().to_string().as_str()),
                                     set_valign: Align::Center,
                                     set_width_request: 160,
                                     connect_changed(sender) => move |entry| {
-                                        if let Ok(url) = Url::from_str(&entry.text()) {
-                                            send!(sender, SlaveConfigMsg::SetVideoUrl(url));
-                                            entry.remove_css_class("error");
-                                        } else {
-                                            entry.add_css_class("error");
+                                        match parse_video_url(&entry.text()) {
+                                            Ok(url) => {
+                                                send!(sender, SlaveConfigMsg::SetVideoUrl(url));
+                                                entry.remove_css_class("error");
+                                                entry.set_tooltip_text(None);
+                                            },
+                                            Err(err) => {
+                                                entry.add_css_class("error");
+                                                entry.set_tooltip_text(Some(err));
+                                            },
                                         }
                                     }
                                 },
                             },
+                            add = &ComboRow {
+                                set_title: tr("画面缓存策略"),
+                                set_subtitle: tr("低延迟优先时画面落后于视频流会自动跳帧，完整性优先时不跳帧并缓存最多 120 帧，会占用更多内存并可能增大延迟"),
+                                set_model: Some(&{
+                                    let model = StringList::new(&[]);
+                                    for value in AppSinkQueueMode::iter() {
+                                        model.append(&value.to_string());
+                                    }
+                                    model
+                                }),
+                                set_selected: track!(model.changed(SlaveConfigModel::appsink_queue_mode()), AppSinkQueueMode::iter().position(|x| x == model.appsink_queue_mode).unwrap() as u32),
+                                connect_selected_notify(sender) => move |row| {
+                                    send!(sender, SlaveConfigMsg::SetAppSinkQueueMode(AppSinkQueueMode::iter().nth(row.selected() as usize).unwrap()));
+                                }
+                            },
+                            add = &ActionRow {
+                                set_title: tr("自动拉流"),
+                                set_subtitle: tr("启动上位机后自动启动该机位的视频流拉流"),
+                                add_suffix: auto_polling_switch = &Switch {
+                                    set_active: track!(model.changed(SlaveConfigModel::auto_polling()), *model.get_auto_polling()),
+                                    set_valign: Align::Center,
+                                    connect_state_set(sender) => move |_switch, state| {
+                                        send!(sender, SlaveConfigMsg::SetAutoPolling(state));
+                                        Inhibit(false)
+                                    }
+                                },
+                                set_activatable_widget: Some(&auto_polling_switch),
+                            },
                             add = &ActionRow {
-                                set_title: "启用画面自动跳帧",
-                                set_subtitle: "当机位画面与视频流延迟过大时，自动跳帧以避免延迟提升",
-                                add_suffix: appsink_queue_leaky_enabled_switch = &Switch {
-                                    set_active: track!(model.changed(SlaveConfigModel::appsink_queue_leaky_enabled()), *model.get_appsink_queue_leaky_enabled()),
+                                set_title: tr("自动重连"),
+                                set_subtitle: tr("视频流中断时自动重新启动管道，重试间隔逐渐增加，直至手动停止拉流"),
+                                add_suffix: auto_reconnect_video_switch = &Switch {
+                                    set_active: track!(model.changed(SlaveConfigModel::auto_reconnect_video()), *model.get_auto_reconnect_video()),
                                     set_valign: Align::Center,
                                     connect_state_set(sender) => move |_switch, state| {
-                                        send!(sender, SlaveConfigMsg::SetAppSinkQueueLeakyEnabled(state));
+                                        send!(sender, SlaveConfigMsg::SetAutoReconnectVideo(state));
                                         Inhibit(false)
                                     }
                                 },
-                                set_activatable_widget: Some(&appsink_queue_leaky_enabled_switch),
+                                set_activatable_widget: Some(&auto_reconnect_video_switch),
+                            },
+                            add = &ComboRow {
+                                set_title: tr("RTSP 传输协议"),
+                                set_subtitle: tr("接收 RTSP 视频流所使用的传输协议，自动时依次尝试 UDP 与 TCP，位于 NAT 之后的摄像头通常需要使用 TCP"),
+                                set_model: Some(&{
+                                    let model = StringList::new(&[]);
+                                    for value in RtspProtocol::iter() {
+                                        model.append(&value.to_string());
+                                    }
+                                    model
+                                }),
+                                set_selected: track!(model.changed(SlaveConfigModel::rtsp_protocol()), RtspProtocol::iter().position(|x| x == model.rtsp_protocol).unwrap() as u32),
+                                connect_selected_notify(sender) => move |row| {
+                                    send!(sender, SlaveConfigMsg::SetRtspProtocol(RtspProtocol::iter().nth(row.selected() as usize).unwrap()));
+                                }
                             },
                             add = &ExpanderRow {
-                                set_title: "手动配置管道",
+                                set_title: tr("手动配置管道"),
                                 set_show_enable_switch: true,
                                 set_expanded: !*model.get_use_decodebin(),
                                 set_enable_expansion: track!(model.changed(SlaveConfigModel::use_decodebin()), !*model.get_use_decodebin()),
@@ -311,9 +1240,9 @@ impl MicroWidgets<SlaveConfigModel> for SlaveConfigWidgets {
                                     send!(sender, SlaveConfigMsg::SetUsePlaybin(!expander.enables_expansion()));
                                 },
                                 add_row = &ActionRow {
-                                    set_title: "接收缓冲区延迟",
-                                    set_subtitle: "可以增加接收缓冲区延迟，牺牲视频的实时性来换取流畅度的提升",
-                                    add_suffix = &SpinButton::with_range(0.0, 60000.0, 50.0) {
+                                    set_title: tr("接收缓冲区延迟"),
+                                    set_subtitle: tr("可以增加接收缓冲区延迟，牺牲视频的实时性来换取流畅度的提升"),
+                                    add_suffix = &SpinButton::with_range(0.0, VIDEO_LATENCY_MAX as f64, 50.0) {
                                         set_value: track!(model.changed(SlaveConfigModel::video_latency()), model.video_latency as f64),
                                         set_digits: 0,
                                         set_valign: Align::Center,
@@ -323,12 +1252,12 @@ impl MicroWidgets<SlaveConfigModel> for SlaveConfigWidgets {
                                         }
                                     },
                                     add_suffix = &Label {
-                                        set_label: "毫秒",
+                                        set_label: tr("毫秒"),
                                     },
                                 },
                                 add_row = &ComboRow {
-                                    set_title: "色彩空间转换",
-                                    set_subtitle: "设置视频编解码、视频流显示要求的色彩空间转换所使用的硬件",
+                                    set_title: tr("色彩空间转换"),
+                                    set_subtitle: tr("设置视频编解码、视频流显示要求的色彩空间转换所使用的硬件"),
                                     set_model: Some(&{
                                         let model = StringList::new(&[]);
                                         for value in ColorspaceConversion::iter() {
@@ -342,8 +1271,8 @@ impl MicroWidgets<SlaveConfigModel> for SlaveConfigWidgets {
                                     }
                                 },
                                 add_row = &ComboRow {
-                                    set_title: "解码器",
-                                    set_subtitle: "解码视频流使用的解码器",
+                                    set_title: tr("解码器"),
+                                    set_subtitle: tr("解码视频流使用的解码器"),
                                     set_model: Some(&{
                                         let model = StringList::new(&[]);
                                         for value in VideoCodec::iter() {
@@ -357,8 +1286,8 @@ impl MicroWidgets<SlaveConfigModel> for SlaveConfigWidgets {
                                     }
                                 },
                                 add_row = &ComboRow {
-                                    set_title: "解码器接口",
-                                    set_subtitle: "解码视频流使用的解码器接口",
+                                    set_title: tr("解码器接口"),
+                                    set_subtitle: tr("解码视频流使用的解码器接口"),
                                     set_model: Some(&{
                                         let model = StringList::new(&[]);
                                         for value in VideoCodecProvider::iter() {
@@ -373,7 +1302,8 @@ impl MicroWidgets<SlaveConfigModel> for SlaveConfigWidgets {
                                 },
                             },
                             add = &ExpanderRow {
-                                set_title: "录制时重新编码",
+                                set_title: tr("录制时重新编码"),
+                                set_subtitle: tr("解码后按指定的编码器与码率重新编码再录制，会占用大量 CPU，关闭时直接录制接收到的视频流"),
                                 set_show_enable_switch: true,
                                 set_expanded: *model.get_reencode_recording_video(),
                                 set_enable_expansion: track!(model.changed(SlaveConfigModel::reencode_recording_video()), *model.get_reencode_recording_video()),
@@ -381,8 +1311,8 @@ impl MicroWidgets<SlaveConfigModel> for SlaveConfigWidgets {
                                     send!(sender, SlaveConfigMsg::SetReencodeRecordingVideo(expander.enables_expansion()));
                                 },
                                 add_row = &ComboRow {
-                                    set_title: "编码器",
-                                    set_subtitle: "视频录制时使用的编码器",
+                                    set_title: tr("编码器"),
+                                    set_subtitle: tr("视频录制时使用的编码器"),
                                     set_model: Some(&{
                                         let model = StringList::new(&[]);
                                         for value in VideoCodec::iter() {
@@ -396,8 +1326,8 @@ impl MicroWidgets<SlaveConfigModel> for SlaveConfigWidgets {
                                     }
                                 },
                                 add_row = &ComboRow {
-                                    set_title: "编码器接口",
-                                    set_subtitle: "视频录制时调用的编码器接口",
+                                    set_title: tr("编码器接口"),
+                                    set_subtitle: tr("视频录制时调用的编码器接口"),
                                     set_model: Some(&{
                                         let model = StringList::new(&[]);
                                         for value in VideoCodecProvider::iter() {
@@ -410,6 +1340,99 @@ impl MicroWidgets<SlaveConfigModel> for SlaveConfigWidgets {
                                         send!(sender, SlaveConfigMsg::SetVideoEncoderCodecProvider(VideoCodecProvider::iter().nth(row.selected() as usize).unwrap()))
                                     }
                                 },
+                                add_row = &ActionRow {
+                                    set_title: tr("码率"),
+                                    set_subtitle: tr("重新编码录制时的目标码率"),
+                                    add_suffix = &SpinButton::with_range(RECORDING_BITRATE_RANGE.0 as f64, RECORDING_BITRATE_RANGE.1 as f64, 500.0) {
+                                        set_value: track!(model.changed(SlaveConfigModel::recording_bitrate()), model.recording_bitrate as f64),
+                                        set_digits: 0,
+                                        set_valign: Align::Center,
+                                        set_can_focus: false,
+                                        connect_value_changed(sender) => move |button| {
+                                            send!(sender, SlaveConfigMsg::SetRecordingBitrate(button.value() as u32));
+                                        }
+                                    },
+                                    add_suffix = &Label {
+                                        set_label: "kbps",
+                                    },
+                                },
+                                add_row = &ActionRow {
+                                    set_title: tr("录制时旋转与翻转"),
+                                    set_subtitle: tr("对录制的视频应用与画面相同的旋转与翻转，未重新编码时录制的视频始终保持原样"),
+                                    add_suffix: orient_recording_video_switch = &Switch {
+                                        set_active: track!(model.changed(SlaveConfigModel::orient_recording_video()), *model.get_orient_recording_video()),
+                                        set_valign: Align::Center,
+                                        connect_state_set(sender) => move |_switch, state| {
+                                            send!(sender, SlaveConfigMsg::SetOrientRecordingVideo(state));
+                                            Inhibit(false)
+                                        }
+                                    },
+                                    set_activatable_widget: Some(&orient_recording_video_switch),
+                                },
+                            },
+                            add = &ExpanderRow {
+                                set_title: tr("同时录制代理视频"),
+                                set_subtitle: tr("录制时额外保存一份降低分辨率的视频以便快速回看，使用上方配置的编码器"),
+                                set_show_enable_switch: true,
+                                set_expanded: *model.get_proxy_recording_enabled(),
+                                set_enable_expansion: track!(model.changed(SlaveConfigModel::proxy_recording_enabled()), *model.get_proxy_recording_enabled()),
+                                connect_enable_expansion_notify(sender) => move |expander| {
+                                    send!(sender, SlaveConfigMsg::SetProxyRecordingEnabled(expander.enables_expansion()));
+                                },
+                                add_row = &ActionRow {
+                                    set_title: tr("代理视频高度"),
+                                    set_subtitle: tr("代理视频的垂直分辨率，宽度按画面比例缩放"),
+                                    add_suffix = &SpinButton::with_range(144.0, 2160.0, 2.0) {
+                                        set_value: track!(model.changed(SlaveConfigModel::proxy_recording_height()), model.proxy_recording_height as f64),
+                                        set_digits: 0,
+                                        set_valign: Align::Center,
+                                        set_can_focus: false,
+                                        connect_value_changed(sender) => move |button| {
+                                            send!(sender, SlaveConfigMsg::SetProxyRecordingHeight(button.value() as u32));
+                                        }
+                                    },
+                                    add_suffix = &Label {
+                                        set_label: tr("像素"),
+                                    },
+                                },
+                            },
+                            add = &ExpanderRow {
+                                set_title: tr("录制遥测字幕"),
+                                set_subtitle: tr("录制时将下位机状态信息写入与录像同名的字幕文件"),
+                                set_show_enable_switch: true,
+                                set_expanded: *model.get_telemetry_subtitle_enabled(),
+                                set_enable_expansion: track!(model.changed(SlaveConfigModel::telemetry_subtitle_enabled()), *model.get_telemetry_subtitle_enabled()),
+                                connect_enable_expansion_notify(sender) => move |expander| {
+                                    send!(sender, SlaveConfigMsg::SetTelemetrySubtitleEnabled(expander.enables_expansion()));
+                                },
+                                add_row = &ComboRow {
+                                    set_title: tr("字幕格式"),
+                                    set_subtitle: tr("遥测字幕文件的格式"),
+                                    set_model: Some(&{
+                                        let model = StringList::new(&[]);
+                                        for value in SubtitleFormat::iter() {
+                                            model.append(&value.to_string());
+                                        }
+                                        model
+                                    }),
+                                    set_selected: track!(model.changed(SlaveConfigModel::telemetry_subtitle_format()), SubtitleFormat::iter().position(|x| x == model.telemetry_subtitle_format).unwrap() as u32),
+                                    connect_selected_notify(sender) => move |row| {
+                                        send!(sender, SlaveConfigMsg::SetTelemetrySubtitleFormat(SubtitleFormat::iter().nth(row.selected() as usize).unwrap()))
+                                    }
+                                },
+                            },
+                            add = &ActionRow {
+                                set_title: tr("记录帧时间戳"),
+                                set_subtitle: tr("录制时将每帧的 PTS 与到达上位机的系统时间写入与录像同名的 .timestamps.csv 文件，用于对齐多个机位的录像；到达时间为软件时间戳，包含网络与解码延迟，机位之间的误差通常为数十毫秒，无法代替硬件同步"),
+                                add_suffix: frame_timestamps_switch = &Switch {
+                                    set_active: track!(model.changed(SlaveConfigModel::frame_timestamps_enabled()), *model.get_frame_timestamps_enabled()),
+                                    set_valign: Align::Center,
+                                    connect_state_set(sender) => move |_switch, state| {
+                                        send!(sender, SlaveConfigMsg::SetFrameTimestampsEnabled(state));
+                                        Inhibit(false)
+                                    }
+                                },
+                                set_activatable_widget: Some(&frame_timestamps_switch),
                             },
                         },
                     },
@@ -18,17 +18,22 @@
 
 use std::{
     cell::RefCell,
+    collections::BTreeMap,
     fmt::Debug,
+    fs,
     path::PathBuf,
     rc::Rc,
     sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 
 use adw::StatusPage;
 use gdk_pixbuf::Pixbuf;
-use glib::{clone, MainContext, Sender};
-use gst::{prelude::*, Pipeline};
-use gtk::{prelude::*, Box as GtkBox, Picture, Stack};
+use glib::{clone, MainContext, Sender, SourceId};
+use gst::{prelude::*, MessageView, Pipeline};
+use gtk::{
+    gsk, prelude::*, Align, Box as GtkBox, DrawingArea, Frame, GestureClick, Label, Overlay, Picture, Stack,
+};
 use relm4::{send, MicroModel, MicroWidgets};
 use relm4_macros::micro_widget;
 
@@ -37,10 +42,48 @@ use derivative::*;
 use super::{slave_config::SlaveConfigModel, SlaveMsg};
 use crate::{
     async_glib::{Future, Promise},
-    preferences::PreferencesModel,
-    slave::video::{ImageFormat, MatExt, VideoSource},
+    i18n::tr,
+    file_name::{create_directory, timestamp_file_name, unique_file_path},
+    preferences::{get_pipeline_graph_path, is_remote_path, PreferencesModel},
+    slave::video::{
+        attach_frame_counter, attach_stream_statistics, FrameTimestamp, FrameTimestampLog, FrameTimestampRecorder, Histogram, ImageFormat,
+        MatExt, StreamCounter, TelemetrySubtitle, VideoEncoder, VideoFormat, VideoSource, HISTOGRAM_BINS,
+    },
 };
 
+// 单路录制的编码设置，不指定编码器时直接录制接收到的视频流
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordSettings {
+    pub encoder: Option<VideoEncoder>,
+    pub height: Option<u32>,
+    pub bitrate: Option<u32>, // 重新编码时的目标码率（kbps），不指定时使用编码器的默认值
+}
+
+// 按照机位配置创建视频管道，视频 URL 的协议不受支持时返回 None
+pub fn create_pipeline_from_config(config: &SlaveConfigModel) -> Option<Result<Pipeline, String>> {
+    let video_source = VideoSource::from_url(config.get_video_url())?;
+    Some(if *config.get_use_decodebin() {
+        super::video::create_decodebin_pipeline(
+            video_source,
+            config.get_rtsp_protocol().clone(),
+            *config.get_appsink_queue_mode(),
+        )
+    } else {
+        super::video::create_pipeline(
+            video_source,
+            *config.get_video_latency(),
+            config.get_rtsp_protocol().clone(),
+            config.get_colorspace_conversion().clone(),
+            config.get_video_decoder().clone(),
+            *config.get_appsink_queue_mode(),
+        )
+    })
+}
+
+const RECONNECT_INITIAL_DELAY: Duration = Duration::from_secs(1);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+const SCREENSHOT_RETRY_INITIAL_DELAY: Duration = Duration::from_secs(1);
+
 #[tracker::track]
 #[derive(Debug, Derivative)]
 #[derivative(Default)]
@@ -48,12 +91,59 @@ pub struct SlaveVideoModel {
     #[no_eq]
     pub pixbuf: Option<Pixbuf>,
     #[no_eq]
+    pub native_paintable: Option<gdk::Paintable>, // 使用 GTK 视频输出时由其渲染的画面
+    pub native_frame: bool, // GTK 视频输出已收到画面，此时显示 native_paintable 而不是 pixbuf
+    pub frame_count: u64,
+    #[no_eq]
     pub pipeline: Option<Pipeline>,
     #[no_eq]
+    pub bus_watch: Option<SourceId>,
+    pub reconnect_attempt: Option<u32>,
+    #[no_eq]
+    pub reconnect_timer: Option<SourceId>,
+    #[no_eq]
     pub config: Arc<Mutex<SlaveConfigModel>>,
-    pub record_handle: Option<((gst::Element, gst::Pad), Vec<gst::Element>)>,
+    pub record_handles: BTreeMap<PathBuf, ((gst::Element, gst::Pad), Vec<gst::Element>)>,
+    pub record_start_time: Option<Instant>,
+    pub record_elapsed: u64, // 已录制的秒数，仅用于显示
+    #[no_eq]
+    pub record_timer: Option<SourceId>,
+    #[no_eq]
+    pub telemetry_subtitle: Option<(PathBuf, TelemetrySubtitle)>,
+    #[no_eq]
+    pub frame_timestamp_recorder: Option<(PathBuf, FrameTimestampRecorder)>,
+    #[no_eq]
+    pub frame_timestamp: Arc<Mutex<Option<FrameTimestamp>>>, // 最近一次显示的帧的时间戳
+    #[no_eq]
+    pub aborted_record_elements: Vec<String>, // 已因出错而移除的录制分支中的元素，忽略其后续报告的错误
+    #[no_eq]
+    pub stream_counter: Option<Arc<Mutex<StreamCounter>>>,
+    #[no_eq]
+    pub statistics_sampled_time: Option<Instant>,
+    #[no_eq]
+    pub statistics_timer: Option<SourceId>,
+    #[no_eq]
+    pub queued_frame_counter: Option<Arc<Mutex<u64>>>, // 当前管道进入显示分支的帧数
+    pub pipeline_start_frame_count: u64, // 当前管道启动时已显示的帧数
+    pub dropped_frames: u64, // 此前的管道丢弃的帧数
+    pub video_reconnects: u64,
     #[derivative(Default(value = "Rc::new(RefCell::new(PreferencesModel::load_or_default()))"))]
     pub preferences: Rc<RefCell<PreferencesModel>>,
+    pub measurement_points: Vec<(i32, i32)>,
+    pub measurement: Option<String>,
+    #[no_eq]
+    pub histogram: Rc<RefCell<Option<Histogram>>>,
+    #[no_eq]
+    pub secondary_pipeline: Option<Pipeline>, // 副摄像头的管道，仅在开启画中画时运行
+    #[no_eq]
+    pub secondary_bus_watch: Option<SourceId>,
+    #[no_eq]
+    pub secondary_pixbuf: Option<Pixbuf>,
+    pub pip_swapped: bool, // 主画面显示副摄像头的画面，画中画显示主摄像头的画面
+    #[no_eq]
+    pub secondary_record_handle: Option<(PathBuf, (gst::Element, gst::Pad), Vec<gst::Element>)>,
+    #[no_eq]
+    pub secondary_record_stopping: Option<Future<()>>, // 副摄像头的录制分支写入完成前不能停止其管道
 }
 
 impl SlaveVideoModel {
@@ -71,20 +161,793 @@ impl SlaveVideoModel {
         self.pipeline.is_some()
     }
 
+    pub fn has_frame(&self) -> bool {
+        self.pixbuf.is_some() || self.native_frame
+    }
+
+    pub fn is_secondary_running(&self) -> bool {
+        self.secondary_pipeline.is_some()
+    }
+
+    fn secondary_paintable(&self) -> Option<gdk::Paintable> {
+        self.secondary_pixbuf
+            .as_ref()
+            .map(|pixbuf| gdk::Texture::for_pixbuf(pixbuf).upcast())
+    }
+
+    pub fn main_paintable(&self) -> Option<gdk::Paintable> {
+        if self.pip_swapped {
+            self.secondary_paintable()
+        } else {
+            self.display_paintable()
+        }
+    }
+
+    pub fn pip_paintable(&self) -> Option<gdk::Paintable> {
+        if self.pip_swapped {
+            self.display_paintable()
+        } else {
+            self.secondary_paintable()
+        }
+    }
+
+    pub fn display_paintable(&self) -> Option<gdk::Paintable> {
+        if self.native_frame {
+            self.native_paintable.clone()
+        } else {
+            self.pixbuf
+                .as_ref()
+                .map(|pixbuf| gdk::Texture::for_pixbuf(pixbuf).upcast())
+        }
+    }
+
+    // GTK 视频输出的画面不经过内存中的 Pixbuf，截图时将当前画面绘制为纹理后再读回
+    pub fn current_pixbuf(&self) -> Option<Pixbuf> {
+        if !self.native_frame {
+            return self.pixbuf.clone();
+        }
+        let image = self.native_paintable.as_ref()?.current_image();
+        let (width, height) = (image.intrinsic_width(), image.intrinsic_height());
+        if width <= 0 || height <= 0 {
+            return None;
+        }
+        let snapshot = gtk::Snapshot::new();
+        image.snapshot(&snapshot, width as f64, height as f64);
+        let node = snapshot.to_node()?;
+        let renderer = gsk::CairoRenderer::new();
+        renderer.realize(None).ok()?;
+        let texture = renderer.render_texture(&node, None);
+        renderer.unrealize();
+        gdk::pixbuf_get_from_texture(&texture)
+    }
+
+    // 两种显示方式收到画面时共同的处理
+    fn frame_arrived(&mut self, parent_sender: &Sender<SlaveMsg>) {
+        if !self.has_frame() {
+            send!(parent_sender, SlaveMsg::PollingChanged(true)); // 主要是更新截图按钮的状态
+        }
+        if self.reconnect_attempt.is_some() {
+            log::info!(
+                "[{}] 视频流已重新连接",
+                self.config.lock().unwrap().get_video_url()
+            );
+            self.set_reconnect_attempt(None);
+        }
+        self.frame_count += 1;
+    }
+
+    // 使用 GTK 视频输出时启用了需要访问画面的功能，在未录制时改用 appsink 重新启动管道
+    fn restart_for_frame_processing(&mut self, parent_sender: &Sender<SlaveMsg>, sender: &Sender<SlaveVideoMsg>) {
+        if self.is_recording() {
+            send!(
+                parent_sender,
+                SlaveMsg::ShowToastMessage(String::from(tr("录制期间无法切换画面的显示方式，画面处理将在重新拉流后生效。")))
+            );
+            return;
+        }
+        log::info!(
+            "[{}] 画面处理需要访问画面数据，改用 appsink 重新启动管道",
+            self.config.lock().unwrap().get_video_url()
+        );
+        if let Some(bus_watch) = self.bus_watch.take() {
+            bus_watch.remove();
+        }
+        self.stop_stream_statistics(parent_sender);
+        if let Some(pipeline) = self.pipeline.take() {
+            super::video::discard_pipeline(&pipeline);
+        }
+        self.update(SlaveVideoMsg::StartPipeline, parent_sender, sender.clone());
+    }
+
+    // 进入显示分支但未能显示的帧数，包括少量正在处理的帧
+    pub fn dropped_frame_count(&self) -> u64 {
+        let displayed = self.frame_count - self.pipeline_start_frame_count;
+        self.dropped_frames
+            + self
+                .queued_frame_counter
+                .as_ref()
+                .map_or(0, |counter| counter.lock().unwrap().saturating_sub(displayed))
+    }
+
     pub fn is_recording(&self) -> bool {
-        self.record_handle.is_some()
+        !self.record_handles.is_empty()
+    }
+
+    pub fn record_indicator_markup(&self) -> String {
+        let elapsed = self.record_elapsed;
+        format!(
+            "<span foreground=\"#e01b24\">●</span> REC {:02}:{:02}:{:02}",
+            elapsed / 3600,
+            elapsed / 60 % 60,
+            elapsed % 60
+        )
+    }
+
+    // 开始录制时记录开始时间并每秒更新录制时长，全部录制分支停止后立即清除
+    fn update_record_indicator(&mut self, sender: &Sender<SlaveVideoMsg>) {
+        match (self.is_recording(), self.record_start_time.is_some()) {
+            (true, false) => {
+                self.set_record_start_time(Some(Instant::now()));
+                self.set_record_elapsed(0);
+                self.record_timer = Some(glib::timeout_add_local(
+                    Duration::from_secs(1),
+                    clone!(@strong sender => move || {
+                        Continue(sender.send(SlaveVideoMsg::RecordTick).is_ok())
+                    }),
+                ));
+            }
+            (false, true) => {
+                if let Some(timer) = self.record_timer.take() {
+                    timer.remove();
+                }
+                self.set_record_start_time(None);
+            }
+            _ => (),
+        }
+    }
+
+    // 每次启动管道时重新开始统计，此后每秒将统计结果发送给机位
+    fn start_stream_statistics(&mut self, pipeline: &Pipeline, sender: &Sender<SlaveVideoMsg>) {
+        let frame_counter = Arc::new(Mutex::new(0));
+        if attach_frame_counter(pipeline, frame_counter.clone()) {
+            self.queued_frame_counter = Some(frame_counter);
+            self.pipeline_start_frame_count = self.frame_count;
+        }
+        let counter = Arc::new(Mutex::new(StreamCounter::default()));
+        if attach_stream_statistics(pipeline, counter.clone()) {
+            self.stream_counter = Some(counter);
+            self.statistics_sampled_time = Some(Instant::now());
+            self.statistics_timer = Some(glib::timeout_add_local(
+                Duration::from_secs(1),
+                clone!(@strong sender => move || {
+                    Continue(sender.send(SlaveVideoMsg::StatisticsTick).is_ok())
+                }),
+            ));
+        }
+    }
+
+    fn stop_stream_statistics(&mut self, parent_sender: &Sender<SlaveMsg>) {
+        if let Some(timer) = self.statistics_timer.take() {
+            timer.remove();
+        }
+        self.stream_counter = None;
+        self.statistics_sampled_time = None;
+        self.dropped_frames = self.dropped_frame_count();
+        self.queued_frame_counter = None;
+        send!(parent_sender, SlaveMsg::StreamStatisticsUpdated(None));
+    }
+
+    fn start_record_branch(
+        &mut self,
+        pathbuf: PathBuf,
+        settings: RecordSettings,
+        parent_sender: &Sender<SlaveMsg>,
+    ) -> bool {
+        log::info!(
+            "[{}] 开始录制：{}",
+            self.config.lock().unwrap().get_slave_url(),
+            pathbuf.to_string_lossy()
+        );
+        if self.record_handles.contains_key(&pathbuf) {
+            return false;
+        }
+        let record_handle = match &self.pipeline {
+            Some(pipeline) => connect_record_branch(pipeline, &self.config.lock().unwrap(), &settings, &pathbuf),
+            None => return false,
+        };
+        match record_handle {
+            Ok((elements, pad)) => {
+                self.get_mut_record_handles()
+                    .insert(pathbuf, (pad, Vec::from(elements)));
+                send!(parent_sender, SlaveMsg::RecordingChanged(true));
+                true
+            }
+            Err(err) => {
+                send!(parent_sender, SlaveMsg::ErrorMessage(err.to_string()));
+                send!(parent_sender, SlaveMsg::RecordingChanged(self.is_recording()));
+                false
+            }
+        }
+    }
+
+    fn start_telemetry_subtitle(&mut self, record_pathbuf: &PathBuf) {
+        let format = *self.config.lock().unwrap().get_telemetry_subtitle_format();
+        let pathbuf = record_pathbuf.with_extension(format.extension());
+        match TelemetrySubtitle::create(&pathbuf, format) {
+            Ok(subtitle) => self.telemetry_subtitle = Some((record_pathbuf.clone(), subtitle)),
+            Err(err) => log::error!(
+                "[{}] 无法创建遥测字幕文件 {}：{}",
+                self.config.lock().unwrap().get_slave_url(),
+                pathbuf.to_string_lossy(),
+                err
+            ),
+        }
+    }
+
+    // 帧时间戳文件与录像同名，扩展名为 .timestamps.csv
+    fn start_frame_timestamp_recorder(&mut self, record_pathbuf: &PathBuf) {
+        let slave_url = self.config.lock().unwrap().get_slave_url().clone();
+        let pad = match self.record_handles.get(record_pathbuf) {
+            Some(((_, pad), _)) => pad.clone(),
+            None => return,
+        };
+        let pathbuf = record_pathbuf.with_extension("timestamps.csv");
+        match FrameTimestampLog::create(&pathbuf, &slave_url) {
+            Ok(log) => match FrameTimestampRecorder::attach(&pad, log) {
+                Some(recorder) => self.frame_timestamp_recorder = Some((record_pathbuf.clone(), recorder)),
+                None => log::error!("[{}] 无法在录制分支上记录帧时间戳", slave_url),
+            },
+            Err(err) => log::error!(
+                "[{}] 无法创建帧时间戳文件 {}：{}",
+                slave_url,
+                pathbuf.to_string_lossy(),
+                err
+            ),
+        }
+    }
+
+    fn finish_frame_timestamp_recorder(&mut self) {
+        if let Some((pathbuf, recorder)) = self.frame_timestamp_recorder.take() {
+            if let Err(err) = recorder.finish() {
+                log::error!(
+                    "[{}] 无法写入录像 {} 的帧时间戳：{}",
+                    self.config.lock().unwrap().get_slave_url(),
+                    pathbuf.to_string_lossy(),
+                    err
+                );
+            }
+        }
+    }
+
+    fn finish_telemetry_subtitle(&mut self) {
+        if let Some((pathbuf, subtitle)) = self.telemetry_subtitle.take() {
+            if let Err(err) = subtitle.finish() {
+                log::error!(
+                    "[{}] 无法写入录像 {} 的遥测字幕：{}",
+                    self.config.lock().unwrap().get_slave_url(),
+                    pathbuf.to_string_lossy(),
+                    err
+                );
+            }
+        }
+    }
+
+    // 停止指定的录制分支，未指定时停止全部分支，返回的 Future 在各分支写入完成后完成
+    fn stop_record_branches(
+        &mut self,
+        target: Option<PathBuf>,
+        parent_sender: &Sender<SlaveMsg>,
+    ) -> Future<()> {
+        let paths: Vec<PathBuf> = match target {
+            Some(pathbuf) => vec![pathbuf],
+            None => self.record_handles.keys().cloned().collect(),
+        };
+        let mut futures = Vec::<Future<()>>::new();
+        if target.is_none() {
+            if let Some(future) = self.stop_secondary_record_branch() {
+                futures.push(future);
+            }
+        }
+        for pathbuf in paths {
+            if let Some((teepad, elements)) = self.get_mut_record_handles().remove(&pathbuf) {
+                log::info!(
+                    "[{}] 停止录制：{}",
+                    self.config.lock().unwrap().get_slave_url(),
+                    pathbuf.to_string_lossy()
+                );
+                if matches!(&self.telemetry_subtitle, Some((subtitle_pathbuf, _)) if *subtitle_pathbuf == pathbuf) {
+                    self.finish_telemetry_subtitle();
+                }
+                if matches!(&self.frame_timestamp_recorder, Some((timestamp_pathbuf, _)) if *timestamp_pathbuf == pathbuf) {
+                    self.finish_frame_timestamp_recorder();
+                }
+                if let Some(pipeline) = &self.pipeline {
+                    match super::video::disconnect_elements_to_pipeline(
+                        pipeline, &teepad, &elements,
+                    ) {
+                        Ok(future) => futures.push(future),
+                        Err(err) => log::error!("无法停止录制：{}", err),
+                    }
+                }
+            }
+        }
+        let recording = self.is_recording();
+        Future::sequence(futures.into_iter()).map(clone!(@strong parent_sender => move |_| {
+            send!(parent_sender, SlaveMsg::RecordingChanged(recording));
+        }))
+    }
+
+    // 副摄像头与主摄像头同时开始录制，录像文件名以 _secondary 后缀区分
+    fn start_secondary_record_branch(&mut self, pathbuf: &PathBuf, settings: &RecordSettings, parent_sender: &Sender<SlaveMsg>) {
+        let pipeline = match &self.secondary_pipeline {
+            Some(pipeline) if self.secondary_record_handle.is_none() => pipeline,
+            _ => return,
+        };
+        let pathbuf = pathbuf.with_file_name(format!(
+            "{}_secondary.mkv",
+            pathbuf.file_stem().unwrap_or_default().to_string_lossy()
+        ));
+        let config = self.config.lock().unwrap().secondary_config();
+        let config = match config {
+            Some(config) => config,
+            None => return,
+        };
+        match connect_record_branch(pipeline, &config, settings, &pathbuf) {
+            Ok((elements, pad)) => {
+                log::info!("[{}] 开始录制副摄像头：{}", config.get_slave_url(), pathbuf.to_string_lossy());
+                self.secondary_record_handle = Some((pathbuf, pad, elements));
+            }
+            Err(err) => send!(
+                parent_sender,
+                SlaveMsg::ShowToastMessage(format!("{}{}", tr("无法录制副摄像头："), err))
+            ),
+        }
+    }
+
+    fn stop_secondary_record_branch(&mut self) -> Option<Future<()>> {
+        let (pathbuf, teepad, elements) = self.secondary_record_handle.take()?;
+        log::info!(
+            "[{}] 停止录制副摄像头：{}",
+            self.config.lock().unwrap().get_slave_url(),
+            pathbuf.to_string_lossy()
+        );
+        match super::video::disconnect_elements_to_pipeline(self.secondary_pipeline.as_ref()?, &teepad, &elements) {
+            Ok(future) => {
+                self.secondary_record_stopping = Some(future.clone());
+                Some(future)
+            }
+            Err(err) => {
+                log::error!("无法停止录制副摄像头：{}", err);
+                None
+            }
+        }
+    }
+
+    // 副摄像头的管道不进行画面处理，也不参与帧率与码率统计
+    fn start_secondary_pipeline(&mut self, parent_sender: &Sender<SlaveMsg>, sender: &Sender<SlaveVideoMsg>) {
+        let config = match self.config.lock().unwrap().secondary_config() {
+            Some(config) => config,
+            None => return,
+        };
+        log::debug!("启动副摄像头管道：{}", config.get_video_url());
+        let pipeline = match create_pipeline_from_config(&config) {
+            Some(Ok(pipeline)) => pipeline,
+            Some(Err(msg)) => {
+                send!(parent_sender, SlaveMsg::ErrorMessage(msg));
+                send!(parent_sender, SlaveMsg::SecondaryPollingChanged(false));
+                return;
+            }
+            None => {
+                send!(
+                    parent_sender,
+                    SlaveMsg::ErrorMessage(String::from(tr("副摄像头视频 URL 有误，请检查并修改后重试。")))
+                );
+                send!(parent_sender, SlaveMsg::SecondaryPollingChanged(false));
+                return;
+            }
+        };
+        let (mat_sender, mat_receiver) = MainContext::channel(glib::PRIORITY_DEFAULT);
+        let (histogram_sender, _) = MainContext::channel(glib::PRIORITY_DEFAULT);
+        let (format_sender, _) = MainContext::channel(glib::PRIORITY_DEFAULT);
+        if let Err(msg) = super::video::attach_pipeline_callback(
+            &pipeline,
+            mat_sender,
+            histogram_sender,
+            format_sender,
+            Arc::new(Mutex::new(config)),
+            Default::default(),
+        ) {
+            super::video::discard_pipeline(&pipeline);
+            send!(parent_sender, SlaveMsg::ErrorMessage(msg));
+            send!(parent_sender, SlaveMsg::SecondaryPollingChanged(false));
+            return;
+        }
+        mat_receiver.attach(
+            None,
+            clone!(@strong sender => move |mat| {
+                Continue(sender.send(SlaveVideoMsg::SetSecondaryPixbuf(mat.as_pixbuf())).is_ok())
+            }),
+        );
+        let bus_watch = watch_secondary_pipeline_bus(&pipeline, sender.clone());
+        match pipeline.set_state(gst::State::Playing) {
+            Ok(_) => {
+                self.secondary_pipeline = Some(pipeline);
+                self.secondary_bus_watch = bus_watch;
+                send!(parent_sender, SlaveMsg::SecondaryPollingChanged(true));
+            }
+            Err(_) => {
+                if let Some(bus_watch) = bus_watch {
+                    bus_watch.remove();
+                }
+                super::video::discard_pipeline(&pipeline);
+                send!(parent_sender, SlaveMsg::ErrorMessage(String::from(tr("无法启动副摄像头的管道，请检查副摄像头视频 URL 及相关资源是否可用。"))));
+                send!(parent_sender, SlaveMsg::SecondaryPollingChanged(false));
+            }
+        }
+    }
+
+    // 正在录制副摄像头时等待录像写入完成后再停止管道
+    fn stop_secondary_pipeline(&mut self, parent_sender: &Sender<SlaveMsg>) {
+        if let Some(bus_watch) = self.secondary_bus_watch.take() {
+            bus_watch.remove();
+        }
+        let recording = self.stop_secondary_record_branch();
+        let pending = self.secondary_record_stopping.take();
+        if let Some(pipeline) = self.secondary_pipeline.take() {
+            match recording.or(pending) {
+                Some(future) => future.for_each(move |_| {
+                    pipeline.set_state(gst::State::Null).ok();
+                }),
+                None => {
+                    pipeline.set_state(gst::State::Null).ok();
+                }
+            }
+        }
+        self.set_secondary_pixbuf(None);
+        self.set_pip_swapped(false);
+        send!(parent_sender, SlaveMsg::SecondaryPollingChanged(false));
+    }
+
+    // 导出当前运行中的主、副摄像头管道的结构图，没有运行中的管道时返回空列表
+    fn export_pipeline_graphs(&self) -> Result<Vec<PathBuf>, String> {
+        let pipelines: Vec<(&Pipeline, &str)> = [(self.pipeline.as_ref(), ""), (self.secondary_pipeline.as_ref(), "_secondary")]
+            .into_iter()
+            .filter_map(|(pipeline, suffix)| pipeline.map(|pipeline| (pipeline, suffix)))
+            .collect();
+        if pipelines.is_empty() {
+            return Ok(Vec::new());
+        }
+        let directory = get_pipeline_graph_path();
+        create_directory(&directory)?;
+        let name = timestamp_file_name();
+        pipelines
+            .into_iter()
+            .map(|(pipeline, suffix)| {
+                let pathbuf = unique_file_path(&directory, &format!("{}{}", name, suffix), "dot");
+                let dot = pipeline.debug_to_dot_data(gst::DebugGraphDetails::all());
+                fs::write(&pathbuf, dot.as_str()).map_err(|err| err.to_string())?;
+                Ok(pathbuf)
+            })
+            .collect()
+    }
+
+    // 每次重试的间隔翻倍，直至达到上限
+    fn schedule_reconnect(&mut self, sender: &Sender<SlaveVideoMsg>) {
+        let attempt = self.reconnect_attempt.map_or(1, |attempt| attempt + 1);
+        let delay =
+            (RECONNECT_INITIAL_DELAY * 2u32.pow((attempt - 1).min(5))).min(RECONNECT_MAX_DELAY);
+        log::info!(
+            "[{}] 将在 {} 秒后第 {} 次尝试重新连接视频流",
+            self.config.lock().unwrap().get_video_url(),
+            delay.as_secs(),
+            attempt
+        );
+        self.set_reconnect_attempt(Some(attempt));
+        self.video_reconnects += 1;
+        self.set_pixbuf(None);
+        self.set_native_frame(false);
+        self.reconnect_timer = Some(glib::timeout_add_local_once(
+            delay,
+            clone!(@strong sender => move || {
+                send!(sender, SlaveVideoMsg::RestartPipeline);
+            }),
+        ));
+    }
+
+    fn save_pixbuf(&self, pathbuf: &PathBuf) -> Option<Result<(), glib::Error>> {
+        Some(write_pixbuf(&self.current_pixbuf()?, pathbuf))
+    }
+
+    // 写入网络存储失败时将画面保留在内存中，按照间隔翻倍的方式重试
+    fn save_screenshot(
+        &self,
+        pathbuf: PathBuf,
+        pixbuf: Pixbuf,
+        attempt: u32,
+        parent_sender: &Sender<SlaveMsg>,
+        sender: &Sender<SlaveVideoMsg>,
+    ) {
+        match write_pixbuf(&pixbuf, &pathbuf) {
+            Ok(_) => send!(
+                parent_sender,
+                SlaveMsg::ShowToastMessage(format!(
+                    "{}{}",
+                    tr("截图保存成功："),
+                    pathbuf.to_str().unwrap()
+                ))
+            ),
+            Err(err) if attempt < *self.preferences.borrow().get_network_write_retries() as u32 && is_remote_path(&pathbuf) => {
+                let delay = SCREENSHOT_RETRY_INITIAL_DELAY * 2u32.pow(attempt);
+                log::warn!(
+                    "[{}] 截图写入网络存储失败，{} 秒后进行第 {} 次重试：{}",
+                    self.config.lock().unwrap().get_slave_url(),
+                    delay.as_secs(),
+                    attempt + 1,
+                    err
+                );
+                glib::timeout_add_local_once(
+                    delay,
+                    clone!(@strong sender => move || {
+                        send!(sender, SlaveVideoMsg::RetrySaveScreenshot(pathbuf, pixbuf, attempt + 1));
+                    }),
+                );
+            }
+            Err(err) => send!(
+                parent_sender,
+                SlaveMsg::ShowToastMessage(format!(
+                    "{}{}",
+                    tr("截图保存失败："),
+                    err.to_string()
+                ))
+            ),
+        }
+    }
+
+    // 录制分支中的元素（如写入网络存储的 filesink）出错时仅停止该分支，不影响拉流与其他录制分支
+    fn abort_record_branch(&mut self, source: &str, error: &str, parent_sender: &Sender<SlaveMsg>) -> bool {
+        let pathbuf = match self
+            .record_handles
+            .iter()
+            .find(|(_, (_, elements))| elements.iter().any(|element| element.name().as_str() == source))
+        {
+            Some((pathbuf, _)) => pathbuf.clone(),
+            None => return false,
+        };
+        let (teepad, elements) = self.get_mut_record_handles().remove(&pathbuf).unwrap();
+        log::error!(
+            "[{}] 录像 {} 写入失败，录制已停止：{}",
+            self.config.lock().unwrap().get_slave_url(),
+            pathbuf.to_string_lossy(),
+            error
+        );
+        self.aborted_record_elements
+            .extend(elements.iter().map(|element| element.name().to_string()));
+        if matches!(&self.telemetry_subtitle, Some((subtitle_pathbuf, _)) if *subtitle_pathbuf == pathbuf) {
+            self.finish_telemetry_subtitle();
+        }
+        if matches!(&self.frame_timestamp_recorder, Some((timestamp_pathbuf, _)) if *timestamp_pathbuf == pathbuf) {
+            self.finish_frame_timestamp_recorder();
+        }
+        if let Some(pipeline) = &self.pipeline {
+            if let Err(err) = super::video::remove_elements_from_pipeline(pipeline, &teepad, &elements) {
+                log::error!("无法移除录制分支：{}", err);
+            }
+        }
+        send!(parent_sender, SlaveMsg::RecordingChanged(self.is_recording()));
+        send!(
+            parent_sender,
+            SlaveMsg::ShowToastMessage(format!(
+                "{}{}：{}",
+                tr("录像写入失败，录制已停止："),
+                pathbuf.to_string_lossy(),
+                error
+            ))
+        );
+        true
+    }
+
+    fn cancel_reconnect(&mut self) {
+        if let Some(reconnect_timer) = self.reconnect_timer.take() {
+            reconnect_timer.remove();
+        }
+        self.set_reconnect_attempt(None);
+    }
+
+    // 将控件上的坐标映射为画面上的像素坐标
+    fn map_to_frame(&self, x: f64, y: f64, width: i32, height: i32) -> Option<(i32, i32)> {
+        let pixbuf = self.pixbuf.as_ref()?;
+        widget_to_frame_coordinates(
+            (x, y),
+            (width, height),
+            (pixbuf.width(), pixbuf.height()),
+            *self.config.lock().unwrap().get_keep_video_display_ratio(),
+        )
+    }
+
+    fn pixel_at(&self, (x, y): (i32, i32)) -> Option<(u8, u8, u8)> {
+        let pixbuf = self.pixbuf.as_ref()?;
+        let offset = (y * pixbuf.rowstride() + x * pixbuf.n_channels()) as usize;
+        let pixels = unsafe { pixbuf.pixels() };
+        match pixels.get(offset..offset + 3) {
+            Some([r, g, b]) => Some((*r, *g, *b)),
+            _ => None,
+        }
+    }
+}
+
+// 将画面控件上的坐标换算为画面的像素坐标。画面会缩放至填满控件，保持长宽比时居中显示并在两侧或上下留出黑边，
+// 点击落在黑边上或画面尺寸无效时返回 None。缩放、选区与取色等需要定位画面像素的功能均应使用此函数
+pub fn widget_to_frame_coordinates(
+    (x, y): (f64, f64),
+    (width, height): (i32, i32),
+    (frame_width, frame_height): (i32, i32),
+    keep_ratio: bool,
+) -> Option<(i32, i32)> {
+    if width <= 0 || height <= 0 || frame_width <= 0 || frame_height <= 0 {
+        return None;
+    }
+    let (width, height) = (width as f64, height as f64);
+    let (frame_width, frame_height) = (frame_width as f64, frame_height as f64);
+    let (scale_x, scale_y) = if keep_ratio {
+        let scale = (width / frame_width).min(height / frame_height);
+        (scale, scale)
+    } else {
+        (width / frame_width, height / frame_height)
+    };
+    let offset_x = (width - frame_width * scale_x) / 2.0;
+    let offset_y = (height - frame_height * scale_y) / 2.0;
+    let frame_x = ((x - offset_x) / scale_x).floor();
+    let frame_y = ((y - offset_y) / scale_y).floor();
+    if frame_x < 0.0 || frame_y < 0.0 || frame_x >= frame_width || frame_y >= frame_height {
+        None
+    } else {
+        Some((frame_x as i32, frame_y as i32))
+    }
+}
+
+pub fn write_pixbuf(pixbuf: &Pixbuf, pathbuf: &PathBuf) -> Result<(), glib::Error> {
+    let format = pathbuf
+        .extension()
+        .unwrap()
+        .to_str()
+        .and_then(ImageFormat::from_extension)
+        .unwrap();
+    pixbuf.savev(pathbuf, &format.to_string().to_lowercase(), &[])
+}
+
+// 按照录制设置创建录制分支的元素并连接到管道中，重新编码时连接解码后的画面，否则直接录制接收到的视频流
+fn connect_record_branch(
+    pipeline: &Pipeline,
+    config: &SlaveConfigModel,
+    settings: &RecordSettings,
+    pathbuf: &PathBuf,
+) -> Result<(Vec<gst::Element>, (gst::Element, gst::Pad)), String> {
+    match &settings.encoder {
+        Some(encoder) => {
+            let orientation = if *config.get_orient_recording_video() {
+                config.video_orientation()
+            } else {
+                Default::default()
+            };
+            encoder
+                .gst_record_elements(
+                    config.get_colorspace_conversion().clone(),
+                    orientation,
+                    settings.height,
+                    settings.bitrate,
+                    &pathbuf.to_str().unwrap(),
+                )
+                .and_then(|elements| {
+                    super::video::connect_elements_to_pipeline(pipeline, "tee_decoded", &elements)
+                        .map(|pad| (elements, pad))
+                })
+        }
+        None => config
+            .video_decoder
+            .gst_record_elements(&pathbuf.to_str().unwrap())
+            .and_then(|elements| {
+                super::video::connect_elements_to_pipeline(pipeline, "tee_source", &elements)
+                    .map(|pad| (elements, pad))
+            }),
     }
 }
 
+// 副摄像头的管道出错或结束时直接停止，不自动重新连接
+fn watch_secondary_pipeline_bus(pipeline: &Pipeline, sender: Sender<SlaveVideoMsg>) -> Option<SourceId> {
+    pipeline
+        .bus()?
+        .add_watch_local(move |_bus, message| {
+            match message.view() {
+                MessageView::Error(err) => send!(
+                    sender,
+                    SlaveVideoMsg::SecondaryPipelineTerminated(Some(err.error().to_string()))
+                ),
+                MessageView::Eos(_) => send!(sender, SlaveVideoMsg::SecondaryPipelineTerminated(None)),
+                MessageView::StateChanged(state_changed) if state_changed.current() == gst::State::Playing => {
+                    if let Some(pipeline) = message.src().and_then(|source| source.downcast::<Pipeline>().ok()) {
+                        super::video::request_keyframe(&pipeline);
+                    }
+                }
+                _ => (),
+            }
+            Continue(true)
+        })
+        .ok()
+}
+
+// 将管道运行过程中产生的错误、警告与流结束消息转发给机位画面
+fn watch_pipeline_bus(pipeline: &Pipeline, sender: Sender<SlaveVideoMsg>) -> Option<SourceId> {
+    pipeline
+        .bus()?
+        .add_watch_local(move |_bus, message| {
+            let source = message
+                .src()
+                .map(|source| source.name().to_string())
+                .unwrap_or_default();
+            match message.view() {
+                MessageView::Error(err) => {
+                    log::debug!("管道元素 {} 出错：{:?}", source, err.debug());
+                    send!(
+                        sender,
+                        SlaveVideoMsg::PipelineElementError(source, err.error().to_string())
+                    );
+                }
+                MessageView::Warning(warning) => {
+                    send!(
+                        sender,
+                        SlaveVideoMsg::PipelineWarning(format!(
+                            "{}{}{}{}",
+                            tr("管道元素 "),
+                            source,
+                            tr(" 警告："),
+                            warning.error()
+                        ))
+                    );
+                }
+                MessageView::Eos(_) => send!(sender, SlaveVideoMsg::PipelineTerminated(None)),
+                // 管道进入播放状态后立即请求关键帧，缩短等待首个画面的时间
+                MessageView::StateChanged(state_changed)
+                    if state_changed.current() == gst::State::Playing
+                        && message.src().map_or(false, |source| source.is::<Pipeline>()) =>
+                {
+                    send!(sender, SlaveVideoMsg::RequestFrame)
+                }
+                _ => (),
+            }
+            Continue(true)
+        })
+        .ok()
+}
+
 pub enum SlaveVideoMsg {
     StartPipeline,
     StopPipeline,
-    SetPixbuf(Option<Pixbuf>),
+    RestartPipeline,
+    PipelineTerminated(Option<String>),
+    PipelineElementError(String, String), // 出错的元素名称与错误信息
+    PipelineWarning(String),
+    SetPixbuf(Option<Pixbuf>), // 为 None 时画面无法转换，将被丢弃
+    NativeFrameRendered,
     StartRecord(PathBuf),
-    StopRecord(Option<Promise<()>>),
+    StartRecordBranch(PathBuf, RecordSettings),
+    StopRecord(Option<PathBuf>, Option<Promise<()>>),
+    TelemetryReceived(Vec<(String, String)>),
     ConfigUpdated(SlaveConfigModel),
-    SaveScreenshot(PathBuf),
+    SaveScreenshot(PathBuf, Option<Pixbuf>), // 未指定画面时保存当前画面
+    RetrySaveScreenshot(PathBuf, Pixbuf, u32),
+    SaveTimelapseFrame(PathBuf),
     RequestFrame,
+    PictureClicked(f64, f64, i32, i32),
+    SetHistogram(Histogram),
+    SetVideoFormat(VideoFormat),
+    RecordTick,
+    StatisticsTick,
+    StartSecondaryPipeline,
+    StopSecondaryPipeline,
+    SecondaryPipelineTerminated(Option<String>),
+    SetSecondaryPixbuf(Option<Pixbuf>),
+    SwapPip,
+    ExportPipelineGraph,
 }
 
 impl MicroModel for SlaveVideoModel {
@@ -100,158 +963,314 @@ impl MicroModel for SlaveVideoModel {
     ) {
         self.reset();
         match msg {
+            SlaveVideoMsg::SetPixbuf(_) if self.pipeline.is_none() => (), // 已停止的管道中残留的画面
+            SlaveVideoMsg::SetPixbuf(None) => {
+                // 无法转换的画面直接丢弃，继续显示上一帧
+                log::debug!("[{}] 已丢弃格式无效的画面", self.config.lock().unwrap().get_video_url());
+            }
             SlaveVideoMsg::SetPixbuf(pixbuf) => {
-                if self.get_pixbuf().is_none() {
-                    send!(parent_sender, SlaveMsg::PollingChanged(true)); // 主要是更新截图按钮的状态
-                }
+                self.frame_arrived(parent_sender);
+                self.set_native_frame(false);
                 self.set_pixbuf(pixbuf)
             }
+            SlaveVideoMsg::NativeFrameRendered if self.pipeline.is_none() => (),
+            SlaveVideoMsg::NativeFrameRendered => {
+                self.frame_arrived(parent_sender);
+                if !self.native_frame {
+                    self.set_pixbuf(None);
+                    self.set_native_frame(true);
+                }
+            }
             SlaveVideoMsg::StartRecord(pathbuf) => {
-                if let Some(pipeline) = &self.pipeline {
+                let (settings, proxy_settings) = {
                     let config = self.config.lock().unwrap();
-                    let encoder = if *config.get_reencode_recording_video() {
-                        Some(config.get_video_encoder())
+                    let settings = RecordSettings {
+                        encoder: if *config.get_reencode_recording_video() {
+                            Some(config.get_video_encoder().clone())
+                        } else {
+                            None
+                        },
+                        height: None,
+                        bitrate: Some(*config.get_recording_bitrate()),
+                    };
+                    let proxy_settings = if *config.get_proxy_recording_enabled() {
+                        Some(RecordSettings {
+                            encoder: Some(config.get_video_encoder().clone()),
+                            height: Some(*config.get_proxy_recording_height()),
+                            bitrate: None,
+                        })
                     } else {
                         None
                     };
-                    let colorspace_conversion = config.get_colorspace_conversion().clone();
-                    let record_handle = match encoder {
-                        Some(encoder) => {
-                            let elements = encoder.gst_record_elements(
-                                colorspace_conversion,
-                                &pathbuf.to_str().unwrap(),
-                            );
-                            let elements_and_pad = elements.and_then(|elements| {
-                                super::video::connect_elements_to_pipeline(
-                                    pipeline,
-                                    "tee_decoded",
-                                    &elements,
-                                )
-                                .map(|pad| (elements, pad))
-                            });
-                            elements_and_pad
-                        }
-                        None => {
-                            let elements = config
-                                .video_decoder
-                                .gst_record_elements(&pathbuf.to_str().unwrap());
-                            let elements_and_pad = elements.and_then(|elements| {
-                                super::video::connect_elements_to_pipeline(
-                                    pipeline,
-                                    "tee_source",
-                                    &elements,
-                                )
-                                .map(|pad| (elements, pad))
-                            });
-                            elements_and_pad
-                        }
+                    (settings, proxy_settings)
+                };
+                if let Some(proxy_settings) = proxy_settings {
+                    let proxy_pathbuf = pathbuf.with_file_name(format!(
+                        "{}_proxy.mkv",
+                        pathbuf.file_stem().unwrap_or_default().to_string_lossy()
+                    ));
+                    self.start_record_branch(proxy_pathbuf, proxy_settings, parent_sender);
+                }
+                if *self.config.lock().unwrap().get_record_secondary_video() {
+                    self.start_secondary_record_branch(&pathbuf, &settings, parent_sender);
+                }
+                if self.start_record_branch(pathbuf.clone(), settings, parent_sender) {
+                    let (subtitle_enabled, frame_timestamps_enabled) = {
+                        let config = self.config.lock().unwrap();
+                        (*config.get_telemetry_subtitle_enabled(), *config.get_frame_timestamps_enabled())
                     };
-                    match record_handle {
-                        Ok((elements, pad)) => {
-                            self.record_handle = Some((pad, Vec::from(elements)));
-                            send!(parent_sender, SlaveMsg::RecordingChanged(true));
-                        }
-                        Err(err) => {
-                            send!(parent_sender, SlaveMsg::ErrorMessage(err.to_string()));
-                            send!(parent_sender, SlaveMsg::RecordingChanged(false));
-                        }
+                    if subtitle_enabled {
+                        self.start_telemetry_subtitle(&pathbuf);
+                    }
+                    if frame_timestamps_enabled {
+                        self.start_frame_timestamp_recorder(&pathbuf);
                     }
                 }
             }
-            SlaveVideoMsg::StopRecord(promise) => {
-                if let Some(pipeline) = &self.pipeline {
-                    if let Some((teepad, elements)) = &self.record_handle {
-                        super::video::disconnect_elements_to_pipeline(pipeline, teepad, elements)
-                            .unwrap()
-                            .for_each(clone!(@strong parent_sender => move |_| {
-                                send!(parent_sender, SlaveMsg::RecordingChanged(false));
-                                if let Some(promise) = promise {
-                                    promise.success(());
-                                }
-                            }));
+            SlaveVideoMsg::StartSecondaryPipeline if self.secondary_pipeline.is_some() => {
+                send!(parent_sender, SlaveMsg::SecondaryPollingChanged(true));
+            }
+            SlaveVideoMsg::StartSecondaryPipeline => self.start_secondary_pipeline(parent_sender, &sender),
+            SlaveVideoMsg::StopSecondaryPipeline => self.stop_secondary_pipeline(parent_sender),
+            SlaveVideoMsg::SecondaryPipelineTerminated(_) if self.secondary_pipeline.is_none() => (),
+            SlaveVideoMsg::SecondaryPipelineTerminated(error) => {
+                let reason = error.unwrap_or_else(|| String::from(tr("视频流已结束")));
+                log::warn!(
+                    "[{}] 副摄像头管道已停止：{}",
+                    self.config.lock().unwrap().get_slave_url(),
+                    reason
+                );
+                if let Some(bus_watch) = self.secondary_bus_watch.take() {
+                    bus_watch.remove();
+                }
+                if self.secondary_record_handle.take().is_some() {
+                    send!(
+                        parent_sender,
+                        SlaveMsg::ShowToastMessage(String::from(tr("副摄像头视频流中断，其录制已停止，录像文件可能不完整。")))
+                    );
+                }
+                self.secondary_record_stopping = None;
+                if let Some(pipeline) = self.secondary_pipeline.take() {
+                    pipeline.set_state(gst::State::Null).ok();
+                }
+                self.set_secondary_pixbuf(None);
+                self.set_pip_swapped(false);
+                send!(
+                    parent_sender,
+                    SlaveMsg::ShowToastMessage(format!("{}{}", tr("副摄像头画面已停止："), reason))
+                );
+                send!(parent_sender, SlaveMsg::SecondaryPollingChanged(false));
+            }
+            SlaveVideoMsg::SetSecondaryPixbuf(_) if self.secondary_pipeline.is_none() => (),
+            SlaveVideoMsg::SetSecondaryPixbuf(None) => (),
+            SlaveVideoMsg::SetSecondaryPixbuf(pixbuf) => self.set_secondary_pixbuf(pixbuf),
+            SlaveVideoMsg::SwapPip => {
+                if self.secondary_pixbuf.is_some() || self.pip_swapped {
+                    self.set_pip_swapped(!self.pip_swapped);
+                    self.get_mut_measurement_points().clear(); // 测量点的坐标仅对主摄像头的画面有效
+                    self.set_measurement(None);
+                }
+            }
+            SlaveVideoMsg::ExportPipelineGraph => {
+                let msg = match self.export_pipeline_graphs() {
+                    Ok(paths) if paths.is_empty() => String::from(tr("当前没有运行中的视频管道。")),
+                    Ok(paths) => {
+                        let paths: Vec<String> = paths.iter().map(|path| path.to_string_lossy().to_string()).collect();
+                        log::info!("已导出视频管道结构图：{}", paths.join(", "));
+                        format!("{}{}", tr("已导出视频管道结构图："), paths.join(", "))
+                    }
+                    Err(err) => format!("{}{}", tr("无法导出视频管道结构图："), err),
+                };
+                send!(parent_sender, SlaveMsg::ShowToastMessage(msg));
+            }
+            SlaveVideoMsg::StartRecordBranch(pathbuf, settings) => {
+                self.start_record_branch(pathbuf, settings, parent_sender);
+            }
+            SlaveVideoMsg::StopRecord(target, promise) => {
+                let future = self.stop_record_branches(target, parent_sender);
+                if let Some(promise) = promise {
+                    future.for_each(move |_| promise.success(()));
+                }
+            }
+            SlaveVideoMsg::TelemetryReceived(infos) => {
+                if let Some((pathbuf, subtitle)) = &mut self.telemetry_subtitle {
+                    if let Err(err) = subtitle.push(&infos) {
+                        log::warn!(
+                            "[{}] 无法写入录像 {} 的遥测字幕：{}",
+                            self.config.lock().unwrap().get_slave_url(),
+                            pathbuf.to_string_lossy(),
+                            err
+                        );
                     }
-                    self.set_record_handle(None);
                 }
             }
             SlaveVideoMsg::ConfigUpdated(config) => {
+                let processing_enabled = config.requires_frame_processing()
+                    && !self.config.lock().unwrap().requires_frame_processing();
                 *self.get_mut_config().lock().unwrap() = config;
+                if processing_enabled && self.pipeline.is_some() && self.native_paintable.is_some() {
+                    self.restart_for_frame_processing(parent_sender, &sender);
+                }
+            }
+            SlaveVideoMsg::StartPipeline if self.pipeline.is_some() => {
+                send!(
+                    parent_sender,
+                    SlaveMsg::ShowToastMessage(String::from(tr("管道已在运行。")))
+                );
+                send!(parent_sender, SlaveMsg::PollingChanged(true));
             }
             SlaveVideoMsg::StartPipeline => {
-                assert!(self.pipeline == None);
                 let config = self.get_config().lock().unwrap();
-                let video_url = config.get_video_url();
-                if let Some(video_source) = VideoSource::from_url(video_url) {
-                    let video_decoder = config.get_video_decoder().clone();
-                    let colorspace_conversion = config.get_colorspace_conversion().clone();
-                    let use_decodebin = config.get_use_decodebin().clone();
-                    let appsink_leaky_enabled = config.get_appsink_queue_leaky_enabled().clone();
-                    let latency = config.get_video_latency().clone();
-                    drop(config); // 结束 &self 的生命周期
-
-                    match if use_decodebin {
-                        super::video::create_decodebin_pipeline(video_source, appsink_leaky_enabled)
-                    } else {
-                        super::video::create_pipeline(
-                            video_source,
-                            latency,
-                            colorspace_conversion,
-                            video_decoder,
-                            appsink_leaky_enabled,
-                        )
-                    } {
+                log::debug!("启动管道：{}", config.get_video_url());
+                let mut native_display = *self.preferences.borrow().get_native_video_sink_enabled()
+                    && !config.requires_frame_processing();
+                let pipeline = create_pipeline_from_config(&config);
+                drop(config); // 结束 &self 的生命周期
+                if native_display && gst::ElementFactory::find("gtk4paintablesink").is_none() {
+                    log::warn!("未找到 gtk4paintablesink 元素，已改用 appsink 显示画面");
+                    native_display = false;
+                }
+                if let Some(pipeline) = pipeline {
+                    match pipeline {
                         Ok(pipeline) => {
-                            let sender = sender.clone();
+                            let native_paintable = if native_display {
+                                match super::video::replace_display_sink(&pipeline) {
+                                    Ok(paintable) => Some(paintable),
+                                    Err(msg) => {
+                                        super::video::discard_pipeline(&pipeline);
+                                        self.cancel_reconnect();
+                                        send!(parent_sender, SlaveMsg::ErrorMessage(msg));
+                                        send!(parent_sender, SlaveMsg::PollingChanged(false));
+                                        return;
+                                    }
+                                }
+                            } else {
+                                None
+                            };
+                            let bus_watch = watch_pipeline_bus(&pipeline, sender.clone());
+                            let frame_sender = sender.clone();
                             let (mat_sender, mat_receiver) =
                                 MainContext::channel(glib::PRIORITY_DEFAULT);
-                            super::video::attach_pipeline_callback(
-                                &pipeline,
-                                mat_sender,
-                                self.get_config().clone(),
-                            )
-                            .unwrap();
-                            mat_receiver.attach(None, move |mat| {
-                                sender
-                                    .send(SlaveVideoMsg::SetPixbuf(Some(mat.as_pixbuf())))
+                            let (histogram_sender, histogram_receiver) =
+                                MainContext::channel(glib::PRIORITY_DEFAULT);
+                            let (format_sender, format_receiver) =
+                                MainContext::channel(glib::PRIORITY_DEFAULT);
+                            let (native_frame_sender, native_frame_receiver) =
+                                MainContext::channel(glib::PRIORITY_DEFAULT);
+                            let callback = match &native_paintable {
+                                Some(_) => super::video::attach_native_display_callback(
+                                    &pipeline,
+                                    native_frame_sender,
+                                    format_sender,
+                                    self.frame_timestamp.clone(),
+                                ),
+                                None => super::video::attach_pipeline_callback(
+                                    &pipeline,
+                                    mat_sender,
+                                    histogram_sender,
+                                    format_sender,
+                                    self.get_config().clone(),
+                                    self.frame_timestamp.clone(),
+                                ),
+                            };
+                            if let Err(msg) = callback {
+                                if let Some(bus_watch) = bus_watch {
+                                    bus_watch.remove();
+                                }
+                                super::video::discard_pipeline(&pipeline);
+                                self.cancel_reconnect();
+                                send!(parent_sender, SlaveMsg::ErrorMessage(msg));
+                                send!(parent_sender, SlaveMsg::PollingChanged(false));
+                                return;
+                            }
+                            mat_receiver.attach(
+                                None,
+                                clone!(@strong frame_sender => move |mat| {
+                                    frame_sender
+                                        .send(SlaveVideoMsg::SetPixbuf(mat.as_pixbuf()))
+                                        .unwrap();
+                                    Continue(true)
+                                }),
+                            );
+                            native_frame_receiver.attach(
+                                None,
+                                clone!(@strong frame_sender => move |()| {
+                                    frame_sender
+                                        .send(SlaveVideoMsg::NativeFrameRendered)
+                                        .unwrap();
+                                    Continue(true)
+                                }),
+                            );
+                            format_receiver.attach(
+                                None,
+                                clone!(@strong frame_sender => move |format| {
+                                    frame_sender
+                                        .send(SlaveVideoMsg::SetVideoFormat(format))
+                                        .unwrap();
+                                    Continue(true)
+                                }),
+                            );
+                            histogram_receiver.attach(None, move |histogram| {
+                                frame_sender
+                                    .send(SlaveVideoMsg::SetHistogram(histogram))
                                     .unwrap();
                                 Continue(true)
                             });
                             match pipeline.set_state(gst::State::Playing) {
                                 Ok(_) => {
+                                    self.start_stream_statistics(&pipeline, &sender);
+                                    self.set_native_frame(false);
+                                    self.native_paintable = native_paintable;
                                     self.set_pipeline(Some(pipeline));
+                                    self.set_bus_watch(bus_watch);
                                     send!(parent_sender, SlaveMsg::PollingChanged(true));
                                 }
                                 Err(_) => {
-                                    send!(parent_sender, SlaveMsg::ErrorMessage(String::from("无法启动管道，这可能是由于管道使用的资源不存在或被占用导致的，请检查相关资源是否可用。")));
-                                    send!(parent_sender, SlaveMsg::PollingChanged(false));
+                                    if let Some(bus_watch) = bus_watch {
+                                        bus_watch.remove();
+                                    }
+                                    // 部分元素可能已进入 Ready 或 Paused 状态并占用端口等资源
+                                    super::video::discard_pipeline(&pipeline);
+                                    if self.reconnect_attempt.is_some() {
+                                        self.schedule_reconnect(&sender);
+                                    } else {
+                                        send!(parent_sender, SlaveMsg::ErrorMessage(String::from(tr("无法启动管道，这可能是由于管道使用的资源不存在或被占用导致的，请检查相关资源是否可用。"))));
+                                        send!(parent_sender, SlaveMsg::PollingChanged(false));
+                                    }
                                 }
                             }
                         }
                         Err(msg) => {
+                            self.cancel_reconnect();
                             send!(parent_sender, SlaveMsg::ErrorMessage(String::from(msg)));
                             send!(parent_sender, SlaveMsg::PollingChanged(false));
                         }
                     }
                 } else {
+                    self.cancel_reconnect();
                     send!(
                         parent_sender,
-                        SlaveMsg::ErrorMessage(String::from("拉流 URL 有误，请检查并修改后重试。"))
+                        SlaveMsg::ErrorMessage(String::from(tr("拉流 URL 有误，请检查并修改后重试。")))
                     );
                     send!(parent_sender, SlaveMsg::PollingChanged(false));
                 }
             }
+            SlaveVideoMsg::StopPipeline if self.pipeline.is_none() => {
+                self.cancel_reconnect(); // 管道可能正在等待重新连接
+                send!(parent_sender, SlaveMsg::PollingChanged(false)); // 管道可能已因错误而终止
+            }
             SlaveVideoMsg::StopPipeline => {
-                assert!(self.pipeline != None);
+                log::debug!("停止管道");
+                self.cancel_reconnect();
+                self.stop_stream_statistics(parent_sender);
+                send!(parent_sender, SlaveMsg::VideoFormatChanged(None));
+                if let Some(bus_watch) = self.bus_watch.take() {
+                    bus_watch.remove();
+                }
                 let mut futures = Vec::<Future<()>>::new();
                 let recording = self.is_recording();
                 if recording {
-                    let promise = Promise::new();
-                    let future = promise.future();
-                    self.update(
-                        SlaveVideoMsg::StopRecord(Some(promise)),
-                        parent_sender,
-                        sender.clone(),
-                    );
-                    futures.push(future);
+                    futures.push(self.stop_record_branches(None, parent_sender));
                 }
                 let promise = Promise::new();
                 futures.push(promise.future());
@@ -291,7 +1310,8 @@ impl MicroModel for SlaveVideoModel {
                                 if recording {
                                     send!(parent_sender, SlaveMsg::RecordingChanged(false));
                                 }
-                                send!(parent_sender, SlaveMsg::ShowToastMessage(String::from("等待管道响应超时，已将其强制终止。")));
+                                log::warn!("等待管道响应超时，已将其强制终止");
+                                send!(parent_sender, SlaveMsg::ShowToastMessage(String::from(tr("等待管道响应超时，已将其强制终止。"))));
                                 pipeline.set_state(gst::State::Null).unwrap();
                             }),
                         );
@@ -302,47 +1322,186 @@ impl MicroModel for SlaveVideoModel {
                     }
                 }
             }
-            SlaveVideoMsg::SaveScreenshot(pathbuf) => {
-                assert!(self.pixbuf != None);
-                if let Some(pixbuf) = &self.pixbuf {
-                    let format = pathbuf
-                        .extension()
-                        .unwrap()
-                        .to_str()
-                        .and_then(ImageFormat::from_extension)
-                        .unwrap();
-                    match pixbuf.savev(&pathbuf, &format.to_string().to_lowercase(), &[]) {
-                        Ok(_) => send!(
+            SlaveVideoMsg::PipelineElementError(_, _) if self.pipeline.is_none() => (),
+            SlaveVideoMsg::PipelineElementError(source, _) if self.aborted_record_elements.contains(&source) => (),
+            SlaveVideoMsg::PipelineElementError(source, error) => {
+                if !self.abort_record_branch(&source, &error, parent_sender) {
+                    self.update(
+                        SlaveVideoMsg::PipelineTerminated(Some(format!(
+                            "{}{}{}{}",
+                            tr("管道元素 "),
+                            source,
+                            tr(" 出错："),
+                            error
+                        ))),
+                        parent_sender,
+                        sender.clone(),
+                    );
+                }
+            }
+            SlaveVideoMsg::PipelineTerminated(_) if self.pipeline.is_none() => (), // 管道已被停止
+            SlaveVideoMsg::PipelineTerminated(error) => {
+                if let Some(bus_watch) = self.bus_watch.take() {
+                    bus_watch.remove();
+                }
+                if self.is_recording() {
+                    self.get_mut_record_handles().clear();
+                    self.finish_telemetry_subtitle();
+                    self.finish_frame_timestamp_recorder();
+                    send!(parent_sender, SlaveMsg::RecordingChanged(false));
+                    send!(
+                        parent_sender,
+                        SlaveMsg::ShowToastMessage(String::from(tr("视频流中断，录制已停止，录像文件可能不完整。")))
+                    );
+                }
+                if let Some(pipeline) = self.pipeline.take() {
+                    pipeline.set_state(gst::State::Null).ok();
+                }
+                self.stop_stream_statistics(parent_sender);
+                if *self.config.lock().unwrap().get_auto_reconnect_video() {
+                    let reason = error.unwrap_or_else(|| String::from(tr("视频流已结束")));
+                    log::warn!(
+                        "[{}] {}",
+                        self.config.lock().unwrap().get_video_url(),
+                        reason
+                    );
+                    if self.reconnect_attempt.is_none() {
+                        send!(
                             parent_sender,
                             SlaveMsg::ShowToastMessage(format!(
-                                "截图保存成功：{}",
-                                pathbuf.to_str().unwrap()
+                                "{}{}",
+                                tr("视频流中断，正在尝试重新连接："),
+                                reason
                             ))
-                        ),
-                        Err(err) => send!(
+                        );
+                    }
+                    self.schedule_reconnect(&sender);
+                    return; // 保持拉流状态直至重新连接成功或用户停止拉流
+                }
+                match error {
+                    Some(msg) => send!(parent_sender, SlaveMsg::ErrorMessage(msg)),
+                    None => {
+                        log::warn!(
+                            "[{}] 视频流已结束",
+                            self.config.lock().unwrap().get_video_url()
+                        );
+                        send!(
                             parent_sender,
-                            SlaveMsg::ShowToastMessage(format!(
-                                "截图保存失败：{}",
-                                err.to_string()
-                            ))
-                        ),
+                            SlaveMsg::ShowToastMessage(String::from(tr("视频流已结束，管道已停止。")))
+                        );
                     }
                 }
+                send!(parent_sender, SlaveMsg::PollingChanged(false));
+            }
+            SlaveVideoMsg::RestartPipeline => {
+                self.reconnect_timer = None; // 定时器触发后即已被移除
+                if self.reconnect_attempt.is_some() {
+                    self.update(SlaveVideoMsg::StartPipeline, parent_sender, sender.clone());
+                }
+            }
+            SlaveVideoMsg::PipelineWarning(msg) => {
+                log::warn!("[{}] {}", self.config.lock().unwrap().get_video_url(), msg);
+                send!(parent_sender, SlaveMsg::ShowToastMessage(msg));
+            }
+            SlaveVideoMsg::SaveScreenshot(_, None) if !self.has_frame() => {
+                send!(
+                    parent_sender,
+                    SlaveMsg::ShowToastMessage(String::from(tr("当前没有画面，无法截图。")))
+                );
+            }
+            SlaveVideoMsg::SaveScreenshot(pathbuf, pixbuf) => {
+                match pixbuf.or_else(|| self.current_pixbuf()) {
+                    Some(pixbuf) => self.save_screenshot(pathbuf, pixbuf, 0, parent_sender, &sender),
+                    None => send!(
+                        parent_sender,
+                        SlaveMsg::ShowToastMessage(String::from(tr("无法读取当前画面，截图失败。")))
+                    ),
+                }
+            }
+            SlaveVideoMsg::RetrySaveScreenshot(pathbuf, pixbuf, attempt) => {
+                self.save_screenshot(pathbuf, pixbuf, attempt, parent_sender, &sender);
+            }
+            SlaveVideoMsg::SaveTimelapseFrame(pathbuf) => match self.save_pixbuf(&pathbuf) {
+                Some(Ok(_)) => send!(parent_sender, SlaveMsg::TimelapseFrameSaved),
+                Some(Err(err)) => log::error!(
+                    "[{}] 定时截图保存失败：{}",
+                    self.config.lock().unwrap().get_slave_url(),
+                    err
+                ),
+                None => (),
+            },
+            SlaveVideoMsg::SetHistogram(histogram) => {
+                *self.get_mut_histogram().borrow_mut() = Some(histogram);
+            }
+            SlaveVideoMsg::SetVideoFormat(format) => {
+                if format.is_low_resolution() {
+                    log::warn!(
+                        "[{}] 视频分辨率较低（{}），请检查摄像头或推流端的设置",
+                        self.config.lock().unwrap().get_slave_url(),
+                        format.to_string()
+                    );
+                }
+                send!(parent_sender, SlaveMsg::VideoFormatChanged(Some(format)));
+            }
+            SlaveVideoMsg::PictureClicked(x, y, width, height) => {
+                if !*self.config.lock().unwrap().get_measurement_enabled() || self.pip_swapped {
+                    return;
+                }
+                if let Some(point) = self.map_to_frame(x, y, width, height) {
+                    if self.measurement_points.len() >= 2 {
+                        self.get_mut_measurement_points().clear();
+                    }
+                    self.get_mut_measurement_points().push(point);
+                    let mut measurement = match self.pixel_at(point) {
+                        Some((r, g, b)) => {
+                            format!("({}, {})  RGB({}, {}, {})", point.0, point.1, r, g, b)
+                        }
+                        None => format!("({}, {})", point.0, point.1),
+                    };
+                    if let [(x1, y1), (x2, y2)] = self.measurement_points[..] {
+                        let distance = (((x2 - x1).pow(2) + (y2 - y1).pow(2)) as f64).sqrt();
+                        measurement.push_str(&format!(
+                            "\n{}{:.1}{}",
+                            tr("距离："),
+                            distance,
+                            tr(" 像素")
+                        ));
+                    }
+                    self.set_measurement(Some(measurement));
+                }
             }
             SlaveVideoMsg::RequestFrame => {
                 if let Some(pipeline) = &self.pipeline {
-                    pipeline
-                        .by_name("display")
-                        .unwrap()
-                        .dynamic_cast::<gst_app::AppSink>()
-                        .unwrap()
-                        .send_event(gst::event::CustomDownstream::new(gst::Structure::new(
-                            "resend",
-                            &[],
-                        )));
+                    if !super::video::request_keyframe(pipeline) {
+                        log::debug!("视频源未处理关键帧请求");
+                    }
+                }
+                // GTK 视频输出没有可供重发的画面缓存
+                if let Some(appsink) = self
+                    .pipeline
+                    .as_ref()
+                    .and_then(|pipeline| pipeline.by_name("display"))
+                    .and_then(|element| element.dynamic_cast::<gst_app::AppSink>().ok())
+                {
+                    appsink.send_event(gst::event::CustomDownstream::new(gst::Structure::new(
+                        "resend",
+                        &[],
+                    )));
+                }
+            }
+            SlaveVideoMsg::RecordTick => {
+                if let Some(start_time) = self.record_start_time {
+                    self.set_record_elapsed(start_time.elapsed().as_secs());
+                }
+            }
+            SlaveVideoMsg::StatisticsTick => {
+                if let (Some(counter), Some(sampled_time)) = (&self.stream_counter, self.statistics_sampled_time.replace(Instant::now())) {
+                    let statistics = counter.lock().unwrap().take_statistics(sampled_time.elapsed());
+                    send!(parent_sender, SlaveMsg::StreamStatisticsUpdated(Some(statistics)));
                 }
             }
         }
+        self.update_record_indicator(&sender);
     }
 }
 
@@ -359,23 +1518,285 @@ impl MicroWidgets<SlaveVideoModel> for SlaveVideoWidgets {
             append = &Stack {
                 set_vexpand: true,
                 set_hexpand: true,
+                add_css_class: "video-frame",
                 add_child = &StatusPage {
-                    set_icon_name: Some("face-uncertain-symbolic"),
-                    set_title: "无信号",
-                    set_description: Some("请点击上方按钮启动视频拉流"),
-                    set_visible: track!(model.changed(SlaveVideoModel::pixbuf()), model.pixbuf == None),
+                    set_icon_name: track!(model.changed(SlaveVideoModel::reconnect_attempt()), Some(if model.reconnect_attempt.is_some() { "view-refresh-symbolic" } else { "face-uncertain-symbolic" })),
+                    set_title: track!(model.changed(SlaveVideoModel::reconnect_attempt()), if model.reconnect_attempt.is_some() { tr("正在重新连接…") } else { tr("无信号") }),
+                    set_description: track!(model.changed(SlaveVideoModel::reconnect_attempt()), Some(match model.reconnect_attempt {
+                        Some(attempt) => format!("{}{}", tr("视频流已中断，重试次数："), attempt),
+                        None => String::from(tr("请点击上方按钮启动视频拉流")),
+                    }.as_str())),
+                    set_visible: track!(model.changed(SlaveVideoModel::pixbuf()) || model.changed(SlaveVideoModel::native_frame()), !model.has_frame()),
                 },
-                add_child = &Picture {
-                    set_hexpand: true,
-                    set_vexpand: true,
-                    set_can_shrink: true,
-                    set_keep_aspect_ratio: track!(model.changed(SlaveVideoModel::config()), *model.config.lock().unwrap().get_keep_video_display_ratio()),
-                    set_pixbuf: track!(model.changed(SlaveVideoModel::pixbuf()), match &model.pixbuf {
-                        Some(pixbuf) => Some(&pixbuf),
-                        None => None,
-                    }),
+                add_child = &Overlay {
+                    set_child = Some(&Picture) {
+                        set_hexpand: true,
+                        set_vexpand: true,
+                        set_can_shrink: true,
+                        set_keep_aspect_ratio: track!(model.changed(SlaveVideoModel::config()), *model.config.lock().unwrap().get_keep_video_display_ratio()),
+                        set_paintable: track!(model.changed(SlaveVideoModel::pixbuf()) || model.changed(SlaveVideoModel::native_frame()) || model.changed(SlaveVideoModel::secondary_pixbuf()) || model.changed(SlaveVideoModel::pip_swapped()), model.main_paintable().as_ref()),
+                        add_controller = &GestureClick {
+                            connect_pressed(sender) => move |gesture, _n_press, x, y| {
+                                let widget = gesture.widget();
+                                send!(sender, SlaveVideoMsg::PictureClicked(x, y, widget.width(), widget.height()));
+                            },
+                        },
+                    },
+                    add_overlay: histogram_area = &DrawingArea {
+                        set_halign: Align::End,
+                        set_valign: Align::Start,
+                        set_margin_end: 8,
+                        set_margin_top: 8,
+                        set_content_width: 192,
+                        set_content_height: 96,
+                        set_can_target: false,
+                        set_visible: track!(model.changed(SlaveVideoModel::config()) || model.changed(SlaveVideoModel::histogram()), model.histogram.borrow().is_some() && *model.config.lock().unwrap().get_histogram_enabled()),
+                        set_draw_func: {
+                            let histogram = model.histogram.clone();
+                            move |_area, cr, width, height| {
+                                if let Some(histogram) = histogram.borrow().as_ref() {
+                                    draw_histogram(cr, histogram, width as f64, height as f64);
+                                }
+                            }
+                        },
+                    },
+                    add_overlay = &Label {
+                        set_halign: Align::Start,
+                        set_valign: Align::Start,
+                        set_margin_start: 8,
+                        set_margin_top: 8,
+                        add_css_class: "osd",
+                        add_css_class: "numeric",
+                        set_can_target: false,
+                        set_visible: track!(model.changed(SlaveVideoModel::record_start_time()), model.record_start_time.is_some()),
+                        set_markup: track!(model.changed(SlaveVideoModel::record_elapsed()) || model.changed(SlaveVideoModel::record_start_time()), &model.record_indicator_markup()),
+                    },
+                    add_overlay = &Frame {
+                        set_halign: Align::End,
+                        set_valign: Align::End,
+                        set_margin_end: 8,
+                        set_margin_bottom: 8,
+                        set_tooltip_text: Some(tr("点击交换主画面与画中画")),
+                        set_visible: track!(model.changed(SlaveVideoModel::secondary_pixbuf()) || model.changed(SlaveVideoModel::pip_swapped()), model.secondary_pixbuf.is_some()),
+                        set_child = Some(&Picture) {
+                            set_width_request: 240,
+                            set_height_request: 135,
+                            set_can_shrink: true,
+                            set_paintable: track!(model.changed(SlaveVideoModel::pixbuf()) || model.changed(SlaveVideoModel::native_frame()) || model.changed(SlaveVideoModel::secondary_pixbuf()) || model.changed(SlaveVideoModel::pip_swapped()), model.pip_paintable().as_ref()),
+                        },
+                        add_controller = &GestureClick {
+                            connect_pressed(sender) => move |_gesture, _n_press, _x, _y| {
+                                send!(sender, SlaveVideoMsg::SwapPip);
+                            },
+                        },
+                    },
+                    add_overlay = &Label {
+                        set_halign: Align::Start,
+                        set_valign: Align::End,
+                        set_margin_start: 8,
+                        set_margin_bottom: 8,
+                        add_css_class: "osd",
+                        set_selectable: true,
+                        set_visible: track!(model.changed(SlaveVideoModel::measurement()) || model.changed(SlaveVideoModel::config()), model.measurement.is_some() && *model.config.lock().unwrap().get_measurement_enabled()),
+                        set_label: track!(model.changed(SlaveVideoModel::measurement()), model.measurement.as_deref().unwrap_or_default()),
+                    },
                 },
             },
         }
     }
+
+    fn post_view() {
+        if model.changed(SlaveVideoModel::histogram()) {
+            self.histogram_area.queue_draw();
+        }
+    }
+}
+
+fn draw_histogram(cr: &gtk::cairo::Context, histogram: &Histogram, width: f64, height: f64) {
+    cr.set_source_rgba(0.0, 0.0, 0.0, 0.5);
+    cr.paint().expect("Couldn't paint on Cairo Context");
+    let max = histogram
+        .iter()
+        .flat_map(|bins| bins.iter())
+        .cloned()
+        .fold(1.0f32, f32::max) as f64;
+    let colors = [(1.0, 0.2, 0.2), (0.2, 1.0, 0.2), (0.3, 0.5, 1.0)];
+    for (bins, (r, g, b)) in histogram.iter().zip(colors) {
+        cr.set_source_rgba(r, g, b, 0.9);
+        for (index, value) in bins.iter().enumerate() {
+            let x = width * index as f64 / (HISTOGRAM_BINS - 1) as f64;
+            let y = height - height * (*value as f64) / max;
+            if index == 0 {
+                cr.move_to(x, y);
+            } else {
+                cr.line_to(x, y);
+            }
+        }
+        cr.stroke().expect("Couldn't stroke on Cairo Context");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_model() -> SlaveVideoModel {
+        SlaveVideoModel::new(Rc::new(RefCell::new(PreferencesModel::default())), Default::default())
+    }
+
+    // 在独立的主循环上下文中处理消息，返回发送给机位的全部消息
+    fn update_and_collect(model: &mut SlaveVideoModel, msg: SlaveVideoMsg) -> Vec<SlaveMsg> {
+        let context = MainContext::new();
+        let (parent_sender, parent_receiver) = MainContext::channel(glib::PRIORITY_DEFAULT);
+        let (sender, _receiver) = MainContext::channel(glib::PRIORITY_DEFAULT);
+        let messages = Rc::new(RefCell::new(Vec::new()));
+        parent_receiver.attach(
+            Some(&context),
+            clone!(@strong messages => move |msg| {
+                messages.borrow_mut().push(msg);
+                glib::Continue(true)
+            }),
+        );
+        model.update(msg, &parent_sender, sender);
+        while context.iteration(false) {}
+        messages.take()
+    }
+
+    #[test]
+    fn stop_pipeline_without_pipeline_reports_polling_stopped() {
+        let mut model = test_model();
+        let messages = update_and_collect(&mut model, SlaveVideoMsg::StopPipeline);
+        assert!(matches!(messages.as_slice(), [SlaveMsg::PollingChanged(false)]));
+        assert!(!model.is_running());
+    }
+
+    #[test]
+    fn start_pipeline_while_running_keeps_existing_pipeline() {
+        gst::init().unwrap();
+        let mut model = test_model();
+        let pipeline = Pipeline::new(None);
+        model.pipeline = Some(pipeline.clone());
+        let messages = update_and_collect(&mut model, SlaveVideoMsg::StartPipeline);
+        assert!(matches!(
+            messages.as_slice(),
+            [SlaveMsg::ShowToastMessage(_), SlaveMsg::PollingChanged(true)]
+        ));
+        assert_eq!(model.pipeline, Some(pipeline));
+    }
+
+    #[test]
+    fn screenshot_without_frame_shows_toast() {
+        let mut model = test_model();
+        let messages = update_and_collect(
+            &mut model,
+            SlaveVideoMsg::SaveScreenshot(PathBuf::from("screenshot.png"), None),
+        );
+        assert!(matches!(messages.as_slice(), [SlaveMsg::ShowToastMessage(_)]));
+    }
+
+    #[test]
+    fn pixbuf_after_stop_is_ignored() {
+        let mut model = test_model();
+        let pixbuf = Pixbuf::new(gdk_pixbuf::Colorspace::Rgb, false, 8, 4, 4);
+        let messages = update_and_collect(&mut model, SlaveVideoMsg::SetPixbuf(pixbuf));
+        assert!(messages.is_empty());
+        assert!(!model.has_frame());
+    }
+
+    #[test]
+    fn widget_to_frame_coordinates_table() {
+        // (控件坐标, 控件尺寸, 画面尺寸, 保持长宽比, 期望的画面坐标)
+        let cases = [
+            ((0.0, 0.0), (200, 100), (200, 100), true, Some((0, 0))),
+            ((199.5, 99.5), (200, 100), (200, 100), true, Some((199, 99))),
+            ((200.0, 100.0), (200, 100), (200, 100), true, None),
+            ((10.0, 10.0), (400, 200), (200, 100), true, Some((5, 5))),
+            // 控件比画面高，上下留出黑边
+            ((10.0, 50.0), (400, 400), (200, 100), true, None),
+            ((10.0, 150.0), (400, 400), (200, 100), true, Some((5, 25))),
+            ((399.0, 299.0), (400, 400), (200, 100), true, Some((199, 99))),
+            ((10.0, 300.0), (400, 400), (200, 100), true, None),
+            // 不保持长宽比时拉伸填满控件
+            ((10.0, 50.0), (400, 400), (200, 100), false, Some((5, 12))),
+            ((399.0, 399.0), (400, 400), (200, 100), false, Some((199, 99))),
+            // 控件比画面窄，缩小后上下留出黑边
+            ((50.0, 200.0), (100, 400), (200, 100), true, Some((100, 50))),
+            ((50.0, 100.0), (100, 400), (200, 100), true, None),
+            // 控件左右留出黑边
+            ((50.0, 50.0), (400, 100), (200, 100), true, None),
+            ((150.0, 50.0), (400, 100), (200, 100), true, Some((50, 50))),
+            ((-1.0, 0.0), (200, 100), (200, 100), false, None),
+            // 尺寸无效
+            ((10.0, 10.0), (0, 100), (200, 100), true, None),
+            ((10.0, 10.0), (200, 100), (200, 0), false, None),
+        ];
+        for (point, widget_size, frame_size, keep_ratio, expected) in cases {
+            assert_eq!(
+                widget_to_frame_coordinates(point, widget_size, frame_size, keep_ratio),
+                expected,
+                "{:?} {:?} {:?} {}",
+                point,
+                widget_size,
+                frame_size,
+                keep_ratio
+            );
+        }
+    }
+
+    // 录制过程中与下位机的通讯中断不应影响录像，手动停止后录像文件正常写入完成
+    #[test]
+    fn recording_survives_connection_loss_and_finalizes_on_stop() {
+        gst::init().unwrap();
+        if ["videotestsrc", "matroskamux"].iter().any(|name| gst::ElementFactory::find(name).is_none()) {
+            return; // 测试环境缺少所需的插件
+        }
+        let pathbuf = std::env::temp_dir().join(format!("rov-host-test-{}.mkv", std::process::id()));
+        let pipeline = gst::parse_launch(
+            "videotestsrc is-live=true ! video/x-raw,width=64,height=48,framerate=30/1 ! tee name=tee_decoded allow-not-linked=true",
+        )
+        .unwrap()
+        .downcast::<Pipeline>()
+        .unwrap();
+        let elements: Vec<gst::Element> = ["queue", "matroskamux", "filesink"]
+            .iter()
+            .map(|name| gst::ElementFactory::make(name, None).unwrap())
+            .collect();
+        elements[2].set_property("location", pathbuf.to_str().unwrap());
+        let handle = super::super::video::connect_elements_to_pipeline(&pipeline, "tee_decoded", &elements).unwrap();
+        pipeline.set_state(gst::State::Playing).unwrap();
+        let mut model = test_model();
+        model.pipeline = Some(pipeline.clone());
+        model.get_mut_record_handles().insert(pathbuf.clone(), (handle, elements));
+        std::thread::sleep(Duration::from_millis(500));
+
+        // 按默认首选项，通讯中断时机位不会向视频组件发送停止录制
+        let stops_recording = super::super::stops_recording_on_connection_lost(
+            model.is_recording(),
+            &model.preferences.borrow(),
+        );
+        assert!(!stops_recording);
+        std::thread::sleep(Duration::from_millis(500));
+        assert!(model.is_recording());
+
+        let (finished_sender, finished_receiver) = std::sync::mpsc::channel();
+        let promise = Promise::new();
+        promise.future().for_each(move |_| finished_sender.send(()).unwrap_or_default());
+        update_and_collect(&mut model, SlaveVideoMsg::StopRecord(None, Some(promise)));
+        finished_receiver.recv_timeout(Duration::from_secs(5)).expect("录制分支未能写入完成");
+        assert!(!model.is_recording());
+        pipeline.set_state(gst::State::Null).unwrap();
+
+        let content = fs::read(&pathbuf).unwrap();
+        fs::remove_file(&pathbuf).ok();
+        assert!(content.starts_with(&[0x1A, 0x45, 0xDF, 0xA3])); // Matroska 文件的 EBML 头
+        assert!(content.len() > 1024);
+    }
+
+    #[test]
+    fn termination_after_stop_is_ignored() {
+        let mut model = test_model();
+        let messages = update_and_collect(&mut model, SlaveVideoMsg::PipelineTerminated(Some(String::from("EOS"))));
+        assert!(messages.is_empty());
+        assert!(!model.is_running());
+    }
 }
@@ -0,0 +1,249 @@
+/* thruster_mix.rs
+ *
+ * Copyright 2021-2022 Bohong Huang
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    f64::consts::PI,
+    rc::Rc,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use adw::{prelude::*, HeaderBar, Window};
+use glib::{clone, Sender};
+use gtk::{cairo, Align, Box as GtkBox, Button, DrawingArea, Grid, Label, Orientation, SpinButton};
+use relm4::send;
+use serde::{Deserialize, Serialize};
+
+use super::{ControlPacket, MotionPacket, SlaveMsg, SlaveStatusClass};
+use crate::i18n::tr;
+
+const PREVIEW_UPDATE_INTERVAL: Duration = Duration::from_millis(50);
+
+// 推进器的安装位置以机体中心为原点，x 向右、y 向前，取值范围为 -1 到 1
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Thruster {
+    pub name: String,
+    pub position: (f32, f32),
+    pub angle: Option<f32>, // 水平推进器正向推力的方向，单位为度，0 为正前方，顺时针为正，垂直推进器为 None
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub rot: f32,
+}
+
+impl Thruster {
+    fn new(name: &str, position: (f32, f32), angle: Option<f32>, [x, y, z, rot]: [f32; 4]) -> Thruster {
+        Thruster { name: String::from(name), position, angle, x, y, z, rot }
+    }
+
+    fn coefficients_mut(&mut self) -> [&mut f32; 4] {
+        [&mut self.x, &mut self.y, &mut self.z, &mut self.rot]
+    }
+}
+
+// 推进器混控仅由下位机执行，上位机中的混控矩阵只用于预览，以便下水前检查推进器的接线与方向
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ThrusterMix(pub Vec<Thruster>);
+
+impl Default for ThrusterMix {
+    // 四个水平推进器呈 45° 矢量布置、两个垂直推进器位于两侧的六推进器布局
+    fn default() -> Self {
+        ThrusterMix(vec![
+            Thruster::new("FL", (-0.6, 0.7), Some(45.0), [1.0, 1.0, 0.0, 1.0]),
+            Thruster::new("FR", (0.6, 0.7), Some(-45.0), [-1.0, 1.0, 0.0, -1.0]),
+            Thruster::new("RL", (-0.6, -0.7), Some(-45.0), [-1.0, 1.0, 0.0, 1.0]),
+            Thruster::new("RR", (0.6, -0.7), Some(45.0), [1.0, 1.0, 0.0, -1.0]),
+            Thruster::new("VL", (-0.7, 0.0), None, [0.0, 0.0, 1.0, 0.0]),
+            Thruster::new("VR", (0.7, 0.0), None, [0.0, 0.0, 1.0, 0.0]),
+        ])
+    }
+}
+
+impl ThrusterMix {
+    // 输出超出范围时等比例缩小，以保持各推进器之间的比例
+    pub fn outputs(&self, motion: &MotionPacket) -> Vec<f32> {
+        let outputs: Vec<f32> = self
+            .0
+            .iter()
+            .map(|thruster| {
+                thruster.x * motion.x + thruster.y * motion.y + thruster.z * motion.z + thruster.rot * motion.rot
+            })
+            .collect();
+        let max = outputs.iter().fold(1.0f32, |max, output| max.max(output.abs()));
+        outputs.into_iter().map(|output| output / max).collect()
+    }
+}
+
+fn draw_mix(cr: &cairo::Context, mix: &ThrusterMix, outputs: &[f32], width: f64, height: f64) {
+    let scale = width.min(height) / 2.0 * 0.7;
+    let (center_x, center_y) = (width / 2.0, height / 2.0);
+    let to_widget = |(x, y): (f32, f32)| (center_x + x as f64 * scale, center_y - y as f64 * scale);
+    let thruster_radius = scale * 0.12;
+
+    cr.set_source_rgba(0.5, 0.5, 0.5, 0.3);
+    cr.rectangle(center_x - scale * 0.5, center_y - scale * 0.8, scale, scale * 1.6);
+    cr.fill().expect("Couldn't fill on Cairo Context");
+    cr.set_source_rgba(0.5, 0.5, 0.5, 0.8);
+    cr.move_to(center_x - scale * 0.15, center_y - scale * 0.6);
+    cr.line_to(center_x, center_y - scale * 0.75);
+    cr.line_to(center_x + scale * 0.15, center_y - scale * 0.6);
+    cr.set_line_width(2.0);
+    cr.stroke().expect("Couldn't stroke on Cairo Context");
+
+    for (thruster, output) in mix.0.iter().zip(outputs) {
+        let (x, y) = to_widget(thruster.position);
+        let output = *output as f64;
+        if output >= 0.0 {
+            cr.set_source_rgba(0.15, 0.64, 0.41, 0.3 + 0.7 * output.abs());
+        } else {
+            cr.set_source_rgba(0.88, 0.11, 0.14, 0.3 + 0.7 * output.abs());
+        }
+        cr.arc(x, y, thruster_radius, 0.0, 2.0 * PI);
+        cr.fill().expect("Couldn't fill on Cairo Context");
+        match thruster.angle {
+            // 箭头指向实际推力的方向，长度与输出成正比
+            Some(angle) if output != 0.0 => {
+                let angle = (angle as f64).to_radians();
+                let length = thruster_radius + scale * 0.4 * output.abs();
+                let sign = output.signum();
+                let (end_x, end_y) = (x + sign * length * angle.sin(), y - sign * length * angle.cos());
+                cr.set_line_width(3.0);
+                cr.move_to(x, y);
+                cr.line_to(end_x, end_y);
+                cr.stroke().expect("Couldn't stroke on Cairo Context");
+            }
+            _ => (),
+        }
+        cr.set_source_rgb(0.9, 0.9, 0.9);
+        let text = match thruster.angle {
+            Some(_) => format!("{} {:+.2}", thruster.name, output),
+            None => format!("{} {}{:.2}", thruster.name, if output >= 0.0 { "↑" } else { "↓" }, output.abs()),
+        };
+        cr.move_to(x - thruster_radius, y + thruster_radius * 2.5);
+        cr.show_text(&text).expect("Couldn't show text on Cairo Context");
+    }
+}
+
+fn coefficient_spin_button(value: f32) -> SpinButton {
+    let spin_button = SpinButton::with_range(-1.0, 1.0, 0.1);
+    spin_button.set_digits(2);
+    spin_button.set_value(value as f64);
+    spin_button.set_valign(Align::Center);
+    spin_button
+}
+
+// 根据当前的控制输入实时预览各推进器的输出，修改的混控系数随机位配置保存
+pub fn thruster_mix_window<T>(
+    mix: ThrusterMix,
+    status: Arc<Mutex<HashMap<SlaveStatusClass, i16>>>,
+    swap_xy: bool,
+    parent_window: Option<&T>,
+    sender: Sender<SlaveMsg>,
+) -> Window
+where
+    T: IsA<gtk::Window>,
+{
+    let mix = Rc::new(RefCell::new(mix));
+    let window = Window::builder()
+        .title(tr("推进器混控预览"))
+        .default_width(720)
+        .default_height(480)
+        .destroy_with_parent(true)
+        .build();
+    window.set_transient_for(parent_window);
+
+    let area = DrawingArea::builder()
+        .hexpand(true)
+        .vexpand(true)
+        .width_request(320)
+        .height_request(320)
+        .build();
+    area.set_draw_func(clone!(@strong mix => move |_area, cr, width, height| {
+        let mut control_packet = ControlPacket::from_status_map(&status.lock().unwrap());
+        if swap_xy {
+            std::mem::swap(&mut control_packet.motion.x, &mut control_packet.motion.y);
+        }
+        let mix = mix.borrow();
+        draw_mix(cr, &mix, &mix.outputs(&control_packet.motion), width as f64, height as f64);
+    }));
+    glib::timeout_add_local(
+        PREVIEW_UPDATE_INTERVAL,
+        clone!(@weak area => @default-return Continue(false), move || {
+            area.queue_draw();
+            Continue(true)
+        }),
+    );
+
+    let grid = Grid::builder()
+        .row_spacing(5)
+        .column_spacing(5)
+        .margin_top(10)
+        .margin_bottom(10)
+        .margin_start(10)
+        .margin_end(10)
+        .valign(Align::Center)
+        .build();
+    for (column, title) in [tr("推进器"), "X", "Y", "Z", tr("旋转")].into_iter().enumerate() {
+        grid.attach(&Label::new(Some(title)), column as i32, 0, 1, 1);
+    }
+    let mut spin_buttons = Vec::new();
+    for (row, thruster) in mix.borrow_mut().0.iter_mut().enumerate() {
+        grid.attach(&Label::new(Some(&thruster.name)), 0, row as i32 + 1, 1, 1);
+        for (column, coefficient) in thruster.coefficients_mut().into_iter().enumerate() {
+            let spin_button = coefficient_spin_button(*coefficient);
+            spin_button.connect_value_changed(clone!(@strong mix, @strong sender => move |button| {
+                let mut mix = mix.borrow_mut();
+                if let Some(thruster) = mix.0.get_mut(row) {
+                    *thruster.coefficients_mut()[column] = button.value() as f32;
+                }
+                send!(sender, SlaveMsg::SetThrusterMix(mix.clone()));
+            }));
+            grid.attach(&spin_button, column as i32 + 1, row as i32 + 1, 1, 1);
+            spin_buttons.push((row, column, spin_button));
+        }
+    }
+
+    let header_bar = HeaderBar::new();
+    let reset_button = Button::with_label(tr("恢复默认设置"));
+    reset_button.set_tooltip_text(Some(tr("恢复默认的六推进器矢量布局")));
+    reset_button.connect_clicked(clone!(@strong mix, @strong sender => move |_button| {
+        let mut default = ThrusterMix::default();
+        // 推进器数量相同时更新输入框，输入框的信号会随之更新混控系数
+        if default.0.len() == mix.borrow().0.len() {
+            for (row, column, spin_button) in &spin_buttons {
+                spin_button.set_value(*default.0[*row].coefficients_mut()[*column] as f64);
+            }
+        }
+        *mix.borrow_mut() = default.clone();
+        send!(sender, SlaveMsg::SetThrusterMix(default));
+    }));
+    header_bar.pack_start(&reset_button);
+
+    let body = GtkBox::new(Orientation::Horizontal, 0);
+    body.append(&area);
+    body.append(&grid);
+    let content = GtkBox::new(Orientation::Vertical, 0);
+    content.append(&header_bar);
+    content.append(&body);
+    window.set_content(Some(&content));
+    window.present();
+    window
+}
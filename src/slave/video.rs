@@ -16,11 +16,12 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use std::{str::FromStr, sync::{Arc, Mutex}, ffi::c_void};
+use std::{str::FromStr, sync::{Arc, Mutex}, ffi::c_void, time::{Duration, Instant, SystemTime, UNIX_EPOCH}};
+use std::{fs::File, io::{self, BufWriter, Write}, path::Path};
 
 use glib::{Sender, clone, EnumClass};
 use gtk::prelude::*;
-use gst::{Element, Pad, PadProbeType, Pipeline, element_error, 
+use gst::{Element, Pad, PadProbeId, PadProbeType, Pipeline, element_error, 
             prelude::*, PadProbeReturn, PadProbeData, EventView};
 use gdk_pixbuf::{Colorspace, Pixbuf};
 
@@ -33,6 +34,7 @@ use strum_macros::{EnumIter, Display as EnumToString};
 use url::Url;
 
 use crate::async_glib::{Future, Promise};
+use crate::i18n::tr;
 
 use super::slave_config::SlaveConfigModel;
 
@@ -61,10 +63,275 @@ impl ImageFormat {
     }
 }
 
+#[derive(EnumIter, EnumToString, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum SubtitleFormat {
+    WebVTT, SRT
+}
+
+impl Default for SubtitleFormat {
+    fn default() -> Self {
+        Self::WebVTT
+    }
+}
+
+impl SubtitleFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SubtitleFormat::WebVTT => "vtt",
+            SubtitleFormat::SRT => "srt",
+        }
+    }
+
+    fn format_timestamp(&self, time: Duration) -> String {
+        let millis = time.as_millis();
+        let (hours, minutes, seconds, millis) = (millis / 3600000, millis / 60000 % 60, millis / 1000 % 60, millis % 1000);
+        match self {
+            SubtitleFormat::WebVTT => format!("{:02}:{:02}:{:02}.{:03}", hours, minutes, seconds, millis),
+            SubtitleFormat::SRT => format!("{:02}:{:02}:{:02},{:03}", hours, minutes, seconds, millis),
+        }
+    }
+}
+
+// 与录像同时写入的遥测字幕，每条字幕从收到数据时开始显示，直至收到下一条数据
+#[derive(Debug)]
+pub struct TelemetrySubtitle {
+    format: SubtitleFormat,
+    writer: BufWriter<File>,
+    start_time: Instant,
+    cue_count: usize,
+    pending_cue: Option<(Duration, String)>,
+}
+
+impl TelemetrySubtitle {
+    pub fn create(path: &Path, format: SubtitleFormat) -> io::Result<TelemetrySubtitle> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        if format == SubtitleFormat::WebVTT {
+            // 字幕时间相对于录制开始的时刻，记录该时刻的系统时间以便与其他机位的录像对齐，SRT 格式不支持注释
+            writeln!(writer, "WEBVTT\n\nNOTE start_unix_us={}\n", unix_micros(SystemTime::now()))?;
+        }
+        Ok(TelemetrySubtitle { format, writer, start_time: Instant::now(), cue_count: 0, pending_cue: None })
+    }
+
+    pub fn push(&mut self, infos: &[(String, String)]) -> io::Result<()> {
+        let time = self.start_time.elapsed();
+        self.write_pending_cue(time)?;
+        let text = infos.iter()
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect::<Vec<_>>()
+            .join("\n");
+        if !text.is_empty() {
+            self.pending_cue = Some((time, text));
+        }
+        Ok(())
+    }
+
+    fn write_pending_cue(&mut self, end: Duration) -> io::Result<()> {
+        if let Some((start, text)) = self.pending_cue.take() {
+            self.cue_count += 1;
+            if self.format == SubtitleFormat::SRT {
+                writeln!(self.writer, "{}", self.cue_count)?;
+            }
+            writeln!(self.writer, "{} --> {}", self.format.format_timestamp(start), self.format.format_timestamp(end))?;
+            writeln!(self.writer, "{}\n", text)?;
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        let time = self.start_time.elapsed();
+        self.write_pending_cue(time)?;
+        self.writer.flush()
+    }
+}
+
+fn unix_micros(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_micros() as u64
+}
+
+// 画面的时间戳，pts_us 为 GStreamer 缓冲区的显示时间戳（相对于视频流开始，取决于视频源），
+// arrival_unix_us 为缓冲区经过上位机管道时的系统时间，属于软件时间戳
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FrameTimestamp {
+    pub pts_us: Option<u64>,
+    pub arrival_unix_us: u64,
+}
+
+impl FrameTimestamp {
+    fn from_buffer(buffer: &gst::BufferRef) -> FrameTimestamp {
+        FrameTimestamp {
+            pts_us: buffer.pts().map(|pts| pts.useconds()),
+            arrival_unix_us: unix_micros(SystemTime::now()),
+        }
+    }
+}
+
+// 与录像同时写入的帧时间戳文件（CSV），格式与精度限制见 README.org
+#[derive(Debug)]
+pub struct FrameTimestampLog {
+    writer: BufWriter<File>,
+    frame_count: u64,
+    failed: bool,
+}
+
+impl FrameTimestampLog {
+    pub fn create(path: &Path, slave_url: &Url) -> io::Result<FrameTimestampLog> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "# slave_url={}", slave_url)?;
+        writeln!(writer, "# start_unix_us={}", unix_micros(SystemTime::now()))?;
+        writeln!(writer, "frame,pts_us,arrival_unix_us")?;
+        Ok(FrameTimestampLog { writer, frame_count: 0, failed: false })
+    }
+
+    fn push(&mut self, timestamp: &FrameTimestamp) {
+        if self.failed {
+            return;
+        }
+        let pts = timestamp.pts_us.map(|pts| pts.to_string()).unwrap_or_default();
+        if let Err(err) = writeln!(self.writer, "{},{},{}", self.frame_count, pts, timestamp.arrival_unix_us) {
+            log::warn!("无法写入帧时间戳，此后的帧将不再记录：{}", err);
+            self.failed = true;
+        }
+        self.frame_count += 1;
+    }
+
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+// 在录制分支连接的 tee 输出端口上记录帧时间戳，结束录制时需先移除探针再完成写入
+#[derive(Debug)]
+pub struct FrameTimestampRecorder {
+    pad: Pad,
+    probe_id: Option<PadProbeId>,
+    log: Arc<Mutex<FrameTimestampLog>>,
+}
+
+impl FrameTimestampRecorder {
+    pub fn attach(pad: &Pad, log: FrameTimestampLog) -> Option<FrameTimestampRecorder> {
+        let log = Arc::new(Mutex::new(log));
+        let probe_id = pad.add_probe(PadProbeType::BUFFER | PadProbeType::BUFFER_LIST, clone!(@strong log => move |_pad, info| {
+            let mut log = log.lock().unwrap();
+            match &info.data {
+                Some(PadProbeData::Buffer(buffer)) => log.push(&FrameTimestamp::from_buffer(buffer)),
+                Some(PadProbeData::BufferList(list)) => {
+                    for buffer in list.iter() {
+                        log.push(&FrameTimestamp::from_buffer(buffer));
+                    }
+                },
+                _ => (),
+            }
+            PadProbeReturn::Ok
+        }))?;
+        Some(FrameTimestampRecorder { pad: pad.clone(), probe_id: Some(probe_id), log })
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        if let Some(probe_id) = self.probe_id.take() {
+            self.pad.remove_probe(probe_id);
+        }
+        self.log.lock().unwrap().finish()
+    }
+}
+
+pub const VIDEO_LATENCY_MAX: u32 = 60000;
+
+#[derive(EnumIter, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum RtspProtocol {
+    Auto, UDP, TCP
+}
+
+impl ToString for RtspProtocol {
+    fn to_string(&self) -> String {
+        match self {
+            RtspProtocol::Auto => tr("自动"),
+            RtspProtocol::UDP => "UDP",
+            RtspProtocol::TCP => "TCP",
+        }.to_string()
+    }
+}
+
+impl Default for RtspProtocol {
+    fn default() -> Self {
+        Self::Auto
+    }
+}
+
+impl RtspProtocol {
+    // 自动时不设置 protocols 属性，保留 rtspsrc 默认依次尝试 UDP 组播、UDP 与 TCP 的行为
+    fn gst_protocols(&self) -> Option<&'static str> {
+        match self {
+            RtspProtocol::Auto => None,
+            RtspProtocol::UDP => Some("udp"),
+            RtspProtocol::TCP => Some("tcp"),
+        }
+    }
+}
+
+// 完整性优先时送往画面的队列最多缓存的帧数，解码后的 1080p 画面每帧约 3 MB，缓存满时约占用 360 MB 内存
+const COMPLETENESS_QUEUE_MAX_BUFFERS: u32 = 120;
+
+#[derive(EnumIter, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum AppSinkQueueMode {
+    Latency, Completeness
+}
+
+impl ToString for AppSinkQueueMode {
+    fn to_string(&self) -> String {
+        match self {
+            AppSinkQueueMode::Latency => tr("低延迟优先"),
+            AppSinkQueueMode::Completeness => tr("完整性优先"),
+        }.to_string()
+    }
+}
+
+impl Default for AppSinkQueueMode {
+    fn default() -> Self { Self::Latency }
+}
+
+impl AppSinkQueueMode {
+    // 低延迟优先时画面落后于视频流则丢弃旧帧，完整性优先时不丢帧并增大缓存，缓存满时阻塞上游
+    fn configure_queue(&self, queue: &gst::Element) {
+        match self {
+            AppSinkQueueMode::Latency => {
+                queue.set_property_from_value("leaky", &EnumClass::new(queue.property_type("leaky").unwrap()).unwrap().to_value(2).unwrap());
+            },
+            AppSinkQueueMode::Completeness => {
+                queue.set_property("max-size-buffers", COMPLETENESS_QUEUE_MAX_BUFFERS);
+                queue.set_property("max-size-bytes", 0u32);
+                queue.set_property("max-size-time", 0u64);
+            },
+        }
+    }
+}
+
 pub enum VideoSource {
     RTP(Url), UDP(Url), RTSP(Url)
 }
 
+// URL 中的用户名与密码经过百分号编码，设置给 rtspsrc 前需要解码，不构成有效编码的 % 按原样保留
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = bytes.get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            },
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            },
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
 impl VideoSource {
     pub fn from_url(url: &Url) -> Option<VideoSource> {
         match url.scheme() {
@@ -75,7 +342,8 @@ impl VideoSource {
         }
     }
     
-    fn gst_src_elements(&self, latency: u32, video_decoder: VideoDecoder) -> Result<Vec<Element>, String> {
+    fn gst_src_elements(&self, latency: u32, rtsp_protocol: RtspProtocol, video_decoder: VideoDecoder) -> Result<Vec<Element>, String> {
+        let latency = latency.min(VIDEO_LATENCY_MAX);
         let mut elements = Vec::new();
         match self {
             VideoSource::UDP(url) | VideoSource::RTP(url) => {
@@ -99,12 +367,21 @@ impl VideoSource {
             },
             VideoSource::RTSP(url) => {
                 let rtspsrc = gst::ElementFactory::make("rtspsrc", Some("source")).map_err(|_| "Missing element: rtspsrc")?;
-                rtspsrc.set_property("location", url.to_string());
-                rtspsrc.set_property("user-id", url.username());
+                // 凭据仅通过 user-id 与 user-pw 传递，rtspsrc 会根据摄像头的质询自动选择 Basic 或 Digest 认证
+                let mut location = url.clone();
+                location.set_username("").ok();
+                location.set_password(None).ok();
+                rtspsrc.set_property("location", location.to_string());
+                if !url.username().is_empty() {
+                    rtspsrc.set_property("user-id", percent_decode(url.username()));
+                }
                 if let Some(password) = url.password() {
-                    rtspsrc.set_property("user-pw", password);
+                    rtspsrc.set_property("user-pw", percent_decode(password));
                 }
                 rtspsrc.set_property("latency", latency);
+                if let Some(protocols) = rtsp_protocol.gst_protocols() {
+                    rtspsrc.set_property_from_str("protocols", protocols);
+                }
                 elements.push(rtspsrc);
             },
         }
@@ -119,9 +396,219 @@ impl VideoSource {
     }
 }
 
-#[derive(EnumIter, EnumToString, PartialEq, Clone, Debug)]
+#[derive(EnumIter, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum VideoAlgorithm {
-    CLAHE
+    CLAHE, WhiteBalance, Dehaze, Denoise
+}
+
+impl ToString for VideoAlgorithm {
+    fn to_string(&self) -> String {
+        match self {
+            VideoAlgorithm::CLAHE => "CLAHE",
+            VideoAlgorithm::WhiteBalance => tr("自动白平衡"),
+            VideoAlgorithm::Dehaze => tr("去雾（高 CPU 占用）"),
+            VideoAlgorithm::Denoise => tr("降噪"),
+        }.to_string()
+    }
+}
+
+impl VideoAlgorithm {
+    fn apply(&self, mat: &Mat, config: &SlaveConfigModel) -> Result<Mat> {
+        match self {
+            VideoAlgorithm::CLAHE => correct_underwater_color(mat).and_then(apply_clahe),
+            VideoAlgorithm::WhiteBalance => apply_white_balance(mat, config.white_balance_strength as f64 / 100.0),
+            VideoAlgorithm::Dehaze => apply_dehaze(mat, config.dehaze_strength as f64 / 100.0),
+            VideoAlgorithm::Denoise => config.denoise_mode.apply(mat),
+        }
+    }
+}
+
+#[derive(EnumIter, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum DenoiseMode {
+    Fast, Quality
+}
+
+impl ToString for DenoiseMode {
+    fn to_string(&self) -> String {
+        match self {
+            DenoiseMode::Fast => tr("快速（双边滤波）"),
+            DenoiseMode::Quality => tr("高质量（非局部均值，高 CPU 占用）"),
+        }.to_string()
+    }
+}
+
+impl Default for DenoiseMode {
+    fn default() -> Self { Self::Fast }
+}
+
+impl DenoiseMode {
+    // 参数针对弱光下常见 ROV 摄像头的噪声选取，在去除颗粒噪声的同时尽量保留边缘
+    fn apply(&self, mat: &Mat) -> Result<Mat> {
+        let mut result = Mat::default();
+        match self {
+            DenoiseMode::Fast => imgproc::bilateral_filter(mat, &mut result, 5, 30.0, 5.0, cv::core::BORDER_DEFAULT)?,
+            DenoiseMode::Quality => cv::photo::fast_nl_means_denoising_colored(mat, &mut result, 5.0, 5.0, 7, 21)?,
+        }
+        Ok(result)
+    }
+}
+
+// 可选的增强算法处理分辨率（画面高度），为 None 时按原始分辨率处理
+pub const PROCESSING_HEIGHTS: [u32; 4] = [1080, 720, 480, 360];
+
+// 多个增强算法按照列表中的顺序依次处理画面，降噪总是最先进行，以免其他算法放大噪声，未启用增强算法时返回 None
+// 设置了处理分辨率时先将画面缩小再处理，处理后放大回原尺寸，仅影响显示的画面，录像不受影响
+fn enhance_frame(mat: &Mat, config: &SlaveConfigModel) -> Result<Option<Mat>> {
+    if config.video_algorithms.is_empty() {
+        return Ok(None);
+    }
+    let size = mat.size()?;
+    let downscaled = match config.processing_height {
+        Some(height) if (height as i32) < size.height && size.height > 0 => {
+            let mut downscaled = Mat::default();
+            let width = ((size.width as i64 * height as i64) / size.height as i64).max(1) as i32;
+            imgproc::resize(mat, &mut downscaled, Size::new(width, height as i32), 0.0, 0.0, imgproc::INTER_AREA)?;
+            Some(downscaled)
+        },
+        _ => None,
+    };
+    let mut enhanced: Option<Mat> = None;
+    let (denoise, others): (Vec<_>, Vec<_>) = config.video_algorithms.iter().partition(|algorithm| **algorithm == VideoAlgorithm::Denoise);
+    for algorithm in denoise.into_iter().chain(others) {
+        enhanced = Some(algorithm.apply(enhanced.as_ref().or(downscaled.as_ref()).unwrap_or(mat), config)?);
+    }
+    match (enhanced, downscaled) {
+        (Some(enhanced), Some(_)) => {
+            let mut upscaled = Mat::default();
+            imgproc::resize(&enhanced, &mut upscaled, size, 0.0, 0.0, imgproc::INTER_LINEAR)?;
+            Ok(Some(upscaled))
+        },
+        (enhanced, _) => Ok(enhanced),
+    }
+}
+
+#[derive(EnumIter, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum VideoRotation {
+    None, Clockwise, Rotate180, CounterClockwise
+}
+
+impl ToString for VideoRotation {
+    fn to_string(&self) -> String {
+        match self {
+            VideoRotation::None => tr("无"),
+            VideoRotation::Clockwise => tr("顺时针 90°"),
+            VideoRotation::Rotate180 => tr("180°"),
+            VideoRotation::CounterClockwise => tr("逆时针 90°"),
+        }.to_string()
+    }
+}
+
+impl Default for VideoRotation {
+    fn default() -> Self { Self::None }
+}
+
+#[derive(EnumIter, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum VideoFlip {
+    None, Horizontal, Vertical
+}
+
+impl ToString for VideoFlip {
+    fn to_string(&self) -> String {
+        match self {
+            VideoFlip::None => tr("无"),
+            VideoFlip::Horizontal => tr("水平翻转"),
+            VideoFlip::Vertical => tr("垂直翻转"),
+        }.to_string()
+    }
+}
+
+impl Default for VideoFlip {
+    fn default() -> Self { Self::None }
+}
+
+// 先旋转再翻转，画面显示与录制保持一致
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub struct VideoOrientation(pub VideoRotation, pub VideoFlip);
+
+impl VideoOrientation {
+    fn gst_elements(&self) -> Result<Vec<Element>, String> {
+        let method_names = [
+            match self.0 {
+                VideoRotation::None => None,
+                VideoRotation::Clockwise => Some("clockwise"),
+                VideoRotation::Rotate180 => Some("rotate-180"),
+                VideoRotation::CounterClockwise => Some("counterclockwise"),
+            },
+            match self.1 {
+                VideoFlip::None => None,
+                VideoFlip::Horizontal => Some("horizontal-flip"),
+                VideoFlip::Vertical => Some("vertical-flip"),
+            },
+        ];
+        method_names.into_iter().flatten().map(|method| {
+            let videoflip = gst::ElementFactory::make("videoflip", None).map_err(|_| "Missing element: videoflip")?;
+            videoflip.set_property_from_str("method", method);
+            Ok(videoflip)
+        }).collect()
+    }
+
+    // 未旋转或翻转时返回 None，画面无效时返回错误而不是崩溃，由调用者显示未经旋转的画面
+    fn apply(&self, mat: &Mat) -> Result<Option<Mat>> {
+        let rotated = match self.0 {
+            VideoRotation::None => None,
+            rotation => {
+                let mut rotated = Mat::default();
+                let code = match rotation {
+                    VideoRotation::Clockwise => cv::core::ROTATE_90_CLOCKWISE,
+                    VideoRotation::CounterClockwise => cv::core::ROTATE_90_COUNTERCLOCKWISE,
+                    _ => cv::core::ROTATE_180,
+                };
+                cv::core::rotate(mat, &mut rotated, code)?;
+                Some(rotated)
+            },
+        };
+        match self.1 {
+            VideoFlip::None => Ok(rotated),
+            flip => {
+                let mut flipped = Mat::default();
+                cv::core::flip(rotated.as_ref().unwrap_or(mat), &mut flipped, if flip == VideoFlip::Horizontal { 1 } else { 0 })?;
+                Ok(Some(flipped))
+            },
+        }
+    }
+}
+
+// 以画面宽高的比例表示的裁剪区域
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct VideoRegion {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Default for VideoRegion {
+    fn default() -> Self {
+        Self { x: 0.0, y: 0.0, width: 1.0, height: 1.0 }
+    }
+}
+
+impl VideoRegion {
+    fn crop(&self, mat: Mat) -> Mat {
+        let (cols, rows) = (mat.cols(), mat.rows());
+        if cols <= 0 || rows <= 0 {
+            return mat;
+        }
+        // 将区域限制在画面内，并保证至少包含一个像素
+        let x = ((self.x.clamp(0.0, 1.0) * cols as f64) as i32).min(cols - 1);
+        let y = ((self.y.clamp(0.0, 1.0) * rows as f64) as i32).min(rows - 1);
+        let width = ((self.width.clamp(0.0, 1.0) * cols as f64) as i32).clamp(1, cols - x);
+        let height = ((self.height.clamp(0.0, 1.0) * rows as f64) as i32).clamp(1, rows - y);
+        if (x, y, width, height) == (0, 0, cols, rows) {
+            return mat;
+        }
+        Mat::roi(&mat, cv::core::Rect::new(x, y, width, height)).and_then(|roi| roi.try_clone()).unwrap_or(mat)
+    }
 }
 
 #[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
@@ -168,11 +655,11 @@ pub enum VideoCodecProvider {
 impl ToString for VideoCodecProvider {
     fn to_string(&self) -> String {
         match self {
-            VideoCodecProvider::Native => "原生 (软件)",
-            VideoCodecProvider::AVCodec => "FFMPEG (软件)",
-            VideoCodecProvider::NVCodec => "NVIDIA (硬件)",
-            VideoCodecProvider::VAAPI => "VAAPI (硬件)",
-            VideoCodecProvider::D3D11 => "Direct3D 11 (硬件)",
+            VideoCodecProvider::Native => tr("原生 (软件)"),
+            VideoCodecProvider::AVCodec => tr("FFMPEG (软件)"),
+            VideoCodecProvider::NVCodec => tr("NVIDIA (硬件)"),
+            VideoCodecProvider::VAAPI => tr("VAAPI (硬件)"),
+            VideoCodecProvider::D3D11 => tr("Direct3D 11 (硬件)"),
         }.to_string()
     }
 }
@@ -192,16 +679,44 @@ impl VideoCodecProvider {
             },
         }
     }
+
+    // 各编码器设置码率的属性名与单位不同，返回属性名及换算后的属性值
+    fn bitrate_property(&self, codec: VideoCodec, kbps: u32) -> (&'static str, u64) {
+        let kbps = kbps as u64;
+        match (self, codec) {
+            (VideoCodecProvider::AVCodec, _) => ("bitrate", kbps * 1000),
+            (VideoCodecProvider::Native, VideoCodec::VP8 | VideoCodec::VP9) => ("target-bitrate", kbps * 1000),
+            (VideoCodecProvider::Native, VideoCodec::AV1) => ("target-bitrate", kbps),
+            _ => ("bitrate", kbps),
+        }
+    }
 }
 
 impl VideoEncoder {
-    pub fn gst_record_elements(&self, colorspace_conversion: ColorspaceConversion, filename: &str) -> Result<Vec<Element>, String> {
+    pub fn gst_record_elements(&self, colorspace_conversion: ColorspaceConversion, orientation: VideoOrientation, height: Option<u32>, bitrate: Option<u32>, filename: &str) -> Result<Vec<Element>, String> {
         let mut elements = Vec::new();
         let queue_to_file = gst::ElementFactory::make("queue", None).map_err(|_| "Missing element: queue")?;
         elements.push(queue_to_file);
         elements.extend_from_slice(&colorspace_conversion.gst_elements()?);
+        elements.extend_from_slice(&orientation.gst_elements()?);
+        if let Some(height) = height {
+            let videoscale = gst::ElementFactory::make("videoscale", None).map_err(|_| "Missing element: videoscale")?;
+            let capsfilter = gst::ElementFactory::make("capsfilter", None).map_err(|_| "Missing element: capsfilter")?;
+            let caps = gst::caps::Caps::from_str(&format!("video/x-raw, height=(int){}", height)).map_err(|_| "Cannot create capability for videoscale")?;
+            capsfilter.set_property("caps", caps);
+            elements.push(videoscale);
+            elements.push(capsfilter);
+        }
         let encoder_name = self.1.format_codec(self.0, true);
         let encoder = gst::ElementFactory::make(&encoder_name, None).map_err(|_| format!("Missing element: {}", &encoder_name))?;
+        if let Some(bitrate) = bitrate {
+            let (property, value) = self.1.bitrate_property(self.0, bitrate);
+            if encoder.property_type(property).is_some() {
+                encoder.set_property_from_str(property, &value.to_string());
+            } else {
+                log::warn!("编码器 {} 不支持设置码率", &encoder_name);
+            }
+        }
         elements.push(encoder);
         match self.0 {
             VideoCodec::H264 => {
@@ -329,6 +844,18 @@ pub fn connect_elements_to_pipeline(pipeline: &Pipeline, tee_name: &str, element
     Ok((output_tee, teepad))
 }
 
+// 立即移除出错的录制分支，不等待其写入完成
+pub fn remove_elements_from_pipeline(pipeline: &Pipeline, (output_tee, teepad): &(Element, Pad), elements: &[Element]) -> Result<(), String> {
+    let first_sinkpad = elements.first().unwrap().static_pad("sink").unwrap();
+    teepad.unlink(&first_sinkpad).map_err(|_| "Cannot unlink elements")?;
+    output_tee.remove_pad(teepad).map_err(|_| "Cannot remove pad from output tee")?;
+    for element in elements.iter() {
+        element.set_state(gst::State::Null).ok();
+    }
+    pipeline.remove_many(&elements.iter().collect::<Vec<_>>()).map_err(|_| "Cannot remove elements from pipeline")?;
+    Ok(())
+}
+
 pub fn disconnect_elements_to_pipeline(pipeline: &Pipeline, (output_tee, teepad): &(Element, Pad), elements: &[Element]) -> Result<Future<()>, String> {
     let first_sinkpad = elements.first().unwrap().static_pad("sink").unwrap();
     teepad.unlink(&first_sinkpad).map_err(|_| "Cannot unlink elements")?;
@@ -353,66 +880,166 @@ pub fn disconnect_elements_to_pipeline(pipeline: &Pipeline, (output_tee, teepad)
     });
     first_sinkpad.send_event(gst::event::Eos::new());
     let future = future.map(clone!(@strong pipeline => move |_| {
-        pipeline.remove_many(&elements.iter().collect::<Vec<_>>()).map_err(|_| "Cannot remove elements from pipeline").unwrap();
+        // 各录制分支单独移除，某一分支出错不应影响其他分支
+        if pipeline.remove_many(&elements.iter().collect::<Vec<_>>()).is_err() {
+            log::warn!("无法从管道中移除录制分支");
+        }
         for element in elements.iter() {
-            element.set_state(gst::State::Null).unwrap();
+            element.set_state(gst::State::Null).ok();
         }
     }));
     Ok(future)
 }
 
-pub fn create_decodebin_pipeline(source: VideoSource, appsink_queue_leaky_enabled: bool) -> Result<gst::Pipeline, String> {
+// 根据元素名称推断提供该元素的软件包，仅用于提示用户
+fn gst_element_package(name: &str) -> &'static str {
+    match name {
+        "queue" | "tee" | "filesink" => "gstreamer",
+        "appsink" | "videoconvert" | "uridecodebin" => "gst-plugins-base",
+        "x264enc" => "gst-plugins-ugly",
+        "x265enc" | "h264parse" | "h265parse" | "av1enc" | "av1dec" => "gst-plugins-bad",
+        name if name.starts_with("av") => "gst-libav",
+        name if name.starts_with("nv") || name.starts_with("cuda") || name.starts_with("d3d11") => "gst-plugins-bad",
+        name if name.starts_with("vaapi") => "gstreamer-vaapi",
+        _ => "gst-plugins-good",
+    }
+}
+
+// 检查拉流与录制所需的元素，返回缺失的元素及提供该元素的软件包
+pub fn missing_gst_elements(decoder: VideoDecoder, encoder: &VideoEncoder, colorspace_conversion: ColorspaceConversion) -> Vec<(String, &'static str)> {
+    let mut names: Vec<String> = ["udpsrc", "rtspsrc", "rtpjitterbuffer", "uridecodebin", "appsink", "tee", "queue", "videoconvert", "matroskamux", "filesink"]
+        .iter().map(|name| name.to_string()).collect();
+    names.push(decoder.0.depay_name());
+    names.push(decoder.1.format_codec(decoder.0, false));
+    names.push(encoder.1.format_codec(encoder.0, true));
+    for codec in [decoder.0, encoder.0] {
+        match codec {
+            VideoCodec::H264 => names.push("h264parse".to_string()),
+            VideoCodec::H265 => names.push("h265parse".to_string()),
+            _ => (),
+        }
+    }
+    names.extend(match colorspace_conversion {
+        ColorspaceConversion::CPU => vec![],
+        ColorspaceConversion::CUDA => vec!["cudaupload", "cudaconvert", "cudadownload"],
+        ColorspaceConversion::D3D11 => vec!["d3d11upload", "d3d11convert", "d3d11download"],
+    }.into_iter().map(|name| name.to_string()));
+    names.sort();
+    names.dedup();
+    names.into_iter()
+        .filter(|name| gst::ElementFactory::find(name).is_none())
+        .map(|name| { let package = gst_element_package(&name); (name, package) })
+        .collect()
+}
+
+// 构建过程中出错时将管道置于 Null 状态并移除已添加的元素，避免反复启动失败时残留 GStreamer 对象
+fn finish_pipeline(pipeline: gst::Pipeline, result: Result<(), String>) -> Result<gst::Pipeline, String> {
+    match result {
+        Ok(()) => Ok(pipeline),
+        Err(err) => {
+            discard_pipeline(&pipeline);
+            Err(err)
+        },
+    }
+}
+
+pub fn discard_pipeline(pipeline: &gst::Pipeline) {
+    pipeline.set_state(gst::State::Null).ok();
+    for element in pipeline.children() {
+        element.set_state(gst::State::Null).ok();
+        pipeline.remove(&element).ok();
+    }
+}
+
+fn pad_media(pad: &Pad) -> Option<String> {
+    pad.current_caps().or_else(|| pad.caps())?.iter().flat_map(|x| x.iter()).find_map(|(key, value)| {
+        if key == "media" {
+            value.get::<String>().ok()
+        } else {
+            None
+        }
+    })
+}
+
+// 动态创建的端口在回调中连接，此时无法返回错误，连接失败时向总线发送错误消息，由总线监视终止管道
+fn link_added_pad(element: &Element, pad: &Pad, sink_pad: &Pad, description: &str) {
+    if pad.link(sink_pad).is_err() {
+        element_error!(element, gst::CoreError::Negotiation, ("Cannot delay link {}", description));
+    }
+}
+
+fn request_tee_pad(tee: &Element, sink: &Element, description: &str) -> Result<(), String> {
+    let tee_pad = tee.request_pad_simple("src_%u").ok_or_else(|| format!("Cannot request pad from {}", description))?;
+    let sink_pad = sink.static_pad("sink").ok_or_else(|| format!("Missing sink pad of {}", description))?;
+    tee_pad.link(&sink_pad).map_err(|_| format!("Cannot link {}", description))?;
+    Ok(())
+}
+
+pub fn create_decodebin_pipeline(source: VideoSource, rtsp_protocol: RtspProtocol, appsink_queue_mode: AppSinkQueueMode) -> Result<gst::Pipeline, String> {
     let pipeline = gst::Pipeline::new(None);
+    let result = build_decodebin_pipeline(&pipeline, source, rtsp_protocol, appsink_queue_mode);
+    finish_pipeline(pipeline, result)
+}
+
+fn build_decodebin_pipeline(pipeline: &gst::Pipeline, source: VideoSource, rtsp_protocol: RtspProtocol, appsink_queue_mode: AppSinkQueueMode) -> Result<(), String> {
     let uridecodebin = gst::ElementFactory::make("uridecodebin3", None).map_err(|_| "Missing element: uridecodebin3")
         .and(gst::ElementFactory::make("uridecodebin", None).map_err(|_| "Missing element: uridecodebin"))?;
     let appsink = gst::ElementFactory::make("appsink", Some("display")).map_err(|_| "Missing element: appsink")?;
     let caps_app = gst::caps::Caps::from_str("video/x-raw, format=RGB").map_err(|_| "Cannot create capability for appsink")?;
     let tee_decoded = gst::ElementFactory::make("tee", Some("tee_decoded")).map_err(|_| "Missing element: tee")?;
-    let queue_to_app = gst::ElementFactory::make("queue", None).map_err(|_| "Missing element: queue")?;
+    let queue_to_app = gst::ElementFactory::make("queue", Some("display_queue")).map_err(|_| "Missing element: queue")?;
     let videoconvert = gst::ElementFactory::make("videoconvert", None).map_err(|_| "Missing element: videoconvert")?;
     pipeline.add_many(&[&uridecodebin, &appsink, &tee_decoded, &queue_to_app, &videoconvert]).map_err(|_| "Cannot create pipeline")?;
-    if appsink_queue_leaky_enabled {
-        queue_to_app.set_property_from_value("leaky", &EnumClass::new(queue_to_app.property_type("leaky").unwrap()).unwrap().to_value(2).unwrap());
-    }
+    appsink_queue_mode.configure_queue(&queue_to_app);
     appsink.set_property("caps", caps_app);
     videoconvert.link(&appsink).map_err(|_| "Cannot link videoconvert to the appsink")?;
     queue_to_app.link(&videoconvert).map_err(|_| "Cannot link appsink queue to the videoconvert")?;
-    tee_decoded.request_pad_simple("src_%u").unwrap().link(&queue_to_app.static_pad("sink").unwrap()).map_err(|_| "Cannot link tee to appsink queue")?;
+    request_tee_pad(&tee_decoded, &queue_to_app, "tee to appsink queue")?;
     let url = match &source {
         VideoSource::RTP(url) | VideoSource::UDP(url) | VideoSource::RTSP(url) => url,
     };
     uridecodebin.set_property("uri", url.to_string());
+    uridecodebin.connect("source-setup", false, move |args| {
+        if let [_element, source] = args {
+            if let Ok(source) = source.get::<Element>() {
+                match rtsp_protocol.gst_protocols() {
+                    Some(protocols) if source.find_property("protocols").is_some() => { // 仅 rtspsrc 具有该属性
+                        source.set_property_from_str("protocols", protocols);
+                    },
+                    _ => (),
+                }
+            }
+        }
+        None
+    });
+    let video_sink_pad = tee_decoded.static_pad("sink").ok_or("Missing sink pad of tee_decoded")?;
     uridecodebin.connect("pad-added", true, move |args| {
-        if let [_element, pad] = args {
-            let pad = pad.get::<Pad>().unwrap();
-            let media = pad.caps().unwrap().iter().flat_map(|x| x.iter()).find_map(|(key, value)| {
-                if key == "media" {
-                    Some(value.get::<String>().unwrap())
-                } else {
-                    None
+        if let [element, pad] = args {
+            if let (Ok(element), Ok(pad)) = (element.get::<Element>(), pad.get::<Pad>()) {
+                match pad_media(&pad).as_deref() {
+                    Some("video") => link_added_pad(&element, &pad, &video_sink_pad, "uridecodebin to tee_decoded"),
+                    Some("audio") => {},
+                    Some(_) | None => {
+                        if pad.can_link(&video_sink_pad) {
+                            link_added_pad(&element, &pad, &video_sink_pad, "uridecodebin to tee_decoded");
+                        }
+                    },
                 }
-            });
-            let video_sink_pad = tee_decoded.static_pad("sink").unwrap();
-            match media.as_deref() {
-                Some("video") => {
-                    pad.link(&video_sink_pad).map_err(|_| "Cannot delay link uridecodebin to tee_decoded").unwrap();
-                },
-                Some("audio") => {},
-                Some(_) | None => {
-                    if pad.can_link(&video_sink_pad) {
-                        pad.link(&video_sink_pad).map_err(|_| "Cannot delay link uridecodebin to tee_decoded").unwrap();
-                    }
-                },
             }
         }
         None
     });
-    Ok(pipeline)
+    Ok(())
 }
 
-pub fn create_pipeline(source: VideoSource, latency: u32, colorspace_conversion: ColorspaceConversion, decoder: VideoDecoder, appsink_queue_leaky_enabled: bool) -> Result<gst::Pipeline, String> {
+pub fn create_pipeline(source: VideoSource, latency: u32, rtsp_protocol: RtspProtocol, colorspace_conversion: ColorspaceConversion, decoder: VideoDecoder, appsink_queue_mode: AppSinkQueueMode) -> Result<gst::Pipeline, String> {
     let pipeline = gst::Pipeline::new(None);
-    let src_elements = source.gst_src_elements(latency, decoder)?;
+    let result = build_pipeline(&pipeline, source, latency, rtsp_protocol, colorspace_conversion, decoder, appsink_queue_mode);
+    finish_pipeline(pipeline, result)
+}
+
+fn build_pipeline(pipeline: &gst::Pipeline, source: VideoSource, latency: u32, rtsp_protocol: RtspProtocol, colorspace_conversion: ColorspaceConversion, decoder: VideoDecoder, appsink_queue_mode: AppSinkQueueMode) -> Result<(), String> {
+    let src_elements = source.gst_src_elements(latency, rtsp_protocol, decoder)?;
     let (video_src, depay_elements) = src_elements.split_first().ok_or_else(|| "Source element is empty")?;
     let video_src = video_src.clone();
     let appsink = gst::ElementFactory::make("appsink", Some("display")).map_err(|_| "Missing element: appsink")?;
@@ -421,7 +1048,7 @@ pub fn create_pipeline(source: VideoSource, latency: u32, colorspace_conversion:
     let tee_source = gst::ElementFactory::make("tee", Some("tee_source")).map_err(|_| "Missing element: tee")?;
     let tee_decoded = gst::ElementFactory::make("tee", Some("tee_decoded")).map_err(|_| "Missing element: tee")?;
     let queue_to_decode = gst::ElementFactory::make("queue", None).map_err(|_| "Missing element: queue")?;
-    let queue_to_app = gst::ElementFactory::make("queue", None).map_err(|_| "Missing element: queue")?;
+    let queue_to_app = gst::ElementFactory::make("queue", Some("display_queue")).map_err(|_| "Missing element: queue")?;
     let colorspace_conversion_elements = colorspace_conversion.gst_elements()?;
     let decoder_elements = decoder.gst_main_elements()?;
     
@@ -462,31 +1089,22 @@ pub fn create_pipeline(source: VideoSource, latency: u32, colorspace_conversion:
         },
         _ => return Err("Missing decoder element".to_string()),
     }
-    if appsink_queue_leaky_enabled {
-        queue_to_app.set_property_from_value("leaky", &EnumClass::new(queue_to_app.property_type("leaky").unwrap()).unwrap().to_value(2).unwrap());
-    }
+    appsink_queue_mode.configure_queue(&queue_to_app);
     // appsink.set_property("sync", true);
-    tee_source.request_pad_simple("src_%u").unwrap().link(&queue_to_decode.static_pad("sink").unwrap()).map_err(|_| "Cannot link tee to decoder queue")?;
-    tee_decoded.request_pad_simple("src_%u").unwrap().link(&queue_to_app.static_pad("sink").unwrap()).map_err(|_| "Cannot link tee to appsink queue")?;
+    request_tee_pad(&tee_source, &queue_to_decode, "tee to decoder queue")?;
+    request_tee_pad(&tee_decoded, &queue_to_app, "tee to appsink queue")?;
     match (depay_elements.first(), depay_elements.last()) {
         (Some(first), Some(last)) => {
-            let first = first.clone();
+            let first_sink_pad = first.static_pad("sink").ok_or("Missing sink pad of the first depay element")?;
             if let Some(src) = video_src.static_pad("src") {
-                src.link(&first.static_pad("sink").unwrap()).map_err(|_| "Cannot link video source element to the first depay element").unwrap();
+                src.link(&first_sink_pad).map_err(|_| "Cannot link video source element to the first depay element")?;
             } else {
                 video_src.connect("pad-added", true, move |args| {
-                    if let [_element, pad] = args {
-                        let pad = pad.get::<Pad>().unwrap();
-                        let media = pad.caps().unwrap().iter().flat_map(|x| x.iter()).find_map(|(key, value)| {
-                            if key == "media" {
-                                Some(value.get::<String>().unwrap())
-                            } else {
-                                None
+                    if let [element, pad] = args {
+                        if let (Ok(element), Ok(pad)) = (element.get::<Element>(), pad.get::<Pad>()) {
+                            if pad_media(&pad).as_deref() == Some("video") {
+                                link_added_pad(&element, &pad, &first_sink_pad, "video source element to the first depay element");
                             }
-                        });
-                        
-                        if media.map_or(false, |x| x.eq("video")) {
-                            pad.link(&first.static_pad("sink").unwrap()).map_err(|_| "Cannot delay link video source element to the first depay element").unwrap();
                         }
                     }
                     None
@@ -496,61 +1114,240 @@ pub fn create_pipeline(source: VideoSource, latency: u32, colorspace_conversion:
         },
         _ => video_src.link(&tee_source).map_err(|_| "Cannot link video source to tee")?,
     }
-    Ok(pipeline)
+    Ok(())
 }
 
-fn correct_underwater_color(src: Mat) -> Mat {
+// 管道的输出格式改变时可能收到空白或通道数不符的画面，此时返回错误而不是在管道的回调线程中崩溃
+fn correct_underwater_color(src: &Mat) -> Result<Mat> {
+    if src.empty() || src.channels() != 3 {
+        return Err(cv::Error::new(cv::core::StsBadArg, format!("Unexpected image with size {:?} and {} channels", src.size()?, src.channels())));
+    }
     let mut image = Mat::default();
-    src.convert_to(&mut image, cv::core::CV_32FC3, 1.0, 0.0).expect("Cannot convert source image");
-    let image = (image / 255.0).into_result().unwrap();
+    src.convert_to(&mut image, cv::core::CV_32FC3, 1.0, 0.0)?;
+    let image = (image / 255.0).into_result()?;
     let mut channels = cv::types::VectorOfMat::new();
-    cv::core::split(&image, &mut channels).expect("Cannot split image");
+    cv::core::split(&image, &mut channels)?;
     let [mut mean, mut std] = [cv::core::Scalar::default(); 2];
     let image_original_size = image;
     let mut image = Mat::default();
-    cv::imgproc::resize(&image_original_size, &mut image, Size::new(128, 128), 0.0, 0.0, imgproc::INTER_NEAREST).expect("Cannot resize image");
-    cv::core::mean_std_dev(&image, &mut mean, &mut std, &cv::core::no_array()).expect("Cannot calculate mean and standard deviation for image");
+    cv::imgproc::resize(&image_original_size, &mut image, Size::new(128, 128), 0.0, 0.0, imgproc::INTER_NEAREST)?;
+    cv::core::mean_std_dev(&image, &mut mean, &mut std, &cv::core::no_array())?;
     const U: f64 = 3.0;
     let min_max = mean.iter().zip(std.iter()).map(|(mean, std)| (mean - U * std, mean + U * std));
-    let channels = channels.iter().zip(min_max).map(|(channel, (min, max))| (channel - VecN::from(min)) / (max - min) * 255.0).map(|x| x.into_result().and_then(|x| x.to_mat()).unwrap());
+    let channels = channels.iter().zip(min_max).map(|(channel, (min, max))| (channel - VecN::from(min)) / (max - min) * 255.0).map(|x| x.into_result().and_then(|x| x.to_mat())).collect::<Result<Vec<_>>>()?;
     let channels = VectorOfMat::from_iter(channels);
     let mut image = Mat::default();
-    cv::core::merge(&channels, &mut image).expect("Cannot merge result channels");
+    cv::core::merge(&channels, &mut image)?;
+    let mut result = Mat::default();
+    image.convert_to(&mut result, cv::core::CV_8UC3, 1.0, 0.0)?;
+    Ok(result)
+}
+
+// 灰度世界白平衡，将各通道的均值向三者的平均值靠拢，强度为 0 时不改变画面，为 1 时完全校正
+fn apply_white_balance(src: &Mat, strength: f64) -> Result<Mat> {
+    if src.empty() || src.channels() != 3 {
+        return Err(cv::Error::new(cv::core::StsBadArg, format!("Unexpected image with size {:?} and {} channels", src.size()?, src.channels())));
+    }
+    let mean = cv::core::mean(src, &cv::core::no_array())?;
+    let gray = (mean[0] + mean[1] + mean[2]) / 3.0;
+    let mut channels = VectorOfMat::new();
+    cv::core::split(src, &mut channels)?;
+    let channels = channels.iter().enumerate().map(|(index, channel)| {
+        let gain = if mean[index] > 0.0 { 1.0 + strength * (gray / mean[index] - 1.0) } else { 1.0 };
+        let mut balanced = Mat::default();
+        channel.convert_to(&mut balanced, -1, gain, 0.0).map(|_| balanced)
+    }).collect::<Result<Vec<_>>>()?;
+    let mut result = Mat::default();
+    cv::core::merge(&VectorOfMat::from_iter(channels), &mut result)?;
+    Ok(result)
+}
+
+// 估计透射率时将画面缩小到该宽度以减少计算量
+const DEHAZE_ESTIMATION_WIDTH: i32 = 320;
+const DEHAZE_PATCH_SIZE: i32 = 7;
+const DEHAZE_MIN_TRANSMISSION: f64 = 0.1;
+
+// 各像素邻域内所有通道的最小值
+fn dark_channel(channels: &VectorOfMat) -> Result<Mat> {
+    let mut dark = channels.get(0)?;
+    for channel in channels.iter().skip(1) {
+        let mut min = Mat::default();
+        cv::core::min(&dark, &channel, &mut min)?;
+        dark = min;
+    }
+    let anchor = cv::core::Point::new(-1, -1);
+    let kernel = imgproc::get_structuring_element(imgproc::MORPH_RECT, Size::new(DEHAZE_PATCH_SIZE, DEHAZE_PATCH_SIZE), anchor)?;
+    let mut eroded = Mat::default();
+    imgproc::erode(&dark, &mut eroded, &kernel, anchor, 1, cv::core::BORDER_REPLICATE, imgproc::morphology_default_border_value()?)?;
+    Ok(eroded)
+}
+
+// 暗通道先验去雾，在缩小的画面上估计大气光（水体背景光）与透射率，再按原尺寸恢复画面，强度为去除雾气的比例
+fn apply_dehaze(src: &Mat, strength: f64) -> Result<Mat> {
+    if src.empty() || src.channels() != 3 {
+        return Err(cv::Error::new(cv::core::StsBadArg, format!("Unexpected image with size {:?} and {} channels", src.size()?, src.channels())));
+    }
+    let mut image = Mat::default();
+    src.convert_to(&mut image, cv::core::CV_32FC3, 1.0 / 255.0, 0.0)?;
+    let size = image.size()?;
+    let estimation_width = DEHAZE_ESTIMATION_WIDTH.min(size.width);
+    let estimation_size = Size::new(estimation_width, ((estimation_width * size.height) / size.width).max(1));
+    let mut small = Mat::default();
+    imgproc::resize(&image, &mut small, estimation_size, 0.0, 0.0, imgproc::INTER_AREA)?;
+    let mut small_channels = VectorOfMat::new();
+    cv::core::split(&small, &mut small_channels)?;
+    // 以暗通道最亮处的颜色作为大气光
+    let mut light_location = cv::core::Point::default();
+    cv::core::min_max_loc(&dark_channel(&small_channels)?, None, None, None, Some(&mut light_location), &cv::core::no_array())?;
+    let light = *small.at_2d::<cv::core::Vec3f>(light_location.y, light_location.x)?;
+    let normalized = small_channels.iter().enumerate().map(|(index, channel)| {
+        let mut normalized = Mat::default();
+        channel.convert_to(&mut normalized, -1, 1.0 / (light[index] as f64).max(1e-3), 0.0).map(|_| normalized)
+    }).collect::<Result<Vec<_>>>()?;
+    // 透射率 t = 1 - 强度 × 归一化画面的暗通道，平滑后放大到原尺寸，并限制下限以免放大噪声
+    let mut transmission = Mat::default();
+    dark_channel(&VectorOfMat::from_iter(normalized))?.convert_to(&mut transmission, -1, -strength, 1.0)?;
+    let mut blurred = Mat::default();
+    imgproc::blur(&transmission, &mut blurred, Size::new(DEHAZE_PATCH_SIZE, DEHAZE_PATCH_SIZE), cv::core::Point::new(-1, -1), cv::core::BORDER_REPLICATE)?;
+    let mut transmission = Mat::default();
+    imgproc::resize(&blurred, &mut transmission, size, 0.0, 0.0, imgproc::INTER_LINEAR)?;
+    let mut limited = Mat::default();
+    transmission.convert_to(&mut limited, -1, 1.0 - DEHAZE_MIN_TRANSMISSION, DEHAZE_MIN_TRANSMISSION)?;
+    // 恢复画面 J = (I - A) / t + A
+    let mut channels = VectorOfMat::new();
+    cv::core::split(&image, &mut channels)?;
+    let channels = channels.iter().enumerate().map(|(index, channel)| {
+        let light = light[index] as f64;
+        let mut difference = Mat::default();
+        channel.convert_to(&mut difference, -1, 1.0, -light)?;
+        let mut recovered = Mat::default();
+        cv::core::divide2(&difference, &limited, &mut recovered, 1.0, -1)?;
+        let mut result = Mat::default();
+        recovered.convert_to(&mut result, cv::core::CV_8U, 255.0, light * 255.0)?;
+        Ok(result)
+    }).collect::<Result<Vec<_>>>()?;
     let mut result = Mat::default();
-    image.convert_to(&mut result, cv::core::CV_8UC3, 1.0, 0.0).expect("Cannot convert result data type");
-    result
+    cv::core::merge(&VectorOfMat::from_iter(channels), &mut result)?;
+    Ok(result)
 }
 
-#[allow(dead_code)]
-fn apply_clahe(mut mat: Mat) -> Mat {
+fn apply_clahe(mut mat: Mat) -> Result<Mat> {
     let mut channels = VectorOfMat::new();
-    cv::core::split(&mat, &mut channels).expect("Cannot split image");
-    if let Ok(mut clahe) = imgproc::create_clahe(2.0, Size::new(8, 8)) {
-        for mut channel in channels.iter() {
-            clahe.apply(&channel.clone(), &mut channel).expect("Cannot apply CLAHE");
+    cv::core::split(&mat, &mut channels)?;
+    let mut clahe = imgproc::create_clahe(2.0, Size::new(8, 8))?;
+    for mut channel in channels.iter() {
+        clahe.apply(&channel.clone(), &mut channel)?;
+    }
+    cv::core::merge(&channels, &mut mat)?;
+    Ok(mat)
+}
+
+// 图像增强失败时显示未处理的画面，并限制警告日志的频率
+const ENHANCEMENT_WARNING_INTERVAL: Duration = Duration::from_secs(10);
+
+// 依次进行图像增强与旋转，任一步骤失败时跳过该步骤并返回失败原因，以免在 GStreamer 的线程中崩溃
+fn process_frame(mat: Mat, config: &SlaveConfigModel) -> (Mat, Vec<String>) {
+    let mut errors = Vec::new();
+    let mat = match enhance_frame(&mat, config) {
+        Ok(Some(enhanced)) => enhanced,
+        Ok(None) => mat,
+        Err(err) => {
+            errors.push(format!("图像增强失败，已显示未经处理的画面：{}", err));
+            mat
+        },
+    };
+    let mat = match config.video_orientation().apply(&mat) {
+        Ok(Some(oriented)) => oriented,
+        Ok(None) => mat,
+        Err(err) => {
+            errors.push(format!("画面旋转失败，已显示未经旋转的画面：{}", err));
+            mat
+        },
+    };
+    (mat, errors)
+}
+
+pub const HISTOGRAM_BINS: usize = 64;
+const HISTOGRAM_UPDATE_INTERVAL: Duration = Duration::from_millis(250);
+
+pub type Histogram = [Vec<f32>; 3]; // 依次为 R、G、B 通道
+
+fn calculate_histogram(mat: &Mat) -> Result<Histogram> {
+    let mut image = Mat::default();
+    imgproc::resize(mat, &mut image, Size::new(160, 120), 0.0, 0.0, imgproc::INTER_NEAREST)?; // 降采样以减少计算量
+    let images = VectorOfMat::from_iter([image]);
+    let mut histogram: Histogram = Default::default();
+    for (channel, bins) in histogram.iter_mut().enumerate() {
+        let mut hist = Mat::default();
+        imgproc::calc_hist(&images, &cv::types::VectorOfi32::from_iter([channel as i32]), &cv::core::no_array(), &mut hist,
+                           &cv::types::VectorOfi32::from_iter([HISTOGRAM_BINS as i32]), &cv::types::VectorOff32::from_iter([0.0, 256.0]), false)?;
+        *bins = (0..HISTOGRAM_BINS as i32).map(|index| hist.at::<f32>(index).map(|x| *x)).collect::<Result<_>>()?;
+    }
+    Ok(histogram)
+}
+
+// 低于该高度的画面视为分辨率异常，通常是摄像头或推流端的编码参数有误
+const LOW_RESOLUTION_HEIGHT: i32 = 480;
+
+// 显示分支协商得到的画面格式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VideoFormat {
+    pub width: i32,
+    pub height: i32,
+    pub framerate: Option<f64>, // 可变帧率或未指定帧率时为 None
+}
+
+impl VideoFormat {
+    fn from_caps(caps: &gst::CapsRef) -> Option<VideoFormat> {
+        let structure = caps.structure(0)?;
+        let (width, height) = (structure.get("width").ok()?, structure.get("height").ok()?);
+        let framerate = structure.get::<gst::Fraction>("framerate").ok()
+            .filter(|framerate| framerate.numer() > 0 && framerate.denom() > 0)
+            .map(|framerate| framerate.numer() as f64 / framerate.denom() as f64);
+        Some(VideoFormat { width, height, framerate })
+    }
+
+    pub fn is_low_resolution(&self) -> bool {
+        self.height < LOW_RESOLUTION_HEIGHT
+    }
+
+    pub fn aspect_ratio(&self) -> (i32, i32) {
+        fn gcd(a: i32, b: i32) -> i32 {
+            if b == 0 { a } else { gcd(b, a % b) }
         }
+        let divisor = gcd(self.width, self.height).max(1);
+        (self.width / divisor, self.height / divisor)
     }
-    cv::core::merge(&channels, &mut mat).expect("Cannot merge result channels");
-    mat
 }
 
-pub fn attach_pipeline_callback(pipeline: &Pipeline, sender: Sender<Mat>, config: Arc<Mutex<SlaveConfigModel>>) -> Result<(), String> {
+impl ToString for VideoFormat {
+    fn to_string(&self) -> String {
+        let (ratio_width, ratio_height) = self.aspect_ratio();
+        let resolution = format!("{}×{} ({}:{})", self.width, self.height, ratio_width, ratio_height);
+        match self.framerate {
+            Some(framerate) => format!("{} @ {:.0} fps", resolution, framerate),
+            None => resolution,
+        }
+    }
+}
+
+// frame_timestamp 保存最近一次送往画面的帧的时间戳，供截图与采集报告使用
+pub fn attach_pipeline_callback(pipeline: &Pipeline, sender: Sender<Mat>, histogram_sender: Sender<Histogram>, format_sender: Sender<VideoFormat>, config: Arc<Mutex<SlaveConfigModel>>, frame_timestamp: Arc<Mutex<Option<FrameTimestamp>>>) -> Result<(), String> {
     let frame_size: Arc<Mutex<Option<(i32, i32)>>> = Arc::new(Mutex::new(None));
-    let appsink = pipeline.by_name("display").unwrap().dynamic_cast::<gst_app::AppSink>().unwrap();
+    let histogram_updated_time: Arc<Mutex<Option<Instant>>> = Arc::new(Mutex::new(None));
+    let enhancement_warned_time: Arc<Mutex<Option<Instant>>> = Arc::new(Mutex::new(None));
+    let appsink = pipeline.by_name("display")
+        .and_then(|element| element.dynamic_cast::<gst_app::AppSink>().ok())
+        .ok_or("Missing element: appsink")?;
     appsink.set_callbacks(
         gst_app::AppSinkCallbacks::builder()
             .new_event(clone!(@strong frame_size => move |appsink| {
                 if let Ok(miniobj) = appsink.pull_object() {
                     if let Ok(event) = miniobj.downcast::<gst::Event>() {
                         if let EventView::Caps(caps) = event.view() {
-                            let caps = caps.caps();
-                            if let Some(structure) = caps.structure(0) {
-                                match (structure.get("width"), structure.get("height")) {
-                                    (Ok(width), Ok(height)) => {
-                                        *frame_size.lock().unwrap() = Some((width, height));
-                                    },
-                                    _ => (),
-                                }
+                            if let Some(format) = VideoFormat::from_caps(caps.caps()) {
+                                *frame_size.lock().unwrap() = Some((format.width, format.height));
+                                format_sender.send(format).unwrap_or_default();
                             }
                         }
                     }
@@ -568,6 +1365,7 @@ pub fn attach_pipeline_callback(pipeline: &Pipeline, sender: Sender<Mat>, config
                     );
                     gst::FlowError::Error
                 })?;
+                *frame_timestamp.lock().unwrap() = Some(FrameTimestamp::from_buffer(buffer));
                 let map = buffer.map_readable().map_err(|_| {
                     element_error!(
                         appsink,
@@ -581,12 +1379,30 @@ pub fn attach_pipeline_callback(pipeline: &Pipeline, sender: Sender<Mat>, config
                 }.map_err(|_| gst::FlowError::CustomError)?.clone();
                 let mat = match config.lock() {
                     Ok(config) => {
-                        match config.video_algorithms.first() {
-                            Some(VideoAlgorithm::CLAHE) => {
-                                apply_clahe(correct_underwater_color(mat))
-                            },
-                            _ => mat,
+                        let mat = match config.video_crop_region {
+                            Some(region) => region.crop(mat),
+                            None => mat,
+                        };
+                        let (mat, errors) = process_frame(mat, &config);
+                        if !errors.is_empty() {
+                            let mut warned_time = enhancement_warned_time.lock().unwrap();
+                            if warned_time.map_or(true, |time| time.elapsed() >= ENHANCEMENT_WARNING_INTERVAL) {
+                                for error in errors {
+                                    log::warn!("{}", error);
+                                }
+                                *warned_time = Some(Instant::now());
+                            }
                         }
+                        if config.histogram_enabled {
+                            let mut updated_time = histogram_updated_time.lock().unwrap();
+                            if updated_time.map_or(true, |time| time.elapsed() >= HISTOGRAM_UPDATE_INTERVAL) {
+                                if let Ok(histogram) = calculate_histogram(&mat) {
+                                    histogram_sender.send(histogram).unwrap();
+                                }
+                                *updated_time = Some(Instant::now());
+                            }
+                        }
+                        mat
                     },
                     Err(_) => mat,
                 };
@@ -597,22 +1413,268 @@ pub fn attach_pipeline_callback(pipeline: &Pipeline, sender: Sender<Mat>, config
     Ok(())
 }
 
+// 向上游请求关键帧，编码端收到后（RTP 视频流经由 RTCP 反馈）尽快发送 IDR 帧，避免中途拉流时长时间等待下一个关键帧。
+// 不依赖 gstreamer-video，按照其 UpstreamForceKeyUnitEvent 的结构手动构造事件
+pub fn request_keyframe(pipeline: &Pipeline) -> bool {
+    let structure = gst::Structure::builder("GstForceKeyUnit")
+        .field("running-time", u64::MAX) // GST_CLOCK_TIME_NONE，即立即生成
+        .field("all-headers", true)
+        .field("count", 0u32)
+        .build();
+    match pipeline.by_name("display") {
+        Some(display) => display.send_event(gst::event::CustomUpstream::new(structure)),
+        None => false,
+    }
+}
+
+// 以 GTK 视频输出替换管道中的 appsink，须在主线程中于管道启动前调用。替换后的元素沿用 display 的名称，停止管道时等待的流结束事件不受影响
+pub fn replace_display_sink(pipeline: &Pipeline) -> Result<gdk::Paintable, String> {
+    let paintable_sink = gst::ElementFactory::make("gtk4paintablesink", Some("display")).map_err(|_| "Missing element: gtk4paintablesink")?;
+    let appsink = pipeline.by_name("display").ok_or("Missing element: appsink")?;
+    let upstream = appsink.static_pad("sink")
+        .and_then(|pad| pad.peer())
+        .and_then(|pad| pad.parent_element())
+        .ok_or("Appsink is not linked")?;
+    upstream.unlink(&appsink);
+    pipeline.remove(&appsink).map_err(|_| "Cannot remove appsink from pipeline")?;
+    pipeline.add(&paintable_sink).map_err(|_| "Cannot add gtk4paintablesink to pipeline")?;
+    upstream.link(&paintable_sink).map_err(|_| "Cannot link gtk4paintablesink")?;
+    Ok(paintable_sink.property::<gdk::Paintable>("paintable"))
+}
+
+// GTK 视频输出不经过 appsink 的回调，在其输入端口上统计到达的帧并获取画面格式
+pub fn attach_native_display_callback(pipeline: &Pipeline, frame_sender: Sender<()>, format_sender: Sender<VideoFormat>, frame_timestamp: Arc<Mutex<Option<FrameTimestamp>>>) -> Result<(), String> {
+    let pad = pipeline.by_name("display")
+        .and_then(|sink| sink.static_pad("sink"))
+        .ok_or("Missing sink pad of gtk4paintablesink")?;
+    pad.add_probe(PadProbeType::BUFFER | PadProbeType::EVENT_DOWNSTREAM, move |_pad, info| {
+        match &info.data {
+            Some(PadProbeData::Buffer(buffer)) => {
+                *frame_timestamp.lock().unwrap() = Some(FrameTimestamp::from_buffer(buffer));
+                frame_sender.send(()).unwrap_or_default();
+            },
+            Some(PadProbeData::Event(event)) => {
+                if let EventView::Caps(caps) = event.view() {
+                    if let Some(format) = VideoFormat::from_caps(caps.caps()) {
+                        format_sender.send(format).unwrap_or_default();
+                    }
+                }
+            },
+            _ => (),
+        }
+        PadProbeReturn::Ok
+    });
+    Ok(())
+}
+
+// 管道探针累计的视频流数据量及 RTP 包序号，由界面每秒取出并清零
+#[derive(Debug, Default)]
+pub struct StreamCounter {
+    bytes: u64,
+    received_packets: u64,
+    expected_packets: u64,
+    last_sequence: Option<u16>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StreamStatistics {
+    pub bitrate: f64, // 每秒比特数
+    pub packet_loss: Option<f64>, // 丢包率估计，仅 RTP 视频流可用
+}
+
+impl StreamCounter {
+    fn count_buffer(&mut self, buffer: &gst::BufferRef, rtp: bool) {
+        self.bytes += buffer.size() as u64;
+        if !rtp {
+            return;
+        }
+        let sequence = match buffer.map_readable() {
+            Ok(map) if map.len() >= 4 && map[0] >> 6 == 2 => u16::from_be_bytes([map[2], map[3]]),
+            _ => return,
+        };
+        self.received_packets += 1;
+        match self.last_sequence.map(|last| sequence.wrapping_sub(last)) {
+            None => {
+                self.expected_packets += 1;
+                self.last_sequence = Some(sequence);
+            },
+            Some(delta) if delta == 0 || delta >= 0x8000 => (), // 重复或乱序到达的包，已计入期望的包数
+            Some(delta) => {
+                self.expected_packets += delta as u64;
+                self.last_sequence = Some(sequence);
+            },
+        }
+    }
+
+    pub fn take_statistics(&mut self, elapsed: Duration) -> StreamStatistics {
+        let seconds = elapsed.as_secs_f64();
+        let statistics = StreamStatistics {
+            bitrate: if seconds > 0.0 { self.bytes as f64 * 8.0 / seconds } else { 0.0 },
+            packet_loss: (self.expected_packets > 0).then(|| self.expected_packets.saturating_sub(self.received_packets) as f64 / self.expected_packets as f64),
+        };
+        self.bytes = 0;
+        self.received_packets = 0;
+        self.expected_packets = 0;
+        statistics
+    }
+}
+
+impl ToString for StreamStatistics {
+    fn to_string(&self) -> String {
+        let bitrate = if self.bitrate >= 1_000_000.0 {
+            format!("{:.2} Mbps", self.bitrate / 1_000_000.0)
+        } else {
+            format!("{:.0} kbps", self.bitrate / 1000.0)
+        };
+        match self.packet_loss {
+            Some(packet_loss) => format!("{} / {}{:.1}%", bitrate, tr("丢包 "), packet_loss * 100.0),
+            None => bitrate,
+        }
+    }
+}
+
+// RTP 视频流在解包前统计，以便根据包序号估计丢包，其他视频流在 tee_source 处统计，uridecodebin 管道不支持统计
+pub fn attach_stream_statistics(pipeline: &Pipeline, counter: Arc<Mutex<StreamCounter>>) -> bool {
+    let (pad, rtp) = match pipeline.by_name("rtpdepay").and_then(|depay| depay.static_pad("sink")) {
+        Some(pad) => (pad, true),
+        None => match pipeline.by_name("tee_source").and_then(|tee| tee.static_pad("sink")) {
+            Some(pad) => (pad, false),
+            None => return false,
+        },
+    };
+    pad.add_probe(PadProbeType::BUFFER | PadProbeType::BUFFER_LIST, move |_pad, info| {
+        let mut counter = counter.lock().unwrap();
+        match &info.data {
+            Some(PadProbeData::Buffer(buffer)) => counter.count_buffer(buffer, rtp),
+            Some(PadProbeData::BufferList(list)) => list.iter().for_each(|buffer| counter.count_buffer(buffer, rtp)),
+            _ => (),
+        }
+        PadProbeReturn::Ok
+    });
+    true
+}
+
+// 统计进入显示分支队列的帧数，与实际显示的帧数之差即为丢弃的帧数
+pub fn attach_frame_counter(pipeline: &Pipeline, counter: Arc<Mutex<u64>>) -> bool {
+    let pad = match pipeline.by_name("display_queue").and_then(|queue| queue.static_pad("sink")) {
+        Some(pad) => pad,
+        None => return false,
+    };
+    pad.add_probe(PadProbeType::BUFFER | PadProbeType::BUFFER_LIST, move |_pad, info| {
+        let mut counter = counter.lock().unwrap();
+        match &info.data {
+            Some(PadProbeData::Buffer(_)) => *counter += 1,
+            Some(PadProbeData::BufferList(list)) => *counter += list.len() as u64,
+            _ => (),
+        }
+        PadProbeReturn::Ok
+    });
+    true
+}
+
 pub trait MatExt {
-    fn as_pixbuf(&self) -> Pixbuf;
+    fn as_pixbuf(&self) -> Option<Pixbuf>;
 }
 
 impl MatExt for Mat {
-    fn as_pixbuf(&self) -> Pixbuf {
+    // 仅接受非空的 8 位三通道画面，格式变化后或算法处理失败时产生的其他画面返回 None
+    fn as_pixbuf(&self) -> Option<Pixbuf> {
         let width = self.cols();
         let height = self.rows();
-        // let bytes = glib::Bytes::from(self.data_bytes().unwrap());
-        // let pixbuf = Pixbuf::from_bytes(&bytes, Colorspace::Rgb, false, 8, width, height, 1);
-        let size = (width * height * 3) as usize;
-        let pixbuf = Pixbuf::new(Colorspace::Rgb, false, 8, width, height).unwrap();
+        if width <= 0 || height <= 0 || self.typ() != cv::core::CV_8UC3 {
+            return None;
+        }
+        let continuous;
+        let mat = if self.is_continuous() {
+            self
+        } else {
+            continuous = self.try_clone().ok()?; // 裁剪得到的画面可能不连续，复制后再逐行读取
+            &continuous
+        };
+        let data = mat.data_bytes().ok()?;
+        let row_size = width as usize * 3;
+        if data.len() < row_size * height as usize {
+            return None;
+        }
+        let pixbuf = Pixbuf::new(Colorspace::Rgb, false, 8, width, height)?;
+        let rowstride = pixbuf.rowstride() as usize; // 每行末尾可能有对齐填充
         unsafe {
-            pixbuf.pixels()[..size].copy_from_slice(self.data_bytes().unwrap());
+            let pixels = pixbuf.pixels();
+            for (row, src) in data.chunks_exact(row_size).take(height as usize).enumerate() {
+                pixels[row * rowstride..row * rowstride + row_size].copy_from_slice(src);
+            }
         }
-        pixbuf
+        Some(pixbuf)
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    fn enhancing_config() -> SlaveConfigModel {
+        let mut config = SlaveConfigModel::default();
+        config.video_algorithms = VideoAlgorithm::iter().collect();
+        config.video_rotation = VideoRotation::Clockwise;
+        config.video_flip = VideoFlip::Horizontal;
+        config
+    }
+
+    // 管道的输出格式改变时可能收到空白的画面，处理失败时应原样显示而不是崩溃
+    #[test]
+    fn empty_frame_falls_back_without_panicking() {
+        let (mat, errors) = process_frame(Mat::default(), &enhancing_config());
+        assert!(mat.empty());
+        assert!(!errors.is_empty());
+    }
+
+    #[test]
+    fn wrong_channel_frame_falls_back_to_unprocessed() {
+        let gray = Mat::new_rows_cols_with_default(4, 6, cv::core::CV_8UC1, cv::core::Scalar::all(128.0)).unwrap();
+        assert!(enhance_frame(&gray, &enhancing_config()).is_err());
+        let (mat, errors) = process_frame(gray, &enhancing_config());
+        assert!(!errors.is_empty());
+        assert_eq!(mat.channels(), 1);
+        assert_eq!(mat.size().unwrap(), Size::new(4, 6)); // 未经增强，但仍顺时针旋转
+    }
+
+    #[test]
+    fn tiny_frame_keeps_its_size() {
+        let mut config = enhancing_config();
+        config.processing_height = Some(360);
+        let pixel = Mat::new_rows_cols_with_default(1, 1, cv::core::CV_8UC3, cv::core::Scalar::all(64.0)).unwrap();
+        let (mat, _errors) = process_frame(pixel, &config);
+        assert_eq!(mat.size().unwrap(), Size::new(1, 1));
+    }
+
+    // 缺少解码器元素时 create_pipeline 应返回错误，构建过程中添加过的元素应全部移除并回到 Null 状态，管道本身随之释放
+    #[test]
+    fn missing_decoder_leaves_no_pipeline_state() {
+        gst::init().unwrap();
+        let decoder = VideoCodec::iter()
+            .flat_map(|codec| VideoCodecProvider::iter().map(move |provider| VideoDecoder(codec, provider)))
+            .find(|decoder| decoder.gst_main_elements().is_err());
+        let decoder = match decoder {
+            Some(decoder) => decoder,
+            None => return, // 所有解码器均可用时无从构造缺少元素的情形
+        };
+        let source = || VideoSource::from_url(&Url::parse("udp://127.0.0.1:5600").unwrap()).unwrap();
+        let err = create_pipeline(source(), 0, RtspProtocol::default(), ColorspaceConversion::default(), decoder, AppSinkQueueMode::default()).unwrap_err();
+        assert!(err.starts_with("Missing element"), "{}", err);
+
+        let pipeline = gst::Pipeline::new(None);
+        let added: Arc<Mutex<Vec<Element>>> = Arc::new(Mutex::new(Vec::new()));
+        pipeline.connect_element_added(clone!(@strong added => move |_, element| {
+            added.lock().unwrap().push(element.clone());
+        }));
+        let weak_pipeline = pipeline.downgrade();
+        let result = build_pipeline(&pipeline, source(), 0, RtspProtocol::default(), ColorspaceConversion::default(), decoder, AppSinkQueueMode::default());
+        assert_eq!(finish_pipeline(pipeline, result).unwrap_err(), err);
+        assert!(weak_pipeline.upgrade().is_none());
+        for element in added.lock().unwrap().iter() {
+            assert!(element.parent().is_none(), "{}", element.name());
+            assert_eq!(element.current_state(), gst::State::Null, "{}", element.name());
+        }
+    }
+}
@@ -0,0 +1,299 @@
+/* annotation.rs
+ *
+ * Copyright 2021-2022 Bohong Huang
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::{
+    cell::{Cell, RefCell},
+    f64::consts::PI,
+    rc::Rc,
+};
+
+use gdk::prelude::GdkCairoContextExt;
+use gdk_pixbuf::Pixbuf;
+use glib::clone;
+use gtk::{
+    cairo, prelude::*, Box as GtkBox, Button, CheckButton, DrawingArea, Entry, GestureDrag,
+    HeaderBar, Inhibit, Orientation, ToggleButton, Window,
+};
+
+use crate::i18n::tr;
+
+#[derive(Clone, Copy, PartialEq)]
+enum AnnotationTool {
+    Rectangle,
+    Arrow,
+}
+
+// 标注的坐标均为画面上的像素坐标，与预览时的缩放无关
+#[derive(Clone, Copy)]
+struct Annotation {
+    tool: AnnotationTool,
+    start: (f64, f64),
+    end: (f64, f64),
+}
+
+fn line_width(width: f64) -> f64 {
+    (width / 300.0).max(2.0)
+}
+
+fn draw_annotation(cr: &cairo::Context, annotation: &Annotation, line_width: f64) {
+    let (start_x, start_y) = annotation.start;
+    let (end_x, end_y) = annotation.end;
+    cr.set_source_rgb(0.88, 0.11, 0.14);
+    cr.set_line_width(line_width);
+    match annotation.tool {
+        AnnotationTool::Rectangle => {
+            cr.rectangle(start_x, start_y, end_x - start_x, end_y - start_y);
+        }
+        AnnotationTool::Arrow => {
+            let angle = (end_y - start_y).atan2(end_x - start_x);
+            let head_length = line_width * 5.0;
+            cr.move_to(start_x, start_y);
+            cr.line_to(end_x, end_y);
+            for side in [-1.0, 1.0] {
+                cr.move_to(end_x, end_y);
+                cr.line_to(
+                    end_x - head_length * (angle + side * PI / 6.0).cos(),
+                    end_y - head_length * (angle + side * PI / 6.0).sin(),
+                );
+            }
+        }
+    }
+    cr.stroke().expect("Couldn't stroke on Cairo Context");
+}
+
+// 说明文字绘制在画面底部的半透明背景上
+fn draw_caption(cr: &cairo::Context, layout: &pango::Layout, width: f64, height: f64) {
+    let (_, text_height) = layout.pixel_size();
+    let margin = height / 40.0;
+    let band_height = text_height as f64 + margin * 2.0;
+    cr.set_source_rgba(0.0, 0.0, 0.0, 0.6);
+    cr.rectangle(0.0, height - band_height, width, band_height);
+    cr.fill().expect("Couldn't fill on Cairo Context");
+    cr.set_source_rgb(1.0, 1.0, 1.0);
+    cr.move_to(margin, height - band_height + margin);
+    pangocairo::show_layout(cr, layout);
+}
+
+fn caption_layout(area: &DrawingArea, caption: &str, width: f64, height: f64) -> Option<pango::Layout> {
+    if caption.trim().is_empty() {
+        return None;
+    }
+    let layout = area.create_pango_layout(Some(caption.trim()));
+    let mut font = pango::FontDescription::new();
+    font.set_weight(pango::Weight::Bold);
+    font.set_absolute_size(height / 20.0 * pango::SCALE as f64);
+    layout.set_font_description(Some(&font));
+    layout.set_width(((width - height / 20.0) * pango::SCALE as f64) as i32);
+    layout.set_wrap(pango::WrapMode::WordChar);
+    Some(layout)
+}
+
+fn draw_annotations(
+    cr: &cairo::Context,
+    pixbuf: &Pixbuf,
+    annotations: &[Annotation],
+    caption: Option<&pango::Layout>,
+) {
+    let (width, height) = (pixbuf.width() as f64, pixbuf.height() as f64);
+    cr.set_source_pixbuf(pixbuf, 0.0, 0.0);
+    cr.paint().expect("Couldn't paint on Cairo Context");
+    for annotation in annotations {
+        draw_annotation(cr, annotation, line_width(width));
+    }
+    if let Some(layout) = caption {
+        draw_caption(cr, layout, width, height);
+    }
+}
+
+// 将标注绘制到与原画面相同格式的新画面上
+fn flatten_annotations(
+    pixbuf: &Pixbuf,
+    annotations: &[Annotation],
+    caption: Option<&pango::Layout>,
+) -> Option<Pixbuf> {
+    let (width, height) = (pixbuf.width(), pixbuf.height());
+    let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, width, height).ok()?;
+    {
+        let cr = cairo::Context::new(&surface).ok()?;
+        draw_annotations(&cr, pixbuf, annotations, caption);
+    }
+    let annotated = gdk::pixbuf_get_from_surface(&surface, 0, 0, width, height)?;
+    let flattened = pixbuf.copy()?;
+    annotated.copy_area(0, 0, width, height, &flattened, 0, 0);
+    Some(flattened)
+}
+
+// 预览时画面按比例缩放并居中，返回缩放比例与偏移量
+fn preview_transform(area: &DrawingArea, pixbuf: &Pixbuf) -> (f64, (f64, f64)) {
+    let (width, height) = (area.width() as f64, area.height() as f64);
+    let (pixbuf_width, pixbuf_height) = (pixbuf.width() as f64, pixbuf.height() as f64);
+    let scale = (width / pixbuf_width).min(height / pixbuf_height);
+    (
+        scale,
+        (
+            (width - pixbuf_width * scale) / 2.0,
+            (height - pixbuf_height * scale) / 2.0,
+        ),
+    )
+}
+
+fn widget_to_pixbuf(area: &DrawingArea, pixbuf: &Pixbuf, (x, y): (f64, f64)) -> (f64, f64) {
+    let (scale, (offset_x, offset_y)) = preview_transform(area, pixbuf);
+    (
+        ((x - offset_x) / scale).clamp(0.0, pixbuf.width() as f64),
+        ((y - offset_y) / scale).clamp(0.0, pixbuf.height() as f64),
+    )
+}
+
+// 在冻结的画面上绘制矩形与箭头并添加说明文字，保存时回调标注后的画面以及是否同时保存原始画面，取消时回调 None
+pub fn annotate_screenshot<T, F>(pixbuf: Pixbuf, parent_window: Option<&T>, callback: F) -> Window
+where
+    T: IsA<gtk::Window>,
+    F: 'static + Fn(Option<(Pixbuf, bool)>),
+{
+    let callback = Rc::new(callback);
+    let annotations: Rc<RefCell<Vec<Annotation>>> = Rc::new(RefCell::new(Vec::new()));
+    let drawing: Rc<Cell<Option<Annotation>>> = Rc::new(Cell::new(None));
+    let tool = Rc::new(Cell::new(AnnotationTool::Rectangle));
+
+    let window = Window::builder()
+        .title(tr("标注截图"))
+        .modal(true)
+        .default_width(960)
+        .default_height(640)
+        .build();
+    window.set_transient_for(parent_window);
+    let header_bar = HeaderBar::new();
+    let cancel_button = Button::with_label(tr("取消"));
+    let save_button = Button::with_label(tr("保存"));
+    save_button.add_css_class("suggested-action");
+    header_bar.pack_start(&cancel_button);
+    header_bar.pack_end(&save_button);
+    window.set_titlebar(Some(&header_bar));
+
+    let rectangle_button = ToggleButton::builder()
+        .icon_name("checkbox-symbolic")
+        .tooltip_text(tr("矩形"))
+        .active(true)
+        .build();
+    let arrow_button = ToggleButton::builder()
+        .icon_name("go-next-symbolic")
+        .tooltip_text(tr("箭头"))
+        .group(&rectangle_button)
+        .build();
+    let undo_button = Button::builder()
+        .icon_name("edit-undo-symbolic")
+        .tooltip_text(tr("撤销"))
+        .build();
+    let caption_entry = Entry::builder()
+        .placeholder_text(tr("说明文字"))
+        .hexpand(true)
+        .build();
+    let keep_raw_check = CheckButton::with_label(tr("同时保存原始画面"));
+    let toolbar = GtkBox::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(5)
+        .margin_start(5)
+        .margin_end(5)
+        .margin_top(5)
+        .margin_bottom(5)
+        .build();
+    toolbar.append(&rectangle_button);
+    toolbar.append(&arrow_button);
+    toolbar.append(&undo_button);
+    toolbar.append(&caption_entry);
+    toolbar.append(&keep_raw_check);
+
+    let area = DrawingArea::builder()
+        .hexpand(true)
+        .vexpand(true)
+        .css_classes(vec![String::from("video-frame")])
+        .build();
+    area.set_draw_func(clone!(@strong pixbuf, @strong annotations, @strong drawing, @strong caption_entry => move |area, cr, _width, _height| {
+        let (scale, (offset_x, offset_y)) = preview_transform(area, &pixbuf);
+        cr.translate(offset_x, offset_y);
+        cr.scale(scale, scale);
+        let mut shapes = annotations.borrow().clone();
+        shapes.extend(drawing.get());
+        let layout = caption_layout(area, &caption_entry.text(), pixbuf.width() as f64, pixbuf.height() as f64);
+        draw_annotations(cr, &pixbuf, &shapes, layout.as_ref());
+    }));
+    let gesture = GestureDrag::new();
+    gesture.connect_drag_begin(clone!(@weak area, @strong pixbuf, @strong drawing, @strong tool => move |_gesture, x, y| {
+        let start = widget_to_pixbuf(&area, &pixbuf, (x, y));
+        drawing.set(Some(Annotation { tool: tool.get(), start, end: start }));
+        area.queue_draw();
+    }));
+    gesture.connect_drag_update(clone!(@weak area, @strong pixbuf, @strong drawing => move |gesture, offset_x, offset_y| {
+        if let (Some(annotation), Some((start_x, start_y))) = (drawing.get(), gesture.start_point()) {
+            let end = widget_to_pixbuf(&area, &pixbuf, (start_x + offset_x, start_y + offset_y));
+            drawing.set(Some(Annotation { end, ..annotation }));
+            area.queue_draw();
+        }
+    }));
+    gesture.connect_drag_end(clone!(@weak area, @strong annotations, @strong drawing => move |_gesture, _offset_x, _offset_y| {
+        if let Some(annotation) = drawing.take() {
+            if annotation.start != annotation.end {
+                annotations.borrow_mut().push(annotation);
+            }
+        }
+        area.queue_draw();
+    }));
+    area.add_controller(&gesture);
+
+    rectangle_button.connect_toggled(clone!(@strong tool => move |button| {
+        if button.is_active() {
+            tool.set(AnnotationTool::Rectangle);
+        }
+    }));
+    arrow_button.connect_toggled(clone!(@strong tool => move |button| {
+        if button.is_active() {
+            tool.set(AnnotationTool::Arrow);
+        }
+    }));
+    undo_button.connect_clicked(clone!(@weak area, @strong annotations => move |_button| {
+        annotations.borrow_mut().pop();
+        area.queue_draw();
+    }));
+    caption_entry.connect_changed(clone!(@weak area => move |_entry| {
+        area.queue_draw();
+    }));
+
+    let content = GtkBox::builder().orientation(Orientation::Vertical).build();
+    content.append(&toolbar);
+    content.append(&area);
+    window.set_child(Some(&content));
+
+    save_button.connect_clicked(clone!(@weak window, @weak area, @strong pixbuf, @strong annotations, @weak caption_entry, @weak keep_raw_check, @strong callback => move |_button| {
+        let layout = caption_layout(&area, &caption_entry.text(), pixbuf.width() as f64, pixbuf.height() as f64);
+        match flatten_annotations(&pixbuf, &annotations.borrow(), layout.as_ref()) {
+            Some(annotated) => callback(Some((annotated, keep_raw_check.is_active()))),
+            None => callback(None),
+        }
+        window.destroy();
+    }));
+    cancel_button.connect_clicked(clone!(@weak window => move |_button| {
+        window.close();
+    }));
+    window.connect_close_request(clone!(@strong callback => move |_window| {
+        callback(None);
+        Inhibit(false)
+    }));
+    window.present();
+    window
+}
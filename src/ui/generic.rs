@@ -20,8 +20,11 @@ use std::path::PathBuf;
 
 use gtk::{
     prelude::*, FileChooserAction, FileChooserNative, FileFilter, MessageDialog, ResponseType,
+    SpinButton,
 };
 
+use crate::i18n::tr;
+
 pub fn select_path<T, F>(
     action: FileChooserAction,
     filters: &[FileFilter],
@@ -37,8 +40,8 @@ where
             set_action: action,
             add_filter: iterate!(filters),
             set_create_folders: true,
-            set_cancel_label: Some("取消"),
-            set_accept_label: Some("打开"),
+            set_cancel_label: Some(tr("取消")),
+            set_accept_label: Some(tr("打开")),
             set_modal: true,
             set_transient_for: Some(parent_window),
             connect_response => move |dialog, res_ty| {
@@ -74,7 +77,7 @@ where
             set_title: Some(title),
             set_modal: true,
             set_transient_for: window,
-            add_button: args!("确定", ResponseType::Ok),
+            add_button: args!(tr("确定"), ResponseType::Ok),
             connect_response => |dialog, _response| {
                 dialog.destroy();
             }
@@ -83,3 +86,63 @@ where
     dialog.show();
     dialog
 }
+
+pub fn confirm_message<T, F>(title: &str, msg: &str, window: Option<&T>, callback: F) -> MessageDialog
+where
+    T: IsA<gtk::Window>,
+    F: 'static + Fn() -> (),
+{
+    relm4_macros::view! {
+        dialog = MessageDialog {
+            set_message_type: gtk::MessageType::Question,
+            set_text: Some(msg),
+            set_title: Some(title),
+            set_modal: true,
+            set_transient_for: window,
+            add_button: args!(tr("取消"), ResponseType::Cancel),
+            add_button: args!(tr("确定"), ResponseType::Ok),
+            connect_response => move |dialog, response| {
+                if response == ResponseType::Ok {
+                    callback();
+                }
+                dialog.destroy();
+            }
+        }
+    }
+    dialog.show();
+    dialog
+}
+
+pub fn number_message<T, F>(title: &str, msg: &str, (min, max): (f64, f64), value: f64, window: Option<&T>, callback: F) -> MessageDialog
+where
+    T: IsA<gtk::Window>,
+    F: 'static + Fn(f64) -> (),
+{
+    let spin_button = SpinButton::with_range(min, max, 1.0);
+    spin_button.set_value(value);
+    spin_button.set_digits(0);
+    spin_button.set_activates_default(true);
+    relm4_macros::view! {
+        dialog = MessageDialog {
+            set_message_type: gtk::MessageType::Question,
+            set_text: Some(msg),
+            set_title: Some(title),
+            set_modal: true,
+            set_transient_for: window,
+            add_button: args!(tr("取消"), ResponseType::Cancel),
+            add_button: args!(tr("确定"), ResponseType::Ok),
+            set_default_response: ResponseType::Ok,
+            connect_response[spin_button = spin_button.clone()] => move |dialog, response| {
+                if response == ResponseType::Ok {
+                    callback(spin_button.value());
+                }
+                dialog.destroy();
+            }
+        }
+    }
+    if let Ok(message_area) = dialog.message_area().downcast::<gtk::Box>() {
+        message_area.append(&spin_button);
+    }
+    dialog.show();
+    dialog
+}
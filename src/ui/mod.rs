@@ -1,2 +1,4 @@
+pub mod annotation;
 pub mod generic;
 pub mod graph_view;
+pub mod virtual_joystick;
@@ -0,0 +1,156 @@
+/* virtual_joystick.rs
+ *
+ * Copyright 2021-2022 Bohong Huang
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::{cell::Cell, f64::consts::PI, rc::Rc};
+
+use glib::clone;
+use gtk::{
+    prelude::*, Align, Box as GtkBox, DrawingArea, GestureDrag, Orientation, ToggleButton, Widget,
+};
+
+use crate::i18n::tr;
+use crate::input::{Axis, Button, InputSourceEvent, InputSystem};
+
+const STICK_SIZE: i32 = 160;
+const KNOB_RADIUS_RATIO: f64 = 0.3;
+
+fn draw_stick(cr: &gtk::cairo::Context, position: (f64, f64), width: f64, height: f64) {
+    let (center_x, center_y) = (width / 2.0, height / 2.0);
+    let radius = width.min(height) / 2.0;
+    let knob_radius = radius * KNOB_RADIUS_RATIO;
+    let travel = radius - knob_radius;
+
+    cr.set_source_rgba(0.5, 0.5, 0.5, 0.3);
+    cr.arc(center_x, center_y, radius, 0.0, 2.0 * PI);
+    cr.fill().expect("Couldn't fill on Cairo Context");
+
+    cr.set_source_rgba(1.0, 1.0, 1.0, 0.7);
+    cr.arc(
+        center_x + position.0 * travel,
+        center_y + position.1 * travel,
+        knob_radius,
+        0.0,
+        2.0 * PI,
+    );
+    cr.fill().expect("Couldn't fill on Cairo Context");
+}
+
+fn set_stick_position(
+    area: &DrawingArea,
+    position: &Cell<(f64, f64)>,
+    input_system: &InputSystem,
+    (x_axis, y_axis): (Axis, Axis),
+    (x, y): (f64, f64),
+) {
+    position.set((x, y));
+    area.queue_draw();
+    // 与 SDL 的约定一致，纵轴向下为正
+    input_system.send_virtual_event(InputSourceEvent::AxisChanged(x_axis, (x * i16::MAX as f64) as i16));
+    input_system.send_virtual_event(InputSourceEvent::AxisChanged(y_axis, (y * i16::MAX as f64) as i16));
+}
+
+// 将触点坐标换算为摇杆偏移量，超出摇杆范围时限制在边缘上
+fn stick_offset(area: &DrawingArea, x: f64, y: f64) -> (f64, f64) {
+    let (width, height) = (area.width() as f64, area.height() as f64);
+    let travel = width.min(height) / 2.0 * (1.0 - KNOB_RADIUS_RATIO);
+    let offset_x = (x - width / 2.0) / travel;
+    let offset_y = (y - height / 2.0) / travel;
+    let length = offset_x.hypot(offset_y);
+    if length > 1.0 {
+        (offset_x / length, offset_y / length)
+    } else {
+        (offset_x, offset_y)
+    }
+}
+
+fn virtual_stick(input_system: &Rc<InputSystem>, axes: (Axis, Axis)) -> DrawingArea {
+    let position = Rc::new(Cell::new((0.0, 0.0)));
+    let area = DrawingArea::builder()
+        .content_width(STICK_SIZE)
+        .content_height(STICK_SIZE)
+        .valign(Align::End)
+        .build();
+    area.set_draw_func(clone!(@strong position => move |_area, cr, width, height| {
+        draw_stick(cr, position.get(), width as f64, height as f64);
+    }));
+    let gesture = GestureDrag::new();
+    gesture.connect_drag_begin(clone!(@weak area, @strong position, @strong input_system => move |_gesture, x, y| {
+        set_stick_position(&area, &position, &input_system, axes, stick_offset(&area, x, y));
+    }));
+    gesture.connect_drag_update(clone!(@weak area, @strong position, @strong input_system => move |gesture, offset_x, offset_y| {
+        if let Some((start_x, start_y)) = gesture.start_point() {
+            set_stick_position(&area, &position, &input_system, axes, stick_offset(&area, start_x + offset_x, start_y + offset_y));
+        }
+    }));
+    // 松开后摇杆回中
+    gesture.connect_drag_end(clone!(@weak area, @strong position, @strong input_system => move |_gesture, _offset_x, _offset_y| {
+        set_stick_position(&area, &position, &input_system, axes, (0.0, 0.0));
+    }));
+    area.add_controller(&gesture);
+    area
+}
+
+fn virtual_toggle_button(
+    input_system: &Rc<InputSystem>,
+    label: &str,
+    event: impl Fn(bool) -> InputSourceEvent + 'static,
+) -> ToggleButton {
+    let button = ToggleButton::builder()
+        .label(label)
+        .can_focus(false)
+        .css_classes(vec![String::from("osd")])
+        .build();
+    button.connect_toggled(clone!(@strong input_system => move |button| {
+        input_system.send_virtual_event(event(button.is_active()));
+    }));
+    button
+}
+
+// 左摇杆控制平移，右摇杆控制转向与升降，中间的按钮对应手柄的肩键与扳机
+pub fn virtual_joystick(input_system: &Rc<InputSystem>) -> Widget {
+    let buttons = GtkBox::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(5)
+        .valign(Align::End)
+        .hexpand(true)
+        .halign(Align::Center)
+        .build();
+    buttons.append(&virtual_toggle_button(input_system, tr("张开机械臂"), |active| {
+        InputSourceEvent::ButtonChanged(Button::RightShoulder, active)
+    }));
+    buttons.append(&virtual_toggle_button(input_system, tr("闭合机械臂"), |active| {
+        InputSourceEvent::AxisChanged(Axis::TriggerRight, if active { i16::MAX } else { 0 })
+    }));
+    buttons.append(&virtual_toggle_button(input_system, tr("打开灯光"), |active| {
+        InputSourceEvent::ButtonChanged(Button::LeftShoulder, active)
+    }));
+    buttons.append(&virtual_toggle_button(input_system, tr("关闭灯光"), |active| {
+        InputSourceEvent::AxisChanged(Axis::TriggerLeft, if active { i16::MAX } else { 0 })
+    }));
+    let overlay = GtkBox::builder()
+        .orientation(Orientation::Horizontal)
+        .valign(Align::End)
+        .margin_start(20)
+        .margin_end(20)
+        .margin_bottom(20)
+        .build();
+    overlay.append(&virtual_stick(input_system, (Axis::LeftX, Axis::LeftY)));
+    overlay.append(&buttons);
+    overlay.append(&virtual_stick(input_system, (Axis::RightX, Axis::RightY)));
+    overlay.upcast()
+}